@@ -0,0 +1,250 @@
+//! Static analysis of programs, e.g. to help understand existing programs before studying or modifying them.
+//!
+//! Every 2 bytes of the program are analyzed as an instruction because code and data cannot be told apart without
+//! running the program, so data that happens to look like instructions can skew the results.
+
+use crate::interpreter::{split_word, Interpreter};
+
+/// How a general purpose register is used by a program.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Usage {
+    /// The register is never accessed.
+    Unused,
+    /// The register is read but never written, so it always keeps its initial value of 0.
+    ReadOnly,
+    /// The register is written but never read.
+    WriteOnly,
+    /// The register is only ever loaded with constants, e.g. to pass them to instructions that only take registers.
+    Constant,
+    /// The register is only loaded with 0 or 1, or set as the carry flag, and only tested by skip instructions.
+    Flag,
+    /// The register is incremented or decremented by constants, like a loop counter or a position.
+    Counter,
+    /// The register is used in other ways, e.g. to compute values.
+    General,
+}
+
+impl Usage {
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Self::Unused => "never used",
+            Self::ReadOnly => "read-only",
+            Self::WriteOnly => "write-only",
+            Self::Constant => "constant",
+            Self::Flag => "flag",
+            Self::Counter => "counter",
+            Self::General => "general",
+        }
+    }
+}
+
+/// How often a register is accessed and how it is used.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RegisterUsage {
+    /// The number of instructions reading the register.
+    pub reads: usize,
+    /// The number of instructions writing the register.
+    pub writes: usize,
+    pub usage: Usage,
+}
+
+/// What is known about the accesses of a register while analyzing.
+#[derive(Debug, Default, Clone, Copy)]
+struct Accesses {
+    reads: usize,
+    writes: usize,
+    /// Whether the register is read by anything but a skip instruction.
+    read_by_other: bool,
+    /// Whether the register is written with anything but a constant.
+    written_by_other: bool,
+    /// Whether the register is loaded with a constant other than 0 or 1.
+    loaded_non_boolean: bool,
+    /// Whether the register is set as the carry flag.
+    set_as_flag: bool,
+    /// Whether a constant is added to the register.
+    counted: bool,
+}
+
+impl Accesses {
+    fn get_usage(&self) -> Usage {
+        let non_constant = self.written_by_other || self.counted || self.set_as_flag;
+        match (self.reads, self.writes) {
+            (0, 0) => Usage::Unused,
+            (_, 0) => Usage::ReadOnly,
+            (0, _) => Usage::WriteOnly,
+            _ if !self.read_by_other
+                && !self.loaded_non_boolean
+                && !self.written_by_other
+                && !self.counted =>
+            {
+                Usage::Flag
+            }
+            _ if !non_constant => Usage::Constant,
+            _ if self.counted && !self.written_by_other && !self.set_as_flag => Usage::Counter,
+            _ => Usage::General,
+        }
+    }
+}
+
+/// Analyzes how the program uses each of the registers `V0` to `VF`.
+pub fn analyze_registers(program: &[u8]) -> [RegisterUsage; 16] {
+    let mut registers = [Accesses::default(); 16];
+
+    for bytes in program.chunks_exact(2) {
+        let instruction = Interpreter::get_instruction(bytes[0], bytes[1]);
+        let (nibble1, nibble2, nibble3, nibble4) = split_word(instruction);
+        let (x, y) = (nibble2.0 as usize, nibble3.0 as usize);
+        let byte2 = instruction as u8;
+
+        // Whether the registers read are only tested by a skip instruction.
+        let mut skip = false;
+        let mut reads = Vec::new();
+        let mut writes = Vec::new();
+        let mut carry = false;
+
+        match nibble1.0 {
+            0x3 | 0x4 => {
+                skip = true;
+                reads.push(x);
+            }
+            0x5 | 0x9 if nibble4.0 == 0x0 => {
+                skip = true;
+                reads.extend([x, y]);
+            }
+            0x6 => {
+                let accesses = &mut registers[x];
+                accesses.writes += 1;
+                accesses.loaded_non_boolean |= byte2 > 1;
+            }
+            0x7 => {
+                let accesses = &mut registers[x];
+                accesses.reads += 1;
+                accesses.writes += 1;
+                accesses.counted = true;
+            }
+            0x8 => match nibble4.0 {
+                0x0 => {
+                    reads.push(y);
+                    writes.push(x);
+                }
+                0x1..=0x3 => {
+                    reads.extend([x, y]);
+                    writes.push(x);
+                }
+                0x4 | 0x5 | 0x7 => {
+                    reads.extend([x, y]);
+                    writes.push(x);
+                    carry = true;
+                }
+                0x6 | 0xE => {
+                    reads.push(x);
+                    writes.push(x);
+                    carry = true;
+                }
+                _ => {}
+            },
+            0xB => reads.push(0),
+            0xC => writes.push(x),
+            0xD => {
+                reads.extend([x, y]);
+                carry = true;
+            }
+            0xE if matches!(byte2, 0x9E | 0xA1) => reads.push(x),
+            0xF => match byte2 {
+                0x07 | 0x0A => writes.push(x),
+                0x15 | 0x18 | 0x1E | 0x29 | 0x33 => reads.push(x),
+                0x55 => reads.extend(0..=x),
+                0x65 => writes.extend(0..=x),
+                _ => {}
+            },
+            _ => {}
+        }
+
+        // A register given twice, like in `SE V1, V1`, still only counts as one access.
+        reads.dedup();
+        for register in reads {
+            let accesses = &mut registers[register];
+            accesses.reads += 1;
+            accesses.read_by_other |= !skip;
+        }
+        for register in writes {
+            let accesses = &mut registers[register];
+            accesses.writes += 1;
+            accesses.written_by_other = true;
+        }
+        if carry {
+            let accesses = &mut registers[0xF];
+            accesses.writes += 1;
+            accesses.set_as_flag = true;
+        }
+    }
+
+    registers.map(|accesses| RegisterUsage {
+        reads: accesses.reads,
+        writes: accesses.writes,
+        usage: accesses.get_usage(),
+    })
+}
+
+/// Formats the usage of the registers as human-readable text, one register per line.
+pub fn to_text(registers: &[RegisterUsage; 16]) -> String {
+    registers
+        .iter()
+        .enumerate()
+        .map(|(index, register)| {
+            format!(
+                "V{:X}  {:<10}  {} reads, {} writes\n",
+                index,
+                register.usage.get_name(),
+                register.reads,
+                register.writes
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_registers() {
+        let program = [
+            0x60, 0x05, // LD V0, 0x05
+            0x71, 0x01, // ADD V1, 0x01
+            0x31, 0x10, // SE V1, 0x10
+            0x62, 0x01, // LD V2, 0x01
+            0x42, 0x00, // SNE V2, 0x00
+            0x83, 0x04, // ADD V3, V0
+            0xD0, 0x15, // DRW V0, V1, 0x5
+            0x64, 0x00, // LD V4, 0x00
+            0x85, 0x60, // LD V5, V6
+        ];
+        let registers = analyze_registers(&program);
+        let usages: Vec<Usage> = registers.iter().map(|register| register.usage).collect();
+
+        assert_eq!(
+            usages,
+            vec![
+                Usage::Constant,
+                Usage::Counter,
+                Usage::Flag,
+                Usage::General,
+                Usage::WriteOnly,
+                Usage::WriteOnly,
+                Usage::ReadOnly,
+                Usage::Unused,
+                Usage::Unused,
+                Usage::Unused,
+                Usage::Unused,
+                Usage::Unused,
+                Usage::Unused,
+                Usage::Unused,
+                Usage::Unused,
+                Usage::WriteOnly,
+            ]
+        );
+        assert_eq!(registers[1].reads, 3);
+        assert_eq!(registers[1].writes, 1);
+    }
+}
@@ -0,0 +1,115 @@
+//! Names for regions of memory, to make addresses easier to understand when debugging.
+
+use crate::{
+    util::{parse_number, parse_range},
+    Error,
+};
+use std::{fs, ops::Range, path::Path};
+
+/// A region of memory with a name, like the variable stored in it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    pub range: Range<u16>,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Annotations {
+    annotations: Vec<Annotation>,
+}
+
+impl Annotations {
+    pub fn add(&mut self, range: Range<u16>, name: String) {
+        self.annotations.push(Annotation { range, name });
+    }
+
+    /// Parses an annotation like `0x300..0x302=ball_y` or `0x304=score`, where the end of the range is exclusive.
+    pub fn add_from_str(&mut self, string: &str) -> Result<(), Error> {
+        let (range, name) = string
+            .split_once('=')
+            .and_then(|(range, name)| Some((parse_address_range(range.trim())?, name.trim())))
+            .filter(|(_, name)| !name.is_empty())
+            .ok_or_else(|| {
+                format!(
+                    "Invalid annotation `{}`. Expected something like `0x300..0x302=ball_y`.",
+                    string
+                )
+            })?;
+
+        self.add(range, name.to_string());
+
+        Ok(())
+    }
+
+    /// Loads annotations from a file with one annotation like `0x300..0x302=ball_y` per line.
+    ///
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn load(&mut self, path: &Path) -> Result<(), Error> {
+        let text = fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read annotations: {}", err))?;
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            self.add_from_str(line)
+                .map_err(|err| format!("Line {}: {}", index + 1, err))?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the annotation of the region the address is in.
+    ///
+    /// If the regions overlap, the one added last is returned.
+    pub fn get(&self, address: u16) -> Option<&Annotation> {
+        self.annotations
+            .iter()
+            .rev()
+            .find(|annotation| annotation.range.contains(&address))
+    }
+
+    /// Describes the address by the name of its region, like `score` or `score+2`.
+    pub fn describe(&self, address: u16) -> Option<String> {
+        let annotation = self.get(address)?;
+        let offset = address - annotation.range.start;
+
+        Some(if offset == 0 {
+            annotation.name.clone()
+        } else {
+            format!("{}+{}", annotation.name, offset)
+        })
+    }
+}
+
+/// Parses a range like `0x300..0x302` or a single address.
+fn parse_address_range(string: &str) -> Option<Range<u16>> {
+    if string.contains("..") {
+        parse_range(string)
+    } else {
+        let address = parse_number(string)?;
+        Some(address..address.checked_add(1)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe() {
+        let mut annotations = Annotations::default();
+        annotations.add_from_str("0x300..0x303=score").unwrap();
+        annotations.add_from_str("0x310 = ball_y").unwrap();
+
+        assert_eq!(annotations.describe(0x300), Some("score".into()));
+        assert_eq!(annotations.describe(0x302), Some("score+2".into()));
+        assert_eq!(annotations.describe(0x303), None);
+        assert_eq!(annotations.describe(0x310), Some("ball_y".into()));
+
+        assert!(annotations.add_from_str("0x300..0x302").is_err());
+        assert!(annotations.add_from_str("0x300=").is_err());
+    }
+}
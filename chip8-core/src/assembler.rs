@@ -0,0 +1,207 @@
+//! Assembling of instructions written in the syntax of the disassembler, like `LD VA, 0x02`.
+
+use crate::{util::parse_number, Error};
+
+/// An operand of an instruction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operand {
+    /// A general purpose register like `VA`.
+    Register(u8),
+    /// The address register `I`.
+    I,
+    /// The memory at the address register, `[I]`.
+    Memory,
+    /// The delay timer `DT`.
+    DelayTimer,
+    /// The sound timer `ST`.
+    SoundTimer,
+    /// The pressed key `K`.
+    Key,
+    /// The font character `F`.
+    Font,
+    /// The character of the big font `HF`.
+    BigFont,
+    /// The flags `R`.
+    Flags,
+    /// The binary-coded decimal representation `B`.
+    Bcd,
+    Number(u16),
+}
+
+fn parse_operand(string: &str) -> Option<Operand> {
+    let operand = match string.to_ascii_uppercase().as_str() {
+        "I" => Operand::I,
+        "[I]" => Operand::Memory,
+        "DT" => Operand::DelayTimer,
+        "ST" => Operand::SoundTimer,
+        "K" => Operand::Key,
+        "F" => Operand::Font,
+        "HF" => Operand::BigFont,
+        "R" => Operand::Flags,
+        "B" => Operand::Bcd,
+        register if register.len() == 2 && register.starts_with('V') => {
+            Operand::Register(u8::from_str_radix(&register[1..], 16).ok()?)
+        }
+        _ => Operand::Number(parse_number(string)?),
+    };
+
+    Some(operand)
+}
+
+/// Assembles a single instruction like `LD VA, 0x02` or `DRW V0, V1, 0x5`.
+///
+/// Numbers are hexadecimal with `0x` or decimal. `DW 0x1234` gives the instruction directly.
+pub fn assemble_instruction(line: &str) -> Result<u16, Error> {
+    let line = line.trim();
+    let (mnemonic, operands) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let mnemonic = mnemonic.to_ascii_uppercase();
+    let operands = if operands.trim().is_empty() {
+        Vec::new()
+    } else {
+        operands
+            .split(',')
+            .map(|operand| {
+                parse_operand(operand.trim())
+                    .ok_or_else(|| format!("Invalid operand `{}`.", operand.trim()))
+            })
+            .collect::<Result<Vec<Operand>, String>>()?
+    };
+
+    use Operand::*;
+
+    let invalid = || format!("Invalid operands for `{}`: `{}`.", mnemonic, line);
+    // The numbers need to fit into their part of the instruction.
+    let address = |number: u16| {
+        Some(number)
+            .filter(|number| *number <= 0xFFF)
+            .ok_or_else(invalid)
+    };
+    let byte = |number: u16| {
+        Some(number)
+            .filter(|number| *number <= 0xFF)
+            .ok_or_else(invalid)
+    };
+    let nibble = |number: u16| {
+        Some(number)
+            .filter(|number| *number <= 0xF)
+            .ok_or_else(invalid)
+    };
+    let x = |register: u8| (register as u16) << 8;
+    let y = |register: u8| (register as u16) << 4;
+
+    let instruction = match (mnemonic.as_str(), operands.as_slice()) {
+        ("CLS", []) => 0x00E0,
+        ("RET", []) => 0x00EE,
+        ("SCD", [Number(number)]) => 0x00C0 | nibble(*number)?,
+        ("SCU", [Number(number)]) => 0x00D0 | nibble(*number)?,
+        ("SCR", []) => 0x00FB,
+        ("EXIT", []) => 0x00FD,
+        ("SCL", []) => 0x00FC,
+        ("SYS", [Number(number)]) => address(*number)?,
+        ("JP", [Number(number)]) => 0x1000 | address(*number)?,
+        ("JP", [Register(0), Number(number)]) => 0xB000 | address(*number)?,
+        ("CALL", [Number(number)]) => 0x2000 | address(*number)?,
+        ("SE", [Register(vx), Number(number)]) => 0x3000 | x(*vx) | byte(*number)?,
+        ("SNE", [Register(vx), Number(number)]) => 0x4000 | x(*vx) | byte(*number)?,
+        ("SE", [Register(vx), Register(vy)]) => 0x5000 | x(*vx) | y(*vy),
+        ("LD", [Register(vx), Number(number)]) => 0x6000 | x(*vx) | byte(*number)?,
+        ("ADD", [Register(vx), Number(number)]) => 0x7000 | x(*vx) | byte(*number)?,
+        ("LD", [Register(vx), Register(vy)]) => 0x8000 | x(*vx) | y(*vy),
+        ("OR", [Register(vx), Register(vy)]) => 0x8001 | x(*vx) | y(*vy),
+        ("AND", [Register(vx), Register(vy)]) => 0x8002 | x(*vx) | y(*vy),
+        ("XOR", [Register(vx), Register(vy)]) => 0x8003 | x(*vx) | y(*vy),
+        ("ADD", [Register(vx), Register(vy)]) => 0x8004 | x(*vx) | y(*vy),
+        ("SUB", [Register(vx), Register(vy)]) => 0x8005 | x(*vx) | y(*vy),
+        ("SHR", [Register(vx)]) => 0x8006 | x(*vx),
+        ("SHR", [Register(vx), Register(vy)]) => 0x8006 | x(*vx) | y(*vy),
+        ("SUBN", [Register(vx), Register(vy)]) => 0x8007 | x(*vx) | y(*vy),
+        ("SHL", [Register(vx)]) => 0x800E | x(*vx),
+        ("SHL", [Register(vx), Register(vy)]) => 0x800E | x(*vx) | y(*vy),
+        ("SNE", [Register(vx), Register(vy)]) => 0x9000 | x(*vx) | y(*vy),
+        ("LD", [I, Number(number)]) => 0xA000 | address(*number)?,
+        ("RND", [Register(vx), Number(number)]) => 0xC000 | x(*vx) | byte(*number)?,
+        ("DRW", [Register(vx), Register(vy), Number(number)]) => {
+            0xD000 | x(*vx) | y(*vy) | nibble(*number)?
+        }
+        ("SKP", [Register(vx)]) => 0xE09E | x(*vx),
+        ("SKNP", [Register(vx)]) => 0xE0A1 | x(*vx),
+        ("LD", [Register(vx), DelayTimer]) => 0xF007 | x(*vx),
+        ("LD", [Register(vx), Key]) => 0xF00A | x(*vx),
+        ("LD", [DelayTimer, Register(vx)]) => 0xF015 | x(*vx),
+        ("LD", [SoundTimer, Register(vx)]) => 0xF018 | x(*vx),
+        ("ADD", [I, Register(vx)]) => 0xF01E | x(*vx),
+        ("LD", [Font, Register(vx)]) => 0xF029 | x(*vx),
+        ("LD", [BigFont, Register(vx)]) => 0xF030 | x(*vx),
+        ("LD", [Bcd, Register(vx)]) => 0xF033 | x(*vx),
+        ("LD", [Memory, Register(vx)]) => 0xF055 | x(*vx),
+        ("LD", [Register(vx), Memory]) => 0xF065 | x(*vx),
+        ("LD", [Flags, Register(vx)]) => 0xF075 | x(*vx),
+        ("LD", [Register(vx), Flags]) => 0xF085 | x(*vx),
+        ("DW", [Number(number)]) => *number,
+        (
+            "CLS" | "RET" | "SYS" | "JP" | "CALL" | "SE" | "SNE" | "LD" | "ADD" | "OR" | "AND"
+            | "XOR" | "SUB" | "SHR" | "SUBN" | "SHL" | "RND" | "DRW" | "SKP" | "SKNP" | "DW",
+            _,
+        ) => return Err(invalid().into()),
+        _ => return Err(format!("Unknown instruction `{}`.", mnemonic).into()),
+    };
+
+    Ok(instruction)
+}
+
+/// Assembles a source with one or more instructions per line, separated by `;`.
+///
+/// Everything after a `#` on a line is a comment. Empty instructions are skipped.
+pub fn assemble(source: &str) -> Result<Vec<u16>, Error> {
+    let mut instructions = Vec::new();
+    for (index, line) in source.lines().enumerate() {
+        let code = line.split('#').next().unwrap_or_default();
+        for instruction in code.split(';').filter(|code| !code.trim().is_empty()) {
+            let instruction = assemble_instruction(instruction)
+                .map_err(|err| format!("Line {}: {}", index + 1, err))?;
+            instructions.push(instruction);
+        }
+    }
+
+    Ok(instructions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disassembler::decode;
+
+    #[test]
+    fn test_assemble_instruction() {
+        assert_eq!(assemble_instruction("LD VA, 0x02").unwrap(), 0x6A02);
+        assert_eq!(assemble_instruction("drw v0, v1, 5").unwrap(), 0xD015);
+        assert_eq!(assemble_instruction("LD [I], V3").unwrap(), 0xF355);
+        assert_eq!(assemble_instruction("DW 0x8008").unwrap(), 0x8008);
+        assert!(assemble_instruction("LD VA, 0x100").is_err());
+        assert!(assemble_instruction("JP V1, 0x200").is_err());
+        assert!(assemble_instruction("FOO").is_err());
+    }
+
+    #[test]
+    fn test_disassembly_roundtrip() {
+        // Everything the disassembler outputs assembles to an instruction it outputs the same for.
+        for instruction in 0..=u16::MAX {
+            let (mnemonic, operands, _) = decode(instruction);
+            let line = format!("{} {}", mnemonic, operands.join(", "));
+            let assembled = assemble_instruction(&line).unwrap();
+            assert_eq!(decode(assembled), decode(instruction), "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_assemble() {
+        assert_eq!(
+            assemble("LD V0, 5; ADD V0, 1\n# Clear\nCLS # again\n").unwrap(),
+            vec![0x6005, 0x7001, 0x00E0]
+        );
+        assert_eq!(
+            assemble("CLS\nFOO").unwrap_err(),
+            "Line 2: Unknown instruction `FOO`."
+        );
+    }
+}
@@ -0,0 +1,431 @@
+use crate::{symbols::Symbols, util::parse_number, Error};
+use std::ops::Range;
+
+/// How many bytes `mem` shows if no length is given.
+const DEFAULT_MEMORY_LENGTH: u16 = 16;
+/// How many bytes are shown per line of memory.
+const MEMORY_LINE_LENGTH: usize = 16;
+
+/// What the commands of the debugger do, as shown by `help`.
+pub const HELP: &[&str] = &[
+    "mem <address> [length]  show the memory, 16 bytes by default",
+    "reg                     show the registers",
+    "step [count]            execute instructions, one by default",
+    "continue, c or Enter    resume execution",
+    "break <address>         pause before executing the instruction at the address",
+    "break                   list the breakpoints",
+    "delete <address>        remove the breakpoint at the address",
+    "dump <path> [rows]      write the screen to a file, and the given rows of the sprite at I",
+    "memdump <path>          write the whole memory to a binary file",
+    "help                    show this list",
+];
+
+/// A rectangular region of the display.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Region {
+    pub x: Range<u16>,
+    pub y: Range<u16>,
+}
+
+impl Region {
+    /// Returns whether the two regions share at least one pixel.
+    pub fn intersects(&self, other: &Region) -> bool {
+        self.x.start < other.x.end
+            && other.x.start < self.x.end
+            && self.y.start < other.y.end
+            && other.y.start < self.y.end
+    }
+}
+
+/// A region of memory whose writes are watched.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Watchpoint {
+    pub range: Range<u16>,
+    /// Whether execution is paused when the region is written to, instead of only logging the write.
+    pub pause: bool,
+}
+
+/// How many addresses are tracked for self-modifying code, which is the memory of XO-CHIP.
+const TRACKED_MEMORY_SIZE: usize = 0x10000;
+
+/// Finds self-modifying code: instructions that are executed after the program wrote them, and writes to
+/// instructions that were executed before.
+///
+/// Every instruction is reported only once, as programs that modify themselves usually do so over and over.
+#[derive(Debug, Clone)]
+pub struct SelfModificationDetector {
+    /// Whether execution is paused when self-modifying code is found, instead of only logging it.
+    pub pause: bool,
+    executed: Vec<bool>,
+    written: Vec<bool>,
+    /// The addresses of the instructions that were reported.
+    reported: Vec<bool>,
+}
+
+impl SelfModificationDetector {
+    pub fn new(pause: bool) -> Self {
+        Self {
+            pause,
+            executed: vec![false; TRACKED_MEMORY_SIZE],
+            written: vec![false; TRACKED_MEMORY_SIZE],
+            reported: vec![false; TRACKED_MEMORY_SIZE],
+        }
+    }
+
+    /// Returns a message if the instruction at the address is self-modifying code that was not reported yet.
+    /// `write_range` is the memory the instruction writes to, if any.
+    pub fn check(
+        &self,
+        address: u16,
+        instruction: u16,
+        write_range: Option<&Range<u16>>,
+        symbols: &Symbols,
+    ) -> Option<String> {
+        if self.reported[address as usize] {
+            return None;
+        }
+        let describe = |address| describe(address, symbols);
+        match self.find_overwritten(address, write_range)? {
+            None => Some(format!(
+                "Self-modifying code: executing {:04X} at {}, which the program wrote",
+                instruction,
+                describe(address)
+            )),
+            Some(overwritten) => Some(format!(
+                "Self-modifying code: {:04X} at {} overwrites the code executed at {}",
+                instruction,
+                describe(address),
+                describe(overwritten)
+            )),
+        }
+    }
+
+    /// Records that the instruction at the address was executed, and that it wrote to the range, if any.
+    pub fn record(&mut self, address: u16, write_range: Option<&Range<u16>>) {
+        if self.find_overwritten(address, write_range).is_some() {
+            self.reported[address as usize] = true;
+        }
+
+        self.executed[address as usize] = true;
+        self.executed[address.wrapping_add(1) as usize] = true;
+        if let Some(range) = write_range {
+            for address in range.clone() {
+                self.written[address as usize] = true;
+            }
+        }
+    }
+
+    /// Returns `Some(None)` if the instruction at the address was written by the program and
+    /// `Some(Some(address))` if the instruction writes to code executed at the address.
+    fn find_overwritten(
+        &self,
+        address: u16,
+        write_range: Option<&Range<u16>>,
+    ) -> Option<Option<u16>> {
+        if self.written[address as usize] || self.written[address.wrapping_add(1) as usize] {
+            return Some(None);
+        }
+        let overwritten = write_range?
+            .clone()
+            .find(|address| self.executed[*address as usize])?;
+        Some(Some(overwritten))
+    }
+}
+
+/// Decides when execution of the program is paused so that its state can be inspected.
+#[derive(Debug, Default)]
+pub struct Debugger {
+    /// Execution is paused before executing the instruction at any of these addresses.
+    pub breakpoints: Vec<u16>,
+    /// Execution is paused when a sprite is drawn within any of these regions.
+    pub draw_breakpoints: Vec<Region>,
+    /// Writes to memory within any of these regions pause execution or are logged.
+    pub write_watchpoints: Vec<Watchpoint>,
+    /// Finds self-modifying code, if enabled.
+    pub self_modification: Option<SelfModificationDetector>,
+    /// Names for addresses, shown instead of or next to them.
+    pub symbols: Symbols,
+    /// Messages about watched writes that did not pause execution, until they are taken.
+    log: Vec<String>,
+}
+
+impl Debugger {
+    /// Returns the watchpoint that is hit when the given range of memory is written to.
+    ///
+    /// Watchpoints pausing execution take precedence.
+    pub fn hit_write_watchpoint(&self, range: &Range<u16>) -> Option<&Watchpoint> {
+        let mut hits = self.write_watchpoints.iter().filter(|watchpoint| {
+            watchpoint.range.start < range.end && range.start < watchpoint.range.end
+        });
+
+        hits.clone()
+            .find(|watchpoint| watchpoint.pause)
+            .or_else(|| hits.next())
+    }
+
+    /// Formats the address with its symbol, if any, like `0x2F4 <draw_ball+4>`.
+    pub fn describe(&self, address: u16) -> String {
+        describe(address, &self.symbols)
+    }
+
+    pub fn log(&mut self, message: String) {
+        self.log.push(message);
+    }
+
+    /// Returns the messages logged since the last call and forgets them.
+    pub fn take_log(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.log)
+    }
+
+    /// Returns the breakpoint that is hit when a sprite is drawn within the given region.
+    pub fn hit_draw_breakpoint(&self, region: &Region) -> Option<&Region> {
+        self.draw_breakpoints
+            .iter()
+            .find(|breakpoint| breakpoint.intersects(region))
+    }
+}
+
+fn describe(address: u16, symbols: &Symbols) -> String {
+    match symbols.describe(address) {
+        Some(name) => format!("{:#05X} <{}>", address, name),
+        None => format!("{:#05X}", address),
+    }
+}
+
+/// A command typed into the debugger while paused.
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Memory {
+        address: u16,
+        length: u16,
+    },
+    Registers,
+    /// Executes the given number of instructions.
+    Step(u16),
+    Continue,
+    Break(u16),
+    ListBreakpoints,
+    Delete(u16),
+    /// Writes the display to the file, followed by the given number of rows of the sprite at I if any.
+    Dump {
+        path: String,
+        sprite_rows: Option<u16>,
+    },
+    /// Writes the whole memory to the file.
+    MemoryDump(String),
+    Help,
+}
+
+impl Command {
+    /// Parses a command like `mem 0x300 16`. Numbers are decimal or hexadecimal prefixed with `0x`, and addresses may
+    /// also be symbols.
+    pub fn parse(line: &str, symbols: &Symbols) -> Result<Self, Error> {
+        let number = |word: &str| {
+            parse_number(word).ok_or_else(|| Error::from(format!("Invalid number `{}`.", word)))
+        };
+        let address = |word: &str| {
+            parse_number(word)
+                .or_else(|| symbols.get_address(word))
+                .ok_or_else(|| Error::from(format!("Invalid number or unknown symbol `{}`.", word)))
+        };
+
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let command = match words.as_slice() {
+            [] | ["continue"] | ["c"] => Self::Continue,
+            ["mem", start] => Self::Memory {
+                address: address(start)?,
+                length: DEFAULT_MEMORY_LENGTH,
+            },
+            ["mem", start, length] => Self::Memory {
+                address: address(start)?,
+                length: number(length)?,
+            },
+            ["reg"] => Self::Registers,
+            ["step"] => Self::Step(1),
+            ["step", count] => Self::Step(number(count)?),
+            ["break"] => Self::ListBreakpoints,
+            ["break", target] => Self::Break(address(target)?),
+            ["delete", target] => Self::Delete(address(target)?),
+            ["dump", path] => Self::Dump {
+                path: path.to_string(),
+                sprite_rows: None,
+            },
+            ["dump", path, rows] => Self::Dump {
+                path: path.to_string(),
+                sprite_rows: Some(number(rows)?),
+            },
+            ["memdump", path] => Self::MemoryDump(path.to_string()),
+            ["help"] => Self::Help,
+            [command @ ("mem" | "reg" | "step" | "continue" | "c" | "break" | "delete" | "dump"
+            | "memdump" | "help"), ..] => {
+                return Err(format!(
+                    "Wrong number of arguments for `{}`. Type `help` for a list of commands.",
+                    command
+                )
+                .into())
+            }
+            [command, ..] => {
+                return Err(format!(
+                    "Unknown command `{}`. Type `help` for a list of commands.",
+                    command
+                )
+                .into())
+            }
+        };
+
+        Ok(command)
+    }
+}
+
+/// Formats the given range of memory like `0x300  00 E0 A2 2A ...`, 16 bytes per line. The range ends at the end of
+/// the memory.
+pub fn format_memory(memory: &[u8], address: u16, length: u16) -> Vec<String> {
+    let start = (address as usize).min(memory.len());
+    let end = (start + length as usize).min(memory.len());
+
+    memory[start..end]
+        .chunks(MEMORY_LINE_LENGTH)
+        .enumerate()
+        .map(|(index, bytes)| {
+            let bytes: Vec<String> = bytes.iter().map(|byte| format!("{:02X}", byte)).collect();
+            format!(
+                "{:#05X}  {}",
+                start + index * MEMORY_LINE_LENGTH,
+                bytes.join(" ")
+            )
+        })
+        .collect()
+}
+
+/// Draws the given number of rows of the sprite at the address like the display dump, one byte per row with `#` for
+/// set pixels and `.` for unset ones. The sprite ends at the end of the memory.
+pub fn format_sprite(memory: &[u8], address: u16, rows: u16) -> Vec<String> {
+    let start = (address as usize).min(memory.len());
+    let end = (start + rows as usize).min(memory.len());
+
+    memory[start..end]
+        .iter()
+        .map(|byte| {
+            (0..8)
+                .rev()
+                .map(|bit| if byte & (1 << bit) != 0 { '#' } else { '.' })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intersects() {
+        let region = Region { x: 0..8, y: 0..8 };
+
+        assert!(region.intersects(&Region { x: 7..15, y: 7..9 }));
+        assert!(region.intersects(&Region { x: 2..4, y: 2..4 }));
+        assert!(!region.intersects(&Region { x: 8..16, y: 0..8 }));
+        assert!(!region.intersects(&Region { x: 0..8, y: 8..9 }));
+    }
+
+    #[test]
+    fn test_hit_write_watchpoint() {
+        let mut debugger = Debugger::default();
+        debugger.write_watchpoints.push(Watchpoint {
+            range: 0x000..0x200,
+            pause: false,
+        });
+        debugger.write_watchpoints.push(Watchpoint {
+            range: 0x100..0x101,
+            pause: true,
+        });
+
+        assert_eq!(debugger.hit_write_watchpoint(&(0x200..0x203)), None);
+        assert!(
+            !debugger
+                .hit_write_watchpoint(&(0x1FE..0x201))
+                .unwrap()
+                .pause
+        );
+        assert!(
+            debugger
+                .hit_write_watchpoint(&(0x0FE..0x101))
+                .unwrap()
+                .pause
+        );
+    }
+
+    #[test]
+    fn test_parse_command() {
+        let mut symbols = Symbols::default();
+        symbols.add(0x2F0, "draw_ball".into());
+        let parse = |line| Command::parse(line, &symbols);
+
+        assert_eq!(parse(""), Ok(Command::Continue));
+        assert_eq!(
+            parse("mem 0x300 32"),
+            Ok(Command::Memory {
+                address: 0x300,
+                length: 32
+            })
+        );
+        assert_eq!(
+            parse("mem 768"),
+            Ok(Command::Memory {
+                address: 0x300,
+                length: 16
+            })
+        );
+        assert_eq!(parse("  step   10 "), Ok(Command::Step(10)));
+        assert_eq!(parse("break draw_ball"), Ok(Command::Break(0x2F0)));
+        assert_eq!(
+            parse("break 0x2G0"),
+            Err("Invalid number or unknown symbol `0x2G0`.".into())
+        );
+        assert_eq!(
+            parse("dump screen.txt 0xF"),
+            Ok(Command::Dump {
+                path: "screen.txt".into(),
+                sprite_rows: Some(15)
+            })
+        );
+        assert_eq!(
+            parse("memdump memory.bin"),
+            Ok(Command::MemoryDump("memory.bin".into()))
+        );
+        assert_eq!(parse("step x"), Err("Invalid number `x`.".into()));
+        assert_eq!(
+            parse("reg V0"),
+            Err("Wrong number of arguments for `reg`. Type `help` for a list of commands.".into())
+        );
+        assert_eq!(
+            parse("run"),
+            Err("Unknown command `run`. Type `help` for a list of commands.".into())
+        );
+    }
+
+    #[test]
+    fn test_format_memory() {
+        let memory: Vec<u8> = (0..=0xFF).collect();
+        assert_eq!(
+            format_memory(&memory, 0xF8, 20),
+            vec!["0x0F8  F8 F9 FA FB FC FD FE FF"]
+        );
+        assert_eq!(
+            format_memory(&memory, 0x10, 18),
+            vec![
+                "0x010  10 11 12 13 14 15 16 17 18 19 1A 1B 1C 1D 1E 1F",
+                "0x020  20 21"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_sprite() {
+        let memory = [0x00, 0xF0, 0x81, 0xFF];
+        assert_eq!(
+            format_sprite(&memory, 1, 5),
+            vec!["####....", "#......#", "########"]
+        );
+    }
+}
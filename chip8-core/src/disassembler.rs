@@ -0,0 +1,391 @@
+use crate::{
+    interpreter::{split_word, Interpreter, Nibble},
+    symbols::Symbols,
+    util,
+};
+use std::{convert::TryInto, fmt::Write};
+
+/// A single disassembled instruction.
+#[derive(Debug, PartialEq)]
+pub struct Instruction {
+    /// The address of the instruction in memory at runtime.
+    pub address: u16,
+    /// The raw bytes the instruction is made of. This is only one byte if the program has an odd length.
+    pub bytes: Vec<u8>,
+    pub mnemonic: &'static str,
+    pub operands: Vec<String>,
+    /// The address this instruction refers to, e.g. the destination of a jump.
+    pub target: Option<u16>,
+    /// The addresses of all instructions that refer to this instruction's address.
+    pub xrefs: Vec<u16>,
+    /// The name of the instruction's address, if it has a symbol.
+    pub label: Option<String>,
+}
+
+fn register(nibble: Nibble) -> String {
+    format!("V{:X}", nibble.0)
+}
+
+fn address(address: u16) -> String {
+    format!("{:#05X}", address)
+}
+
+fn byte(byte: u8) -> String {
+    format!("{:#04X}", byte)
+}
+
+/// Decodes the instruction into its mnemonic, operands and the address it refers to.
+///
+/// Unknown instructions are decoded as `DW` (define word), i.e. as plain data.
+pub fn decode(instruction: u16) -> (&'static str, Vec<String>, Option<u16>) {
+    let (nibble1, nibble2, nibble3, nibble4) = split_word(instruction);
+    let tribble = instruction & 0x0FFF;
+    let byte2 = instruction as u8;
+    let (x, y) = (register(nibble2), register(nibble3));
+
+    let (mnemonic, operands, target) = match nibble1.0 {
+        0x0 => match tribble {
+            0x0E0 => ("CLS", vec![], None),
+            0x0EE => ("RET", vec![], None),
+            0x0C0..=0x0CF => ("SCD", vec![format!("{:#X}", nibble4.0)], None),
+            0x0D0..=0x0DF => ("SCU", vec![format!("{:#X}", nibble4.0)], None),
+            0x0FB => ("SCR", vec![], None),
+            0x0FD => ("EXIT", vec![], None),
+            0x0FC => ("SCL", vec![], None),
+            _ => ("SYS", vec![address(tribble)], None),
+        },
+        0x1 => ("JP", vec![address(tribble)], Some(tribble)),
+        0x2 => ("CALL", vec![address(tribble)], Some(tribble)),
+        0x3 => ("SE", vec![x, byte(byte2)], None),
+        0x4 => ("SNE", vec![x, byte(byte2)], None),
+        0x5 if nibble4.0 == 0x0 => ("SE", vec![x, y], None),
+        0x6 => ("LD", vec![x, byte(byte2)], None),
+        0x7 => ("ADD", vec![x, byte(byte2)], None),
+        0x8 => match nibble4.0 {
+            0x0 => ("LD", vec![x, y], None),
+            0x1 => ("OR", vec![x, y], None),
+            0x2 => ("AND", vec![x, y], None),
+            0x3 => ("XOR", vec![x, y], None),
+            0x4 => ("ADD", vec![x, y], None),
+            0x5 => ("SUB", vec![x, y], None),
+            0x6 => ("SHR", vec![x], None),
+            0x7 => ("SUBN", vec![x, y], None),
+            0xE => ("SHL", vec![x], None),
+            _ => data(instruction),
+        },
+        0x9 if nibble4.0 == 0x0 => ("SNE", vec![x, y], None),
+        0xA => ("LD", vec!["I".into(), address(tribble)], Some(tribble)),
+        0xB => ("JP", vec!["V0".into(), address(tribble)], None),
+        0xC => ("RND", vec![x, byte(byte2)], None),
+        0xD => ("DRW", vec![x, y, format!("{:#X}", nibble4.0)], None),
+        0xE => match byte2 {
+            0x9E => ("SKP", vec![x], None),
+            0xA1 => ("SKNP", vec![x], None),
+            _ => data(instruction),
+        },
+        0xF => match byte2 {
+            0x07 => ("LD", vec![x, "DT".into()], None),
+            0x0A => ("LD", vec![x, "K".into()], None),
+            0x15 => ("LD", vec!["DT".into(), x], None),
+            0x18 => ("LD", vec!["ST".into(), x], None),
+            0x1E => ("ADD", vec!["I".into(), x], None),
+            0x29 => ("LD", vec!["F".into(), x], None),
+            0x30 => ("LD", vec!["HF".into(), x], None),
+            0x33 => ("LD", vec!["B".into(), x], None),
+            0x55 => ("LD", vec!["[I]".into(), x], None),
+            0x65 => ("LD", vec![x, "[I]".into()], None),
+            0x75 => ("LD", vec!["R".into(), x], None),
+            0x85 => ("LD", vec![x, "R".into()], None),
+            _ => data(instruction),
+        },
+        _ => data(instruction),
+    };
+
+    (mnemonic, operands, target)
+}
+
+fn data(instruction: u16) -> (&'static str, Vec<String>, Option<u16>) {
+    ("DW", vec![format!("{:#06X}", instruction)], None)
+}
+
+/// Disassembles the whole program, instruction by instruction, as loaded at the address.
+///
+/// Anything past the end of the address space is left out.
+pub fn disassemble(program: &[u8], load_address: u16) -> Vec<Instruction> {
+    let mut instructions: Vec<Instruction> = program
+        .chunks(2)
+        .enumerate()
+        .map_while(|(index, bytes)| {
+            let address = get_address(load_address, index * 2)?;
+
+            let (mnemonic, operands, target) = if let [byte1, byte2] = *bytes {
+                decode(Interpreter::get_instruction(byte1, byte2))
+            } else {
+                ("DB", vec![byte(bytes[0])], None)
+            };
+
+            Some(Instruction {
+                address,
+                bytes: bytes.to_vec(),
+                mnemonic,
+                operands,
+                target,
+                xrefs: Vec::new(),
+                label: None,
+            })
+        })
+        .collect();
+
+    // Now that we know all instructions, resolve which instructions refer to which.
+    let references: Vec<(u16, u16)> = instructions
+        .iter()
+        .filter_map(|instruction| Some((instruction.target?, instruction.address)))
+        .collect();
+    for (target, address) in references {
+        if let Some(instruction) = instructions
+            .iter_mut()
+            .find(|instruction| instruction.address == target)
+        {
+            instruction.xrefs.push(address);
+        }
+    }
+
+    instructions
+}
+
+/// Returns the address of the byte at the offset into the program, if it is not past the end of the address space.
+fn get_address(load_address: u16, offset: usize) -> Option<u16> {
+    load_address.checked_add(offset.try_into().ok()?)
+}
+
+/// Labels the instructions with the symbols of their addresses and replaces the addresses they refer to with their
+/// symbols.
+pub fn apply_symbols(instructions: &mut [Instruction], symbols: &Symbols) {
+    for instruction in instructions {
+        instruction.label = symbols.get_name(instruction.address).map(String::from);
+        if let Some(name) = instruction
+            .target
+            .and_then(|target| symbols.get_name(target))
+        {
+            let target = address(instruction.target.unwrap());
+            for operand in &mut instruction.operands {
+                if *operand == target {
+                    *operand = name.to_string();
+                }
+            }
+        }
+    }
+}
+
+/// Formats the instructions as human-readable text, one instruction per line. Labels are on lines of their own.
+pub fn to_text(instructions: &[Instruction]) -> String {
+    let mut text = String::new();
+
+    for instruction in instructions {
+        if let Some(label) = &instruction.label {
+            let _ = writeln!(text, "{}:", label);
+        }
+        let bytes: String = instruction
+            .bytes
+            .iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect();
+
+        let _ = write!(
+            text,
+            "{}  {:<4}  {:<4} {}",
+            address(instruction.address),
+            bytes,
+            instruction.mnemonic,
+            instruction.operands.join(", ")
+        );
+        if !instruction.xrefs.is_empty() {
+            let xrefs: Vec<String> = instruction
+                .xrefs
+                .iter()
+                .map(|xref| address(*xref))
+                .collect();
+            let _ = write!(text, "  ; referenced by {}", xrefs.join(", "));
+        }
+        text.push('\n');
+    }
+
+    text
+}
+
+/// Formats the instructions as a JSON array of records so that other tools can process them. Records of labeled
+/// instructions have a `label` too.
+pub fn to_json(instructions: &[Instruction]) -> String {
+    fn list<T>(items: &[T], format: impl Fn(&T) -> String) -> String {
+        items.iter().map(format).collect::<Vec<String>>().join(", ")
+    }
+
+    let records: Vec<String> = instructions
+        .iter()
+        .map(|instruction| {
+            let label = match &instruction.label {
+                Some(label) => format!(r#", "label": {}"#, util::to_json_string(label)),
+                None => String::new(),
+            };
+            format!(
+                r#"  {{"address": {}, "bytes": [{}], "mnemonic": {}, "operands": [{}], "xrefs": [{}]{}}}"#,
+                instruction.address,
+                list(&instruction.bytes, |byte| byte.to_string()),
+                util::to_json_string(instruction.mnemonic),
+                list(&instruction.operands, |operand| util::to_json_string(operand)),
+                list(&instruction.xrefs, |xref| xref.to_string()),
+                label,
+            )
+        })
+        .collect();
+
+    if records.is_empty() {
+        "[]\n".into()
+    } else {
+        format!("[\n{}\n]\n", records.join(",\n"))
+    }
+}
+
+/// The number of bytes in each line of a hex dump.
+const HEXDUMP_LINE_LENGTH: usize = 16;
+
+/// Formats the program as a hex dump with the addresses at runtime, as loaded at the address.
+///
+/// The bytes are grouped into instructions so that they line up with the disassembly. Every line ends with the bytes
+/// as ASCII characters, with `.` for unprintable ones. Anything past the end of the address space is left out.
+pub fn to_hexdump(program: &[u8], load_address: u16) -> String {
+    let mut text = String::new();
+
+    for (index, line) in program.chunks(HEXDUMP_LINE_LENGTH).enumerate() {
+        let start = match get_address(load_address, index * HEXDUMP_LINE_LENGTH) {
+            Some(start) => start,
+            None => break,
+        };
+        let line = &line[..line.len().min(0x10000 - start as usize)];
+        let words: Vec<String> = line
+            .chunks(2)
+            .map(|word| word.iter().map(|byte| format!("{:02X}", byte)).collect())
+            .collect();
+        let ascii: String = line
+            .iter()
+            .map(|byte| {
+                if byte.is_ascii_graphic() || *byte == b' ' {
+                    *byte as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+
+        let _ = writeln!(
+            text,
+            "{}  {:<width$}  {}",
+            address(start),
+            words.join(" "),
+            ascii,
+            // 4 digits and a space per instruction, without a space after the last one.
+            width = HEXDUMP_LINE_LENGTH / 2 * 5 - 1
+        );
+    }
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::START_POINT;
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode(0x00E0), ("CLS", vec![], None));
+        assert_eq!(decode(0x00C4), ("SCD", vec!["0x4".to_string()], None));
+        assert_eq!(
+            decode(0x1234),
+            ("JP", vec!["0x234".to_string()], Some(0x234))
+        );
+        assert_eq!(
+            decode(0x8AB4),
+            ("ADD", vec!["VA".to_string(), "VB".to_string()], None)
+        );
+        assert_eq!(
+            decode(0xF155),
+            ("LD", vec!["[I]".to_string(), "V1".to_string()], None)
+        );
+        assert_eq!(decode(0x8008), ("DW", vec!["0x8008".to_string()], None));
+    }
+
+    #[test]
+    fn test_xrefs() {
+        // 0x200: JP 0x204
+        // 0x202: CALL 0x204
+        // 0x204: RET
+        // 0x206: one byte of data
+        let instructions = disassemble(&[0x12, 0x04, 0x22, 0x04, 0x00, 0xEE, 0xFF], START_POINT);
+
+        assert_eq!(instructions.len(), 4);
+        assert_eq!(instructions[2].xrefs, vec![0x200, 0x202]);
+        assert_eq!(instructions[3].mnemonic, "DB");
+        assert_eq!(instructions[3].bytes, vec![0xFF]);
+
+        // 0x600: JP 0x602
+        // 0x602: JP 0x600
+        let instructions = disassemble(&[0x16, 0x02, 0x16, 0x00], 0x600);
+        assert_eq!(instructions[0].xrefs, vec![0x602]);
+        assert_eq!(instructions[1].xrefs, vec![0x600]);
+
+        assert_eq!(disassemble(&[0x00, 0xE0, 0x00, 0xE0], 0xFFFE).len(), 1);
+    }
+
+    #[test]
+    fn test_json() {
+        let instructions = disassemble(&[0x12, 0x00], START_POINT);
+
+        assert_eq!(
+            to_json(&instructions),
+            "[\n  {\"address\": 512, \"bytes\": [18, 0], \"mnemonic\": \"JP\", \"operands\": [\"0x200\"], \"xrefs\": [512]}\n]\n"
+        );
+    }
+
+    #[test]
+    fn test_apply_symbols() {
+        let mut symbols = Symbols::default();
+        symbols.add(0x202, "loop".into());
+        // 0x200: LD V0, 0x02
+        // 0x202: JP 0x202
+        let mut instructions = disassemble(&[0x60, 0x02, 0x12, 0x02], START_POINT);
+        apply_symbols(&mut instructions, &symbols);
+
+        assert_eq!(
+            to_text(&instructions),
+            "0x200  6002  LD   V0, 0x02\n\
+             loop:\n\
+             0x202  1202  JP   loop  ; referenced by 0x202\n"
+        );
+        assert!(to_json(&instructions)
+            .contains(r#""operands": ["loop"], "xrefs": [514], "label": "loop"}"#));
+
+        let mut symbols = Symbols::default();
+        symbols.add(0x200, r#"ball"x"#.into());
+        let mut instructions = disassemble(&[0x12, 0x00], START_POINT);
+        apply_symbols(&mut instructions, &symbols);
+        assert!(to_json(&instructions)
+            .contains(r#""operands": ["ball\"x"], "xrefs": [512], "label": "ball\"x"}"#));
+    }
+
+    #[test]
+    fn test_to_hexdump() {
+        let mut program: Vec<u8> = (0..16).collect();
+        program.extend(b"AB!");
+
+        assert_eq!(
+            to_hexdump(&program, START_POINT),
+            "0x200  0001 0203 0405 0607 0809 0A0B 0C0D 0E0F  ................\n\
+             0x210  4142 21                                  AB!\n"
+        );
+        assert_eq!(
+            to_hexdump(&program, 0xFFF0),
+            "0xFFF0  0001 0203 0405 0607 0809 0A0B 0C0D 0E0F  ................\n"
+        );
+    }
+}
@@ -0,0 +1,827 @@
+use crate::util::Bits;
+
+/// A position on the display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Point {
+    pub x: u16,
+    pub y: u16,
+}
+
+/// A pixel that changed, with its new value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelChange {
+    pub point: Point,
+    /// Whether the pixel is now set in any plane, i.e. not black.
+    pub set: bool,
+}
+
+/// How many planes XO-CHIP programs can draw on.
+pub const PLANE_COUNT: u8 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Size {
+    pub width: u16,
+    pub height: u16,
+}
+
+/// How much larger the display is than the screen in each direction.
+const MARGIN: u16 = 10;
+
+pub const SIZE: Size = get_size(SCREEN_SIZE);
+
+/// The size of the screen programs draw on. The display has some additional margin around it.
+pub const SCREEN_SIZE: Size = Size {
+    width: 64,
+    height: 32,
+};
+
+/// The size of the screen of the hi-res mode of the COSMAC VIP, which has twice as many rows.
+pub const HIRES_SCREEN_SIZE: Size = Size {
+    width: 64,
+    height: 64,
+};
+
+/// Returns the size of the display with the given screen, including the margin.
+pub const fn get_size(screen_size: Size) -> Size {
+    Size {
+        width: screen_size.width + MARGIN,
+        height: screen_size.height + MARGIN,
+    }
+}
+
+/// The display where the graphics are drawn on.
+///
+/// The display has two planes, of which normal programs only use the first, so that they see a monochrome display.
+/// XO-CHIP programs can select which planes are drawn on and cleared. The color of every pixel is a bitmask of the
+/// planes it is set in, so there are four colors, of which `0` is black and `1` is white.
+///
+/// It keeps track of the pixels that changed so that a frontend only needs to draw those, e.g. once per frame.
+#[derive(Debug)]
+pub struct Display {
+    screen_size: Size,
+    /// The colors of the pixels, row by row.
+    grid: Vec<Vec<u8>>,
+    /// The bitmask of the planes that are drawn on and cleared.
+    planes: u8,
+    /// The points of the pixels that sprites turned off since the collisions were last taken, if they are tracked.
+    collisions: Option<Vec<Point>>,
+    /// The colors of the pixels when the changes were last taken with [`Display::take_changes`].
+    ///
+    /// A pixel that is flipped twice in between, e.g. by drawing a sprite twice to move it, did not change.
+    taken: Vec<Vec<u8>>,
+}
+
+impl Display {
+    /// Creates a display with the normal screen of [`SCREEN_SIZE`].
+    pub fn new() -> Self {
+        Self::with_screen_size(SCREEN_SIZE)
+    }
+
+    /// Creates a display with a screen of the given size, like [`HIRES_SCREEN_SIZE`].
+    pub fn with_screen_size(screen_size: Size) -> Self {
+        let size = get_size(screen_size);
+        let grid = vec![vec![0; size.width as usize]; size.height as usize];
+        Self {
+            screen_size,
+            taken: grid.clone(),
+            grid,
+            planes: 0b01,
+            collisions: None,
+        }
+    }
+
+    /// Returns the size of the whole display, including the margin around the screen.
+    pub fn size(&self) -> Size {
+        get_size(self.screen_size)
+    }
+
+    /// Returns the size of the screen programs draw on.
+    pub fn screen_size(&self) -> Size {
+        self.screen_size
+    }
+
+    /// Starts keeping track of the pixels that sprites turn off, which set VF, to show them when debugging.
+    pub fn track_collisions(&mut self) {
+        self.collisions.get_or_insert_with(Vec::new);
+    }
+
+    /// Returns the points of the pixels that sprites turned off since the last call, if collisions are tracked, and
+    /// forgets them.
+    pub fn take_collisions(&mut self) -> Vec<Point> {
+        self.collisions
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    /// Returns the bitmask of the planes that are drawn on and cleared.
+    pub fn selected_planes(&self) -> u8 {
+        self.planes
+    }
+
+    /// Selects the planes to draw on and clear by a bitmask, with `0b01` for the first plane and `0b10` for the
+    /// second one.
+    pub fn select_planes(&mut self, planes: u8) {
+        self.planes = planes & 0b11;
+    }
+
+    /// Returns how many planes are selected, i.e. how many sprites [`Display::draw_sprite`] draws.
+    pub fn selected_plane_count(&self) -> u16 {
+        self.planes.count_ones() as u16
+    }
+
+    /// Returns whether the pixel is set in any plane.
+    pub fn get(&self, point: Point) -> bool {
+        self.get_color(point) != 0
+    }
+
+    /// Returns the color of the pixel, the bitmask of the planes it is set in.
+    pub fn get_color(&self, point: Point) -> u8 {
+        self.grid[point.y as usize][point.x as usize]
+    }
+
+    fn xor(&mut self, point: Point, planes: u8) {
+        self.grid[point.y as usize][point.x as usize] ^= planes;
+    }
+
+    /// Returns all pixels of the plane, `0` being the first, row by row.
+    pub fn get_pixels(&self, plane: u8) -> Vec<bool> {
+        self.grid
+            .iter()
+            .flatten()
+            .map(|color| color & (1 << plane) != 0)
+            .collect()
+    }
+
+    /// Replaces all pixels of the plane, `0` being the first, row by row.
+    pub fn set_pixels(&mut self, plane: u8, pixels: &[bool]) {
+        for (color, pixel) in self.grid.iter_mut().flatten().zip(pixels) {
+            *color = *color & !(1 << plane) | (*pixel as u8) << plane;
+        }
+    }
+
+    /// Returns the screen as a bitmap of 8 pixels per byte, row by row from the top left,
+    /// with the most significant bit being the leftmost pixel.
+    ///
+    /// A pixel is set if it is set in any plane.
+    pub fn get_frame(&self) -> Vec<u8> {
+        self.grid[..self.screen_size.height as usize]
+            .iter()
+            .flat_map(|row| row[..self.screen_size.width as usize].chunks(8))
+            .map(|pixels| {
+                pixels
+                    .iter()
+                    .fold(0, |byte, color| (byte << 1) | (*color != 0) as u8)
+            })
+            .collect()
+    }
+
+    /// Returns the points of all pixels whose color changed since the last call, row by row, and forgets them.
+    pub fn take_changes(&mut self) -> Vec<Point> {
+        let mut changes = Vec::new();
+        for (y, (row, taken_row)) in self.grid.iter().zip(&mut self.taken).enumerate() {
+            for (x, (color, taken)) in row.iter().zip(taken_row).enumerate() {
+                if color != taken {
+                    *taken = *color;
+                    changes.push(Point {
+                        x: x as u16,
+                        y: y as u16,
+                    });
+                }
+            }
+        }
+        changes
+    }
+
+    /// Returns all pixels that changed since the changes were last taken, with their new values, row by row.
+    ///
+    /// This lets frontends update incrementally, e.g. once per frame, without comparing whole frames themselves.
+    pub fn take_delta(&mut self) -> Vec<PixelChange> {
+        self.take_changes()
+            .into_iter()
+            .map(|point| PixelChange {
+                point,
+                set: self.get(point),
+            })
+            .collect()
+    }
+
+    /// Clears the selected planes.
+    pub fn clear(&mut self) {
+        let planes = self.planes;
+        for color in self.grid.iter_mut().flatten() {
+            *color &= !planes;
+        }
+    }
+
+    /// Shifts the selected planes of the screen by the given number of pixels, to the right and down for positive
+    /// offsets. Pixels shifted out of the screen are lost and the ones shifted in are unset.
+    pub fn scroll(&mut self, x_offset: i16, y_offset: i16) {
+        let screen_size = self.screen_size;
+        let planes = self.planes;
+        let previous = self.grid.clone();
+        for y in 0..screen_size.height {
+            for x in 0..screen_size.width {
+                let source_x = x as i16 - x_offset;
+                let source_y = y as i16 - y_offset;
+                let source = if (0..screen_size.width as i16).contains(&source_x)
+                    && (0..screen_size.height as i16).contains(&source_y)
+                {
+                    previous[source_y as usize][source_x as usize] & planes
+                } else {
+                    0
+                };
+                let color = &mut self.grid[y as usize][x as usize];
+                *color = *color & !planes | source;
+            }
+        }
+    }
+
+    /// Draws the sprite on every selected plane and returns whether any screen pixel is flipped from set to unset in
+    /// any of them.
+    ///
+    /// With both planes selected, the bytes are the sprite of the first plane followed by the one of the second plane,
+    /// each of half the bytes.
+    ///
+    /// The starting point wraps around the screen. Pixels past the right or bottom edge wrap around to the other side
+    /// if `wrap` is set and are clipped otherwise.
+    pub fn draw_sprite(&mut self, point: Point, bytes: &[u8], wrap: bool) -> bool {
+        let plane_count = self.selected_plane_count() as usize;
+        if plane_count == 0 {
+            return false;
+        }
+
+        let selected = self.planes;
+        let planes = (0..PLANE_COUNT)
+            .map(|plane| 1 << plane)
+            .filter(|plane| selected & plane != 0);
+        let mut collision = false;
+        for (plane, sprite) in planes.zip(bytes.chunks(bytes.len().div_ceil(plane_count).max(1))) {
+            collision |= self.draw_plane_sprite(point, sprite, plane, wrap);
+        }
+        collision
+    }
+
+    /// Draws the sprite on the plane given as a bitmask and returns whether any screen pixel of it is flipped from set
+    /// to unset.
+    fn draw_plane_sprite(&mut self, point: Point, bytes: &[u8], plane: u8, wrap: bool) -> bool {
+        let screen_size = self.screen_size;
+        let start = Point {
+            x: point.x % screen_size.width,
+            y: point.y % screen_size.height,
+        };
+
+        let mut collision = false;
+        for (row, byte) in bytes.iter().enumerate() {
+            for (column, bit) in Bits::new(*byte).enumerate() {
+                let mut point = Point {
+                    x: start.x + column as u16,
+                    y: start.y + row as u16,
+                };
+                if wrap {
+                    point.x %= screen_size.width;
+                    point.y %= screen_size.height;
+                } else if point.x >= screen_size.width || point.y >= screen_size.height {
+                    continue;
+                }
+
+                if bit {
+                    if self.get_color(point) & plane != 0 {
+                        collision = true;
+                        if let Some(collisions) = &mut self.collisions {
+                            collisions.push(point);
+                        }
+                    }
+                    self.xor(point, plane);
+                }
+            }
+        }
+
+        collision
+    }
+}
+
+impl Default for Display {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// How many bytes each character of the font takes up.
+pub const FONT_CHARACTER_SIZE: u16 = 7;
+
+// The 4x5 inbuilt font.
+#[rustfmt::skip]
+pub const FONT: [u8; 16 * FONT_CHARACTER_SIZE as usize] = [
+    // 0
+    0b11110000,
+    0b10010000,
+    0b10010000,
+    0b10010000,
+    0b11110000,
+    0b00000000,
+    0b00000000,
+
+    // 1
+    0b00110000,
+    0b01010000,
+    0b10010000,
+    0b00010000,
+    0b00010000,
+    0b00000000,
+    0b00000000,
+
+    // 2
+    0b01110000,
+    0b10010000,
+    0b00110000,
+    0b01000000,
+    0b11110000,
+    0b00000000,
+    0b00000000,
+
+    // 3
+    0b01100000,
+    0b10010000,
+    0b00110000,
+    0b10010000,
+    0b01100000,
+    0b00000000,
+    0b00000000,
+
+    // 4
+    0b10010000,
+    0b10010000,
+    0b11110000,
+    0b00010000,
+    0b00010000,
+    0b00000000,
+    0b00000000,
+
+    // 5
+    0b11110000,
+    0b10000000,
+    0b11100000,
+    0b00010000,
+    0b11100000,
+    0b00000000,
+    0b00000000,
+
+    // 6
+    0b01110000,
+    0b10000000,
+    0b11100000,
+    0b10010000,
+    0b01100000,
+    0b00000000,
+    0b00000000,
+
+    // 7
+    0b11110000,
+    0b00010000,
+    0b00100000,
+    0b01000000,
+    0b01000000,
+    0b00000000,
+    0b00000000,
+
+    // 8
+    0b01100000,
+    0b10010000,
+    0b01100000,
+    0b10010000,
+    0b01100000,
+    0b00000000,
+    0b00000000,
+
+    // 9
+    0b01100000,
+    0b10010000,
+    0b01110000,
+    0b00010000,
+    0b01100000,
+    0b00000000,
+    0b00000000,
+
+    // A
+    0b01100000,
+    0b10010000,
+    0b11110000,
+    0b10010000,
+    0b10010000,
+    0b00000000,
+    0b00000000,
+
+    // B
+    0b11100000,
+    0b10010000,
+    0b11100000,
+    0b10010000,
+    0b11100000,
+    0b00000000,
+    0b00000000,
+
+    // C
+    0b01100000,
+    0b10010000,
+    0b10000000,
+    0b10010000,
+    0b01100000,
+    0b00000000,
+    0b00000000,
+
+    // D
+    0b11100000,
+    0b10010000,
+    0b10010000,
+    0b10010000,
+    0b11100000,
+    0b00000000,
+    0b00000000,
+
+    // E
+    0b11110000,
+    0b10000000,
+    0b11110000,
+    0b10000000,
+    0b11110000,
+    0b00000000,
+    0b00000000,
+
+    // F
+    0b11110000,
+    0b10000000,
+    0b11110000,
+    0b10000000,
+    0b10000000,
+    0b00000000,
+    0b00000000,
+];
+
+/// How many bytes each character of the big font takes up.
+pub const BIG_FONT_CHARACTER_SIZE: u16 = 10;
+
+/// Where the big font is in memory, right after the small one.
+pub const BIG_FONT_ADDRESS: u16 = FONT.len() as u16;
+
+// The 8x10 font of SUPER-CHIP for drawing large digits, e.g. scores in the hi-res mode, with the letters of XO-CHIP.
+#[rustfmt::skip]
+pub const BIG_FONT: [u8; 16 * BIG_FONT_CHARACTER_SIZE as usize] = [
+    // 0
+    0b11111111,
+    0b11111111,
+    0b11000011,
+    0b11000011,
+    0b11000011,
+    0b11000011,
+    0b11000011,
+    0b11000011,
+    0b11111111,
+    0b11111111,
+
+    // 1
+    0b00011000,
+    0b01111000,
+    0b01111000,
+    0b00011000,
+    0b00011000,
+    0b00011000,
+    0b00011000,
+    0b00011000,
+    0b11111111,
+    0b11111111,
+
+    // 2
+    0b11111111,
+    0b11111111,
+    0b00000011,
+    0b00000011,
+    0b11111111,
+    0b11111111,
+    0b11000000,
+    0b11000000,
+    0b11111111,
+    0b11111111,
+
+    // 3
+    0b11111111,
+    0b11111111,
+    0b00000011,
+    0b00000011,
+    0b11111111,
+    0b11111111,
+    0b00000011,
+    0b00000011,
+    0b11111111,
+    0b11111111,
+
+    // 4
+    0b11000011,
+    0b11000011,
+    0b11000011,
+    0b11000011,
+    0b11111111,
+    0b11111111,
+    0b00000011,
+    0b00000011,
+    0b00000011,
+    0b00000011,
+
+    // 5
+    0b11111111,
+    0b11111111,
+    0b11000000,
+    0b11000000,
+    0b11111111,
+    0b11111111,
+    0b00000011,
+    0b00000011,
+    0b11111111,
+    0b11111111,
+
+    // 6
+    0b11111111,
+    0b11111111,
+    0b11000000,
+    0b11000000,
+    0b11111111,
+    0b11111111,
+    0b11000011,
+    0b11000011,
+    0b11111111,
+    0b11111111,
+
+    // 7
+    0b11111111,
+    0b11111111,
+    0b00000011,
+    0b00000011,
+    0b00000110,
+    0b00001100,
+    0b00011000,
+    0b00011000,
+    0b00011000,
+    0b00011000,
+
+    // 8
+    0b11111111,
+    0b11111111,
+    0b11000011,
+    0b11000011,
+    0b11111111,
+    0b11111111,
+    0b11000011,
+    0b11000011,
+    0b11111111,
+    0b11111111,
+
+    // 9
+    0b11111111,
+    0b11111111,
+    0b11000011,
+    0b11000011,
+    0b11111111,
+    0b11111111,
+    0b00000011,
+    0b00000011,
+    0b11111111,
+    0b11111111,
+
+    // A
+    0b01111110,
+    0b11111111,
+    0b11000011,
+    0b11000011,
+    0b11000011,
+    0b11111111,
+    0b11111111,
+    0b11000011,
+    0b11000011,
+    0b11000011,
+
+    // B
+    0b11111100,
+    0b11111100,
+    0b11000011,
+    0b11000011,
+    0b11111100,
+    0b11111100,
+    0b11000011,
+    0b11000011,
+    0b11111100,
+    0b11111100,
+
+    // C
+    0b00111100,
+    0b11111111,
+    0b11000011,
+    0b11000000,
+    0b11000000,
+    0b11000000,
+    0b11000000,
+    0b11000011,
+    0b11111111,
+    0b00111100,
+
+    // D
+    0b11111100,
+    0b11111110,
+    0b11000011,
+    0b11000011,
+    0b11000011,
+    0b11000011,
+    0b11000011,
+    0b11000011,
+    0b11111110,
+    0b11111100,
+
+    // E
+    0b11111111,
+    0b11111111,
+    0b11000000,
+    0b11000000,
+    0b11111111,
+    0b11111111,
+    0b11000000,
+    0b11000000,
+    0b11111111,
+    0b11111111,
+
+    // F
+    0b11111111,
+    0b11111111,
+    0b11000000,
+    0b11000000,
+    0b11111111,
+    0b11111111,
+    0b11000000,
+    0b11000000,
+    0b11000000,
+    0b11000000,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_draw_sprite_edges() {
+        let mut display = Display::new();
+        let corner = Point {
+            x: SCREEN_SIZE.width - 4,
+            y: SCREEN_SIZE.height - 1,
+        };
+
+        assert!(!display.draw_sprite(corner, &[0xFF, 0xFF], false));
+        assert!(display.get(Point { x: 63, y: 31 }));
+        assert!(!display.get(Point { x: 0, y: 31 }));
+        assert!(!display.get(Point { x: 64, y: 31 }));
+        assert!(!display.get(Point { x: 60, y: 0 }));
+        display.clear();
+
+        assert!(!display.draw_sprite(corner, &[0xFF, 0xFF], true));
+        assert!(display.get(Point { x: 63, y: 31 }));
+        assert!(display.get(Point { x: 3, y: 31 }));
+        assert!(display.get(Point { x: 60, y: 0 }));
+        assert!(display.get(Point { x: 3, y: 0 }));
+        assert!(!display.get(Point { x: 64, y: 31 }));
+
+        // The starting point wraps around either way.
+        display.clear();
+        display.draw_sprite(
+            Point {
+                x: 64 + 2,
+                y: 32 + 1,
+            },
+            &[0x80],
+            false,
+        );
+        assert!(display.get(Point { x: 2, y: 1 }));
+    }
+
+    #[test]
+    fn test_take_changes() {
+        let mut display = Display::new();
+
+        display.draw_sprite(Point { x: 0, y: 0 }, &[0xC0], false);
+        assert_eq!(
+            display.take_changes(),
+            vec![Point { x: 0, y: 0 }, Point { x: 1, y: 0 }]
+        );
+        assert_eq!(display.take_changes(), vec![]);
+
+        // Moving a sprite only changes the pixels that end up different.
+        display.draw_sprite(Point { x: 0, y: 0 }, &[0xC0], false);
+        display.draw_sprite(Point { x: 1, y: 0 }, &[0xC0], false);
+        assert_eq!(
+            display.take_changes(),
+            vec![Point { x: 0, y: 0 }, Point { x: 2, y: 0 }]
+        );
+    }
+
+    #[test]
+    fn test_take_delta() {
+        let mut display = Display::new();
+
+        display.draw_sprite(Point { x: 0, y: 0 }, &[0x80], false);
+        display.take_changes();
+        display.draw_sprite(Point { x: 0, y: 0 }, &[0xC0], false);
+        assert_eq!(
+            display.take_delta(),
+            vec![
+                PixelChange {
+                    point: Point { x: 0, y: 0 },
+                    set: false
+                },
+                PixelChange {
+                    point: Point { x: 1, y: 0 },
+                    set: true
+                }
+            ]
+        );
+        assert_eq!(display.take_delta(), vec![]);
+    }
+
+    #[test]
+    fn test_planes() {
+        let mut display = Display::new();
+        let origin = Point { x: 0, y: 0 };
+
+        display.select_planes(0b11);
+        assert!(!display.draw_sprite(origin, &[0xC0, 0x60], false));
+        let colors: Vec<u8> = (0..3)
+            .map(|x| display.get_color(Point { x, y: 0 }))
+            .collect();
+        assert_eq!(colors, [0b01, 0b11, 0b10]);
+        display.take_changes();
+
+        // Collisions are only detected on the selected planes.
+        display.select_planes(0b10);
+        assert!(!display.draw_sprite(origin, &[0x80], false));
+        assert_eq!(display.take_changes(), vec![origin]);
+        assert!(display.draw_sprite(origin, &[0x80], false));
+
+        display.clear();
+        assert_eq!(display.get_color(Point { x: 1, y: 0 }), 0b01);
+        assert!(!display.get(Point { x: 2, y: 0 }));
+        assert_eq!(display.get_frame()[0], 0b1100_0000);
+        assert_eq!(display.get_pixels(1)[..3], [false, false, false]);
+    }
+
+    #[test]
+    fn test_collisions() {
+        let mut display = Display::new();
+        display.draw_sprite(Point { x: 0, y: 0 }, &[0xC0], false);
+        display.draw_sprite(Point { x: 1, y: 0 }, &[0xC0], false);
+        assert_eq!(display.take_collisions(), vec![]);
+
+        display.track_collisions();
+        display.draw_sprite(Point { x: 2, y: 0 }, &[0xC0], false);
+        assert_eq!(display.take_collisions(), vec![Point { x: 2, y: 0 }]);
+        assert_eq!(display.take_collisions(), vec![]);
+    }
+
+    #[test]
+    fn test_scroll() {
+        let mut display = Display::new();
+        display.draw_sprite(Point { x: 0, y: 0 }, &[0x80], false);
+        display.draw_sprite(Point { x: 63, y: 31 }, &[0x80], false);
+
+        display.scroll(0, 2);
+        assert!(display.get(Point { x: 0, y: 2 }));
+        assert!(!display.get(Point { x: 0, y: 0 }));
+        // Pixels do not move into the margin.
+        assert!(!display.get(Point { x: 63, y: 33 }));
+
+        display.scroll(-4, 0);
+        assert!(!display.get(Point { x: 0, y: 2 }));
+        display.scroll(4, -2);
+        assert!(!display.get(Point { x: 4, y: 0 }));
+
+        // Only the selected planes scroll.
+        display.clear();
+        display.select_planes(0b11);
+        display.draw_sprite(Point { x: 0, y: 0 }, &[0x80, 0x80], false);
+        display.select_planes(0b10);
+        display.scroll(1, 0);
+        assert_eq!(display.get_color(Point { x: 0, y: 0 }), 0b01);
+        assert_eq!(display.get_color(Point { x: 1, y: 0 }), 0b10);
+    }
+
+    #[test]
+    fn test_hires() {
+        let mut display = Display::with_screen_size(HIRES_SCREEN_SIZE);
+        assert_eq!(
+            display.size(),
+            Size {
+                width: 74,
+                height: 74
+            }
+        );
+
+        display.draw_sprite(Point { x: 0, y: 63 }, &[0x80, 0x80], true);
+        assert!(display.get(Point { x: 0, y: 63 }));
+        assert!(display.get(Point { x: 0, y: 0 }));
+        assert_eq!(display.get_frame().len(), 64 * 64 / 8);
+    }
+}
@@ -0,0 +1,1721 @@
+use crate::{
+    debugger::{Debugger, Region},
+    disassembler,
+    display::{self, Display, Point},
+    metrics::Metrics,
+    quirks::{MemoryEnd, Quirks},
+    savestate::State,
+    util, Error,
+};
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use std::{collections::VecDeque, fmt, iter, ops::Range};
+
+const GENERAL_PURPOSE_REGISTER_COUNT: usize = 16;
+pub const MEMORY_SIZE: usize = 0x1000;
+/// The size of the memory with [`Quirks::xo_chip`], which addresses of 16 bits cover.
+pub const XO_CHIP_MEMORY_SIZE: usize = 0x10000;
+const CALL_STACK_RANGE: Range<usize> = 0xEA0..0xEFF;
+pub const START_POINT: u16 = 0x200;
+/// How many return addresses the stack holds, usually and with [`Quirks::small_stack`].
+const STACK_SIZE: usize = 16;
+const SMALL_STACK_SIZE: usize = 12;
+/// How many flags SUPER-CHIP programs can save with `FX75`, which are kept when the program is run again.
+pub const FLAG_COUNT: usize = 8;
+/// How many of the most recently executed instructions errors list.
+const RECENT_INSTRUCTION_COUNT: usize = 8;
+
+pub struct Interpreter {
+    /// The program counter, indicating where we are in the program.
+    pc: Tribble,
+    /// General purpose registers.
+    ///
+    /// There are 16 registers named V0 to VF. VF is a flag register.
+    gpr: [u8; GENERAL_PURPOSE_REGISTER_COUNT],
+    /// The address register.
+    i: Tribble,
+    display: Display,
+    /// The stack. It is only used to store return addresses when subroutines are called.
+    // TODO: Should it be merged into `memory`?
+    stack: Vec<Tribble>,
+    /// The available memory, whose size depends on the quirks the interpreter was created with.
+    memory: Vec<u8>,
+    /// The random number generator.
+    rng: SmallRng,
+    /// The delay timer. It decrements at a speed of 60 hertz until it reaches 0.
+    delay_timer: u8,
+    /// The sound timer. It decrements at a speed of 60 hertz until it reaches 0.
+    /// If it's not zero, a beeping sound is made.
+    sound_timer: u8,
+    debugger: Debugger,
+    /// The flags saved with `FX75`, which are named after the RPL user flags of the HP-48 calculators SUPER-CHIP ran
+    /// on. Frontends can keep them across runs, e.g. for high scores.
+    flags: [u8; FLAG_COUNT],
+    /// The key that was just pressed on the keypad, as set by the frontend.
+    key: Option<u8>,
+    /// Which keys of the keypad are currently held down, as set by the frontend.
+    held_keys: [bool; 16],
+    /// The number of instructions executed so far.
+    instruction_count: u64,
+    /// The addresses of the most recently executed instructions, oldest first, for errors.
+    recent_instructions: VecDeque<u16>,
+    /// Whether the breakpoint that was hit at the current instruction has already been reported,
+    /// so that the next step executes the instruction instead of stopping again.
+    breakpoint_reported: bool,
+    /// How to behave where implementations differ.
+    quirks: Quirks,
+    /// The key pressed while waiting for a key, if it has to be released before the program continues.
+    pressed_key: Option<u8>,
+    /// The hash of the program, to only load savestates taken of the same program.
+    rom_hash: u64,
+    metrics: Metrics,
+}
+
+/// The values of all registers, to inspect the state of the interpreter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Registers {
+    pub pc: u16,
+    pub gpr: [u8; GENERAL_PURPOSE_REGISTER_COUNT],
+    pub i: u16,
+    pub delay_timer: u8,
+    pub sound_timer: u8,
+}
+
+/// What happened in a step of execution.
+#[derive(Debug, PartialEq)]
+pub enum Step {
+    /// An instruction was executed.
+    Executed,
+    /// The program is waiting for a key to be pressed. The instruction is executed once a key is set.
+    AwaitingKey,
+    /// A breakpoint was hit before the instruction was executed. The next step executes it.
+    Breakpoint(String),
+    /// The program exited with `00FD` or the end of the memory has been reached.
+    Ended,
+}
+
+/// Returns the range of memory the instruction writes to with the given address register, if any.
+pub fn get_write_range(instruction: u16, i: u16) -> Option<Range<u16>> {
+    let length = match instruction & 0xF0FF {
+        0xF033 => 3,
+        0xF055 => ((instruction >> 8) & 0xF) + 1,
+        _ => return None,
+    };
+
+    Some(i..i.saturating_add(length))
+}
+
+/// Returns whether the program is for the hi-res mode of the COSMAC VIP with a 64x64 screen.
+///
+/// These programs start by jumping to `0x260`, past where the interpreter that supports the mode was loaded.
+pub fn is_hires(program: &[u8]) -> bool {
+    program.starts_with(&[0x12, 0x60])
+}
+
+impl Interpreter {
+    /// Creates an interpreter for the program. Programs for the hi-res mode get a 64x64 screen, see [`is_hires`].
+    ///
+    /// The seed is used for the random number generator so that runs can be reproduced.
+    pub fn new(program: Vec<u8>, seed: u64) -> Result<Self, Error> {
+        Self::with_load_address(program, seed, START_POINT)
+    }
+
+    /// Creates an interpreter for the program loaded at the given address instead of [`START_POINT`], where it also
+    /// starts, e.g. at `0x600` for programs of the ETI-660.
+    pub fn with_load_address(
+        program: Vec<u8>,
+        seed: u64,
+        load_address: u16,
+    ) -> Result<Self, Error> {
+        Self::with_quirks(program, seed, load_address, Quirks::default())
+    }
+
+    /// Creates an interpreter for the program loaded at the given address, behaving as the quirks say from the start.
+    ///
+    /// Unlike the other quirks, [`Quirks::xo_chip`] cannot be changed later as it decides the size of the memory.
+    pub fn with_quirks(
+        program: Vec<u8>,
+        seed: u64,
+        load_address: u16,
+        quirks: Quirks,
+    ) -> Result<Self, Error> {
+        /// Loads the inbuilt 4x5 font and the 8x10 font after it into memory.
+        fn load_font(memory: &mut [u8]) {
+            for (i, char) in display::FONT.iter().chain(&display::BIG_FONT).enumerate() {
+                memory[i] = *char;
+            }
+        }
+
+        let memory_size = quirks.get_memory_size();
+        let mut memory = vec![0; memory_size];
+        load_font(&mut memory);
+
+        if load_address as usize >= memory_size {
+            return Err(format!("The load address is past {:#05X}.", memory_size - 1).into());
+        }
+        for (i, program_byte) in program.iter().enumerate() {
+            if let Some(memory_byte) = memory.get_mut(load_address as usize + i) {
+                *memory_byte = *program_byte;
+            } else {
+                return Err(format!(
+                    "Program is bigger than {} bytes.",
+                    memory_size - load_address as usize
+                )
+                .into());
+            }
+        }
+
+        Ok(Self {
+            pc: Tribble(load_address),
+            gpr: [0; 16],
+            i: Tribble(0x000),
+            display: if is_hires(&program) {
+                Display::with_screen_size(display::HIRES_SCREEN_SIZE)
+            } else {
+                Display::new()
+            },
+            stack: Vec::<Tribble>::new(),
+            memory,
+            rng: SmallRng::seed_from_u64(seed),
+            delay_timer: 0,
+            sound_timer: 0,
+            debugger: Debugger::default(),
+            flags: [0; FLAG_COUNT],
+            key: None,
+            held_keys: [false; 16],
+            instruction_count: 0,
+            recent_instructions: VecDeque::with_capacity(RECENT_INSTRUCTION_COUNT),
+            breakpoint_reported: false,
+            quirks,
+            pressed_key: None,
+            rom_hash: util::hash(&program),
+            metrics: Metrics::default(),
+        })
+    }
+
+    /// Returns the flags saved with `FX75`.
+    pub fn flags(&self) -> [u8; FLAG_COUNT] {
+        self.flags
+    }
+
+    /// Sets the flags loaded with `FX85`, e.g. to the ones saved in an earlier run.
+    pub fn set_flags(&mut self, flags: [u8; FLAG_COUNT]) {
+        self.flags = flags;
+    }
+
+    pub fn display(&self) -> &Display {
+        &self.display
+    }
+
+    /// Returns the display mutably, e.g. to take the pixels that changed.
+    pub fn display_mut(&mut self) -> &mut Display {
+        &mut self.display
+    }
+
+    /// Returns the memory mutably, e.g. for devices mapped into it.
+    pub fn memory(&self) -> &[u8] {
+        &self.memory
+    }
+
+    pub fn memory_mut(&mut self) -> &mut [u8] {
+        &mut self.memory
+    }
+
+    pub fn debugger(&self) -> &Debugger {
+        &self.debugger
+    }
+
+    pub fn debugger_mut(&mut self) -> &mut Debugger {
+        &mut self.debugger
+    }
+
+    pub fn quirks_mut(&mut self) -> &mut Quirks {
+        &mut self.quirks
+    }
+
+    /// Sets the key that was just pressed on the keypad, or `None` if no key was pressed.
+    ///
+    /// This is the key a program waiting for a key press gets. Whether keys are held down is set separately with
+    /// [`Interpreter::set_key_held`].
+    pub fn set_key(&mut self, key: Option<u8>) {
+        if key.is_some() {
+            self.metrics.key_events += 1;
+        }
+        self.key = key;
+    }
+
+    /// Sets whether the key is held down, which the program can check at any time.
+    pub fn set_key_held(&mut self, key: u8, held: bool) {
+        self.held_keys[key as usize & 0xF] = held;
+    }
+
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    /// Returns the metrics mutably, e.g. to count what only the frontend knows about.
+    pub fn metrics_mut(&mut self) -> &mut Metrics {
+        &mut self.metrics
+    }
+
+    /// Returns the number of instructions executed so far.
+    pub fn instruction_count(&self) -> u64 {
+        self.instruction_count
+    }
+
+    pub fn get_registers(&self) -> Registers {
+        Registers {
+            pc: self.pc.0,
+            gpr: self.gpr,
+            i: self.i.0,
+            delay_timer: self.delay_timer,
+            sound_timer: self.sound_timer,
+        }
+    }
+
+    /// Returns the instruction about to be executed, or `None` if the end of the memory has been reached.
+    pub fn get_current_instruction(&self) -> Option<u16> {
+        self.get_bytes()
+            .map(|(byte1, byte2)| Self::get_instruction(byte1, byte2))
+    }
+}
+
+impl Interpreter {
+    /// Makes the snapshots taken from now on be for the program, which was changed in memory while running.
+    ///
+    /// Snapshots taken before are for the old program and can no longer be loaded.
+    pub fn set_program(&mut self, program: &[u8]) {
+        self.rom_hash = util::hash(program);
+    }
+
+    /// Takes a snapshot of the complete state.
+    pub fn save_state(&self) -> State {
+        State {
+            rom_hash: self.rom_hash,
+            pc: self.pc.0,
+            gpr: self.gpr,
+            i: self.i.0,
+            stack: self.stack.iter().map(|address| address.0).collect(),
+            memory: self.memory.clone(),
+            delay_timer: self.delay_timer,
+            sound_timer: self.sound_timer,
+            display_width: self.display.size().width,
+            display_height: self.display.size().height,
+            display: self.display.get_pixels(0),
+            display_plane2: self.display.get_pixels(1),
+            planes: self.display.selected_planes(),
+        }
+    }
+
+    /// Restores the state from a snapshot.
+    ///
+    /// The display is replaced without tracking the changed pixels so it needs to be drawn again completely.
+    pub fn load_state(&mut self, state: &State) -> Result<(), Error> {
+        if state.rom_hash != self.rom_hash {
+            return Err("The savestate is for a different program.".into());
+        }
+        if state.memory.len() != self.memory.len() {
+            return Err("The savestate's memory size does not match.".into());
+        }
+        let size = self.display.size();
+        if (state.display_width, state.display_height) != (size.width, size.height) {
+            return Err("The savestate's display size does not match.".into());
+        }
+
+        self.pc = Tribble(state.pc);
+        self.gpr = state.gpr;
+        self.i = Tribble(state.i);
+        self.stack = state.stack.iter().copied().map(Tribble).collect();
+        self.memory.copy_from_slice(&state.memory);
+        self.delay_timer = state.delay_timer;
+        self.sound_timer = state.sound_timer;
+        self.display.set_pixels(0, &state.display);
+        self.display.set_pixels(1, &state.display_plane2);
+        self.display.select_planes(state.planes);
+        self.recent_instructions.clear();
+
+        Ok(())
+    }
+}
+
+/// 4 bits.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Nibble(pub u8);
+
+/// 3 nibbles or 12 bits, or 16 bits for addresses with [`Quirks::xo_chip`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Tribble(u16);
+
+impl fmt::Display for Tribble {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!("{:#05X}", self.0))
+    }
+}
+
+/// Splits the 16 bits into 4 nibbles (one nibble is 4 bits and 4x4 = 16).
+pub fn split_word(word: u16) -> (Nibble, Nibble, Nibble, Nibble) {
+    // Zero out the last 3 nibbles at the end of the word,
+    // i.e. only keep the first of the 4 nibbles.
+    let mut nibbles_to_remove = 3;
+    let nibble1 = Nibble((word >> (4 * nibbles_to_remove)) as u8);
+
+    // And now for the rest keep only the relevant nibble with bitwise AND operations. `F` is the nibble to keep.
+    // Then with more right shifts the remaining nibbles/zeroes are removed.
+    nibbles_to_remove -= 1;
+    let nibble2 = Nibble(((word & 0x0F00) >> (4 * nibbles_to_remove)) as u8);
+    nibbles_to_remove -= 1;
+    let nibble3 = Nibble(((word & 0x00F0) >> (4 * nibbles_to_remove)) as u8);
+    nibbles_to_remove -= 1;
+    let nibble4 = Nibble(((word & 0x000F) >> (4 * nibbles_to_remove)) as u8);
+
+    (nibble1, nibble2, nibble3, nibble4)
+}
+
+impl Tribble {
+    fn new(nibble1: Nibble, nibble2: Nibble, nibble3: Nibble) -> Self {
+        Self((((nibble1.0 as u16) << 4) | (nibble2.0 as u16)) << 4 | (nibble3.0 as u16))
+    }
+}
+
+impl Interpreter {
+    /// Fetches two bytes (making up one instruction) from the binary.
+    ///
+    /// Returns `None` if the end of the program has been reached.
+    fn get_bytes(&self) -> Option<(u8, u8)> {
+        let byte1 = self.memory.get(self.pc.0 as usize)?;
+        let byte2 = self.memory.get(self.pc.0 as usize + 1)?;
+
+        Some((*byte1, *byte2))
+    }
+
+    /// Returns information about the current state, for debugging.
+    pub fn get_info(&self) -> Vec<String> {
+        let instruction = self
+            .get_current_instruction()
+            .map_or(String::from("none"), |instruction| {
+                format!("{:#06X}", instruction)
+            });
+
+        vec![
+            format!("Instruction about to execute: {}", instruction),
+            match self.debugger.symbols.describe(self.pc.0) {
+                Some(name) => format!("Program counter: {:#06X} <{}>", self.pc.0, name),
+                None => format!("Program counter: {:#06X}", self.pc.0),
+            },
+            format!(
+                "Registers: {}",
+                String::from("[")
+                    + &self
+                        .gpr
+                        .iter()
+                        .enumerate()
+                        .map(|(index, register)| format!("V{:X}: {:X}", index, register))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                    + "]"
+            ),
+            format!("Address register (I): {}", self.i),
+            format!("Delay timer: {}", self.delay_timer),
+            format!("Sound timer: {}", self.sound_timer),
+        ]
+    }
+
+    /// Decrements the delay and sound timers. This needs to be called 60 times per second.
+    pub fn update_timers(&mut self) {
+        self.metrics.frames += 1;
+
+        if self.delay_timer > 0 {
+            self.delay_timer -= 1;
+        }
+
+        if self.sound_timer > 0 {
+            self.sound_timer -= 1;
+        }
+    }
+
+    /// Executes the next instruction, unless a breakpoint is hit or the program is waiting for a key.
+    ///
+    /// Errors are followed by lines describing the state, as the error alone often does not say how the program got
+    /// there.
+    pub fn step(&mut self) -> Result<Step, Error> {
+        let instruction = self.get_current_instruction();
+        self.execute().map_err(|err| {
+            let mut lines = vec![err.into_owned()];
+            if let Some(instruction) = instruction {
+                lines.extend(self.get_error_context(instruction));
+            }
+            lines.join("\n").into()
+        })
+    }
+
+    /// Describes the state for an error at the instruction.
+    fn get_error_context(&self, instruction: u16) -> Vec<String> {
+        let (mnemonic, operands, _) = disassembler::decode(instruction);
+        let registers: Vec<String> = self
+            .gpr
+            .iter()
+            .enumerate()
+            .map(|(index, register)| format!("V{:X}={:02X}", index, register))
+            .collect();
+        let list = |addresses: Vec<u16>| {
+            if addresses.is_empty() {
+                String::from("none")
+            } else {
+                let addresses: Vec<String> = addresses
+                    .into_iter()
+                    .map(|address| self.debugger.describe(address))
+                    .collect();
+                addresses.join(", ")
+            }
+        };
+
+        vec![
+            format!("  Instruction: {} {}", mnemonic, operands.join(", "))
+                .trim_end()
+                .to_string(),
+            format!(
+                "  Registers: {} I={} DT={:02X} ST={:02X}",
+                registers.join(" "),
+                self.i,
+                self.delay_timer,
+                self.sound_timer
+            ),
+            format!(
+                "  Stack, most recent last: {}",
+                list(self.stack.iter().map(|address| address.0).collect())
+            ),
+            format!(
+                "  Executed before, most recent last: {}",
+                list(self.recent_instructions.iter().copied().collect())
+            ),
+        ]
+    }
+
+    fn execute(&mut self) -> Result<Step, Error> {
+        let (byte1, byte2) = match self.get_bytes() {
+            Some(bytes) => bytes,
+            None => return Ok(Step::Ended),
+        };
+        let instruction = Self::get_instruction(byte1, byte2);
+        let (nibble1, nibble2, nibble3, nibble4) = split_word(instruction);
+        let tribble = Tribble::new(nibble2, nibble3, nibble4);
+
+        if self.debugger.breakpoints.contains(&self.pc.0) && !self.breakpoint_reported {
+            self.breakpoint_reported = true;
+            return Ok(Step::Breakpoint(format!(
+                "Breakpoint hit at {}",
+                self.debugger.describe(self.pc.0)
+            )));
+        }
+        if nibble1.0 == 0xD && !self.breakpoint_reported {
+            let regions = self.get_sprite_regions(nibble2, nibble3, nibble4);
+            if let Some(breakpoint) = regions
+                .iter()
+                .find_map(|region| self.debugger.hit_draw_breakpoint(region))
+            {
+                let reason = format!(
+                    "Breakpoint hit: drawing within x {:?}, y {:?}",
+                    breakpoint.x, breakpoint.y
+                );
+                self.breakpoint_reported = true;
+                return Ok(Step::Breakpoint(reason));
+            }
+        }
+        let write_range = get_write_range(instruction, self.i.0);
+        if let Some(range) = &write_range {
+            if let Some(watchpoint) = self.debugger.hit_write_watchpoint(range) {
+                let message = format!(
+                    "Watchpoint hit: {:04X} at {} writes to {:#05X}..{:#05X}",
+                    instruction,
+                    self.debugger.describe(self.pc.0),
+                    range.start,
+                    range.end
+                );
+                if !watchpoint.pause {
+                    self.debugger.log(message);
+                } else if !self.breakpoint_reported {
+                    self.breakpoint_reported = true;
+                    return Ok(Step::Breakpoint(message));
+                }
+            }
+        }
+        let self_modification = self
+            .debugger
+            .self_modification
+            .as_ref()
+            .and_then(|detector| {
+                let message = detector.check(
+                    self.pc.0,
+                    instruction,
+                    write_range.as_ref(),
+                    &self.debugger.symbols,
+                )?;
+                Some((message, detector.pause))
+            });
+        if let Some((message, pause)) = self_modification {
+            if !pause {
+                self.debugger.log(message);
+            } else if !self.breakpoint_reported {
+                self.breakpoint_reported = true;
+                return Ok(Step::Breakpoint(message));
+            }
+        }
+        self.breakpoint_reported = false;
+        if let Some(detector) = &mut self.debugger.self_modification {
+            detector.record(self.pc.0, write_range.as_ref());
+        }
+
+        let key = self.key;
+        // Errors say where they happened, as the program counter has moved on by then.
+        let pc = self.pc.0;
+        let name = self.debugger.symbols.describe(pc);
+        let locate = |err: Error| -> Error {
+            match &name {
+                Some(name) => format!("{} ({:04X} at {:#05X} <{}>)", err, instruction, pc, name),
+                None => format!("{} ({:04X} at {:#05X})", err, instruction, pc),
+            }
+            .into()
+        };
+
+        self.next_instruction();
+
+        match nibble1.0 {
+            0x0 => match tribble.0 {
+                0x0E0 => {
+                    self.clear_display();
+                }
+                // The hi-res mode clears its larger screen with its own instruction.
+                0x230 if self.display.screen_size() == display::HIRES_SCREEN_SIZE => {
+                    self.clear_display();
+                }
+                0x0EE => {
+                    self.r#return().map_err(locate)?;
+                }
+                0x0C0..=0x0CF => self.scroll_display(0, nibble4.0 as i16),
+                0x0D0..=0x0DF if self.quirks.xo_chip => self.scroll_display(0, -(nibble4.0 as i16)),
+                // The program exits, staying at this instruction so that it ends again if stepped further.
+                0x0FD => {
+                    self.pc = Tribble(pc);
+                    return Ok(Step::Ended);
+                }
+                0x0FB => self.scroll_display(4, 0),
+                0x0FC => self.scroll_display(-4, 0),
+                _ => {
+                    // Exit the interpreter and execute machine code at the given address in memory of the
+                    // RCA 1802 for COSMAC VIP.
+                    // For that, we would need a COSMAC VIP emulator. Luckily this instruction is mostly unused.
+                }
+            },
+            0x1 => {
+                self.jump(tribble);
+            }
+            0x2 => {
+                self.call(tribble).map_err(locate)?;
+            }
+            0x3 => self.value_equality_skip(nibble2, byte2),
+            0x4 => self.value_inequality_skip(nibble2, byte2),
+            0x5 => self.register_equality_skip(nibble2, nibble3),
+            0x6 => self.set_register_to_value(nibble2, byte2),
+            0x7 => self.add_to_register(nibble2, byte2),
+            0x8 => match nibble4.0 {
+                0x0 => self.set_registers(nibble2, nibble3),
+                0x1 => self.or_registers(nibble2, nibble3),
+                0x2 => self.and_registers(nibble2, nibble3),
+                0x3 => self.xor_registers(nibble2, nibble3),
+                0x4 => self.add_registers(nibble2, nibble3),
+                0x5 => self.sub_registers1(nibble2, nibble3),
+                0x6 => self.shift_register_right(nibble2),
+                0x7 => self.sub_registers2(nibble2, nibble3),
+                0xE => self.shift_register_left(nibble2),
+
+                _ => return Err(self.error(byte1, byte2)),
+            },
+            0x9 => self.register_inequality_skip(nibble2, nibble3),
+            0xA => self.set_address_register(tribble),
+            0xB => self.jump_with_register(tribble),
+            0xC => self.generate_random(nibble2, byte2),
+            0xD => self
+                .draw_sprite(nibble2, nibble3, nibble4)
+                .map_err(locate)?,
+            0xE => match nibble3.0 {
+                0x9 => self.key_equality_skip(nibble2),
+                0xA => self.key_inequality_skip(nibble2),
+                _ => return Err(self.error(byte1, byte2)),
+            },
+            0xF => match byte2 {
+                0x00 if nibble2.0 == 0 && self.quirks.xo_chip => {
+                    self.set_long_address_register().map_err(locate)?
+                }
+                0x01 if self.quirks.xo_chip => self.select_planes(nibble2),
+                0x07 => self.get_delay_timer(nibble2),
+                0x0A => {
+                    if !self.await_key(nibble2, key) {
+                        return Ok(Step::AwaitingKey);
+                    }
+                }
+                0x15 => self.set_delay_timer(nibble2),
+                0x18 => self.set_sound_timer(nibble2),
+                0x1E => self.add_address_register(nibble2),
+                0x29 => self.set_sprite(nibble2),
+                0x30 => self.set_big_sprite(nibble2),
+                0x33 => self.set_address_register_to_bcd(nibble2).map_err(locate)?,
+                0x55 => self.store_registers(nibble2).map_err(locate)?,
+                0x65 => self.store_memory(nibble2).map_err(locate)?,
+                0x75 => self.save_flags(nibble2).map_err(locate)?,
+                0x85 => self.load_flags(nibble2).map_err(locate)?,
+                _ => return Err(self.error(byte1, byte2)),
+            },
+            _ => {
+                return Err(self.error(byte1, byte2));
+            }
+        }
+
+        self.instruction_count += 1;
+        self.metrics.instructions += 1;
+        if self.recent_instructions.len() == RECENT_INSTRUCTION_COUNT {
+            self.recent_instructions.pop_front();
+        }
+        self.recent_instructions.push_back(pc);
+
+        Ok(Step::Executed)
+    }
+
+    /// Clears the selected planes of the display.
+    fn clear_display(&mut self) {
+        self.display.clear();
+    }
+
+    /// Scrolls the selected planes of the display by the given number of pixels, to the right and down for positive
+    /// offsets.
+    fn scroll_display(&mut self, x_offset: i16, y_offset: i16) {
+        self.display.scroll(x_offset, y_offset);
+    }
+
+    /// Selects the planes of the display to draw on and clear by the bitmask in the given nibble.
+    fn select_planes(&mut self, planes: Nibble) {
+        self.display.select_planes(planes.0);
+    }
+
+    /// Returns from a subroutine.
+    fn r#return(&mut self) -> Result<(), Error> {
+        match self.stack.pop() {
+            Some(address) => {
+                self.jump(address);
+                Ok(())
+            }
+            None => Err("Returned without a subroutine to return from.".into()),
+        }
+    }
+
+    /// Go to the given address.
+    fn jump(&mut self, address: Tribble) {
+        self.pc = address;
+    }
+
+    /// Calls a subroutine at the given address.
+    fn call(&mut self, address: Tribble) -> Result<(), Error> {
+        let stack_size = if self.quirks.small_stack {
+            SMALL_STACK_SIZE
+        } else {
+            STACK_SIZE
+        };
+        if self.stack.len() >= stack_size {
+            return Err(format!(
+                "Called a subroutine with the stack full of {} return addresses.",
+                stack_size
+            )
+            .into());
+        }
+
+        // Push our current address to the stack so that we can return later.
+        self.stack.push(self.pc);
+        self.jump(address);
+        Ok(())
+    }
+
+    /// Skips the next instruction if the value of the register is equal to the byte.
+    fn value_equality_skip(&mut self, register: Nibble, byte: u8) {
+        self.skip_next_instruction_if(self.get_register(register) == byte);
+    }
+
+    /// Skips the next instruction if the value of the register is not equal to the byte.
+    fn value_inequality_skip(&mut self, register: Nibble, byte: u8) {
+        self.skip_next_instruction_if(self.get_register(register) != byte);
+    }
+
+    /// Skips the next instruction if the value of the first register is equal to the value of the second register.
+    fn register_equality_skip(&mut self, register1: Nibble, register2: Nibble) {
+        self.skip_next_instruction_if(self.get_register(register1) == self.get_register(register2));
+    }
+
+    /// Sets the register's value to the given one.
+    fn set_register_to_value(&mut self, register: Nibble, value: u8) {
+        *self.get_mut_register(register) = value;
+    }
+
+    /// Adds the value to the register's value.
+    fn add_to_register(&mut self, register: Nibble, value: u8) {
+        let register = self.get_mut_register(register);
+
+        *register = register.wrapping_add(value);
+    }
+
+    /// Sets the first register's value to the one of the second register.
+    fn set_registers(&mut self, register1: Nibble, register2: Nibble) {
+        *self.get_mut_register(register1) = self.get_register(register2);
+    }
+
+    /// ORs the first register's value with the second register's.
+    fn or_registers(&mut self, register1: Nibble, register2: Nibble) {
+        *self.get_mut_register(register1) |= self.get_register(register2);
+    }
+
+    /// ANDs the first register's value with the second register's.
+    fn and_registers(&mut self, register1: Nibble, register2: Nibble) {
+        *self.get_mut_register(register1) &= self.get_register(register2);
+    }
+
+    /// XORs the first register's value with the second register's.
+    fn xor_registers(&mut self, register1: Nibble, register2: Nibble) {
+        *self.get_mut_register(register1) ^= self.get_register(register2);
+    }
+
+    /// Adds the first register's value to the second register's.
+    ///
+    /// If an overflow occurs, the carry flag is set.
+    fn add_registers(&mut self, register1: Nibble, register2: Nibble) {
+        let register2_value = self.get_register(register2);
+        let register1_value = self.get_mut_register(register1);
+        let (result, overflow) = register1_value.overflowing_add(register2_value);
+        *register1_value = result;
+        if overflow {
+            self.set_flag();
+        } else {
+            self.clear_flag();
+        }
+    }
+
+    /// Subtracts the second register's value from the first register's.
+    ///
+    /// If an underflow occurs, the carry flag is set.
+    fn sub_registers1(&mut self, register1: Nibble, register2: Nibble) {
+        let value2 = self.get_register(register2);
+        let value1 = self.get_mut_register(register1);
+        let (result, underflow) = value1.overflowing_sub(value2);
+        *value1 = result;
+        if underflow {
+            self.clear_flag();
+        } else {
+            self.set_flag();
+        }
+    }
+
+    /// Writes the least significant bit (the last bit) of the given register's value to the flag register and
+    /// shifts the register's value to the right by 1.
+    fn shift_register_right(&mut self, register: Nibble) {
+        let value = self.get_register(register);
+
+        self.store_lsb_in_flag(value);
+
+        *self.get_mut_register(register) >>= 1;
+    }
+
+    /// Subtracts the first register's value from the second register's.
+    ///
+    /// If an underflow occurs, the carry flag is set.
+    fn sub_registers2(&mut self, register1: Nibble, register2: Nibble) {
+        let value2 = self.get_register(register2);
+        let value1 = self.get_mut_register(register1);
+        let (result, underflow) = value2.overflowing_sub(*value1);
+        *value1 = result;
+        if underflow {
+            self.clear_flag();
+        } else {
+            self.set_flag();
+        }
+    }
+
+    /// Writes the most significant bit (the first bit) of the given register's value to the flag register and
+    /// shifts the register's value to the left by 1.
+    fn shift_register_left(&mut self, register: Nibble) {
+        let value = self.get_register(register);
+
+        self.store_msb_in_flag(value);
+
+        *self.get_mut_register(register) <<= 1;
+    }
+
+    /// Skips the next instruction if the value of the first register is not equal to the value of the second register.
+    fn register_inequality_skip(&mut self, register1: Nibble, register2: Nibble) {
+        self.skip_next_instruction_if(self.get_register(register1) != self.get_register(register2));
+    }
+
+    /// Sets the address register to the given value.
+    fn set_address_register(&mut self, address: Tribble) {
+        self.i = address;
+    }
+
+    /// Adds the register V0 to the given address and jumps to it.
+    fn jump_with_register(&mut self, address: Tribble) {
+        let address = Tribble((self.get_register(Nibble(0x0)) as u16).wrapping_add(address.0));
+
+        self.jump(address);
+    }
+
+    /// Generates a random number in range 0..255, bitwise ANDs it and sets it to the given register's value.
+    fn generate_random(&mut self, register: Nibble, byte: u8) {
+        let rn = self.rng.gen::<u8>();
+        let value = rn & byte;
+
+        *self.get_mut_register(register) = value;
+    }
+
+    /// Returns the regions of the display a sprite would be drawn within.
+    ///
+    /// A sprite crossing the edges of the screen is cut off there, or split into the parts wrapping around to the
+    /// other side with the `wrap_sprites` quirk.
+    fn get_sprite_regions(
+        &self,
+        register1: Nibble,
+        register2: Nibble,
+        height: Nibble,
+    ) -> Vec<Region> {
+        let screen_size = self.display.screen_size();
+        let wrap = self.quirks.wrap_sprites;
+        let split = |start: u16, length: u16, size: u16| {
+            let end = start + length;
+            iter::once(start..end.min(size))
+                .chain((wrap && end > size).then(|| 0..end - size))
+                .collect::<Vec<Range<u16>>>()
+        };
+        let xs = split(
+            self.get_register(register1) as u16 % screen_size.width,
+            8,
+            screen_size.width,
+        );
+        let ys = split(
+            self.get_register(register2) as u16 % screen_size.height,
+            height.0 as u16,
+            screen_size.height,
+        );
+
+        ys.iter()
+            .flat_map(|y| {
+                xs.iter().map(move |x| Region {
+                    x: x.clone(),
+                    y: y.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Draws the sprite of the given height at the address register at the position of the given registers.
+    ///
+    /// With both planes selected, the sprite of the second plane follows the one of the first plane in memory.
+    fn draw_sprite(
+        &mut self,
+        register1: Nibble,
+        register2: Nibble,
+        height: Nibble,
+    ) -> Result<(), Error> {
+        let sprite: Vec<u8> = self
+            .get_memory_addresses(height.0 as u16 * self.display.selected_plane_count())?
+            .into_iter()
+            .map(|address| self.memory[address])
+            .collect();
+        self.metrics.draws += 1;
+
+        let x = self.get_register(register1);
+        let y = self.get_register(register2);
+
+        let point = Point {
+            x: x as u16,
+            y: y as u16,
+        };
+
+        let collision = self
+            .display
+            .draw_sprite(point, &sprite, self.quirks.wrap_sprites);
+
+        if collision {
+            self.set_flag();
+        } else {
+            self.clear_flag();
+        }
+
+        Ok(())
+    }
+
+    /// Skips the next instruction if the key of the register's value is held down.
+    fn key_equality_skip(&mut self, register: Nibble) {
+        let key = self.get_register(register);
+
+        self.skip_next_instruction_if(self.held_keys[key as usize & 0xF]);
+    }
+
+    /// Skips the next instruction if the key of the register's value is not held down.
+    fn key_inequality_skip(&mut self, register: Nibble) {
+        let key = self.get_register(register);
+
+        self.skip_next_instruction_if(!self.held_keys[key as usize & 0xF]);
+    }
+
+    fn get_delay_timer(&mut self, register: Nibble) {
+        *self.get_mut_register(register) = self.delay_timer;
+    }
+
+    /// Stores the pressed key in the given register.
+    ///
+    /// Returns `false` if no key is pressed, in which case the instruction needs to be executed again.
+    /// With [`Quirks::await_key_release`], this also waits for the key to be released again.
+    fn await_key(&mut self, register: Nibble, key: Option<u8>) -> bool {
+        if self.quirks.await_key_release {
+            if let Some(pressed_key) = self.pressed_key {
+                if !self.held_keys[pressed_key as usize] {
+                    self.pressed_key = None;
+                    *self.get_mut_register(register) = pressed_key;
+                    return true;
+                }
+            } else {
+                self.pressed_key = key.map(|key| key & 0xF);
+            }
+        } else if let Some(key) = key {
+            *self.get_mut_register(register) = key;
+            return true;
+        }
+
+        self.previous_instruction();
+        false
+    }
+
+    /// Sets the delay timer to the given register's value.
+    fn set_delay_timer(&mut self, register: Nibble) {
+        self.delay_timer = self.get_register(register);
+    }
+
+    /// Sets the sound timer to the given register's value.
+    fn set_sound_timer(&mut self, register: Nibble) {
+        self.sound_timer = self.get_register(register);
+    }
+
+    /// Sets the address register to the 16-bit address in the word after the instruction and skips it.
+    fn set_long_address_register(&mut self) -> Result<(), Error> {
+        let (byte1, byte2) = self
+            .get_bytes()
+            .ok_or("The address of the instruction is past the end of the memory.")?;
+        self.i = Tribble(u16::from_be_bytes([byte1, byte2]));
+        self.next_instruction();
+        Ok(())
+    }
+
+    /// Add the given register's value to the address register.
+    fn add_address_register(&mut self, register: Nibble) {
+        self.i.0 = self.i.0.wrapping_add(self.get_register(register) as u16);
+    }
+
+    /// Sets the address register to the font's sprite of the hexadecimal digit in the given register.
+    fn set_sprite(&mut self, register: Nibble) {
+        let digit = self.get_register(register) & 0xF;
+        self.i.0 = digit as u16 * display::FONT_CHARACTER_SIZE;
+    }
+
+    /// Sets the address register to the character of the big font for the lowest nibble of the register's value.
+    fn set_big_sprite(&mut self, register: Nibble) {
+        let digit = self.get_register(register) & 0xF;
+        self.i.0 = display::BIG_FONT_ADDRESS + digit as u16 * display::BIG_FONT_CHARACTER_SIZE;
+    }
+
+    /// Stores the BCD (binary-coded decimal) representation of the register's value in the memory of the address register.
+    fn set_address_register_to_bcd(&mut self, register: Nibble) -> Result<(), Error> {
+        let value = self.get_register(register);
+        let digits = [value / 100, value / 10 % 10, value % 10];
+
+        for (address, digit) in self.get_memory_addresses(3)?.into_iter().zip(digits) {
+            self.memory[address] = digit;
+        }
+
+        Ok(())
+    }
+
+    /// Stores all register values starting from V0 to the given register in memory of the address register.
+    fn store_registers(&mut self, register: Nibble) -> Result<(), Error> {
+        let addresses = self.get_memory_addresses(register.0 as u16 + 1)?;
+        for (register, address) in (0..=register.0).zip(addresses) {
+            self.memory[address] = self.get_register(Nibble(register));
+        }
+
+        Ok(())
+    }
+
+    /// Fills the registers starting from V0 to the given register with values from memory starting at the address register.
+    fn store_memory(&mut self, register: Nibble) -> Result<(), Error> {
+        let addresses = self.get_memory_addresses(register.0 as u16 + 1)?;
+        for (register, address) in (0..=register.0).zip(addresses) {
+            *self.get_mut_register(Nibble(register)) = self.memory[address];
+        }
+
+        Ok(())
+    }
+
+    /// Saves the registers from V0 to the given register to the flags.
+    fn save_flags(&mut self, register: Nibble) -> Result<(), Error> {
+        let count = self.get_flag_count(register)?;
+        self.flags[..count].copy_from_slice(&self.gpr[..count]);
+
+        Ok(())
+    }
+
+    /// Loads the flags into the registers from V0 to the given register.
+    fn load_flags(&mut self, register: Nibble) -> Result<(), Error> {
+        let count = self.get_flag_count(register)?;
+        self.gpr[..count].copy_from_slice(&self.flags[..count]);
+
+        Ok(())
+    }
+
+    /// Returns how many flags are saved or loaded along with the registers up to the given one.
+    fn get_flag_count(&self, register: Nibble) -> Result<usize, Error> {
+        let count = register.0 as usize + 1;
+        if count > FLAG_COUNT {
+            return Err(format!(
+                "There are only {} flags, up to V{}.",
+                FLAG_COUNT,
+                FLAG_COUNT - 1
+            )
+            .into());
+        }
+
+        Ok(count)
+    }
+
+    /// Returns the addresses of the given number of bytes starting at the address register.
+    ///
+    /// All memory accessed through the address register goes through this so that malformed programs cannot access
+    /// anything out of bounds. Addresses past the end of the memory are handled as set by [`Quirks::memory_end`]. With
+    /// [`MemoryEnd::Error`], nothing is accessed so that the memory is left as it was.
+    fn get_memory_addresses(&self, count: u16) -> Result<Vec<usize>, Error> {
+        let start = self.i.0 as usize;
+        let end = start + count as usize;
+        let memory_size = self.memory.len();
+        if end > memory_size && self.quirks.memory_end == MemoryEnd::Error {
+            return Err(format!(
+                "Memory past {:#05X} was accessed with I = {:#05X}.",
+                memory_size - 1,
+                self.i.0
+            )
+            .into());
+        }
+
+        let addresses = (start..end)
+            .map(|address| match self.quirks.memory_end {
+                MemoryEnd::Wrap => address % memory_size,
+                MemoryEnd::Error | MemoryEnd::Clamp => address.min(memory_size - 1),
+            })
+            .collect();
+
+        Ok(addresses)
+    }
+
+    //
+    // Utilities
+    //
+
+    /// Returns the error for an unknown instruction, leaving the program counter at it.
+    fn error(&mut self, byte1: u8, byte2: u8) -> Error {
+        let instruction = Self::get_instruction(byte1, byte2);
+        self.previous_instruction();
+
+        format!(
+            "Unknown instruction encountered ({:04X} at {}).",
+            instruction,
+            self.debugger.describe(self.pc.0)
+        )
+        .into()
+    }
+
+    /// Stores the least significant bit (LSB, the last bit) of the given value into the flag register.
+    fn store_lsb_in_flag(&mut self, value: u8) {
+        let bit = value & 0b0000_0001;
+        self.gpr[0xF] = bit;
+    }
+
+    /// Stores the most significant bit (MSB, the first bit) of the given value into the flag register.
+    fn store_msb_in_flag(&mut self, value: u8) {
+        let bit = value >> 7;
+        self.gpr[0xF] = bit;
+    }
+
+    /// Sets the flag.
+    fn set_flag(&mut self) {
+        self.gpr[0xF] = 1;
+    }
+
+    /// Zeroes the flag.
+    fn clear_flag(&mut self) {
+        self.gpr[0xF] = 0;
+    }
+
+    /// Skips the next instruction if the condition is `true`.
+    ///
+    /// With [`Quirks::xo_chip`], `F000 NNNN` is skipped as a whole.
+    fn skip_next_instruction_if(&mut self, condition: bool) {
+        if condition {
+            if self.quirks.xo_chip && self.get_bytes() == Some((0xF0, 0x00)) {
+                self.next_instruction();
+            }
+            self.next_instruction();
+        }
+    }
+
+    /// Gets the given register's value.
+    fn get_register(&self, register: Nibble) -> u8 {
+        self.gpr[register.0 as usize]
+    }
+
+    /// Gets a mutable reference to the given register's value.
+    fn get_mut_register(&mut self, register: Nibble) -> &mut u8 {
+        self.gpr.get_mut(register.0 as usize).unwrap()
+    }
+
+    /// Advances the program counter by one instruction.
+    fn next_instruction(&mut self) {
+        self.pc.0 = self.pc.0.wrapping_add(2);
+    }
+
+    /// Reverts the program counter by one instruction.
+    fn previous_instruction(&mut self) {
+        self.pc.0 = self.pc.0.wrapping_sub(2);
+    }
+
+    pub fn get_instruction(byte1: u8, byte2: u8) -> u16 {
+        // One instruction is stored in two bytes as big-endian.
+        // With big endian the bytes are in order and we simply need to put the two bytes together to one 16-bit integer,
+        // i.e. we simply concatenate the two bytes.
+
+        // In binary, this adds 8 zeroes to the end of the bits, making it a 16-bit integer (a word).
+        // Below we will replace those 8 zeroes with data.
+        let word = (byte1 as u16) << 8;
+
+        // And now we simply put the 8 bits of the second byte into those 8 zeroes.
+        word | byte2 as u16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::debugger::{SelfModificationDetector, Watchpoint};
+
+    #[test]
+    fn test_split_word() {
+        let word = 0xABCD;
+
+        let (nibble1, nibble2, nibble3, nibble4) = split_word(word);
+
+        assert_eq!(nibble1, Nibble(0xA));
+        assert_eq!(nibble2, Nibble(0xB));
+        assert_eq!(nibble3, Nibble(0xC));
+        assert_eq!(nibble4, Nibble(0xD));
+    }
+
+    #[test]
+    fn test_instruction_fetching() {
+        let (byte1, byte2) = (0xAB, 0xFE);
+        let instruction = Interpreter::get_instruction(byte1, byte2);
+        assert_eq!(instruction, 0xABFE);
+        let (nibble1, nibble2, nibble3, nibble4) = split_word(instruction);
+        assert_eq!(nibble1, Nibble(0xA));
+        assert_eq!(nibble2, Nibble(0xB));
+        assert_eq!(nibble3, Nibble(0xF));
+        assert_eq!(nibble4, Nibble(0xE));
+        let tribble = Tribble::new(nibble2, nibble3, nibble4);
+        assert_eq!(tribble, Tribble(0xBFE));
+    }
+
+    #[test]
+    fn test_key_skips() {
+        // V0 = 5, then skip if key 5 is held and skip if it is not held.
+        let program = vec![0x60, 0x05, 0xE0, 0x9E, 0x00, 0xE0, 0xE0, 0xA1];
+        let mut interpreter = Interpreter::new(program, 0).unwrap();
+        interpreter.step().unwrap();
+
+        interpreter.set_key_held(0x5, true);
+        interpreter.step().unwrap();
+        assert_eq!(interpreter.get_registers().pc, 0x206);
+
+        interpreter.set_key_held(0x5, false);
+        interpreter.step().unwrap();
+        assert_eq!(interpreter.get_registers().pc, 0x20A);
+    }
+
+    #[test]
+    fn test_await_key_release() {
+        let mut interpreter = Interpreter::new(vec![0xF3, 0x0A], 0).unwrap();
+        interpreter.quirks_mut().await_key_release = true;
+
+        interpreter.set_key(Some(0x7));
+        interpreter.set_key_held(0x7, true);
+        assert_eq!(interpreter.step().unwrap(), Step::AwaitingKey);
+
+        interpreter.set_key(None);
+        assert_eq!(interpreter.step().unwrap(), Step::AwaitingKey);
+
+        interpreter.set_key_held(0x7, false);
+        assert_eq!(interpreter.step().unwrap(), Step::Executed);
+        assert_eq!(interpreter.get_registers().gpr[3], 0x7);
+    }
+
+    #[test]
+    fn test_metrics() {
+        // Draw a sprite in a loop.
+        let program = vec![0xD0, 0x01, 0x12, 0x00];
+        let mut interpreter = Interpreter::new(program, 0).unwrap();
+
+        interpreter.set_key(Some(0x1));
+        for _ in 0..10 {
+            interpreter.step().unwrap();
+        }
+        interpreter.set_key(None);
+        interpreter.update_timers();
+
+        assert_eq!(
+            *interpreter.metrics(),
+            Metrics {
+                instructions: 10,
+                frames: 1,
+                draws: 5,
+                key_events: 1,
+                timer_underruns: 0,
+            }
+        );
+
+        // Loading a state does not reset the metrics.
+        interpreter.load_state(&interpreter.save_state()).unwrap();
+        assert_eq!(interpreter.metrics().instructions, 10);
+    }
+
+    #[test]
+    fn test_write_watchpoints() {
+        // I = 0x1FE, then store V0 and V1 twice.
+        let program = vec![0xA1, 0xFE, 0xF1, 0x55, 0xF1, 0x55];
+        let mut interpreter = Interpreter::new(program, 0).unwrap();
+        interpreter
+            .debugger_mut()
+            .write_watchpoints
+            .push(Watchpoint {
+                range: 0x1FF..0x200,
+                pause: true,
+            });
+        interpreter.step().unwrap();
+
+        assert!(matches!(interpreter.step().unwrap(), Step::Breakpoint(_)));
+        // Continuing executes the instruction.
+        assert_eq!(interpreter.step().unwrap(), Step::Executed);
+
+        interpreter.debugger_mut().write_watchpoints[0].pause = false;
+        assert_eq!(interpreter.step().unwrap(), Step::Executed);
+        assert_eq!(
+            interpreter.debugger_mut().take_log(),
+            ["Watchpoint hit: F155 at 0x204 writes to 0x1FE..0x200"]
+        );
+    }
+
+    #[test]
+    fn test_self_modification() {
+        // V0 = 0x12, I = 0x206, then store V0 over the instruction at 0x206, which is executed next.
+        let program = vec![0x60, 0x12, 0xA2, 0x06, 0xF0, 0x55, 0x00, 0xE0];
+        let mut interpreter = Interpreter::new(program, 0).unwrap();
+        interpreter.debugger_mut().self_modification = Some(SelfModificationDetector::new(false));
+        for _ in 0..3 {
+            interpreter.step().unwrap();
+        }
+        assert!(interpreter.debugger_mut().take_log().is_empty());
+
+        interpreter.step().unwrap();
+        assert_eq!(
+            interpreter.debugger_mut().take_log(),
+            ["Self-modifying code: executing 12E0 at 0x206, which the program wrote"]
+        );
+
+        // V0 = 0x12, I = 0x200, then store V0 over the first instruction, which was executed before.
+        let mut interpreter =
+            Interpreter::new(vec![0x60, 0x12, 0xA2, 0x00, 0xF0, 0x55], 0).unwrap();
+        interpreter.debugger_mut().self_modification = Some(SelfModificationDetector::new(true));
+        interpreter.step().unwrap();
+        interpreter.step().unwrap();
+        assert_eq!(
+            interpreter.step().unwrap(),
+            Step::Breakpoint(
+                "Self-modifying code: F055 at 0x204 overwrites the code executed at 0x200".into()
+            )
+        );
+        assert_eq!(interpreter.step().unwrap(), Step::Executed);
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        // V0 = 1, then jump back to it.
+        let program = vec![0x60, 0x01, 0x12, 0x00];
+        let mut interpreter = Interpreter::new(program, 0).unwrap();
+        interpreter.debugger_mut().breakpoints.push(0x202);
+        assert_eq!(interpreter.step().unwrap(), Step::Executed);
+
+        assert_eq!(
+            interpreter.step().unwrap(),
+            Step::Breakpoint("Breakpoint hit at 0x202".into())
+        );
+        assert_eq!(interpreter.step().unwrap(), Step::Executed);
+        assert_eq!(interpreter.step().unwrap(), Step::Executed);
+        assert!(matches!(interpreter.step().unwrap(), Step::Breakpoint(_)));
+    }
+
+    #[test]
+    fn test_draw_breakpoints() {
+        // V0 = 60, V1 = 30, then draw 4 rows crossing the bottom right corner.
+        let program = vec![0x60, 60, 0x61, 30, 0xD0, 0x14];
+        let run = |wrap_sprites| {
+            let quirks = Quirks {
+                wrap_sprites,
+                ..Quirks::default()
+            };
+            let mut interpreter =
+                Interpreter::with_quirks(program.clone(), 0, START_POINT, quirks).unwrap();
+            // The top left corner, where only the wrapping part of the sprite is drawn.
+            interpreter
+                .debugger_mut()
+                .draw_breakpoints
+                .push(Region { x: 0..2, y: 0..2 });
+            interpreter.step().unwrap();
+            interpreter.step().unwrap();
+            interpreter.step().unwrap()
+        };
+
+        assert!(matches!(run(true), Step::Breakpoint(_)));
+        assert_eq!(run(false), Step::Executed);
+    }
+
+    #[test]
+    fn test_memory_end() {
+        // I = 0xFFE, V0 = 123, then store the BCD, store V0 to V2 and load V0 to V2.
+        let program = vec![0xAF, 0xFE, 0x60, 0x7B, 0xF0, 0x33, 0xF2, 0x55, 0xF2, 0x65];
+
+        let mut interpreter = Interpreter::new(program.clone(), 0).unwrap();
+        interpreter.step().unwrap();
+        interpreter.step().unwrap();
+        assert!(interpreter.step().is_err());
+        assert_eq!(interpreter.memory[0xFFE..], [0, 0]);
+
+        let mut interpreter = Interpreter::new(program.clone(), 0).unwrap();
+        interpreter.quirks_mut().memory_end = MemoryEnd::Wrap;
+        interpreter.step().unwrap();
+        interpreter.step().unwrap();
+        interpreter.step().unwrap();
+        assert_eq!(interpreter.memory[0xFFE..], [1, 2]);
+        assert_eq!(interpreter.memory[0x000], 3);
+        interpreter.step().unwrap();
+        assert_eq!(interpreter.memory[0xFFE..], [123, 0]);
+        assert_eq!(interpreter.memory[0x000], 0);
+        interpreter.step().unwrap();
+        assert_eq!(interpreter.get_registers().gpr[..3], [123, 0, 0]);
+
+        let mut interpreter = Interpreter::new(program, 0).unwrap();
+        interpreter.quirks_mut().memory_end = MemoryEnd::Clamp;
+        interpreter.step().unwrap();
+        interpreter.step().unwrap();
+        interpreter.step().unwrap();
+        assert_eq!(interpreter.memory[0xFFE..], [1, 3]);
+        assert_eq!(interpreter.memory[0x000], display::FONT[0]);
+        interpreter.step().unwrap();
+        assert_eq!(interpreter.memory[0xFFE..], [123, 0]);
+        interpreter.step().unwrap();
+        assert_eq!(interpreter.get_registers().gpr[..3], [123, 0, 0]);
+    }
+
+    #[test]
+    fn test_errors() {
+        // I = 0xFFE, then draw a sprite of 5 bytes.
+        let mut interpreter = Interpreter::new(vec![0xAF, 0xFE, 0xD0, 0x05], 0).unwrap();
+        interpreter.step().unwrap();
+        let err = interpreter.step().unwrap_err();
+        assert_eq!(
+            err.lines().next(),
+            Some("Memory past 0xFFF was accessed with I = 0xFFE. (D005 at 0x202)")
+        );
+
+        let mut interpreter = Interpreter::new(vec![0x00, 0xEE], 0).unwrap();
+        assert_eq!(
+            interpreter.step().unwrap_err().lines().next(),
+            Some("Returned without a subroutine to return from. (00EE at 0x200)")
+        );
+
+        let mut interpreter = Interpreter::new(vec![0xE0, 0x00], 0).unwrap();
+        assert_eq!(
+            interpreter.step().unwrap_err().lines().next(),
+            Some("Unknown instruction encountered (E000 at 0x200).")
+        );
+        assert_eq!(interpreter.get_registers().pc, 0x200);
+
+        let mut interpreter = Interpreter::new(vec![0x00, 0xE0, 0x00, 0xEE], 0).unwrap();
+        interpreter.debugger_mut().symbols.add(0x200, "main".into());
+        interpreter.step().unwrap();
+        assert_eq!(
+            interpreter.step().unwrap_err().lines().next(),
+            Some("Returned without a subroutine to return from. (00EE at 0x202 <main+2>)")
+        );
+    }
+
+    #[test]
+    fn test_error_context() {
+        // V3 = 0x2A, I = 0x123, call a subroutine that does not exist.
+        let mut interpreter = Interpreter::new(
+            vec![0x63, 0x2A, 0xA1, 0x23, 0x22, 0x08, 0x00, 0x00, 0xE0, 0x00],
+            0,
+        )
+        .unwrap();
+        interpreter
+            .debugger_mut()
+            .symbols
+            .add(0x208, "broken".into());
+        for _ in 0..3 {
+            interpreter.step().unwrap();
+        }
+        let registers: Vec<String> = (0..16)
+            .map(|index| format!("V{:X}={:02X}", index, if index == 3 { 0x2A } else { 0 }))
+            .collect();
+        assert_eq!(
+            interpreter.step().unwrap_err(),
+            format!(
+                "Unknown instruction encountered (E000 at 0x208 <broken>).
+  Instruction: DW 0xE000
+  Registers: {} I=0x123 DT=00 ST=00
+  Stack, most recent last: 0x206
+  Executed before, most recent last: 0x200, 0x202, 0x204",
+                registers.join(" ")
+            )
+        );
+    }
+
+    #[test]
+    fn test_stack_size() {
+        // CALL 0x200, calling itself forever.
+        let mut interpreter = Interpreter::new(vec![0x22, 0x00], 0).unwrap();
+        for _ in 0..STACK_SIZE {
+            interpreter.step().unwrap();
+        }
+        assert_eq!(
+            interpreter.step().unwrap_err().lines().next(),
+            Some("Called a subroutine with the stack full of 16 return addresses. (2200 at 0x200)")
+        );
+
+        let mut interpreter = Interpreter::new(vec![0x22, 0x00], 0).unwrap();
+        interpreter.quirks_mut().small_stack = true;
+        for _ in 0..SMALL_STACK_SIZE {
+            interpreter.step().unwrap();
+        }
+        assert!(interpreter.step().is_err());
+    }
+
+    #[test]
+    fn test_xo_chip() {
+        let quirks = Quirks {
+            xo_chip: true,
+            ..Quirks::default()
+        };
+        let program = vec![0; XO_CHIP_MEMORY_SIZE - START_POINT as usize];
+        assert!(Interpreter::new(program.clone(), 0).is_err());
+        assert!(Interpreter::with_quirks(program, 0, START_POINT, quirks.clone()).is_ok());
+
+        // SE V0, 0x00, skipping the long load; I = 0x1234; I = 0xFFFE, then store V0 and V1 there.
+        let program = vec![
+            0x30, 0x00, 0xF0, 0x00, 0x12, 0x34, 0xF0, 0x00, 0xFF, 0xFE, 0xF1, 0x55,
+        ];
+        let mut interpreter =
+            Interpreter::with_quirks(program.clone(), 0, START_POINT, quirks).unwrap();
+        interpreter.step().unwrap();
+        assert_eq!(interpreter.get_registers().pc, 0x206);
+        interpreter.step().unwrap();
+        assert_eq!(interpreter.get_registers().i, 0xFFFE);
+        interpreter.step().unwrap();
+        assert_eq!(interpreter.memory.len(), XO_CHIP_MEMORY_SIZE);
+
+        // Without XO-CHIP, F000 is unknown.
+        let mut interpreter = Interpreter::new(program[2..].to_vec(), 0).unwrap();
+        assert!(interpreter.step().is_err());
+    }
+
+    #[test]
+    fn test_exit() {
+        // LD V0, 0x01; EXIT
+        let mut interpreter = Interpreter::new(vec![0x60, 0x01, 0x00, 0xFD], 0).unwrap();
+        assert_eq!(interpreter.step(), Ok(Step::Executed));
+        assert_eq!(interpreter.step(), Ok(Step::Ended));
+        assert_eq!(interpreter.step(), Ok(Step::Ended));
+        assert_eq!(interpreter.get_registers().pc, 0x202);
+    }
+
+    #[test]
+    fn test_flags() {
+        // LD V0, 0x05; LD V1, 0x06; save V0 and V1 to the flags, LD V0, 0x00; load V0 and V1; save V0 to VF.
+        let program = vec![
+            0x60, 0x05, 0x61, 0x06, 0xF1, 0x75, 0x60, 0x00, 0xF1, 0x85, 0xFF, 0x75,
+        ];
+        let mut interpreter = Interpreter::new(program, 0).unwrap();
+        for _ in 0..3 {
+            interpreter.step().unwrap();
+        }
+        assert_eq!(interpreter.flags(), [5, 6, 0, 0, 0, 0, 0, 0]);
+        interpreter.set_flags([7; FLAG_COUNT]);
+        interpreter.step().unwrap();
+        interpreter.step().unwrap();
+        assert_eq!(interpreter.get_registers().gpr[..3], [7, 7, 0]);
+        assert_eq!(
+            interpreter.step().unwrap_err().lines().next(),
+            Some("There are only 8 flags, up to V7. (FF75 at 0x20A)")
+        );
+    }
+
+    #[test]
+    fn test_big_font() {
+        // LD V0, 0x08; LD HF, V0; DRW V1, V1, 0xA
+        let mut interpreter =
+            Interpreter::new(vec![0x60, 0x08, 0xF0, 0x30, 0xD1, 0x1A], 0).unwrap();
+        interpreter.step().unwrap();
+        interpreter.step().unwrap();
+        assert_eq!(
+            interpreter.get_registers().i,
+            display::BIG_FONT_ADDRESS + 8 * display::BIG_FONT_CHARACTER_SIZE
+        );
+        interpreter.step().unwrap();
+        // The 8 is 10 pixels high, with its middle bar at the fifth and sixth rows.
+        let column: Vec<bool> = (0..11)
+            .map(|y| interpreter.display().get(Point { x: 3, y }))
+            .collect();
+        assert_eq!(
+            column,
+            [true, true, false, false, true, true, false, false, true, true, false]
+        );
+    }
+
+    #[test]
+    fn test_scroll() {
+        // Draw the top row of the font character 0, scroll down by 3, to the right by 4 and to the left by 4.
+        let program = vec![0xD0, 0x01, 0x00, 0xC3, 0x00, 0xFB, 0x00, 0xFC, 0x00, 0xD3];
+        let mut interpreter = Interpreter::new(program.clone(), 0).unwrap();
+        interpreter.step().unwrap();
+        interpreter.step().unwrap();
+        assert!(interpreter.display().get(Point { x: 0, y: 3 }));
+        interpreter.step().unwrap();
+        assert!(interpreter.display().get(Point { x: 4, y: 3 }));
+        assert!(!interpreter.display().get(Point { x: 0, y: 3 }));
+        interpreter.step().unwrap();
+        assert!(interpreter.display().get(Point { x: 0, y: 3 }));
+
+        // Scrolling up is only known to XO-CHIP.
+        interpreter.step().unwrap();
+        assert!(interpreter.display().get(Point { x: 0, y: 3 }));
+        let quirks = Quirks {
+            xo_chip: true,
+            ..Quirks::default()
+        };
+        let mut interpreter = Interpreter::with_quirks(program, 0, START_POINT, quirks).unwrap();
+        for _ in 0..5 {
+            interpreter.step().unwrap();
+        }
+        assert!(interpreter.display().get(Point { x: 0, y: 0 }));
+    }
+
+    #[test]
+    fn test_planes() {
+        let quirks = Quirks {
+            xo_chip: true,
+            ..Quirks::default()
+        };
+        // Select both planes, I = 0x20C, draw the two sprites of one row, select the second plane and clear it.
+        let program = vec![
+            0xF3, 0x01, 0xA2, 0x0C, 0xD0, 0x01, 0xF2, 0x01, 0x00, 0xE0, 0x00, 0x00, 0xC0, 0xA0,
+        ];
+        let mut interpreter =
+            Interpreter::with_quirks(program.clone(), 0, START_POINT, quirks).unwrap();
+        for _ in 0..3 {
+            interpreter.step().unwrap();
+        }
+        let colors: Vec<u8> = (0..3)
+            .map(|x| interpreter.display().get_color(Point { x, y: 0 }))
+            .collect();
+        assert_eq!(colors, [0b11, 0b01, 0b10]);
+
+        let state = interpreter.save_state();
+        interpreter.step().unwrap();
+        interpreter.step().unwrap();
+        assert_eq!(interpreter.display().get_color(Point { x: 0, y: 0 }), 0b01);
+        assert!(!interpreter.display().get(Point { x: 2, y: 0 }));
+        interpreter.load_state(&state).unwrap();
+        assert_eq!(interpreter.display().get_color(Point { x: 2, y: 0 }), 0b10);
+        assert_eq!(interpreter.display().selected_planes(), 0b11);
+
+        // Without XO-CHIP, FN01 is unknown.
+        let mut interpreter = Interpreter::new(program, 0).unwrap();
+        assert!(interpreter.step().is_err());
+    }
+
+    #[test]
+    fn test_hires() {
+        // Jump to 0x260, draw the top row of the font character 0 at the bottom row and clear the screen.
+        let mut program = vec![0x00; 0x64];
+        program[..2].copy_from_slice(&[0x12, 0x60]);
+        program[0x60..].copy_from_slice(&[0xD0, 0x11, 0x02, 0x30]);
+        let mut interpreter = Interpreter::new(program, 0).unwrap();
+        interpreter.gpr[1] = 63;
+        assert_eq!(
+            interpreter.display().screen_size(),
+            display::HIRES_SCREEN_SIZE
+        );
+
+        interpreter.step().unwrap();
+        assert_eq!(interpreter.get_registers().pc, 0x260);
+        interpreter.step().unwrap();
+        assert!(interpreter.display().get(Point { x: 0, y: 63 }));
+        interpreter.step().unwrap();
+        assert!(!interpreter.display().get(Point { x: 0, y: 63 }));
+    }
+
+    #[test]
+    fn test_load_address() {
+        // LD V0, 0x01
+        let mut interpreter = Interpreter::with_load_address(vec![0x60, 0x01], 0, 0x600).unwrap();
+        assert_eq!(interpreter.get_registers().pc, 0x600);
+        interpreter.step().unwrap();
+        assert_eq!(interpreter.get_registers().gpr[0], 0x01);
+
+        assert!(Interpreter::with_load_address(vec![0x60, 0x01], 0, 0xFFF).is_err());
+        assert!(Interpreter::with_load_address(Vec::new(), 0, 0x1000).is_err());
+    }
+
+    #[test]
+    fn test_seed() {
+        // RND V0, 0xFF; RND V1, 0xFF
+        let program = vec![0xC0, 0xFF, 0xC1, 0xFF];
+        let run = |seed| {
+            let mut interpreter = Interpreter::new(program.clone(), seed).unwrap();
+            interpreter.step().unwrap();
+            interpreter.step().unwrap();
+            interpreter.get_registers().gpr
+        };
+        assert_eq!(run(42), run(42));
+        assert_ne!(run(42), run(43));
+        // Replays depend on the numbers staying the same for a seed.
+        assert_eq!(run(42)[..2], [66, 229]);
+    }
+
+    #[test]
+    fn test_set_program() {
+        // LD V0, 0x01
+        let mut interpreter = Interpreter::new(vec![0x60, 0x01], 0).unwrap();
+        let old_state = interpreter.save_state();
+        // LD V0, 0x02
+        interpreter.set_program(&[0x60, 0x02]);
+        assert!(interpreter.load_state(&old_state).is_err());
+        interpreter.load_state(&interpreter.save_state()).unwrap();
+    }
+}
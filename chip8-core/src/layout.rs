@@ -0,0 +1,320 @@
+//! Descriptions of the binary file formats, kept next to the code reading and writing them so that they stay in sync,
+//! and exported as JSON for other tools to read the files, or as a JSON Schema of the files decoded into JSON.
+
+use crate::util::to_json_string;
+
+/// A binary file format: magic bytes, a version, a header and a payload compressed with zstd along with a checksum.
+///
+/// All numbers are big-endian.
+#[derive(Debug)]
+pub struct Layout {
+    pub name: &'static str,
+    /// The extension files of the format usually have.
+    pub extension: &'static str,
+    pub magic: &'static [u8; 8],
+    pub version: u8,
+    /// The fields between the version and the compressed payload.
+    pub header: &'static [Field],
+    /// The fields of the payload once decompressed.
+    pub payload: &'static [Field],
+}
+
+#[derive(Debug)]
+pub struct Field {
+    pub name: &'static str,
+    pub kind: Kind,
+    pub description: &'static str,
+}
+
+/// What a field holds.
+#[derive(Debug)]
+pub enum Kind {
+    U8,
+    U16,
+    U32,
+    U64,
+    /// A fixed number of bytes.
+    Bytes(usize),
+    /// As many bytes as the value of the earlier field of the given name.
+    BytesOfLength(&'static str),
+    /// Entries of the given fields, as many as the value of the earlier field of the given name, or until the end of
+    /// the payload if there is none.
+    List(Option<&'static str>, &'static [Field]),
+    /// Pixels packed into bytes, 8 per byte with the most significant bit first, row by row. The width and the height
+    /// are the values of the earlier fields of the given names.
+    Bitmap(&'static str, &'static str),
+}
+
+impl Layout {
+    /// Returns the size of everything before the compressed payload in bytes.
+    pub fn get_header_size(&self) -> usize {
+        self.magic.len() + 1 + self.header.iter().map(Field::get_size).sum::<usize>()
+    }
+}
+
+impl Field {
+    /// Returns the size of the field in bytes, which needs to be fixed.
+    fn get_size(&self) -> usize {
+        match self.kind {
+            Kind::U8 => 1,
+            Kind::U16 => 2,
+            Kind::U32 => 4,
+            Kind::U64 => 8,
+            Kind::Bytes(length) => length,
+            _ => panic!("The size of `{}` is not fixed.", self.name),
+        }
+    }
+}
+
+/// Formats the layouts as a JSON document.
+pub fn to_json(layouts: &[Layout]) -> String {
+    let formats: Vec<String> = layouts
+        .iter()
+        .map(|layout| {
+            format!(
+                "    {{\n      \"name\": {},\n      \"extension\": {},\n      \"magic\": {},\n      \
+                 \"version\": {},\n      \"header\": {},\n      \"compression\": \"zstd\",\n      \"payload\": {}\n    }}",
+                to_json_string(layout.name),
+                to_json_string(layout.extension),
+                to_json_string(&String::from_utf8_lossy(layout.magic)),
+                layout.version,
+                fields_to_json(layout.header, 6),
+                fields_to_json(layout.payload, 6)
+            )
+        })
+        .collect();
+
+    format!(
+        "{{\n  \"byte_order\": \"big-endian\",\n  \"formats\": [\n{}\n  ]\n}}\n",
+        formats.join(",\n")
+    )
+}
+
+/// Formats the layouts as a JSON Schema of the files decoded into JSON objects with a property for every field, in the
+/// order of the fields. The magic bytes, the version and the compression are given in the descriptions.
+pub fn to_json_schema(layouts: &[Layout]) -> String {
+    let references: Vec<String> = layouts
+        .iter()
+        .map(|layout| format!(r##"{{"$ref": "#/$defs/{}"}}"##, layout.name))
+        .collect();
+    let definitions: Vec<String> = layouts
+        .iter()
+        .map(|layout| {
+            let description = format!(
+                "A .{} file: the magic bytes `{}`, the version {}, the header fields and the payload fields compressed \
+                 with zstd along with a checksum. All numbers are big-endian.",
+                layout.extension,
+                String::from_utf8_lossy(layout.magic),
+                layout.version
+            );
+            let fields: Vec<&Field> = layout.header.iter().chain(layout.payload).collect();
+            format!(
+                "    {}: {}",
+                to_json_string(layout.name),
+                object_to_json_schema(&description, &fields, 4)
+            )
+        })
+        .collect();
+
+    format!(
+        "{{\n  \"$schema\": \"https://json-schema.org/draft/2020-12/schema\",\n  \
+         \"title\": \"CHIP-8 savestates and replays\",\n  \"oneOf\": [{}],\n  \"$defs\": {{\n{}\n  }}\n}}\n",
+        references.join(", "),
+        definitions.join(",\n")
+    )
+}
+
+/// Formats the schema of an object with a required property for every field.
+fn object_to_json_schema(description: &str, fields: &[&Field], indentation: usize) -> String {
+    let indent = " ".repeat(indentation);
+    let properties: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            format!(
+                "{}    {}: {}",
+                indent,
+                to_json_string(field.name),
+                field_to_json_schema(field, indentation + 4)
+            )
+        })
+        .collect();
+    let required: Vec<String> = fields
+        .iter()
+        .map(|field| to_json_string(field.name))
+        .collect();
+
+    format!(
+        "{{\n{indent}  \"description\": {},\n{indent}  \"type\": \"object\",\n{indent}  \"properties\": {{\n{}\n{indent}  }},\n\
+         {indent}  \"required\": [{}],\n{indent}  \"additionalProperties\": false\n{indent}}}",
+        to_json_string(description),
+        properties.join(",\n"),
+        required.join(", "),
+        indent = indent
+    )
+}
+
+fn field_to_json_schema(field: &Field, indentation: usize) -> String {
+    let integer = |bits: u32| {
+        format!(
+            r#"{{"description": {}, "type": "integer", "minimum": 0, "maximum": {}}}"#,
+            to_json_string(field.description),
+            u64::MAX >> (64 - bits)
+        )
+    };
+    let bytes = |description: String, length: Option<usize>| {
+        let length = length.map_or(String::new(), |length| {
+            format!(r#", "minItems": {}, "maxItems": {}"#, length, length)
+        });
+        format!(
+            r#"{{"description": {}, "type": "array", "items": {{"type": "integer", "minimum": 0, "maximum": 255}}{}}}"#,
+            to_json_string(&description),
+            length
+        )
+    };
+
+    match field.kind {
+        Kind::U8 => integer(8),
+        Kind::U16 => integer(16),
+        Kind::U32 => integer(32),
+        Kind::U64 => integer(64),
+        Kind::Bytes(length) => bytes(field.description.to_string(), Some(length)),
+        Kind::BytesOfLength(name) => bytes(
+            format!("{} As many bytes as `{}`.", field.description, name),
+            None,
+        ),
+        Kind::List(count, fields) => {
+            let description = match count {
+                Some(name) => format!("{} As many entries as `{}`.", field.description, name),
+                None => format!("{} Entries until the end of the payload.", field.description),
+            };
+            let fields: Vec<&Field> = fields.iter().collect();
+            format!(
+                r#"{{"description": {}, "type": "array", "items": {}}}"#,
+                to_json_string(&description),
+                object_to_json_schema("An entry.", &fields, indentation)
+            )
+        }
+        Kind::Bitmap(width, height) => format!(
+            r#"{{"description": {}, "type": "array", "items": {{"type": "array", "items": {{"type": "boolean"}}}}}}"#,
+            to_json_string(&format!(
+                "{} `{}` rows of `{}` pixels, which are stored 8 per byte with the most significant bit first.",
+                field.description, height, width
+            ))
+        ),
+    }
+}
+
+fn fields_to_json(fields: &[Field], indentation: usize) -> String {
+    if fields.is_empty() {
+        return "[]".into();
+    }
+
+    let indent = " ".repeat(indentation);
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            let kind = match field.kind {
+                Kind::U8 => r#""type": "u8""#.to_string(),
+                Kind::U16 => r#""type": "u16""#.to_string(),
+                Kind::U32 => r#""type": "u32""#.to_string(),
+                Kind::U64 => r#""type": "u64""#.to_string(),
+                Kind::Bytes(length) => format!(r#""type": "bytes", "length": {}"#, length),
+                Kind::BytesOfLength(name) => {
+                    format!(
+                        r#""type": "bytes", "length_field": {}"#,
+                        to_json_string(name)
+                    )
+                }
+                Kind::List(count, fields) => format!(
+                    r#""type": "list", {}"fields": {}"#,
+                    count.map_or(String::new(), |name| format!(
+                        r#""count_field": {}, "#,
+                        to_json_string(name)
+                    )),
+                    fields_to_json(fields, indentation + 2)
+                ),
+                Kind::Bitmap(width, height) => format!(
+                    r#""type": "bitmap", "width_field": {}, "height_field": {}"#,
+                    to_json_string(width),
+                    to_json_string(height)
+                ),
+            };
+            format!(
+                r#"{}  {{"name": {}, {}, "description": {}}}"#,
+                indent,
+                to_json_string(field.name),
+                kind,
+                to_json_string(field.description)
+            )
+        })
+        .collect();
+
+    format!("[\n{}\n{}]", fields.join(",\n"), indent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_json() {
+        let layout = Layout {
+            name: "test",
+            extension: "test",
+            magic: b"CH8TESTS",
+            version: 1,
+            header: &[Field {
+                name: "seed",
+                kind: Kind::U64,
+                description: "The seed, which is \"random\".",
+            }],
+            payload: &[Field {
+                name: "events",
+                kind: Kind::List(
+                    None,
+                    &[Field {
+                        name: "key",
+                        kind: Kind::U8,
+                        description: "The key.",
+                    }],
+                ),
+                description: "Until the end.",
+            }],
+        };
+        assert_eq!(layout.get_header_size(), 17);
+
+        let json = to_json(&[layout]);
+        assert!(json.contains(
+            r#"{"name": "seed", "type": "u64", "description": "The seed, which is \"random\"."}"#
+        ));
+        assert!(json.contains(r#""type": "list", "fields": ["#));
+    }
+
+    #[test]
+    fn test_to_json_schema() {
+        let layout = Layout {
+            name: "test",
+            extension: "test",
+            magic: b"CH8TESTS",
+            version: 1,
+            header: &[Field {
+                name: "seed",
+                kind: Kind::U16,
+                description: "The \"seed\".",
+            }],
+            payload: &[Field {
+                name: "data",
+                kind: Kind::Bytes(2),
+                description: "The data.",
+            }],
+        };
+
+        let schema = to_json_schema(&[layout]);
+        assert!(schema.contains(r##""oneOf": [{"$ref": "#/$defs/test"}]"##));
+        assert!(schema.contains(
+            r#""seed": {"description": "The \"seed\".", "type": "integer", "minimum": 0, "maximum": 65535}"#
+        ));
+        assert!(schema.contains(r#""minItems": 2, "maxItems": 2}"#));
+        assert!(schema.contains(r#""required": ["seed", "data"]"#));
+    }
+}
@@ -0,0 +1,28 @@
+//! The core of the CHIP-8 interpreter: the interpreter itself, the display as a plain framebuffer and the decoder.
+//!
+//! This does not depend on any particular frontend. A frontend drives the interpreter by setting the pressed key,
+//! executing it step by step and drawing the pixels of the display that changed.
+
+pub mod analysis;
+pub mod annotations;
+pub mod assembler;
+pub mod debugger;
+pub mod disassembler;
+pub mod display;
+pub mod interpreter;
+pub mod layout;
+pub mod link;
+pub mod metrics;
+pub mod octo;
+pub mod patches;
+pub mod quirks;
+pub mod rom;
+pub mod savestate;
+pub mod symbols;
+pub mod timing;
+pub mod util;
+
+pub use interpreter::{Interpreter, Registers, Step};
+use std::borrow::Cow;
+
+pub type Error = Cow<'static, str>;
@@ -0,0 +1,88 @@
+//! A mailbox in memory through which two interpreters can exchange bytes, like over a serial link.
+//!
+//! The mailbox is 4 bytes long:
+//!
+//! * `+0`: the byte to send.
+//! * `+1`: set to nonzero by the program to send the byte. It is cleared once the byte has been sent.
+//! * `+2`: the byte received.
+//! * `+3`: set to 1 once a byte has been received. The program clears it to receive the next byte.
+//!
+//! A program can therefore wait for the flags with `SE`/`SNE` and read or write the bytes with `FX55`/`FX65`.
+
+use crate::interpreter::MEMORY_SIZE;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mailbox {
+    address: u16,
+}
+
+impl Mailbox {
+    /// The number of bytes the mailbox takes up in memory.
+    pub const SIZE: u16 = 4;
+
+    /// Creates a mailbox at the given address, or returns `None` if it does not fit in memory.
+    pub fn new(address: u16) -> Option<Self> {
+        if address as usize + Self::SIZE as usize <= MEMORY_SIZE {
+            Some(Self { address })
+        } else {
+            None
+        }
+    }
+
+    pub fn address(self) -> u16 {
+        self.address
+    }
+
+    /// Takes the byte the program wants to send, if any.
+    pub fn take_outgoing(self, memory: &mut [u8]) -> Option<u8> {
+        let address = self.address as usize;
+        if memory[address + 1] == 0 {
+            return None;
+        }
+
+        memory[address + 1] = 0;
+        Some(memory[address])
+    }
+
+    /// Delivers a received byte to the program.
+    ///
+    /// Returns `false` if the program has not read the previous byte yet, in which case the byte is not delivered.
+    pub fn put_incoming(self, memory: &mut [u8], byte: u8) -> bool {
+        let address = self.address as usize;
+        if memory[address + 3] != 0 {
+            return false;
+        }
+
+        memory[address + 2] = byte;
+        memory[address + 3] = 1;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exchange() {
+        let mailbox = Mailbox::new(0xFF0).unwrap();
+        let mut memory = [0; MEMORY_SIZE];
+
+        assert_eq!(mailbox.take_outgoing(&mut memory), None);
+        memory[0xFF0] = 0x42;
+        memory[0xFF1] = 1;
+        assert_eq!(mailbox.take_outgoing(&mut memory), Some(0x42));
+        assert_eq!(mailbox.take_outgoing(&mut memory), None);
+
+        assert!(mailbox.put_incoming(&mut memory, 0x17));
+        // The previous byte has not been read yet.
+        assert!(!mailbox.put_incoming(&mut memory, 0x18));
+        assert_eq!(memory[0xFF2..0xFF4], [0x17, 1]);
+        memory[0xFF3] = 0;
+        assert!(mailbox.put_incoming(&mut memory, 0x18));
+        assert_eq!(memory[0xFF2], 0x18);
+
+        assert!(Mailbox::new(0xFFC).is_some());
+        assert!(Mailbox::new(0xFFD).is_none());
+    }
+}
@@ -0,0 +1,19 @@
+//! Counters of what the interpreter did, e.g. to show the speed or to check for performance regressions.
+
+/// How often things happened since the interpreter was created.
+///
+/// Unlike the state of the interpreter, these are not restored by loading a savestate.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metrics {
+    /// The number of instructions executed.
+    pub instructions: u64,
+    /// The number of frames, i.e. how often the timers were updated.
+    pub frames: u64,
+    /// The number of sprites drawn.
+    pub draws: u64,
+    /// The number of key presses set by the frontend.
+    pub key_events: u64,
+    /// The number of frames that were skipped because the frontend could not keep up, so that the timers ticked
+    /// several times at once to catch up. The frontend counts these as only it knows the time.
+    pub timer_underruns: u64,
+}
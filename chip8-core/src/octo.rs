@@ -0,0 +1,719 @@
+//! Compiling of sources written for Octo, the assembler most CHIP-8 games are written with nowadays, like `v0 := 5`.
+//!
+//! Only part of the language is supported: labels, constants, aliases, macros without calculations, the statements
+//! of CHIP-8, `i := long`, `i := bighex`, `saveflags`, `loadflags`, `exit` and the scrolling statements, and the
+//! control flow of `if`, `then`, `begin`, `else`, `end`, `loop`, `while` and `again`. Numbers are decimal,
+//! hexadecimal with `0x` or binary with `0b`, and may be negative.
+
+use crate::{interpreter::START_POINT, symbols::Symbols, Error};
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::TryInto,
+};
+
+/// How many macros may be expanded at most, to stop macros that expand to themselves.
+const MAX_EXPANSIONS: usize = 10_000;
+
+/// A word of the source with the number of the line it is on.
+#[derive(Debug, Clone, PartialEq)]
+struct Token {
+    text: String,
+    line: usize,
+}
+
+#[derive(Debug)]
+struct Macro {
+    parameters: Vec<String>,
+    body: Vec<Token>,
+}
+
+/// A block of control flow that is still open.
+#[derive(Debug)]
+enum Block {
+    /// `if ... begin`, with the offset of the jump past the block.
+    Begin(usize),
+    /// `else`, with the offset of the jump past the block.
+    Else(usize),
+    /// `loop`, with the address to jump back to and the offsets of the jumps out of the loop by `while`.
+    Loop(u16, Vec<usize>),
+}
+
+/// A condition of `if` and `while`, as the register compared and the comparison.
+#[derive(Debug)]
+enum Condition {
+    Equal(u8, Operand),
+    NotEqual(u8, Operand),
+    Key(u8),
+    NotKey(u8),
+}
+
+#[derive(Debug)]
+enum Operand {
+    Register(u8),
+    Byte(u8),
+}
+
+/// Compiles the source to the bytes of the program.
+///
+/// If there is a `main` label that is not at the start, the program starts with a jump to it.
+pub fn compile(source: &str) -> Result<Vec<u8>, Error> {
+    compile_with_symbols(source, START_POINT).map(|(bytes, _)| bytes)
+}
+
+/// Like [`compile`], but for the program to be loaded at the address, and also returns the labels as symbols.
+pub fn compile_with_symbols(source: &str, load_address: u16) -> Result<(Vec<u8>, Symbols), Error> {
+    let tokens = expand_macros(tokenize(source))?;
+
+    let mut compiler = Compiler {
+        load_address,
+        tokens: tokens.into(),
+        line: 1,
+        bytes: Vec::new(),
+        labels: HashMap::new(),
+        constants: HashMap::new(),
+        aliases: HashMap::new(),
+        fixups: Vec::new(),
+        blocks: Vec::new(),
+    };
+
+    let defines_main = compiler
+        .tokens
+        .iter()
+        .zip(compiler.tokens.iter().skip(1))
+        .position(|(colon, name)| colon.text == ":" && name.text == "main");
+    if defines_main.is_some_and(|position| position > 0) {
+        compiler.emit_with_label(0x1000, "main".to_string());
+    }
+
+    while let Some(token) = compiler.tokens.pop_front() {
+        compiler.line = token.line;
+        compiler
+            .compile_statement(&token.text)
+            .map_err(|err| format!("Line {}: {}", compiler.line, err))?;
+    }
+
+    compiler.finish()
+}
+
+/// Splits the source into words, leaving out comments, which start with `#`.
+fn tokenize(source: &str) -> Vec<Token> {
+    source
+        .lines()
+        .enumerate()
+        .flat_map(|(index, line)| {
+            let code = line.split('#').next().unwrap_or_default();
+            code.split_whitespace().map(move |text| Token {
+                text: text.to_string(),
+                line: index + 1,
+            })
+        })
+        .collect()
+}
+
+/// Takes out the definitions of macros like `:macro name x y { ... }` and replaces every use of them with their body,
+/// with the parameters replaced by the words after the name.
+fn expand_macros(tokens: Vec<Token>) -> Result<Vec<Token>, Error> {
+    let mut macros: HashMap<String, Macro> = HashMap::new();
+    let mut tokens: VecDeque<Token> = tokens.into();
+    let mut expanded = Vec::new();
+    let mut expansions = 0;
+
+    while let Some(token) = tokens.pop_front() {
+        let at_line = |err: &str| format!("Line {}: {}", token.line, err);
+
+        if token.text == ":macro" {
+            let name = tokens
+                .pop_front()
+                .ok_or_else(|| at_line("Expected the name of the macro."))?;
+            let mut parameters = Vec::new();
+            loop {
+                match tokens.pop_front() {
+                    Some(parameter) if parameter.text == "{" => break,
+                    Some(parameter) => parameters.push(parameter.text),
+                    None => return Err(at_line("Expected `{` after the parameters.").into()),
+                }
+            }
+            let mut body = Vec::new();
+            let mut depth = 0;
+            loop {
+                match tokens.pop_front() {
+                    Some(token) if token.text == "}" && depth == 0 => break,
+                    Some(token) => {
+                        match token.text.as_str() {
+                            "{" => depth += 1,
+                            "}" => depth -= 1,
+                            _ => {}
+                        }
+                        body.push(token);
+                    }
+                    None => return Err(at_line("The macro is missing its `}`.").into()),
+                }
+            }
+            macros.insert(name.text, Macro { parameters, body });
+        } else if let Some(r#macro) = macros.get(&token.text) {
+            expansions += 1;
+            if expansions > MAX_EXPANSIONS {
+                return Err(
+                    at_line("Too many macros were expanded. Does a macro use itself?").into(),
+                );
+            }
+            let mut arguments = HashMap::new();
+            for parameter in &r#macro.parameters {
+                let argument = tokens.pop_front().ok_or_else(|| {
+                    at_line(&format!(
+                        "The macro `{}` takes {} arguments.",
+                        token.text,
+                        r#macro.parameters.len()
+                    ))
+                })?;
+                arguments.insert(parameter.as_str(), argument.text);
+            }
+            // The body is expanded too, as it may use other macros.
+            for body_token in r#macro.body.iter().rev() {
+                let text = arguments
+                    .get(body_token.text.as_str())
+                    .cloned()
+                    .unwrap_or_else(|| body_token.text.clone());
+                tokens.push_front(Token {
+                    text,
+                    line: token.line,
+                });
+            }
+        } else {
+            expanded.push(token);
+        }
+    }
+
+    Ok(expanded)
+}
+
+struct Compiler {
+    /// Where the program is loaded in memory.
+    load_address: u16,
+    /// The tokens left to compile.
+    tokens: VecDeque<Token>,
+    /// The line of the statement being compiled.
+    line: usize,
+    bytes: Vec<u8>,
+    labels: HashMap<String, u16>,
+    constants: HashMap<String, i32>,
+    /// Names for registers.
+    aliases: HashMap<String, u8>,
+    /// The offsets of instructions that take the address of a label, with the label and the line it was used on,
+    /// filled in once all labels are known.
+    fixups: Vec<(usize, String, usize)>,
+    blocks: Vec<Block>,
+}
+
+impl Compiler {
+    fn compile_statement(&mut self, token: &str) -> Result<(), Error> {
+        match token {
+            ":" => {
+                let name = self.next()?;
+                if self.labels.insert(name.clone(), self.here()).is_some() {
+                    return Err(format!("The label `{}` is defined twice.", name).into());
+                }
+            }
+            ":const" => {
+                let name = self.next()?;
+                let value = self.next()?;
+                let value = self
+                    .parse_number(&value)
+                    .ok_or_else(|| format!("Invalid value `{}`.", value))?;
+                self.constants.insert(name, value);
+            }
+            ":alias" => {
+                let name = self.next()?;
+                let register = self.next_register()?;
+                self.aliases.insert(name, register);
+            }
+            "clear" => self.emit(0x00E0),
+            "scroll-down" | "scroll-up" => {
+                let lines = self.next()?;
+                let lines = self
+                    .parse_number(&lines)
+                    .filter(|lines| (0..=0xF).contains(lines))
+                    .ok_or_else(|| format!("Invalid scroll distance `{}`.", lines))?;
+                let opcode = if token == "scroll-down" {
+                    0x00C0
+                } else {
+                    0x00D0
+                };
+                self.emit(opcode | lines as u16)
+            }
+            "scroll-left" => self.emit(0x00FC),
+            "exit" => self.emit(0x00FD),
+            "scroll-right" => self.emit(0x00FB),
+            "return" | ";" => self.emit(0x00EE),
+            "jump" => self.emit_address(0x1000)?,
+            "jump0" => self.emit_address(0xB000)?,
+            ":call" => self.emit_address(0x2000)?,
+            "sprite" => {
+                let vx = self.next_register()?;
+                let vy = self.next_register()?;
+                let height = self.next()?;
+                let height = self
+                    .parse_number(&height)
+                    .filter(|height| (0..=0xF).contains(height))
+                    .ok_or_else(|| format!("Invalid sprite height `{}`.", height))?;
+                self.emit(0xD000 | x(vx) | y(vy) | height as u16);
+            }
+            "bcd" => self.emit_register(0xF033)?,
+            "save" => self.emit_register(0xF055)?,
+            "load" => self.emit_register(0xF065)?,
+            "saveflags" => self.emit_register(0xF075)?,
+            "loadflags" => self.emit_register(0xF085)?,
+            "delay" | "buzzer" => {
+                self.expect(":=")?;
+                let register = self.next_register()?;
+                let instruction = if token == "delay" { 0xF015 } else { 0xF018 };
+                self.emit(instruction | x(register));
+            }
+            "i" => self.compile_i()?,
+            "if" => {
+                let condition = self.next_condition()?;
+                match self.next()?.as_str() {
+                    // The statement after `then` is skipped if the condition is false.
+                    "then" => self.emit(get_skip(&condition, false)),
+                    // The jump past the block is skipped if the condition is true.
+                    "begin" => {
+                        self.emit(get_skip(&condition, true));
+                        self.blocks.push(Block::Begin(self.bytes.len()));
+                        self.emit(0x1000);
+                    }
+                    other => {
+                        return Err(format!("Expected `then` or `begin`, got `{}`.", other).into())
+                    }
+                }
+            }
+            "else" => match self.blocks.pop() {
+                Some(Block::Begin(jump)) => {
+                    self.blocks.push(Block::Else(self.bytes.len()));
+                    self.emit(0x1000);
+                    self.patch_jump(jump);
+                }
+                _ => return Err("`else` without `if ... begin`.".into()),
+            },
+            "end" => match self.blocks.pop() {
+                Some(Block::Begin(jump) | Block::Else(jump)) => self.patch_jump(jump),
+                _ => return Err("`end` without `if ... begin`.".into()),
+            },
+            "loop" => self.blocks.push(Block::Loop(self.here(), Vec::new())),
+            "while" => {
+                let condition = self.next_condition()?;
+                self.emit(get_skip(&condition, true));
+                let jump = self.bytes.len();
+                self.emit(0x1000);
+                match self.blocks.iter_mut().rev().find_map(|block| match block {
+                    Block::Loop(_, breaks) => Some(breaks),
+                    _ => None,
+                }) {
+                    Some(breaks) => breaks.push(jump),
+                    None => return Err("`while` outside of `loop`.".into()),
+                }
+            }
+            "again" => match self.blocks.pop() {
+                Some(Block::Loop(start, breaks)) => {
+                    self.emit(0x1000 | start);
+                    for jump in breaks {
+                        self.patch_jump(jump);
+                    }
+                }
+                _ => return Err("`again` without `loop`.".into()),
+            },
+            _ => {
+                if let Some(register) = self.parse_register(token) {
+                    self.compile_register(register)?;
+                } else if let Some(number) = self.parse_number(token) {
+                    let byte = to_byte(number)
+                        .ok_or_else(|| format!("The number `{}` does not fit in a byte.", token))?;
+                    self.bytes.push(byte);
+                } else if is_name(token) {
+                    // Calling a subroutine by its name.
+                    self.emit_with_label(0x2000, token.to_string());
+                } else {
+                    return Err(format!("Unexpected `{}`.", token).into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Compiles the statements assigning to a register, like `v0 += 1` or `v0 := random 0xFF`.
+    fn compile_register(&mut self, register: u8) -> Result<(), Error> {
+        let operator = self.next()?;
+        let value = self.next()?;
+        let source = self.parse_register(&value);
+
+        let instruction = match (operator.as_str(), value.as_str(), source) {
+            (":=", _, Some(source)) => 0x8000 | y(source),
+            ("|=", _, Some(source)) => 0x8001 | y(source),
+            ("&=", _, Some(source)) => 0x8002 | y(source),
+            ("^=", _, Some(source)) => 0x8003 | y(source),
+            ("+=", _, Some(source)) => 0x8004 | y(source),
+            ("-=", _, Some(source)) => 0x8005 | y(source),
+            (">>=", _, Some(source)) => 0x8006 | y(source),
+            ("=-", _, Some(source)) => 0x8007 | y(source),
+            ("<<=", _, Some(source)) => 0x800E | y(source),
+            (":=", "random", None) => {
+                let mask = self.next()?;
+                0xC000 | self.to_byte(&mask)? as u16
+            }
+            (":=", "delay", None) => 0xF007,
+            (":=", "key", None) => 0xF00A,
+            (":=", _, None) => 0x6000 | self.to_byte(&value)? as u16,
+            ("+=", _, None) => 0x7000 | self.to_byte(&value)? as u16,
+            ("-=", _, None) => {
+                let byte = self.to_byte(&value)?;
+                0x7000 | byte.wrapping_neg() as u16
+            }
+            _ => {
+                return Err(format!(
+                    "Invalid statement `v{:X} {} {}`.",
+                    register, operator, value
+                )
+                .into())
+            }
+        };
+        self.emit(instruction | x(register));
+
+        Ok(())
+    }
+
+    /// Compiles the statements assigning to `I`, like `i := label` or `i += v0`.
+    fn compile_i(&mut self) -> Result<(), Error> {
+        match self.next()?.as_str() {
+            ":=" => match self.peek() {
+                Some("hex") => {
+                    self.next()?;
+                    self.emit_register(0xF029)?;
+                }
+                Some("bighex") => {
+                    self.next()?;
+                    self.emit_register(0xF030)?;
+                }
+                Some("long") => {
+                    self.next()?;
+                    self.emit(0xF000);
+                    let address = self.next()?;
+                    match self.parse_number(&address) {
+                        Some(number) if (0..=0xFFFF).contains(&number) => self.emit(number as u16),
+                        Some(_) => return Err(format!("Invalid address `{}`.", address).into()),
+                        // A long address is not filled in like the others, as it takes the whole word.
+                        None => return Err("`i := long` only supports numbers.".into()),
+                    }
+                }
+                _ => self.emit_address(0xA000)?,
+            },
+            "+=" => self.emit_register(0xF01E)?,
+            operator => return Err(format!("Invalid operator `{}` for `i`.", operator).into()),
+        }
+
+        Ok(())
+    }
+
+    /// Parses a condition like `v0 == 5`, `v0 != v1` or `v0 key`.
+    fn next_condition(&mut self) -> Result<Condition, Error> {
+        let register = self.next_register()?;
+        let condition = match self.next()?.as_str() {
+            "key" => Condition::Key(register),
+            "-key" => Condition::NotKey(register),
+            operator @ ("==" | "!=") => {
+                let value = self.next()?;
+                let operand = match self.parse_register(&value) {
+                    Some(register) => Operand::Register(register),
+                    None => Operand::Byte(self.to_byte(&value)?),
+                };
+                if operator == "==" {
+                    Condition::Equal(register, operand)
+                } else {
+                    Condition::NotEqual(register, operand)
+                }
+            }
+            operator => {
+                return Err(format!(
+                    "Unsupported comparison `{}`. Expected `==`, `!=`, `key` or `-key`.",
+                    operator
+                )
+                .into())
+            }
+        };
+
+        Ok(condition)
+    }
+
+    fn finish(self) -> Result<(Vec<u8>, Symbols), Error> {
+        if let Some(block) = self.blocks.last() {
+            let missing = if matches!(block, Block::Loop(..)) {
+                "again"
+            } else {
+                "end"
+            };
+            return Err(format!("A block is missing its `{}`.", missing).into());
+        }
+
+        let mut bytes = self.bytes;
+        for (offset, label, line) in self.fixups {
+            let address = *self
+                .labels
+                .get(&label)
+                .ok_or_else(|| format!("Line {}: Unknown label `{}`.", line, label))?;
+            if address > 0xFFF {
+                return Err(format!("Line {}: The label `{}` is past 0xFFF.", line, label).into());
+            }
+            bytes[offset] |= (address >> 8) as u8;
+            bytes[offset + 1] = address as u8;
+        }
+
+        let mut symbols = Symbols::default();
+        for (name, address) in self.labels {
+            symbols.add(address, name);
+        }
+
+        Ok((bytes, symbols))
+    }
+
+    fn next(&mut self) -> Result<String, Error> {
+        self.tokens
+            .pop_front()
+            .map(|token| token.text)
+            .ok_or_else(|| "Unexpected end of the source.".into())
+    }
+
+    fn peek(&self) -> Option<&str> {
+        self.tokens.front().map(|token| token.text.as_str())
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), Error> {
+        match self.next()? {
+            token if token == expected => Ok(()),
+            token => Err(format!("Expected `{}`, got `{}`.", expected, token).into()),
+        }
+    }
+
+    fn next_register(&mut self) -> Result<u8, Error> {
+        let token = self.next()?;
+        self.parse_register(&token)
+            .ok_or_else(|| format!("Expected a register, got `{}`.", token).into())
+    }
+
+    /// Parses a register like `v0` or `vA`, or an alias of one.
+    fn parse_register(&self, token: &str) -> Option<u8> {
+        if let Some(register) = self.aliases.get(token) {
+            return Some(*register);
+        }
+        let digit = token.strip_prefix(['v', 'V'])?;
+        if digit.len() != 1 {
+            return None;
+        }
+        u8::from_str_radix(digit, 16).ok()
+    }
+
+    /// Parses a number, which may be the name of a constant.
+    fn parse_number(&self, token: &str) -> Option<i32> {
+        if let Some(value) = self.constants.get(token) {
+            return Some(*value);
+        }
+        let (negative, digits) = match token.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, token),
+        };
+        let number = if let Some(hex) = digits.strip_prefix("0x") {
+            i32::from_str_radix(hex, 16).ok()?
+        } else if let Some(binary) = digits.strip_prefix("0b") {
+            i32::from_str_radix(binary, 2).ok()?
+        } else {
+            digits.parse().ok()?
+        };
+        Some(if negative { -number } else { number })
+    }
+
+    fn to_byte(&self, token: &str) -> Result<u8, Error> {
+        self.parse_number(token)
+            .and_then(to_byte)
+            .ok_or_else(|| format!("Expected a byte, got `{}`.", token).into())
+    }
+
+    /// Returns the address the next byte is at, or `0xFFFF` if that is past the end of the address space.
+    fn here(&self) -> u16 {
+        self.bytes
+            .len()
+            .try_into()
+            .ok()
+            .and_then(|length| self.load_address.checked_add(length))
+            .unwrap_or(u16::MAX)
+    }
+
+    fn emit(&mut self, instruction: u16) {
+        self.bytes.extend_from_slice(&instruction.to_be_bytes());
+    }
+
+    fn emit_register(&mut self, instruction: u16) -> Result<(), Error> {
+        let register = self.next_register()?;
+        self.emit(instruction | x(register));
+        Ok(())
+    }
+
+    /// Emits the instruction with the address that follows, which is a number or a label.
+    fn emit_address(&mut self, instruction: u16) -> Result<(), Error> {
+        let token = self.next()?;
+        match self.parse_number(&token) {
+            Some(address) if (0..=0xFFF).contains(&address) => {
+                self.emit(instruction | address as u16)
+            }
+            Some(_) => return Err(format!("Invalid address `{}`.", token).into()),
+            None => self.emit_with_label(instruction, token),
+        }
+        Ok(())
+    }
+
+    /// Emits the instruction with the address of the label, which is filled in at the end.
+    fn emit_with_label(&mut self, instruction: u16, label: String) {
+        self.fixups.push((self.bytes.len(), label, self.line));
+        self.emit(instruction);
+    }
+
+    /// Makes the jump at the offset jump to the next address.
+    fn patch_jump(&mut self, offset: usize) {
+        let instruction = 0x1000 | self.here();
+        self.bytes[offset..offset + 2].copy_from_slice(&instruction.to_be_bytes());
+    }
+}
+
+/// Returns the instruction skipping the next one when the condition is the given value.
+fn get_skip(condition: &Condition, value: bool) -> u16 {
+    use {Condition::*, Operand::*};
+
+    match (condition, value) {
+        (Equal(vx, Byte(byte)), true) | (NotEqual(vx, Byte(byte)), false) => {
+            0x3000 | x(*vx) | *byte as u16
+        }
+        (NotEqual(vx, Byte(byte)), true) | (Equal(vx, Byte(byte)), false) => {
+            0x4000 | x(*vx) | *byte as u16
+        }
+        (Equal(vx, Register(vy)), true) | (NotEqual(vx, Register(vy)), false) => {
+            0x5000 | x(*vx) | y(*vy)
+        }
+        (NotEqual(vx, Register(vy)), true) | (Equal(vx, Register(vy)), false) => {
+            0x9000 | x(*vx) | y(*vy)
+        }
+        (Key(vx), true) | (NotKey(vx), false) => 0xE09E | x(*vx),
+        (NotKey(vx), true) | (Key(vx), false) => 0xE0A1 | x(*vx),
+    }
+}
+
+/// Converts a number from -128 to 255 to a byte, with negative numbers in two's complement.
+fn to_byte(number: i32) -> Option<u8> {
+    (-128..=255).contains(&number).then_some(number as u8)
+}
+
+/// Returns whether the token can be the name of a label.
+fn is_name(token: &str) -> bool {
+    token
+        .chars()
+        .all(|char| char.is_ascii_alphanumeric() || matches!(char, '_' | '-' | '.'))
+        && !token.starts_with(|char: char| char.is_ascii_digit())
+}
+
+fn x(register: u8) -> u16 {
+    (register as u16) << 8
+}
+
+fn y(register: u8) -> u16 {
+    (register as u16) << 4
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compile() {
+        let source = "
+            : data 0xF0 0x90 # A sprite
+            :const SPEED 2
+            :alias x v1
+            : main
+                clear
+                x := SPEED
+                x += -1
+                i := data
+                sprite x x 2
+                if x == 1 then x := key
+                loop
+                    v0 := random 0xFF
+                    while v0 != 0
+                    draw
+                again
+                if x key begin
+                    delay := x
+                else
+                    buzzer := x
+                end
+            : draw
+                ;
+        ";
+        let (bytes, symbols) = compile_with_symbols(source, START_POINT).unwrap();
+        let words: Vec<u16> = bytes[2..]
+            .chunks(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+        assert_eq!(
+            words,
+            [
+                0xF090, 0x00E0, 0x6102, 0x71FF, 0xA202, 0xD112, 0x4101, 0xF10A, //
+                // The loop.
+                0xC0FF, 0x4000, 0x121C, 0x2226, 0x1212, //
+                // The block.
+                0xE19E, 0x1224, 0xF115, 0x1226, 0xF118, //
+                0x00EE,
+            ]
+        );
+        // The jump to main, as the sprite is before it.
+        assert_eq!(bytes[..2], [0x12, 0x04]);
+        assert_eq!(symbols.to_text(), "0x202=data\n0x204=main\n0x226=draw\n");
+    }
+
+    #[test]
+    fn test_load_address() {
+        let (bytes, symbols) = compile_with_symbols(": main jump main", 0x600).unwrap();
+        assert_eq!(bytes, [0x16, 0x00]);
+        assert_eq!(symbols.to_text(), "0x600=main\n");
+    }
+
+    #[test]
+    fn test_macros() {
+        let source = ":macro add-twice register amount { register += amount register += amount }
+            add-twice v3 5";
+        assert_eq!(compile(source).unwrap(), [0x73, 0x05, 0x73, 0x05]);
+        assert!(compile(":macro loop-forever { loop-forever } loop-forever").is_err());
+    }
+
+    #[test]
+    fn test_super_chip() {
+        assert_eq!(
+            compile("scroll-down 4 scroll-up 1 scroll-left scroll-right").unwrap(),
+            [0x00, 0xC4, 0x00, 0xD1, 0x00, 0xFC, 0x00, 0xFB]
+        );
+        assert_eq!(
+            compile("saveflags v3 loadflags v3").unwrap(),
+            [0xF3, 0x75, 0xF3, 0x85]
+        );
+        assert!(compile("scroll-down 16").is_err());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            compile("v0 := 5\njump nowhere").unwrap_err(),
+            "Line 2: Unknown label `nowhere`."
+        );
+        assert_eq!(
+            compile("loop\nv0 := 256").unwrap_err(),
+            "Line 2: Expected a byte, got `256`."
+        );
+        assert!(compile("if v0 == 1 begin").is_err());
+        assert!(compile("else").is_err());
+    }
+}
@@ -0,0 +1,110 @@
+//! Changes to the program applied in memory when it is loaded, e.g. to fix known bugs of a program without
+//! distributing a modified copy of it.
+
+use crate::{util::parse_number, Error};
+use std::{convert::TryFrom, fs, path::Path};
+
+/// Bytes to write to memory starting at an address.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Patch {
+    pub address: u16,
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Patches {
+    patches: Vec<Patch>,
+}
+
+impl Patches {
+    pub fn add(&mut self, address: u16, bytes: Vec<u8>) {
+        self.patches.push(Patch { address, bytes });
+    }
+
+    /// Parses a patch like `patch 0x3A2 = 0x00 0xEE`, where the `patch` keyword is optional.
+    pub fn add_from_str(&mut self, string: &str) -> Result<(), Error> {
+        let patch = string.trim();
+        let patch = patch.strip_prefix("patch ").unwrap_or(patch);
+
+        let (address, bytes) = patch
+            .split_once('=')
+            .and_then(|(address, bytes)| {
+                let address = parse_number(address.trim())?;
+                let bytes = bytes
+                    .split_whitespace()
+                    .map(|byte| u8::try_from(parse_number(byte)?).ok())
+                    .collect::<Option<Vec<u8>>>()?;
+                Some((address, bytes))
+            })
+            .filter(|(_, bytes)| !bytes.is_empty())
+            .ok_or_else(|| {
+                format!(
+                    "Invalid patch `{}`. Expected something like `patch 0x3A2 = 0x00 0xEE`.",
+                    string
+                )
+            })?;
+
+        self.add(address, bytes);
+
+        Ok(())
+    }
+
+    /// Loads patches from a file with one patch like `patch 0x3A2 = 0x00 0xEE` per line.
+    ///
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn load(&mut self, path: &Path) -> Result<(), Error> {
+        let text =
+            fs::read_to_string(path).map_err(|err| format!("Failed to read patches: {}", err))?;
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            self.add_from_str(line)
+                .map_err(|err| format!("Line {}: {}", index + 1, err))?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the patches to memory in the order they were added.
+    pub fn apply(&self, memory: &mut [u8]) -> Result<(), Error> {
+        for patch in &self.patches {
+            let start = patch.address as usize;
+            let destination = memory
+                .get_mut(start..start + patch.bytes.len())
+                .ok_or_else(|| {
+                    format!(
+                        "The patch at {:#05X} does not fit in memory.",
+                        patch.address
+                    )
+                })?;
+            destination.copy_from_slice(&patch.bytes);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        let mut patches = Patches::default();
+        patches.add_from_str("patch 0x202 = 0x00 0xEE").unwrap();
+        patches.add_from_str("0x204=0x12").unwrap();
+        assert!(patches.add_from_str("patch 0x202 = 0x100").is_err());
+        assert!(patches.add_from_str("patch 0x202 =").is_err());
+
+        let mut memory = [0xFF; 0x206];
+        patches.apply(&mut memory).unwrap();
+        assert_eq!(memory[0x200..], [0xFF, 0xFF, 0x00, 0xEE, 0x12, 0xFF]);
+
+        patches.add(0x205, vec![0x00, 0x00]);
+        assert!(patches.apply(&mut memory).is_err());
+    }
+}
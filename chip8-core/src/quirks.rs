@@ -0,0 +1,157 @@
+//! Detection of instructions whose behavior differs between CHIP-8 implementations.
+//!
+//! Programs written for one implementation often behave weirdly on another because of these differences,
+//! which are commonly called quirks.
+
+use crate::interpreter::{split_word, MEMORY_SIZE, XO_CHIP_MEMORY_SIZE};
+
+/// Choices of how to behave where implementations differ.
+#[derive(Debug, Clone, Default)]
+pub struct Quirks {
+    /// Whether `FX0A` waits for the pressed key to be released again before continuing, like on the COSMAC VIP.
+    pub await_key_release: bool,
+    /// Whether sprites drawn past the right or bottom edge of the screen wrap around to the other side instead of
+    /// being clipped, like on XO-CHIP.
+    pub wrap_sprites: bool,
+    /// What happens when `DXYN`, `FX33`, `FX55` or `FX65` access memory past its end.
+    pub memory_end: MemoryEnd,
+    /// Whether the stack only holds 12 return addresses like on the COSMAC VIP instead of 16 like on SUPER-CHIP.
+    pub small_stack: bool,
+    /// Whether the extensions of XO-CHIP are enabled: 64 KB of memory and `F000 NNNN`, which sets `I` to the 16-bit
+    /// address in the following word.
+    pub xo_chip: bool,
+}
+
+impl Quirks {
+    /// Returns the size of the memory in bytes.
+    pub fn get_memory_size(&self) -> usize {
+        if self.xo_chip {
+            XO_CHIP_MEMORY_SIZE
+        } else {
+            MEMORY_SIZE
+        }
+    }
+}
+
+/// What `DXYN`, `FX33`, `FX55` and `FX65` do with addresses past the end of the memory at `0xFFF`, which they access
+/// when `I` is close to it. Implementations differ here or do not handle this at all.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum MemoryEnd {
+    /// Stop with an error before accessing anything, as the program most likely has a bug.
+    #[default]
+    Error,
+    /// Continue at the start of the memory, like implementations that keep addresses to 12 bits.
+    Wrap,
+    /// Access the last byte instead, so all writes past the end land on it and all reads past the end read it.
+    Clamp,
+}
+
+/// A behavior that differs between implementations.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Quirk {
+    /// `8XY6` and `8XYE` with two different registers.
+    Shift,
+    /// `FX55` and `FX65` followed by an instruction using the address register.
+    LoadStore,
+    /// `BNNN` with a nonzero `VX`, where `X` is the first digit of `NNN`.
+    Jump,
+}
+
+impl Quirk {
+    /// Describes how the program may expect the instruction to behave and which implementation that comes from.
+    pub fn get_warning(self) -> &'static str {
+        match self {
+            Self::Shift => {
+                "Quirk: 8XY6/8XYE shift VX in place here, like SUPER-CHIP. \
+                 If the program behaves weirdly, it may expect VY to be shifted into VX like on the COSMAC VIP."
+            }
+            Self::LoadStore => {
+                "Quirk: FX55/FX65 leave I unchanged here, like SUPER-CHIP. \
+                 If the program behaves weirdly, it may expect I to be incremented like on the COSMAC VIP."
+            }
+            Self::Jump => {
+                "Quirk: BNNN jumps to NNN plus V0 here, like the COSMAC VIP. \
+                 If the program behaves weirdly, it may expect NNN plus VX like on SUPER-CHIP."
+            }
+        }
+    }
+}
+
+/// Watches the executed instructions for quirk-sensitive patterns and reports each quirk only once.
+#[derive(Debug, Default)]
+pub struct QuirkDetector {
+    /// The quirks already reported.
+    reported: Vec<Quirk>,
+    /// Whether `FX55` or `FX65` was executed and `I` has not been set since.
+    load_store_executed: bool,
+}
+
+impl QuirkDetector {
+    /// Checks the instruction about to be executed with the given general purpose registers.
+    ///
+    /// Returns the quirk the instruction depends on, unless it was already reported.
+    pub fn check(&mut self, instruction: u16, gpr: &[u8; 16]) -> Option<Quirk> {
+        let (nibble1, nibble2, nibble3, nibble4) = split_word(instruction);
+        let byte2 = instruction as u8;
+
+        let uses_i =
+            nibble1.0 == 0xD || (nibble1.0 == 0xF && matches!(byte2, 0x1E | 0x33 | 0x55 | 0x65));
+        let sets_i = nibble1.0 == 0xA || (nibble1.0 == 0xF && byte2 == 0x29);
+
+        let quirk = match nibble1.0 {
+            0x8 if matches!(nibble4.0, 0x6 | 0xE) && nibble2 != nibble3 => Some(Quirk::Shift),
+            0xB if gpr[nibble2.0 as usize] != 0 && nibble2.0 != 0 => Some(Quirk::Jump),
+            _ if uses_i && self.load_store_executed => Some(Quirk::LoadStore),
+            _ => None,
+        };
+
+        if sets_i {
+            self.load_store_executed = false;
+        }
+        if nibble1.0 == 0xF && matches!(byte2, 0x55 | 0x65) {
+            self.load_store_executed = true;
+        }
+
+        let quirk = quirk?;
+        if self.reported.contains(&quirk) {
+            None
+        } else {
+            self.reported.push(quirk);
+            Some(quirk)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let mut detector = QuirkDetector::default();
+        let mut gpr = [0; 16];
+
+        assert_eq!(detector.check(0x8006, &gpr), None);
+        assert_eq!(detector.check(0x8016, &gpr), Some(Quirk::Shift));
+        // Every quirk is only reported once.
+        assert_eq!(detector.check(0x801E, &gpr), None);
+
+        assert_eq!(detector.check(0xB123, &gpr), None);
+        gpr[1] = 1;
+        assert_eq!(detector.check(0xB123, &gpr), Some(Quirk::Jump));
+    }
+
+    #[test]
+    fn test_check_load_store() {
+        let mut detector = QuirkDetector::default();
+        let gpr = [0; 16];
+
+        // Setting I in between makes it irrelevant whether it was incremented.
+        assert_eq!(detector.check(0xF255, &gpr), None);
+        assert_eq!(detector.check(0xA300, &gpr), None);
+        assert_eq!(detector.check(0xD015, &gpr), None);
+
+        assert_eq!(detector.check(0xF265, &gpr), None);
+        assert_eq!(detector.check(0xD015, &gpr), Some(Quirk::LoadStore));
+    }
+}
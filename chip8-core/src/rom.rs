@@ -0,0 +1,179 @@
+//! Checks of files before running them as programs, and detection of the platform a program was written for.
+//!
+//! Like in [`crate::analysis`], every 2 bytes of the program are looked at as an instruction, so data that happens to
+//! look like an instruction of another platform can make a program look like it was written for that platform.
+
+use crate::{
+    interpreter::{split_word, MEMORY_SIZE, START_POINT, XO_CHIP_MEMORY_SIZE},
+    Error,
+};
+
+/// The largest program that fits in the memory of CHIP-8 and SUPER-CHIP, loaded at [`START_POINT`].
+pub const MAX_SIZE: usize = MEMORY_SIZE - START_POINT as usize;
+/// The largest program that fits in the memory of XO-CHIP.
+pub const XO_CHIP_MAX_SIZE: usize = XO_CHIP_MEMORY_SIZE - START_POINT as usize;
+
+/// The first bytes of files that are commonly mistaken for programs, and what they are.
+const FILE_SIGNATURES: &[(&[u8], &str)] = &[
+    (b"\x7FELF", "an ELF executable"),
+    (b"\x89PNG\r\n\x1A\n", "a PNG image"),
+    (b"GIF87a", "a GIF image"),
+    (b"GIF89a", "a GIF image"),
+    (b"\xFF\xD8\xFF", "a JPEG image"),
+    (b"%PDF", "a PDF document"),
+    (b"PK\x03\x04", "a ZIP archive"),
+    (b"\x1F\x8B\x08", "a gzip archive"),
+    (b"CH8STATE", "a savestate"),
+    (b"CH8INPUT", "a replay"),
+];
+
+/// A platform programs are written for, each extending the one before.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Platform {
+    Chip8,
+    SuperChip,
+    XoChip,
+}
+
+impl Platform {
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Self::Chip8 => "CHIP-8",
+            Self::SuperChip => "SUPER-CHIP",
+            Self::XoChip => "XO-CHIP",
+        }
+    }
+}
+
+/// Which platform a program looks like it was written for, and why.
+#[derive(Debug, PartialEq)]
+pub struct Detection {
+    pub platform: Platform,
+    /// The first instruction only the platform has, with its address, unless the platform was detected otherwise.
+    pub evidence: Option<(u16, u16)>,
+}
+
+/// Returns an error if the file is obviously not a program: if it is empty, too big for any platform or a file of
+/// another format.
+pub fn check(binary: &[u8]) -> Result<(), Error> {
+    if binary.is_empty() {
+        return Err("The program is empty.".into());
+    }
+    if let Some((_, format)) = FILE_SIGNATURES
+        .iter()
+        .find(|(signature, _)| binary.starts_with(signature))
+    {
+        return Err(format!("This is not a program but {}.", format).into());
+    }
+    if binary.len() > XO_CHIP_MAX_SIZE {
+        return Err(format!(
+            "The file is {} bytes, but programs are at most {} bytes, or {} for XO-CHIP.",
+            binary.len(),
+            MAX_SIZE,
+            XO_CHIP_MAX_SIZE
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Detects the platform the program looks like it was written for by the instructions it contains.
+pub fn detect_platform(program: &[u8]) -> Detection {
+    let mut detection = Detection {
+        platform: Platform::Chip8,
+        evidence: None,
+    };
+
+    for (index, bytes) in program.chunks_exact(2).enumerate() {
+        let instruction = u16::from_be_bytes([bytes[0], bytes[1]]);
+        let platform = get_platform(instruction);
+        if platform > detection.platform {
+            let address = START_POINT + index as u16 * 2;
+            detection = Detection {
+                platform,
+                evidence: Some((address, instruction)),
+            };
+        }
+    }
+
+    if program.len() > MAX_SIZE && detection.platform < Platform::XoChip {
+        detection = Detection {
+            platform: Platform::XoChip,
+            evidence: None,
+        };
+    }
+
+    detection
+}
+
+/// Returns the first platform that has the instruction.
+fn get_platform(instruction: u16) -> Platform {
+    let (nibble1, _, _, nibble4) = split_word(instruction);
+    let byte2 = instruction as u8;
+
+    match (nibble1.0, byte2) {
+        // Scrolling, exiting and switching the resolution.
+        (0x0, 0xC1..=0xCF | 0xFB..=0xFF) if instruction >> 8 == 0x00 => Platform::SuperChip,
+        // Drawing 16x16 sprites.
+        (0xD, _) if nibble4.0 == 0 => Platform::SuperChip,
+        // The big font and the flags.
+        (0xF, 0x30 | 0x75 | 0x85) => Platform::SuperChip,
+        // Scrolling up.
+        (0x0, 0xD1..=0xDF) if instruction >> 8 == 0x00 => Platform::XoChip,
+        // Saving and loading ranges of registers.
+        (0x5, _) if matches!(nibble4.0, 0x2 | 0x3) => Platform::XoChip,
+        // Loading long addresses, the audio pattern, the plane and the pitch.
+        (0xF, 0x00 | 0x02) if instruction & 0x0F00 == 0 => Platform::XoChip,
+        (0xF, 0x01 | 0x3A) => Platform::XoChip,
+        _ => Platform::Chip8,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        assert!(check(&[0x00, 0xE0]).is_ok());
+        assert_eq!(check(&[]).unwrap_err(), "The program is empty.");
+        assert_eq!(
+            check(b"\x89PNG\r\n\x1A\n\0\0\0\x0DIHDR").unwrap_err(),
+            "This is not a program but a PNG image."
+        );
+        assert!(check(&vec![0; XO_CHIP_MAX_SIZE]).is_ok());
+        assert!(check(&vec![0; XO_CHIP_MAX_SIZE + 1]).is_err());
+    }
+
+    #[test]
+    fn test_detect_platform() {
+        // CLS, LD V0, 0x01
+        assert_eq!(
+            detect_platform(&[0x00, 0xE0, 0x60, 0x01]),
+            Detection {
+                platform: Platform::Chip8,
+                evidence: None
+            }
+        );
+        // HIGH, DRW V0, V1, 0, SCU 2
+        assert_eq!(
+            detect_platform(&[0x00, 0xFF, 0xD0, 0x10, 0x00, 0xD2]),
+            Detection {
+                platform: Platform::XoChip,
+                evidence: Some((0x204, 0x00D2))
+            }
+        );
+        assert_eq!(
+            detect_platform(&[0x00, 0xE0, 0x00, 0xFF]),
+            Detection {
+                platform: Platform::SuperChip,
+                evidence: Some((0x202, 0x00FF))
+            }
+        );
+        assert_eq!(
+            detect_platform(&vec![0; MAX_SIZE + 2]).platform,
+            Platform::XoChip
+        );
+    }
+}
@@ -0,0 +1,317 @@
+use crate::layout::{Field, Kind, Layout};
+#[cfg(feature = "savestates")]
+use crate::{
+    util::{self, Reader},
+    Error,
+};
+#[cfg(feature = "savestates")]
+use std::{fs, path::Path};
+
+/// The bytes every savestate file starts with.
+const MAGIC: &[u8; 8] = b"CH8STATE";
+const VERSION: u8 = 3;
+
+/// The layout of savestate files, which needs to be updated along with [`State::to_bytes`].
+pub const LAYOUT: Layout = Layout {
+    name: "savestate",
+    extension: "state",
+    magic: MAGIC,
+    version: VERSION,
+    header: &[Field {
+        name: "rom_hash",
+        kind: Kind::U64,
+        description: "The hash of the program the state was taken of.",
+    }],
+    payload: &[
+        Field {
+            name: "pc",
+            kind: Kind::U16,
+            description: "The program counter.",
+        },
+        Field {
+            name: "gpr",
+            kind: Kind::Bytes(16),
+            description: "The general purpose registers V0 to VF.",
+        },
+        Field {
+            name: "i",
+            kind: Kind::U16,
+            description: "The address register.",
+        },
+        Field {
+            name: "delay_timer",
+            kind: Kind::U8,
+            description: "The delay timer.",
+        },
+        Field {
+            name: "sound_timer",
+            kind: Kind::U8,
+            description: "The sound timer.",
+        },
+        Field {
+            name: "stack_length",
+            kind: Kind::U8,
+            description: "The number of return addresses on the stack.",
+        },
+        Field {
+            name: "stack",
+            kind: Kind::List(
+                Some("stack_length"),
+                &[Field {
+                    name: "address",
+                    kind: Kind::U16,
+                    description: "A return address, from the bottom of the stack.",
+                }],
+            ),
+            description: "The stack.",
+        },
+        Field {
+            name: "memory_length",
+            kind: Kind::U32,
+            description: "The size of the memory.",
+        },
+        Field {
+            name: "memory",
+            kind: Kind::BytesOfLength("memory_length"),
+            description: "The whole memory, including the font.",
+        },
+        Field {
+            name: "display_width",
+            kind: Kind::U16,
+            description: "The width of the display in pixels.",
+        },
+        Field {
+            name: "display_height",
+            kind: Kind::U16,
+            description: "The height of the display in pixels.",
+        },
+        Field {
+            name: "display",
+            kind: Kind::Bitmap("display_width", "display_height"),
+            description: "The pixels of the first plane of the display.",
+        },
+        Field {
+            name: "display_plane2",
+            kind: Kind::Bitmap("display_width", "display_height"),
+            description: "The pixels of the second plane of the display, which only XO-CHIP programs draw on.",
+        },
+        Field {
+            name: "planes",
+            kind: Kind::U8,
+            description: "The bitmask of the planes that are drawn on.",
+        },
+    ],
+};
+
+/// A snapshot of the complete state of the interpreter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct State {
+    /// The hash of the program the state was taken of, so that it is not loaded for another program.
+    pub rom_hash: u64,
+    pub pc: u16,
+    pub gpr: [u8; 16],
+    pub i: u16,
+    pub stack: Vec<u16>,
+    pub memory: Vec<u8>,
+    pub delay_timer: u8,
+    pub sound_timer: u8,
+    pub display_width: u16,
+    pub display_height: u16,
+    /// The pixels of the first plane of the display, row by row.
+    pub display: Vec<bool>,
+    /// The pixels of the second plane of the display, row by row.
+    pub display_plane2: Vec<bool>,
+    /// The bitmask of the planes that are drawn on.
+    pub planes: u8,
+}
+
+#[cfg(feature = "savestates")]
+impl State {
+    /// Serializes the state into the savestate format.
+    ///
+    /// After the magic bytes, the version and the program's hash, the state follows compressed with zstd along with
+    /// a checksum.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&self.rom_hash.to_be_bytes());
+        bytes.extend_from_slice(&util::compress(&self.get_payload())?);
+
+        Ok(bytes)
+    }
+
+    fn get_payload(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.memory.len() + self.display.len() / 8 + 64);
+
+        bytes.extend_from_slice(&self.pc.to_be_bytes());
+        bytes.extend_from_slice(&self.gpr);
+        bytes.extend_from_slice(&self.i.to_be_bytes());
+        bytes.push(self.delay_timer);
+        bytes.push(self.sound_timer);
+
+        bytes.push(self.stack.len() as u8);
+        for address in &self.stack {
+            bytes.extend_from_slice(&address.to_be_bytes());
+        }
+
+        bytes.extend_from_slice(&(self.memory.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(&self.memory);
+
+        bytes.extend_from_slice(&self.display_width.to_be_bytes());
+        bytes.extend_from_slice(&self.display_height.to_be_bytes());
+        for plane in [&self.display, &self.display_plane2] {
+            // Pack 8 pixels into every byte.
+            for pixels in plane.chunks(8) {
+                let byte = pixels.iter().enumerate().fold(0, |byte, (index, pixel)| {
+                    byte | (*pixel as u8) << (7 - index)
+                });
+                bytes.push(byte);
+            }
+        }
+        bytes.push(self.planes);
+
+        bytes
+    }
+
+    /// Deserializes a state from the savestate format.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader::new(bytes);
+
+        if reader.read(MAGIC.len())? != MAGIC {
+            return Err("This is not a savestate.".into());
+        }
+        let version = reader.read_u8()?;
+        if version != VERSION {
+            return Err(format!("Unsupported savestate version {}.", version).into());
+        }
+        let rom_hash = reader.read_u64()?;
+
+        let payload = util::decompress(reader.read_to_end())
+            .map_err(|err| format!("Failed to load savestate: {}", err))?;
+        let mut reader = Reader::new(&payload);
+
+        let pc = reader.read_u16()?;
+        let mut gpr = [0; 16];
+        gpr.copy_from_slice(reader.read(16)?);
+        let i = reader.read_u16()?;
+        let delay_timer = reader.read_u8()?;
+        let sound_timer = reader.read_u8()?;
+
+        let stack_length = reader.read_u8()?;
+        let stack = (0..stack_length)
+            .map(|_| reader.read_u16())
+            .collect::<Result<_, _>>()?;
+
+        let memory_length = reader.read_u32()? as usize;
+        let memory = reader.read(memory_length)?.to_vec();
+
+        let display_width = reader.read_u16()?;
+        let display_height = reader.read_u16()?;
+        let pixel_count = display_width as usize * display_height as usize;
+        let mut read_plane = || -> Result<Vec<bool>, Error> {
+            Ok(reader
+                .read(pixel_count.div_ceil(8))?
+                .iter()
+                .flat_map(|byte| crate::util::Bits::new(*byte))
+                .take(pixel_count)
+                .collect())
+        };
+        let display = read_plane()?;
+        let display_plane2 = read_plane()?;
+        let planes = reader.read_u8()?;
+
+        Ok(Self {
+            rom_hash,
+            pc,
+            gpr,
+            i,
+            stack,
+            memory,
+            delay_timer,
+            sound_timer,
+            display_width,
+            display_height,
+            display,
+            display_plane2,
+            planes,
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        fs::write(path, self.to_bytes()?)
+            .map_err(|err| format!("Failed to write savestate: {}", err).into())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let bytes = fs::read(path).map_err(|err| format!("Failed to read savestate: {}", err))?;
+
+        Self::from_bytes(&bytes)
+    }
+}
+
+#[cfg(all(test, feature = "savestates"))]
+mod tests {
+    use super::*;
+
+    fn get_state() -> State {
+        State {
+            rom_hash: 0x1234_5678_9ABC_DEF0,
+            pc: 0x2F0,
+            gpr: [7; 16],
+            i: 0x300,
+            stack: vec![0x202, 0x24E],
+            memory: (0..=255).collect(),
+            delay_timer: 12,
+            sound_timer: 3,
+            display_width: 5,
+            display_height: 3,
+            display: vec![
+                true, false, true, true, false, //
+                false, false, false, false, true, //
+                true, true, true, true, true,
+            ],
+            display_plane2: vec![
+                false, false, false, false, true, //
+                false, true, false, false, false, //
+                false, false, false, false, false,
+            ],
+            planes: 0b11,
+        }
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let state = get_state();
+
+        assert_eq!(
+            State::from_bytes(&state.to_bytes().unwrap()).unwrap(),
+            state
+        );
+    }
+
+    #[test]
+    fn test_layout() {
+        let bytes = get_state().to_bytes().unwrap();
+        let payload = util::decompress(&bytes[LAYOUT.get_header_size()..]).unwrap();
+        assert_eq!(payload, get_state().get_payload());
+    }
+
+    #[test]
+    fn test_invalid() {
+        let bytes = get_state().to_bytes().unwrap();
+
+        assert!(State::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(State::from_bytes(b"CH8STATF").is_err());
+
+        let mut corrupted = bytes.clone();
+        *corrupted.last_mut().unwrap() ^= 0x01;
+        assert!(State::from_bytes(&corrupted).is_err());
+
+        let mut old_version = bytes;
+        old_version[MAGIC.len()] = 1;
+        assert_eq!(
+            State::from_bytes(&old_version),
+            Err("Unsupported savestate version 1.".into())
+        );
+    }
+}
@@ -0,0 +1,115 @@
+//! Names for addresses in the code of a program, like the labels of its source, to show instead of raw addresses.
+//!
+//! A symbol file has one symbol like `0x2F0=draw_ball` per line, the same syntax as single-address annotations.
+//! Octo sources can be turned into one with `chip8 symbols`.
+
+use crate::{util::parse_number, Error};
+use std::{collections::BTreeMap, fs, path::Path};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Symbols {
+    names: BTreeMap<u16, String>,
+}
+
+impl Symbols {
+    /// Names the address. An address has only one name, so a later name replaces the earlier one.
+    pub fn add(&mut self, address: u16, name: String) {
+        self.names.insert(address, name);
+    }
+
+    /// Parses a symbol like `0x2F0=draw_ball`.
+    pub fn add_from_str(&mut self, string: &str) -> Result<(), Error> {
+        let (address, name) = string
+            .split_once('=')
+            .and_then(|(address, name)| Some((parse_number(address.trim())?, name.trim())))
+            .filter(|(_, name)| !name.is_empty() && !name.contains(char::is_whitespace))
+            .ok_or_else(|| {
+                format!(
+                    "Invalid symbol `{}`. Expected something like `0x2F0=draw_ball`.",
+                    string
+                )
+            })?;
+
+        self.add(address, name.to_string());
+
+        Ok(())
+    }
+
+    /// Loads symbols from a file with one symbol like `0x2F0=draw_ball` per line.
+    ///
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn load(&mut self, path: &Path) -> Result<(), Error> {
+        let text =
+            fs::read_to_string(path).map_err(|err| format!("Failed to read symbols: {}", err))?;
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            self.add_from_str(line)
+                .map_err(|err| format!("Line {}: {}", index + 1, err))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Returns the name of exactly this address.
+    pub fn get_name(&self, address: u16) -> Option<&str> {
+        self.names.get(&address).map(String::as_str)
+    }
+
+    /// Returns the address with the name.
+    pub fn get_address(&self, name: &str) -> Option<u16> {
+        self.names
+            .iter()
+            .find(|(_, symbol)| *symbol == name)
+            .map(|(address, _)| *address)
+    }
+
+    /// Describes the address by the closest name at or before it, like `draw_ball` or `draw_ball+4`.
+    pub fn describe(&self, address: u16) -> Option<String> {
+        let (start, name) = self.names.range(..=address).next_back()?;
+        let offset = address - start;
+
+        Some(if offset == 0 {
+            name.clone()
+        } else {
+            format!("{}+{}", name, offset)
+        })
+    }
+
+    /// Formats the symbols as a symbol file, ordered by address.
+    pub fn to_text(&self) -> String {
+        self.names
+            .iter()
+            .map(|(address, name)| format!("{:#05X}={}\n", address, name))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symbols() {
+        let mut symbols = Symbols::default();
+        symbols.add_from_str("0x2F0=draw_ball").unwrap();
+        symbols.add_from_str(" 512 = main ").unwrap();
+        assert!(symbols.add_from_str("0x300=").is_err());
+        assert!(symbols.add_from_str("main").is_err());
+
+        assert_eq!(symbols.get_name(0x200), Some("main"));
+        assert_eq!(symbols.get_address("draw_ball"), Some(0x2F0));
+        assert_eq!(symbols.describe(0x1FE), None);
+        assert_eq!(symbols.describe(0x2F0), Some("draw_ball".into()));
+        assert_eq!(symbols.describe(0x2F4), Some("draw_ball+4".into()));
+        assert_eq!(symbols.to_text(), "0x200=main\n0x2F0=draw_ball\n");
+    }
+}
@@ -0,0 +1,64 @@
+//! How long instructions took on the original COSMAC VIP, for running programs at their original speed.
+//!
+//! The CDP1802 of the VIP runs at 1.76 MHz with 8 clock cycles per machine cycle, so a frame at 60 Hz has about 3668
+//! machine cycles. The costs are approximations: on the VIP some instructions take longer depending on their operands,
+//! e.g. whether a skip is taken or a sprite crosses a byte boundary, and the display interrupt takes cycles too.
+
+/// How many machine cycles of the VIP pass during a frame.
+pub const VIP_CYCLES_PER_FRAME: u32 = 3668;
+
+/// How many machine cycles the interpreter of the VIP takes to fetch and decode every instruction.
+const FETCH_CYCLES: u32 = 40;
+
+/// Returns about how many machine cycles the instruction took on the VIP, including fetching it.
+pub fn get_vip_cycles(instruction: u16) -> u32 {
+    let x = (instruction >> 8 & 0xF) as u32;
+    let n = (instruction & 0xF) as u32;
+
+    let execution = match instruction >> 12 {
+        0x0 => match instruction {
+            0x00E0 => 3078,
+            0x00EE => 10,
+            // Calls of machine code routines, which can take any time.
+            _ => 0,
+        },
+        0x1 => 12,
+        0x2 => 26,
+        0x3 | 0x4 => 10,
+        0x5 | 0x9 => 14,
+        0x6 => 6,
+        0x7 => 10,
+        0x8 => 44,
+        0xA => 12,
+        0xB => 22,
+        0xC => 36,
+        // Every row of the sprite is shifted into place and combined with two bytes of the screen.
+        0xD => 26 + 68 * n,
+        0xE => 14,
+        0xF => match instruction & 0xFF {
+            0x1E => 16,
+            0x29 => 20,
+            0x33 => 84,
+            0x55 | 0x65 => 14 + 14 * (x + 1),
+            _ => 10,
+        },
+        _ => unreachable!(),
+    };
+
+    FETCH_CYCLES + execution
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_vip_cycles() {
+        assert_eq!(get_vip_cycles(0x6005), 46);
+        // Clearing the screen takes most of a frame.
+        assert!(get_vip_cycles(0x00E0) < VIP_CYCLES_PER_FRAME);
+        assert!(get_vip_cycles(0x00E0) > VIP_CYCLES_PER_FRAME / 2);
+        assert!(get_vip_cycles(0xD01F) > get_vip_cycles(0xD011));
+        assert_eq!(get_vip_cycles(0xF355) - get_vip_cycles(0xF255), 14);
+    }
+}
@@ -0,0 +1,307 @@
+use crate::Error;
+#[cfg(feature = "savestates")]
+use std::io::{self, Write};
+use std::ops::Range;
+
+/// An iterator over the bits of a byte as `bool`s, from left to right, or right to left with `rev`.
+///
+/// ```
+/// # use chip8_core::util::Bits;
+/// let mut bits = Bits::new(0b0110_1001);
+///
+/// assert_eq!(bits.next(), Some(false));
+/// assert_eq!(bits.next(), Some(true));
+/// assert_eq!(bits.next(), Some(true));
+/// assert_eq!(bits.next(), Some(false));
+/// assert_eq!(bits.next(), Some(true));
+/// assert_eq!(bits.next(), Some(false));
+/// assert_eq!(bits.next(), Some(false));
+/// assert_eq!(bits.next(), Some(true));
+/// assert_eq!(bits.next(), None);
+/// ```
+pub struct Bits {
+    byte: u8,
+    index: u8,
+}
+
+impl Bits {
+    pub fn new(byte: u8) -> Self {
+        Self { byte, index: 0 }
+    }
+}
+
+impl Iterator for Bits {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bit = if let Some(rhs) = (u8::BITS as u8 - 1).checked_sub(self.index) {
+            (self.byte >> rhs) & 1
+        } else {
+            return None;
+        };
+
+        self.index += 1;
+
+        Some(bit == 1)
+    }
+}
+
+impl DoubleEndedIterator for Bits {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let bit = if let Some(lhs) = self.byte.checked_shr(self.index as u32) {
+            lhs & 1
+        } else {
+            return None;
+        };
+
+        self.index += 1;
+
+        Some(bit == 1)
+    }
+}
+
+/// Reads big-endian values from bytes, failing if there are not enough bytes left.
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    pub fn read(&mut self, count: usize) -> Result<&'a [u8], Error> {
+        let bytes = self
+            .bytes
+            .get(self.position..self.position + count)
+            .ok_or("Unexpected end of file.")?;
+        self.position += count;
+
+        Ok(bytes)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, Error> {
+        Ok(self.read(1)?[0])
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16, Error> {
+        let bytes = self.read(2)?;
+
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, Error> {
+        let bytes = self.read(4)?;
+
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, Error> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.read(8)?);
+
+        Ok(u64::from_be_bytes(bytes))
+    }
+
+    /// Reads all bytes that are left.
+    pub fn read_to_end(&mut self) -> &'a [u8] {
+        let bytes = &self.bytes[self.position..];
+        self.position = self.bytes.len();
+
+        bytes
+    }
+
+    /// Returns whether all bytes have been read.
+    pub fn is_empty(&self) -> bool {
+        self.position == self.bytes.len()
+    }
+}
+
+/// Hashes the bytes with 64-bit FNV-1a, e.g. to tell whether a file belongs to a program.
+pub fn hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xCBF2_9CE4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01B3)
+    })
+}
+
+/// Compresses the bytes with zstd along with a checksum of them.
+#[cfg(feature = "savestates")]
+pub fn compress(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+    let compress = || -> io::Result<Vec<u8>> {
+        let mut encoder = zstd::Encoder::new(Vec::new(), 0)?;
+        encoder.include_checksum(true)?;
+        encoder.write_all(bytes)?;
+        encoder.finish()
+    };
+
+    compress().map_err(|err| format!("Failed to compress: {}", err).into())
+}
+
+/// Decompresses bytes compressed with [`compress`], failing if they are corrupted.
+#[cfg(feature = "savestates")]
+pub fn decompress(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+    zstd::decode_all(bytes).map_err(|err| format!("The file is corrupted: {}", err).into())
+}
+
+/// Quotes the string as a JSON string, escaping quotes, backslashes and control characters.
+pub fn to_json_string(string: &str) -> String {
+    let mut json = String::with_capacity(string.len() + 2);
+    json.push('"');
+    for char in string.chars() {
+        match char {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            char if char.is_control() => json.push_str(&format!("\\u{:04x}", char as u32)),
+            char => json.push(char),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// Parses a decimal number or a hexadecimal number prefixed with `0x`.
+pub fn parse_number(string: &str) -> Option<u16> {
+    if let Some(hex) = string
+        .strip_prefix("0x")
+        .or_else(|| string.strip_prefix("0X"))
+    {
+        u16::from_str_radix(hex, 16).ok()
+    } else {
+        string.parse().ok()
+    }
+}
+
+/// Parses a range like `0..8`, where the end is exclusive.
+pub fn parse_range(string: &str) -> Option<Range<u16>> {
+    let (start, end) = string.split_once("..")?;
+    let range = parse_number(start)?..parse_number(end)?;
+
+    if range.is_empty() {
+        None
+    } else {
+        Some(range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number("512"), Some(512));
+        assert_eq!(parse_number("0x200"), Some(0x200));
+        assert_eq!(parse_number("0X2F0"), Some(0x2F0));
+        assert_eq!(parse_number("0x"), None);
+        assert_eq!(parse_number("abc"), None);
+    }
+
+    #[test]
+    fn test_to_json_string() {
+        assert_eq!(to_json_string("loop"), r#""loop""#);
+        assert_eq!(
+            to_json_string("say \"hi\"\\\n\u{1}"),
+            r#""say \"hi\"\\\n\u0001""#
+        );
+    }
+
+    #[test]
+    fn test_compress() {
+        let bytes: Vec<u8> = (0..1000).map(|index| (index / 100) as u8).collect();
+        let compressed = compress(&bytes).unwrap();
+
+        assert!(compressed.len() < bytes.len());
+        assert_eq!(decompress(&compressed).unwrap(), bytes);
+
+        let mut corrupted = compressed.clone();
+        let middle = corrupted.len() / 2;
+        corrupted[middle] ^= 0x55;
+        assert!(decompress(&corrupted).is_err());
+        assert!(decompress(&compressed[..compressed.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_bits() {
+        let mut bits = Bits::new(0b0110_1001);
+
+        assert_eq!(bits.next(), Some(false));
+        assert_eq!(bits.next(), Some(true));
+        assert_eq!(bits.next(), Some(true));
+        assert_eq!(bits.next(), Some(false));
+
+        assert_eq!(bits.next(), Some(true));
+        assert_eq!(bits.next(), Some(false));
+        assert_eq!(bits.next(), Some(false));
+        assert_eq!(bits.next(), Some(true));
+        assert_eq!(bits.next(), None);
+
+        let mut bits = Bits::new(0b1100_1100).rev();
+
+        assert_eq!(bits.next(), Some(false));
+        assert_eq!(bits.next(), Some(false));
+        assert_eq!(bits.next(), Some(true));
+        assert_eq!(bits.next(), Some(true));
+
+        assert_eq!(bits.next(), Some(false));
+        assert_eq!(bits.next(), Some(false));
+        assert_eq!(bits.next(), Some(true));
+        assert_eq!(bits.next(), Some(true));
+        assert_eq!(bits.next(), None);
+
+        let mut bits = Bits::new(0b0000_0000);
+
+        assert_eq!(bits.next(), Some(false));
+        assert_eq!(bits.next(), Some(false));
+        assert_eq!(bits.next(), Some(false));
+        assert_eq!(bits.next(), Some(false));
+
+        assert_eq!(bits.next(), Some(false));
+        assert_eq!(bits.next(), Some(false));
+        assert_eq!(bits.next(), Some(false));
+        assert_eq!(bits.next(), Some(false));
+        assert_eq!(bits.next(), None);
+
+        let mut bits = Bits::new(0b1111_1111);
+
+        assert_eq!(bits.next(), Some(true));
+        assert_eq!(bits.next(), Some(true));
+        assert_eq!(bits.next(), Some(true));
+        assert_eq!(bits.next(), Some(true));
+
+        assert_eq!(bits.next(), Some(true));
+        assert_eq!(bits.next(), Some(true));
+        assert_eq!(bits.next(), Some(true));
+        assert_eq!(bits.next(), Some(true));
+        assert_eq!(bits.next(), None);
+
+        let mut bits = Bits::new(0b0101_0101);
+
+        assert_eq!(bits.next(), Some(false));
+        assert_eq!(bits.next(), Some(true));
+        assert_eq!(bits.next(), Some(false));
+        assert_eq!(bits.next(), Some(true));
+
+        assert_eq!(bits.next(), Some(false));
+        assert_eq!(bits.next(), Some(true));
+        assert_eq!(bits.next(), Some(false));
+        assert_eq!(bits.next(), Some(true));
+        assert_eq!(bits.next(), None);
+
+        let mut bits = Bits::new(0b1010_1010);
+
+        assert_eq!(bits.next(), Some(true));
+        assert_eq!(bits.next(), Some(false));
+        assert_eq!(bits.next(), Some(true));
+        assert_eq!(bits.next(), Some(false));
+
+        assert_eq!(bits.next(), Some(true));
+        assert_eq!(bits.next(), Some(false));
+        assert_eq!(bits.next(), Some(true));
+        assert_eq!(bits.next(), Some(false));
+        assert_eq!(bits.next(), None);
+    }
+}
@@ -0,0 +1,249 @@
+//! The interpreter for the browser frontend in `www`, built for `wasm32-unknown-unknown`.
+//!
+//! There is no bindings generator, so the functions exported to JavaScript only take and return numbers and pointers
+//! into the memory of the module. JavaScript copies the program into memory allocated with [`chip8_alloc`], creates
+//! an interpreter with [`chip8_create`] and then runs a frame with [`chip8_run_frame`] 60 times per second, drawing
+//! the colors returned by [`chip8_render`] to a canvas.
+
+use chip8_core::{display::Point, interpreter::START_POINT, Error, Interpreter, Step};
+use std::ptr;
+
+/// What happened in a frame, as returned to JavaScript.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(u32)]
+pub enum Status {
+    Running = 0,
+    /// The program is waiting for a key to be pressed.
+    AwaitingKey = 1,
+    Ended = 2,
+    /// The program stopped with an error, which [`chip8_error`] returns.
+    Failed = 3,
+}
+
+/// An interpreter running in the browser.
+pub struct Web {
+    interpreter: Interpreter,
+    /// The key pressed since the last frame, if any, which a program waiting for a key gets.
+    pressed_key: Option<u8>,
+    /// The color of every pixel of the screen, row by row, as last rendered.
+    pixels: Vec<u8>,
+    error: Option<Error>,
+}
+
+impl Web {
+    pub fn new(program: Vec<u8>, seed: u64) -> Result<Self, Error> {
+        Ok(Self {
+            interpreter: Interpreter::with_load_address(program, seed, START_POINT)?,
+            pressed_key: None,
+            pixels: Vec::new(),
+            error: None,
+        })
+    }
+
+    /// Presses the key or releases it.
+    pub fn set_key(&mut self, key: u8, pressed: bool) {
+        if pressed {
+            self.pressed_key = Some(key);
+        }
+        self.interpreter.set_key_held(key, pressed);
+    }
+
+    /// Executes up to the given number of instructions and decrements the timers once, like in a frame.
+    ///
+    /// The frame ends early if the program waits for a key.
+    pub fn run_frame(&mut self, instructions: u32) -> Status {
+        if self.error.is_some() {
+            return Status::Failed;
+        }
+
+        let mut status = Status::Running;
+        for _ in 0..instructions {
+            self.interpreter.set_key(self.pressed_key.take());
+            match self.interpreter.step() {
+                Ok(Step::Executed) | Ok(Step::Breakpoint(_)) => {}
+                Ok(Step::AwaitingKey) => {
+                    status = Status::AwaitingKey;
+                    break;
+                }
+                Ok(Step::Ended) => return Status::Ended,
+                Err(err) => {
+                    self.error = Some(err);
+                    return Status::Failed;
+                }
+            }
+        }
+        self.interpreter.update_timers();
+
+        status
+    }
+
+    /// Returns the color of every pixel of the screen, row by row, as the bitmask of the planes it is set in.
+    pub fn render(&mut self) -> &[u8] {
+        let display = self.interpreter.display();
+        let screen_size = display.screen_size();
+        self.pixels.clear();
+        for y in 0..screen_size.height {
+            for x in 0..screen_size.width {
+                self.pixels.push(display.get_color(Point { x, y }));
+            }
+        }
+        &self.pixels
+    }
+
+    pub fn is_sounding(&self) -> bool {
+        self.interpreter.get_registers().sound_timer > 0
+    }
+}
+
+/// Allocates zeroed memory of the given length for JavaScript to write the program to.
+#[no_mangle]
+pub extern "C" fn chip8_alloc(length: usize) -> *mut u8 {
+    Box::into_raw(vec![0; length].into_boxed_slice()) as *mut u8
+}
+
+/// Creates an interpreter for the program in memory allocated with [`chip8_alloc`], which it takes over.
+///
+/// Returns null if the program is too large.
+///
+/// # Safety
+///
+/// The program needs to have been allocated with [`chip8_alloc`] with the given length and may not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn chip8_create(program: *mut u8, length: usize, seed: u32) -> *mut Web {
+    let program = Box::from_raw(ptr::slice_from_raw_parts_mut(program, length));
+    match Web::new(program.into_vec(), seed as u64) {
+        Ok(web) => Box::into_raw(Box::new(web)),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Frees the interpreter.
+///
+/// # Safety
+///
+/// The interpreter needs to have been created with [`chip8_create`] and may not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn chip8_destroy(web: *mut Web) {
+    drop(Box::from_raw(web));
+}
+
+/// Presses the key of the keypad from 0 to F if `pressed` is nonzero and releases it otherwise.
+///
+/// # Safety
+///
+/// The interpreter needs to have been created with [`chip8_create`].
+#[no_mangle]
+pub unsafe extern "C" fn chip8_set_key(web: *mut Web, key: u32, pressed: u32) {
+    (*web).set_key(key as u8 & 0xF, pressed != 0);
+}
+
+/// Runs a frame of up to the given number of instructions and returns the [`Status`].
+///
+/// # Safety
+///
+/// The interpreter needs to have been created with [`chip8_create`].
+#[no_mangle]
+pub unsafe extern "C" fn chip8_run_frame(web: *mut Web, instructions: u32) -> u32 {
+    (*web).run_frame(instructions) as u32
+}
+
+/// Returns a pointer to the color of every pixel of the screen, row by row, valid until the next call.
+///
+/// # Safety
+///
+/// The interpreter needs to have been created with [`chip8_create`].
+#[no_mangle]
+pub unsafe extern "C" fn chip8_render(web: *mut Web) -> *const u8 {
+    (*web).render().as_ptr()
+}
+
+/// Returns the width of the screen in pixels, which is 64.
+///
+/// # Safety
+///
+/// The interpreter needs to have been created with [`chip8_create`].
+#[no_mangle]
+pub unsafe extern "C" fn chip8_width(web: *const Web) -> u32 {
+    (*web).interpreter.display().screen_size().width as u32
+}
+
+/// Returns the height of the screen in pixels, which is 64 in the hi-res mode and 32 otherwise.
+///
+/// # Safety
+///
+/// The interpreter needs to have been created with [`chip8_create`].
+#[no_mangle]
+pub unsafe extern "C" fn chip8_height(web: *const Web) -> u32 {
+    (*web).interpreter.display().screen_size().height as u32
+}
+
+/// Returns whether the sound is playing.
+///
+/// # Safety
+///
+/// The interpreter needs to have been created with [`chip8_create`].
+#[no_mangle]
+pub unsafe extern "C" fn chip8_is_sounding(web: *const Web) -> u32 {
+    (*web).is_sounding() as u32
+}
+
+/// Returns the UTF-8 message of the error the program stopped with, whose length [`chip8_error_length`] returns, or
+/// null if there is none.
+///
+/// # Safety
+///
+/// The interpreter needs to have been created with [`chip8_create`].
+#[no_mangle]
+pub unsafe extern "C" fn chip8_error(web: *const Web) -> *const u8 {
+    match &(*web).error {
+        Some(error) => error.as_ptr(),
+        None => ptr::null(),
+    }
+}
+
+/// Returns the length of the message [`chip8_error`] returns.
+///
+/// # Safety
+///
+/// The interpreter needs to have been created with [`chip8_create`].
+#[no_mangle]
+pub unsafe extern "C" fn chip8_error_length(web: *const Web) -> usize {
+    (*web).error.as_ref().map_or(0, |error| error.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_frame() {
+        // LD V0, K; LD F, V0; DRW V0, V0, 0x5; JP 0x206
+        let program = vec![0xF0, 0x0A, 0xF0, 0x29, 0xD0, 0x05, 0x12, 0x06];
+        let mut web = Web::new(program, 0).unwrap();
+        assert_eq!(web.run_frame(10), Status::AwaitingKey);
+
+        web.set_key(1, true);
+        assert_eq!(web.run_frame(10), Status::Running);
+        let pixels = web.render();
+        assert_eq!(pixels.len(), 64 * 32);
+        // The top row of the 1, drawn at 1,1.
+        assert_eq!(pixels[64 + 1..64 + 5], [0, 0, 1, 1]);
+    }
+
+    #[test]
+    fn test_exports() {
+        unsafe {
+            // LD V0, 0xFF; EXIT
+            let program = chip8_alloc(4);
+            program.copy_from([0x60, 0xFF, 0x00, 0xFD].as_ptr(), 4);
+            let web = chip8_create(program, 4, 0);
+            assert!(!web.is_null());
+            assert_eq!(chip8_run_frame(web, 10), Status::Ended as u32);
+            assert_eq!((chip8_width(web), chip8_height(web)), (64, 32));
+            assert!(chip8_error(web).is_null());
+            chip8_destroy(web);
+
+            assert!(chip8_create(chip8_alloc(0x1000), 0x1000, 0).is_null());
+        }
+    }
+}
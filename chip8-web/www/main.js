@@ -0,0 +1,87 @@
+// Runs chip8_web.wasm, built from ../src/lib.rs, on a canvas.
+
+// The keys of the keyboard for the keys of the keypad from 0 to F, the same as the terminal frontend's default keymap.
+const KEYMAP = "x123qweasdzc4rfv";
+const INSTRUCTIONS_PER_FRAME = 10;
+// The colors of the pixels by the planes they are set in.
+const COLORS = [[0, 0, 0], [255, 102, 0], [102, 34, 0], [255, 255, 255]];
+const STATUS = ["", "Waiting for a key.", "The program ended.", "Error: "];
+
+const canvas = document.getElementById("screen");
+const context = canvas.getContext("2d");
+const status = document.getElementById("status");
+
+let exports;
+let web = 0;
+let audio;
+let gain;
+
+WebAssembly.instantiateStreaming(fetch("chip8_web.wasm")).then(({ instance }) => {
+  exports = instance.exports;
+  requestAnimationFrame(frame);
+});
+
+document.getElementById("program").addEventListener("change", async (event) => {
+  const program = new Uint8Array(await event.target.files[0].arrayBuffer());
+  if (web) {
+    exports.chip8_destroy(web);
+    web = 0;
+  }
+  const pointer = exports.chip8_alloc(program.length);
+  new Uint8Array(exports.memory.buffer, pointer, program.length).set(program);
+  web = exports.chip8_create(pointer, program.length, Math.random() * 0xFFFFFFFF);
+  status.textContent = web ? "" : "The program is too large.";
+  // Browsers only allow sound after a user interaction like this one.
+  if (!audio) {
+    audio = new AudioContext();
+    const oscillator = audio.createOscillator();
+    oscillator.type = "square";
+    oscillator.frequency.value = 440;
+    gain = audio.createGain();
+    gain.gain.value = 0;
+    oscillator.connect(gain).connect(audio.destination);
+    oscillator.start();
+  }
+});
+
+function setKey(event, pressed) {
+  const key = KEYMAP.indexOf(event.key.toLowerCase());
+  if (web && event.key.length === 1 && key !== -1) {
+    exports.chip8_set_key(web, key, pressed);
+  }
+}
+addEventListener("keydown", (event) => setKey(event, true));
+addEventListener("keyup", (event) => setKey(event, false));
+
+let last = 0;
+function frame(time) {
+  requestAnimationFrame(frame);
+  // Displays can refresh faster than 60 Hz.
+  if (!web || time - last < 1000 / 60 - 1) {
+    return;
+  }
+  last = time;
+
+  const result = exports.chip8_run_frame(web, INSTRUCTIONS_PER_FRAME);
+  status.textContent = STATUS[result];
+  if (result === 3) {
+    const message = new Uint8Array(exports.memory.buffer, exports.chip8_error(web), exports.chip8_error_length(web));
+    status.textContent += new TextDecoder().decode(message);
+  }
+
+  const width = exports.chip8_width(web);
+  const height = exports.chip8_height(web);
+  if (canvas.width !== width || canvas.height !== height) {
+    canvas.width = width;
+    canvas.height = height;
+  }
+  const pixels = new Uint8Array(exports.memory.buffer, exports.chip8_render(web), width * height);
+  const image = context.createImageData(width, height);
+  pixels.forEach((color, index) => {
+    image.data.set(COLORS[color], index * 4);
+    image.data[index * 4 + 3] = 255;
+  });
+  context.putImageData(image, 0, 0);
+
+  gain.gain.value = exports.chip8_is_sounding(web) ? 0.1 : 0;
+}
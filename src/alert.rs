@@ -0,0 +1,166 @@
+//! Alternatives to hearing the sound, for when audio cannot be relied on.
+
+use crate::terminal::Terminal;
+use chip8_core::Error;
+
+/// The title of the terminal window, as set when initializing the terminal.
+const TITLE: &str = "CHIP-8";
+/// For how many frames the sound needs to play to count as a long beep, which is half a second.
+const LONG_BEEP_FRAMES: u8 = 30;
+/// For how many frames the symbol in the status bar is shown and then hidden while the sound plays.
+const PULSE_FRAMES: u64 = 8;
+/// How many frames pass at least between two rings of the bell, so that long beeps do not ring it all the time.
+const BELL_FRAMES: u8 = 15;
+
+/// A way to show that the sound is playing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Alert {
+    /// A pulsing symbol in the status bar.
+    Status,
+    /// A symbol in the title of the terminal window.
+    Title,
+    /// A desktop notification for long beeps, sent through the terminal.
+    Notification,
+    /// A frame lit up around the display.
+    Border,
+    /// The bell of the terminal, which rings while the sound plays.
+    Bell,
+}
+
+impl Alert {
+    /// Parses alerts separated by commas, like `status,title`, or `none`.
+    pub fn parse_list(string: &str) -> Result<Vec<Self>, Error> {
+        if string == "none" {
+            return Ok(Vec::new());
+        }
+
+        string
+            .split(',')
+            .map(|alert| match alert {
+                "status" => Ok(Self::Status),
+                "title" => Ok(Self::Title),
+                "notify" => Ok(Self::Notification),
+                "border" => Ok(Self::Border),
+                "bell" => Ok(Self::Bell),
+                _ => Err(format!(
+                    "Unknown beep alert `{}`. Expected `status`, `title`, `notify`, `border`, `bell` or `none`.",
+                    alert
+                )
+                .into()),
+            })
+            .collect()
+    }
+}
+
+/// Shows that the sound is playing in the chosen ways.
+#[derive(Debug)]
+pub struct Alerts {
+    alerts: Vec<Alert>,
+    /// Whether the sound was playing in the last frame.
+    sounding: bool,
+    /// How many frames are left until the bell can ring again.
+    bell_cooldown: u8,
+}
+
+impl Alerts {
+    pub fn new(alerts: Vec<Alert>) -> Self {
+        Self {
+            alerts,
+            sounding: false,
+            bell_cooldown: 0,
+        }
+    }
+
+    /// Updates the alerts at the end of a frame with the current value of the sound timer.
+    pub fn update(&mut self, terminal: &mut dyn Terminal, sound_timer: u8) {
+        let sounding = sound_timer > 0;
+        let started = sounding && !self.sounding;
+        if sounding != self.sounding && self.alerts.contains(&Alert::Title) {
+            terminal.write(&get_title_sequence(sounding));
+            terminal.flush();
+        }
+        if started && sound_timer >= LONG_BEEP_FRAMES && self.alerts.contains(&Alert::Notification)
+        {
+            // OSC 9 is shown as a desktop notification by terminals like iTerm2, kitty and WezTerm.
+            terminal.write(&format!("\x1b]9;{}: beep\x07", TITLE));
+            terminal.flush();
+        }
+        if self.should_ring_bell(sounding) {
+            terminal.write("\x07");
+            terminal.flush();
+        }
+        self.sounding = sounding;
+    }
+
+    /// Returns whether the bell rings in this frame, which it does while the sound plays, but not more often than
+    /// every [`BELL_FRAMES`] frames.
+    fn should_ring_bell(&mut self, sounding: bool) -> bool {
+        if self.bell_cooldown > 0 {
+            self.bell_cooldown -= 1;
+            return false;
+        }
+        let ringing = sounding && self.alerts.contains(&Alert::Bell);
+        if ringing {
+            self.bell_cooldown = BELL_FRAMES - 1;
+        }
+        ringing
+    }
+
+    /// Returns whether the frame around the display is lit.
+    pub fn is_border_lit(&self) -> bool {
+        self.sounding && self.alerts.contains(&Alert::Border)
+    }
+
+    /// Returns the symbol to show in the status bar in the given frame, if any.
+    pub fn get_status_symbol(&self, frame: u64) -> Option<&'static str> {
+        let visible = (frame / PULSE_FRAMES).is_multiple_of(2);
+        (self.sounding && visible && self.alerts.contains(&Alert::Status)).then_some("♪")
+    }
+}
+
+/// Returns the escape sequence setting the title of the terminal window, with a symbol if the sound is playing.
+fn get_title_sequence(sounding: bool) -> String {
+    if sounding {
+        format!("\x1b]0;♪ {}\x07", TITLE)
+    } else {
+        format!("\x1b]0;{}\x07", TITLE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_list() {
+        assert_eq!(
+            Alert::parse_list("status,notify,border").unwrap(),
+            vec![Alert::Status, Alert::Notification, Alert::Border]
+        );
+        assert!(Alert::parse_list("status,").is_err());
+        assert_eq!(Alert::parse_list("none").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_should_ring_bell() {
+        let mut alerts = Alerts::new(vec![Alert::Bell]);
+        let rings: Vec<bool> = (0..BELL_FRAMES * 2)
+            .map(|_| alerts.should_ring_bell(true))
+            .collect();
+        assert_eq!(rings.iter().filter(|ring| **ring).count(), 2);
+        assert!(rings[0] && rings[BELL_FRAMES as usize]);
+        assert!(!alerts.should_ring_bell(false));
+    }
+
+    #[test]
+    fn test_get_status_symbol() {
+        let alerts = Alerts {
+            alerts: vec![Alert::Status],
+            sounding: true,
+            bell_cooldown: 0,
+        };
+        assert_eq!(alerts.get_status_symbol(0), Some("♪"));
+        assert_eq!(alerts.get_status_symbol(PULSE_FRAMES), None);
+        assert_eq!(alerts.get_status_symbol(PULSE_FRAMES * 2), Some("♪"));
+    }
+}
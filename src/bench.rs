@@ -0,0 +1,145 @@
+//! Measuring how fast the interpreter executes a program without a terminal, to notice when it gets slower.
+
+use crate::options;
+use chip8_core::{quirks::Quirks, Error, Interpreter, Step};
+use std::{
+    env,
+    time::{Duration, Instant},
+};
+
+/// How many million instructions are executed by default.
+const DEFAULT_MILLIONS: u64 = 10;
+/// After how many instructions the timers are decremented, like at the end of a frame.
+const INSTRUCTIONS_PER_FRAME: u64 = 1000;
+
+/// The classes of instructions, by their first digit.
+const CLASSES: [&str; 16] = [
+    "0NNN", "1NNN", "2NNN", "3XNN", "4XNN", "5XY0", "6XNN", "7XNN", "8XYN", "9XY0", "ANNN", "BNNN",
+    "CXNN", "DXYN", "EXNN", "FXNN",
+];
+
+/// Runs the binary given in the arguments and prints how many instructions were executed per second and how long
+/// each class of instructions took on average.
+///
+/// The arguments are the binary, optionally followed by `--millions <count>` for how many million instructions to
+/// execute, `--quirk <quirk>` and `--load-address <address>`.
+pub fn run(mut args: env::ArgsOs) -> Result<(), Error> {
+    let binary = crate::get_binary(&mut args)?;
+
+    let mut millions = DEFAULT_MILLIONS;
+    let mut quirks = Quirks::default();
+    let mut load_address = chip8_core::interpreter::START_POINT;
+    while let Some(arg) = args.next() {
+        match arg.to_str() {
+            Some(option @ "--millions") => {
+                let value = options::get_value(&mut args, option)?;
+                millions = value
+                    .parse()
+                    .ok()
+                    .filter(|millions| *millions > 0)
+                    .ok_or_else(|| format!("Invalid count `{}`.", value))?;
+            }
+            Some(option @ "--quirk") => {
+                options::parse_quirk(&options::get_value(&mut args, option)?, &mut quirks)?
+            }
+            Some(option @ "--load-address") => {
+                load_address = options::parse_load_address(&options::get_value(&mut args, option)?)?
+            }
+            _ => return Err(format!("Unknown argument: {}", arg.to_string_lossy()).into()),
+        }
+    }
+    let count = millions * 1_000_000;
+    let create_interpreter =
+        || Interpreter::with_quirks(binary.clone(), 0, load_address, quirks.clone());
+
+    // Timing every instruction slows the interpreter down, so the speed is measured in a separate run.
+    let mut interpreter = create_interpreter()?;
+    let start = Instant::now();
+    for index in 0..count {
+        execute(&mut interpreter, &create_interpreter, index)?;
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{} instructions in {:.2} s: {:.0} instructions per second",
+        count,
+        elapsed.as_secs_f64(),
+        count as f64 / elapsed.as_secs_f64()
+    );
+
+    let mut classes = [(0_u64, Duration::ZERO); 16];
+    let mut interpreter = create_interpreter()?;
+    for index in 0..count {
+        let class = interpreter
+            .get_current_instruction()
+            .map(|instruction| instruction as usize >> 12);
+        let start = Instant::now();
+        execute(&mut interpreter, &create_interpreter, index)?;
+        if let Some(class) = class {
+            classes[class].0 += 1;
+            classes[class].1 += start.elapsed();
+        }
+    }
+    print!("{}", format_classes(&classes));
+
+    Ok(())
+}
+
+/// Executes the instruction of the given index. Keys are pressed whenever the program waits for one, the timers are
+/// decremented every few instructions and the program starts over once it ends.
+fn execute<C>(
+    interpreter: &mut Interpreter,
+    create_interpreter: &C,
+    index: u64,
+) -> Result<(), Error>
+where
+    C: Fn() -> Result<Interpreter, Error>,
+{
+    match interpreter.step()? {
+        Step::Executed | Step::Breakpoint(_) => interpreter.set_key(None),
+        Step::AwaitingKey => interpreter.set_key(Some(0)),
+        Step::Ended => *interpreter = create_interpreter()?,
+    }
+    if (index + 1).is_multiple_of(INSTRUCTIONS_PER_FRAME) {
+        interpreter.update_timers();
+    }
+
+    Ok(())
+}
+
+/// Formats the number of instructions of every class that was executed and how long they took on average, slowest
+/// first.
+fn format_classes(classes: &[(u64, Duration); 16]) -> String {
+    let mut rows: Vec<(&str, u64, f64)> = CLASSES
+        .iter()
+        .zip(classes)
+        .filter(|(_, (count, _))| *count > 0)
+        .map(|(name, (count, duration))| {
+            (*name, *count, duration.as_nanos() as f64 / *count as f64)
+        })
+        .collect();
+    rows.sort_by(|a, b| b.2.total_cmp(&a.2));
+
+    let mut text = String::from("class  instructions  ns per instruction\n");
+    for (name, count, nanoseconds) in rows {
+        text += &format!("{}  {:>12}  {:>18.1}\n", name, count, nanoseconds);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_classes() {
+        let mut classes = [(0, Duration::ZERO); 16];
+        classes[0x6] = (4, Duration::from_nanos(100));
+        classes[0xD] = (2, Duration::from_nanos(300));
+        assert_eq!(
+            format_classes(&classes),
+            "class  instructions  ns per instruction\n\
+             DXYN             2               150.0\n\
+             6XNN             4                25.0\n"
+        );
+    }
+}
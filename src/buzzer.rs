@@ -0,0 +1,155 @@
+//! Synthesis of the buzzer, which sounds while the sound timer is active, or plays a sample instead.
+
+use chip8_core::Error;
+use std::{fs::File, io::Write, path::Path};
+
+/// The number of samples per second of the output.
+pub const SAMPLE_RATE: u32 = 44100;
+/// The pitch of the buzzer in hertz.
+const FREQUENCY: f64 = 440.0;
+/// How long the buzzer takes to reach its full volume. This avoids a click when it starts.
+const ATTACK_SECONDS: f64 = 0.005;
+/// How long the buzzer takes to fall silent. This avoids a click when it stops.
+const DECAY_SECONDS: f64 = 0.02;
+/// The loudest sample, leaving some headroom.
+const AMPLITUDE: f64 = i16::MAX as f64 * 0.5;
+
+/// A square wave, or a sample looped while sounding, smoothed by a short attack and decay.
+pub struct Buzzer {
+    /// Where the samples are written to as signed 16-bit little-endian mono PCM, or `None` if they are taken with
+    /// [`Buzzer::next_sample`] instead.
+    output: Option<File>,
+    /// The fraction of every period the wave is high, from 0 to 1.
+    duty_cycle: f64,
+    /// How far into the current period the wave is, from 0 to 1.
+    phase: f64,
+    /// The current volume of the envelope, from 0 to 1.
+    level: f64,
+    /// The samples played instead of the square wave, if any, at [`SAMPLE_RATE`].
+    sample: Option<Vec<i16>>,
+    /// The index of the next sample to play.
+    position: usize,
+}
+
+impl Buzzer {
+    /// Creates the buzzer writing to the given file or named pipe.
+    pub fn create(path: &Path, duty_cycle: f64) -> Result<Self, Error> {
+        let output =
+            File::create(path).map_err(|err| format!("Failed to open audio output: {}", err))?;
+
+        Ok(Self {
+            output: Some(output),
+            ..Self::new(duty_cycle)
+        })
+    }
+
+    /// Creates the buzzer for a frontend that plays the samples itself.
+    pub fn new(duty_cycle: f64) -> Self {
+        Self {
+            output: None,
+            duty_cycle,
+            phase: 0.0,
+            level: 0.0,
+            sample: None,
+            position: 0,
+        }
+    }
+
+    /// Plays the samples instead of the square wave, from the start whenever the buzzer starts sounding.
+    pub fn set_sample(&mut self, sample: Vec<i16>) {
+        self.sample = Some(sample);
+    }
+
+    /// Writes the samples of the given duration with the buzzer sounding or not.
+    pub fn play(&mut self, sounding: bool, seconds: f64) -> Result<(), Error> {
+        let count = (seconds * SAMPLE_RATE as f64).round() as usize;
+        let bytes: Vec<u8> = (0..count)
+            .flat_map(|_| self.next_sample(sounding).to_le_bytes())
+            .collect();
+
+        match &mut self.output {
+            Some(output) => output
+                .write_all(&bytes)
+                .map_err(|err| format!("Failed to write audio: {}", err).into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the next sample at [`SAMPLE_RATE`] with the buzzer sounding or not.
+    pub fn next_sample(&mut self, sounding: bool) -> i16 {
+        if sounding && self.level == 0.0 {
+            self.position = 0;
+        }
+        if sounding {
+            self.level = (self.level + 1.0 / (ATTACK_SECONDS * SAMPLE_RATE as f64)).min(1.0);
+        } else {
+            self.level = (self.level - 1.0 / (DECAY_SECONDS * SAMPLE_RATE as f64)).max(0.0);
+        }
+
+        if let Some(sample) = &self.sample {
+            let value = sample[self.position];
+            self.position = (self.position + 1) % sample.len();
+            return (value as f64 * self.level) as i16;
+        }
+
+        let wave = if self.phase < self.duty_cycle {
+            1.0
+        } else {
+            -1.0
+        };
+        self.phase = (self.phase + FREQUENCY / SAMPLE_RATE as f64).fract();
+
+        (wave * self.level * AMPLITUDE) as i16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_envelope() {
+        let path = std::env::temp_dir().join("chip8-test-envelope.pcm");
+        let mut buzzer = Buzzer::create(&path, 0.5).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(buzzer.next_sample(false), 0);
+
+        // The volume rises gradually instead of jumping to the full amplitude.
+        let attack: Vec<i16> = (0..10).map(|_| buzzer.next_sample(true)).collect();
+        assert!(attack[0].unsigned_abs() < attack[9].unsigned_abs());
+        assert!((attack[9].unsigned_abs() as f64) < AMPLITUDE);
+
+        for _ in 0..SAMPLE_RATE / 10 {
+            buzzer.next_sample(true);
+        }
+        assert_eq!(buzzer.level, 1.0);
+
+        // And it falls silent gradually too.
+        assert_ne!(buzzer.next_sample(false), 0);
+        for _ in 0..SAMPLE_RATE / 10 {
+            buzzer.next_sample(false);
+        }
+        assert_eq!(buzzer.next_sample(false), 0);
+    }
+
+    #[test]
+    fn test_sample() {
+        let path = std::env::temp_dir().join("chip8-test-sample.pcm");
+        let mut buzzer = Buzzer::create(&path, 0.5).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        buzzer.set_sample(vec![i16::MAX, i16::MIN, 100]);
+        buzzer.level = 1.0;
+
+        // The sample loops while sounding.
+        let samples: Vec<i16> = (0..4).map(|_| buzzer.next_sample(true)).collect();
+        assert_eq!(samples, [i16::MAX, i16::MIN, 100, i16::MAX]);
+
+        // And starts over once the buzzer sounds again.
+        for _ in 0..SAMPLE_RATE / 10 {
+            buzzer.next_sample(false);
+        }
+        assert!(buzzer.next_sample(true) > 0);
+        assert!(buzzer.next_sample(true) < 0);
+    }
+}
@@ -0,0 +1,86 @@
+//! A clock ticking at 60 hertz on its own thread, so that the timers keep time however long executing instructions
+//! and drawing to the terminal take.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Counts the ticks that pass in the background until they are taken.
+pub struct Clock {
+    ticks: Arc<AtomicU64>,
+    /// Whether the clock is still used. The thread stops once it is not.
+    running: Arc<AtomicBool>,
+}
+
+impl Clock {
+    /// Starts a thread ticking once every period.
+    pub fn start(period: Duration) -> Self {
+        let ticks = Arc::new(AtomicU64::new(0));
+        let running = Arc::new(AtomicBool::new(true));
+        {
+            let ticks = Arc::clone(&ticks);
+            let running = Arc::clone(&running);
+            thread::spawn(move || {
+                let start = Instant::now();
+                let mut count = 0;
+                while running.load(Ordering::Relaxed) {
+                    count += 1;
+                    // Sleeping until the tick is due instead of for a period keeps oversleeping from adding up.
+                    let due = start + period * count;
+                    thread::sleep(due.saturating_duration_since(Instant::now()));
+                    ticks.fetch_add(1, Ordering::Relaxed);
+                }
+            });
+        }
+
+        Self { ticks, running }
+    }
+
+    /// Returns how many ticks passed since they were last taken.
+    pub fn take_ticks(&self) -> u64 {
+        self.ticks.swap(0, Ordering::Relaxed)
+    }
+
+    /// Forgets the ticks that passed, e.g. while paused.
+    pub fn skip(&self) {
+        self.take_ticks();
+    }
+}
+
+impl Drop for Clock {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock() {
+        // Only how many ticks passed at least is checked, as the thread may be late on a busy machine.
+        let clock = Clock::start(Duration::from_millis(1));
+        let start = Instant::now();
+        while clock.ticks.load(Ordering::Relaxed) < 10 {
+            assert!(
+                start.elapsed() < Duration::from_secs(10),
+                "the clock does not tick"
+            );
+            thread::sleep(Duration::from_millis(1));
+        }
+        // The ticks do not get lost while nobody takes them.
+        assert!(clock.take_ticks() >= 10);
+
+        // This one does not tick during the test.
+        let clock = Clock::start(Duration::from_secs(3600));
+        clock.ticks.store(5, Ordering::Relaxed);
+        clock.skip();
+        assert_eq!(clock.take_ticks(), 0);
+    }
+}
@@ -0,0 +1,311 @@
+//! Running the program in lockstep with the trace of a reference emulator to find where they first diverge, e.g. to
+//! check that a quirk is implemented the same way.
+//!
+//! The trace has a line for every instruction with the registers before executing it, all in hexadecimal:
+//! `<PC> <I> <V0> ... <VF> <DT> <ST>`, like `200 000 00 00 ... 00 00 00`. Other lines say what happened in between:
+//! * `tick`: the timers were decremented, like at the end of a frame.
+//! * `key <key>` and `key`: a key was pressed and held or all keys were released.
+//! * `display <bitmap>`: the screen as of now, as 8 pixels per byte in hexadecimal, row by row from the top left.
+//!
+//! Any reference emulator can be compared by making it print such a trace, to a file or piped into the standard input.
+
+use crate::{options, trace};
+use chip8_core::{annotations::Annotations, quirks::Quirks, Error, Interpreter, Registers, Step};
+use std::{
+    env,
+    fs::File,
+    io::{self, BufRead, BufReader},
+};
+
+/// A line of the trace.
+#[derive(Debug, PartialEq)]
+enum Line {
+    Registers(Registers),
+    Tick,
+    Key(Option<u8>),
+    Display(Vec<u8>),
+}
+
+/// Compares the program with the trace given in the arguments and prints where they diverge, if anywhere.
+///
+/// The arguments are the binary and the path of the trace, or `-` for the standard input, optionally followed by
+/// `--quirk <quirk>` and `--load-address <address>`.
+pub fn run(mut args: env::ArgsOs) -> Result<(), Error> {
+    let binary = crate::get_binary(&mut args)?;
+    let trace: Box<dyn BufRead> = match args.next() {
+        Some(path) if path == "-" => Box::new(BufReader::new(io::stdin())),
+        Some(path) => Box::new(BufReader::new(File::open(&path).map_err(|err| {
+            format!("Failed to open {}: {}", path.to_string_lossy(), err)
+        })?)),
+        None => return Err("No path to the trace given.".into()),
+    };
+
+    let mut quirks = Quirks::default();
+    let mut load_address = chip8_core::interpreter::START_POINT;
+    while let Some(arg) = args.next() {
+        match arg.to_str() {
+            Some(option @ "--quirk") => {
+                options::parse_quirk(&options::get_value(&mut args, option)?, &mut quirks)?
+            }
+            Some(option @ "--load-address") => {
+                load_address = options::parse_load_address(&options::get_value(&mut args, option)?)?
+            }
+            _ => return Err(format!("Unknown argument: {}", arg.to_string_lossy()).into()),
+        }
+    }
+
+    // The random numbers will differ from the reference anyway.
+    let mut interpreter = Interpreter::with_quirks(binary, 0, load_address, quirks)?;
+
+    let mut executed: u64 = 0;
+    // The last executed instruction with the registers before it, to show what caused a divergence.
+    let mut last = None;
+    for (index, line) in trace.lines().enumerate() {
+        let line = line.map_err(|err| format!("Failed to read the trace: {}", err))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let line = parse_line(&line).map_err(|err| format!("Line {}: {}", index + 1, err))?;
+
+        let divergence = match line {
+            Line::Registers(expected) => {
+                let divergence = compare_registers(&interpreter.get_registers(), &expected);
+                if divergence.is_none() {
+                    let before = interpreter.get_registers();
+                    let instruction = interpreter.get_current_instruction();
+                    match interpreter.step()? {
+                        Step::Executed | Step::Breakpoint(_) => {
+                            executed += 1;
+                            last = instruction.map(|instruction| (instruction, before));
+                        }
+                        // The trace goes on with the same registers until the key arrives.
+                        Step::AwaitingKey => {}
+                        Step::Ended => {
+                            return Err(format!(
+                                "The program ended after {} instructions, but the trace goes on.",
+                                executed
+                            )
+                            .into())
+                        }
+                    }
+                }
+                divergence
+            }
+            Line::Tick => {
+                interpreter.update_timers();
+                None
+            }
+            Line::Key(key) => {
+                for other in 0..16 {
+                    interpreter.set_key_held(other, Some(other) == key);
+                }
+                interpreter.set_key(key);
+                None
+            }
+            Line::Display(expected) => compare_display(
+                &interpreter.display().get_frame(),
+                &expected,
+                interpreter.display().screen_size().width,
+            ),
+        };
+
+        if let Some(divergence) = divergence {
+            println!(
+                "Diverged from the reference at line {} of the trace, after {} instructions.",
+                index + 1,
+                executed
+            );
+            if let Some((instruction, before)) = last {
+                println!(
+                    "Last instruction: {}",
+                    trace::format_entry(
+                        instruction,
+                        &before,
+                        &interpreter.get_registers(),
+                        &Annotations::default()
+                    )
+                );
+            }
+            println!("{}", divergence);
+            return Err("The execution diverged.".into());
+        }
+    }
+
+    println!("No divergence in {} instructions.", executed);
+
+    Ok(())
+}
+
+fn parse_line(line: &str) -> Result<Line, Error> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let parse_byte = |field: &str| {
+        u8::from_str_radix(field, 16).map_err(|_| format!("Invalid value `{}`.", field))
+    };
+
+    let line = match fields.as_slice() {
+        ["tick"] => Line::Tick,
+        ["key"] => Line::Key(None),
+        ["key", key] => Line::Key(Some(
+            parse_byte(key)
+                .ok()
+                .filter(|key| *key < 16)
+                .ok_or_else(|| format!("Invalid key `{}`. Expected 0 to F.", key))?,
+        )),
+        ["display", bitmap] => Line::Display(
+            parse_bitmap(bitmap).ok_or_else(|| format!("Invalid bitmap `{}`.", bitmap))?,
+        ),
+        [pc, i, rest @ ..] if rest.len() == 18 => {
+            let parse_address = |field: &str| {
+                u16::from_str_radix(field, 16).map_err(|_| format!("Invalid address `{}`.", field))
+            };
+            let mut gpr = [0; 16];
+            for (register, field) in gpr.iter_mut().zip(rest) {
+                *register = parse_byte(field)?;
+            }
+            Line::Registers(Registers {
+                pc: parse_address(pc)?,
+                i: parse_address(i)?,
+                gpr,
+                delay_timer: parse_byte(rest[16])?,
+                sound_timer: parse_byte(rest[17])?,
+            })
+        }
+        _ => {
+            return Err(format!(
+                "Expected `<PC> <I> <V0> ... <VF> <DT> <ST>`, `tick`, `key [<key>]` or `display <bitmap>`, got `{}`.",
+                line
+            )
+            .into())
+        }
+    };
+
+    Ok(line)
+}
+
+fn parse_bitmap(bitmap: &str) -> Option<Vec<u8>> {
+    (0..bitmap.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(bitmap.get(index..index + 2)?, 16).ok())
+        .collect()
+}
+
+/// Describes the registers that differ from the reference, if any.
+fn compare_registers(actual: &Registers, expected: &Registers) -> Option<String> {
+    let mut differences = Vec::new();
+    if actual.pc != expected.pc {
+        differences.push(format!(
+            "PC is {:#05X} instead of {:#05X}",
+            actual.pc, expected.pc
+        ));
+    }
+    if actual.i != expected.i {
+        differences.push(format!(
+            "I is {:#05X} instead of {:#05X}",
+            actual.i, expected.i
+        ));
+    }
+    for (index, (actual, expected)) in actual.gpr.iter().zip(&expected.gpr).enumerate() {
+        if actual != expected {
+            differences.push(format!(
+                "V{:X} is {:#04X} instead of {:#04X}",
+                index, actual, expected
+            ));
+        }
+    }
+    if actual.delay_timer != expected.delay_timer {
+        differences.push(format!(
+            "DT is {:#04X} instead of {:#04X}",
+            actual.delay_timer, expected.delay_timer
+        ));
+    }
+    if actual.sound_timer != expected.sound_timer {
+        differences.push(format!(
+            "ST is {:#04X} instead of {:#04X}",
+            actual.sound_timer, expected.sound_timer
+        ));
+    }
+
+    (!differences.is_empty()).then(|| differences.join(", ") + ".")
+}
+
+/// Describes the first pixel that differs from the reference, if any, given the width of the screen.
+fn compare_display(actual: &[u8], expected: &[u8], width: u16) -> Option<String> {
+    if actual.len() != expected.len() {
+        return Some(format!(
+            "The display has {} bytes instead of {}.",
+            actual.len(),
+            expected.len()
+        ));
+    }
+
+    let (index, (actual, expected)) = actual
+        .iter()
+        .zip(expected)
+        .enumerate()
+        .find(|(_, (actual, expected))| actual != expected)?;
+    let bit = (actual ^ expected).leading_zeros() as usize;
+    let pixel = index * 8 + bit;
+    let set = actual & (0x80 >> bit) != 0;
+    Some(format!(
+        "The pixel at ({}, {}) is {} but {} in the reference.",
+        pixel % width as usize,
+        pixel / width as usize,
+        if set { "on" } else { "off" },
+        if set { "off" } else { "on" }
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_line() {
+        let line =
+            parse_line("202 300 05 00 00 00 00 00 00 00 00 00 00 00 00 00 00 01 3C 00").unwrap();
+        let mut gpr = [0; 16];
+        gpr[0] = 0x05;
+        gpr[0xF] = 0x01;
+        assert_eq!(
+            line,
+            Line::Registers(Registers {
+                pc: 0x202,
+                i: 0x300,
+                gpr,
+                delay_timer: 0x3C,
+                sound_timer: 0,
+            })
+        );
+        assert_eq!(parse_line("key A").unwrap(), Line::Key(Some(0xA)));
+        assert_eq!(
+            parse_line("display 80ff").unwrap(),
+            Line::Display(vec![0x80, 0xFF])
+        );
+        assert!(parse_line("display 80f").is_err());
+        assert!(parse_line("202 300").is_err());
+    }
+
+    #[test]
+    fn test_compare() {
+        let registers = Registers {
+            pc: 0x200,
+            gpr: [0; 16],
+            i: 0,
+            delay_timer: 0,
+            sound_timer: 0,
+        };
+        let mut expected = registers;
+        assert_eq!(compare_registers(&registers, &expected), None);
+        expected.gpr[0xF] = 1;
+        assert_eq!(
+            compare_registers(&registers, &expected).unwrap(),
+            "VF is 0x00 instead of 0x01."
+        );
+
+        assert_eq!(compare_display(&[0, 0], &[0, 0], 8), None);
+        assert_eq!(
+            compare_display(&[0, 0b0010_0000], &[0, 0], 8).unwrap(),
+            "The pixel at (2, 1) is on but off in the reference."
+        );
+    }
+}
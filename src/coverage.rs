@@ -0,0 +1,91 @@
+//! Tracking which instructions of a program were executed, to see which branches the inputs exercised.
+
+use chip8_core::{disassembler, Error};
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Records the address of every executed instruction and saves the disassembly with the executed instructions marked.
+pub struct Coverage {
+    path: PathBuf,
+    executed: HashSet<u16>,
+}
+
+impl Coverage {
+    /// Creates the report right away so that an invalid path is noticed before running.
+    pub fn create(path: &Path) -> Result<Self, Error> {
+        fs::write(path, "").map_err(|err| format!("Failed to create coverage: {}", err))?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            executed: HashSet::new(),
+        })
+    }
+
+    pub fn record(&mut self, address: u16) {
+        self.executed.insert(address);
+    }
+
+    /// Writes the report for the program loaded at the address.
+    pub fn save(&self, program: &[u8], load_address: u16) -> Result<(), Error> {
+        fs::write(
+            &self.path,
+            format_report(program, load_address, &self.executed),
+        )
+        .map_err(|err| format!("Failed to write coverage: {}", err).into())
+    }
+}
+
+/// Formats the disassembly of the program with every executed instruction marked with a `>`, after a line with how
+/// many of the instructions were executed.
+///
+/// Only instructions at even offsets into the program are listed, so instructions executed at odd ones are not
+/// counted.
+fn format_report(program: &[u8], load_address: u16, executed: &HashSet<u16>) -> String {
+    let instructions = disassembler::disassemble(program, load_address);
+    let listing = disassembler::to_text(&instructions);
+
+    let mut executed_count = 0;
+    let mut lines = String::new();
+    for (instruction, line) in instructions.iter().zip(listing.lines()) {
+        let marker = if executed.contains(&instruction.address) {
+            executed_count += 1;
+            '>'
+        } else {
+            ' '
+        };
+        lines += &format!("{} {}\n", marker, line);
+    }
+
+    format!(
+        "; {} of {} instructions executed ({:.1}%)\n{}",
+        executed_count,
+        instructions.len(),
+        executed_count as f64 / instructions.len().max(1) as f64 * 100.0,
+        lines
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_report() {
+        // LD V0, 0x01; SE V0, 0x01; JP 0x300; JP 0x306
+        let program = [0x60, 0x01, 0x30, 0x01, 0x13, 0x00, 0x13, 0x06];
+        let executed = [0x300, 0x302, 0x306].iter().copied().collect();
+        assert_eq!(
+            format_report(&program, 0x300, &executed),
+            concat!(
+                "; 3 of 4 instructions executed (75.0%)\n",
+                "> 0x300  6001  LD   V0, 0x01  ; referenced by 0x304\n",
+                "> 0x302  3001  SE   V0, 0x01\n",
+                "  0x304  1300  JP   0x300\n",
+                "> 0x306  1306  JP   0x306  ; referenced by 0x306\n",
+            )
+        );
+    }
+}
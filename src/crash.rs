@@ -0,0 +1,124 @@
+//! Writing down everything about the state when the interpreter fails, to look into the failure once the terminal is
+//! restored.
+
+use crate::{options, screenshot};
+use chip8_core::{debugger, Error, Interpreter};
+use std::{
+    collections::VecDeque,
+    ffi::OsStr,
+    fs,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// How many of the most recently executed instructions are remembered.
+const HISTORY_LENGTH: usize = 64;
+
+/// Remembers the addresses of the most recently executed instructions, which is what led to the failure.
+#[derive(Default)]
+pub struct CrashLog {
+    history: VecDeque<u16>,
+}
+
+impl CrashLog {
+    pub fn record(&mut self, address: u16) {
+        if self.history.len() == HISTORY_LENGTH {
+            self.history.pop_front();
+        }
+        self.history.push_back(address);
+    }
+
+    /// Writes the dump next to the program, to `<path>.crash-<seconds since 1970>.txt`, and returns its path.
+    pub fn save(
+        &self,
+        program_path: &OsStr,
+        interpreter: &Interpreter,
+        error: &str,
+    ) -> Result<PathBuf, Error> {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+        let mut path = options::get_base_path(program_path);
+        path.push(format!(".crash-{}.txt", seconds));
+        let path = PathBuf::from(path);
+
+        fs::write(&path, self.to_text(interpreter, error))
+            .map_err(|err| format!("Failed to write the crash dump: {}", err))?;
+
+        Ok(path)
+    }
+
+    /// Formats the error, the registers, the stack, the recently executed instructions, the memory and the display.
+    fn to_text(&self, interpreter: &Interpreter, error: &str) -> String {
+        let debugger = interpreter.debugger();
+        let mut lines = vec![format!("Error: {}", error), String::new()];
+        lines.extend(interpreter.get_info());
+
+        lines.push(String::new());
+        lines.push("Stack, most recent return address first:".into());
+        let stack = interpreter.save_state().stack;
+        if stack.is_empty() {
+            lines.push("empty".into());
+        }
+        lines.extend(
+            stack
+                .iter()
+                .rev()
+                .map(|address| debugger.describe(*address)),
+        );
+
+        lines.push(String::new());
+        lines.push("Recently executed instructions, oldest first:".into());
+        lines.extend(
+            self.history
+                .iter()
+                .map(|address| debugger.describe(*address)),
+        );
+
+        lines.push(String::new());
+        lines.push("Memory:".into());
+        lines.extend(debugger::format_memory(
+            interpreter.memory(),
+            0,
+            interpreter.memory().len() as u16,
+        ));
+
+        lines.push(String::new());
+        lines.push("Display:".into());
+        let mut text = lines.join("\n");
+        text.push('\n');
+        text.push_str(&screenshot::to_dump(interpreter.display()));
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_text() {
+        // Calls a subroutine that is an instruction that does not exist.
+        let mut interpreter =
+            Interpreter::new(vec![0x22, 0x04, 0x00, 0x00, 0xE0, 0x00], 0).unwrap();
+        let mut crash_log = CrashLog::default();
+        for _ in 0..HISTORY_LENGTH + 1 {
+            crash_log.record(0x200);
+        }
+        interpreter.step().unwrap();
+        let error = interpreter.step().unwrap_err();
+
+        let text = crash_log.to_text(&interpreter, &error);
+        assert!(text.starts_with(&format!("Error: {}\n", error)));
+        assert!(text.contains("first:\n0x202\n\nRecently"));
+        assert_eq!(
+            text.lines().filter(|line| *line == "0x200").count(),
+            HISTORY_LENGTH
+        );
+        assert!(text.contains("\n0x200  22 04 00 00 E0 00 00 00"));
+        assert!(text.ends_with(&format!(
+            "Display:\n{}",
+            "................................................................\n".repeat(32)
+        )));
+    }
+}
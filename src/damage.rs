@@ -0,0 +1,123 @@
+//! Logging of everything the renderer writes to the terminal, to check that it only redraws what changed.
+
+use crate::terminal::Point;
+use chip8_core::Error;
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+/// Writes a line for every cursor move and write to a log file, and a summary at the end of every frame.
+#[derive(Debug)]
+pub struct DamageLog {
+    file: BufWriter<File>,
+    frame: u64,
+    /// What was done in the current frame so far.
+    moves: u64,
+    writes: u64,
+    cells: u64,
+    /// The first error writing to the log, which is reported at the end of the frame.
+    error: Option<io::Error>,
+}
+
+impl DamageLog {
+    pub fn create(path: &Path) -> Result<Self, Error> {
+        let file =
+            File::create(path).map_err(|err| format!("Failed to create damage log: {}", err))?;
+
+        Ok(Self {
+            file: BufWriter::new(file),
+            frame: 0,
+            moves: 0,
+            writes: 0,
+            cells: 0,
+            error: None,
+        })
+    }
+
+    pub fn record_move(&mut self, point: Point) {
+        self.moves += 1;
+        let result = writeln!(self.file, "{} move {},{}", self.frame, point.x, point.y);
+        self.keep_error(result);
+    }
+
+    pub fn record_write(&mut self, text: &str) {
+        self.writes += 1;
+        let result = if text.starts_with('\x1b') {
+            writeln!(
+                self.file,
+                "{} write escape sequence of {} bytes",
+                self.frame,
+                text.len()
+            )
+        } else {
+            let cells = get_cell_count(text);
+            self.cells += cells;
+            writeln!(
+                self.file,
+                "{} write {} cells: {:?}",
+                self.frame, cells, text
+            )
+        };
+        self.keep_error(result);
+    }
+
+    /// Writes the summary of the frame, if anything was drawn, and starts the next one.
+    pub fn end_frame(&mut self) -> Result<(), Error> {
+        if self.writes > 0 {
+            let result = writeln!(
+                self.file,
+                "{} summary: {} cells in {} writes, {} cursor moves",
+                self.frame, self.cells, self.writes, self.moves
+            );
+            self.keep_error(result);
+        }
+        self.frame += 1;
+        self.moves = 0;
+        self.writes = 0;
+        self.cells = 0;
+
+        let result = self.file.flush();
+        self.keep_error(result);
+        match self.error.take() {
+            Some(err) => Err(format!("Failed to write damage log: {}", err).into()),
+            None => Ok(()),
+        }
+    }
+
+    fn keep_error(&mut self, result: io::Result<()>) {
+        if let Err(err) = result {
+            self.error.get_or_insert(err);
+        }
+    }
+}
+
+/// Returns how many cells of the terminal the text takes up, assuming every character takes up one.
+fn get_cell_count(text: &str) -> u64 {
+    text.chars().count() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_damage_log() {
+        let path = std::env::temp_dir().join("chip8-test-damage.log");
+        let mut log = DamageLog::create(&path).unwrap();
+        log.end_frame().unwrap();
+        log.record_move(Point { x: 4, y: 2 });
+        log.record_write("████");
+        log.record_write("\x1b_Ga=T\x1b\\");
+        log.end_frame().unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "1 move 4,2\n\
+             1 write 4 cells: \"████\"\n\
+             1 write escape sequence of 8 bytes\n\
+             1 summary: 4 cells in 2 writes, 1 cursor moves\n"
+        );
+    }
+}
@@ -0,0 +1,61 @@
+//! Programs compiled into the interpreter, so that it can be tried out without looking for programs first.
+//!
+//! They are Octo sources in the `demos` directory, written for this interpreter and free to use for anything.
+
+use chip8_core::Error;
+use std::ffi::{OsStr, OsString};
+
+/// What the path of a demo starts with, like `demo:pong`. Such paths are not read from files.
+pub const PATH_PREFIX: &str = "demo:";
+
+/// The names and sources of the demos.
+const DEMOS: &[(&str, &str)] = &[
+    ("logo", include_str!("../demos/logo.8o")),
+    ("maze", include_str!("../demos/maze.8o")),
+    ("pong", include_str!("../demos/pong.8o")),
+];
+
+/// Returns the path standing for the demo with the name, for `--demo <name>`.
+pub fn get_path(name: &OsStr) -> Result<OsString, Error> {
+    let names: Vec<&str> = DEMOS.iter().map(|(name, _)| *name).collect();
+    match name.to_str() {
+        Some(name) if names.contains(&name) => Ok(format!("{}{}", PATH_PREFIX, name).into()),
+        _ => Err(format!(
+            "There is no demo named `{}`. Try one of {}.",
+            name.to_string_lossy(),
+            names.join(", ")
+        )
+        .into()),
+    }
+}
+
+/// Returns the name of the demo if the path stands for one.
+pub fn get_name(path: &OsStr) -> Option<&str> {
+    path.to_str()?.strip_prefix(PATH_PREFIX)
+}
+
+/// Returns the source of the demo the path stands for, if any.
+pub fn get_source(path: &OsStr) -> Option<&'static str> {
+    let name = get_name(path)?;
+    DEMOS
+        .iter()
+        .find(|(demo, _)| *demo == name)
+        .map(|(_, source)| *source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chip8_core::octo;
+
+    #[test]
+    fn test_demos() {
+        for (name, source) in DEMOS {
+            let path = get_path(OsStr::new(name)).unwrap();
+            assert_eq!(get_source(&path), Some(*source));
+            assert!(octo::compile(source).is_ok(), "{} does not compile", name);
+        }
+        assert!(get_path(OsStr::new("tetris")).is_err());
+        assert_eq!(get_source(OsStr::new("pong")), None);
+    }
+}
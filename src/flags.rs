@@ -0,0 +1,45 @@
+//! Keeping the flags SUPER-CHIP programs save with `FX75` across runs, so that they remember e.g. high scores.
+
+use chip8_core::{interpreter::FLAG_COUNT, Error};
+use std::{convert::TryInto, fs, io, path::Path};
+
+/// Loads the flags saved for a program, which are all zero if none were saved yet.
+///
+/// The file holds the flags as they are, one byte each.
+pub fn load(path: &Path) -> Result<[u8; FLAG_COUNT], Error> {
+    match fs::read(path) {
+        Ok(bytes) => bytes.try_into().map_err(|_| {
+            format!(
+                "Invalid flags in {}. Expected {} bytes.",
+                path.display(),
+                FLAG_COUNT
+            )
+            .into()
+        }),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok([0; FLAG_COUNT]),
+        Err(err) => Err(format!("Failed to read flags: {}", err).into()),
+    }
+}
+
+pub fn save(path: &Path, flags: [u8; FLAG_COUNT]) -> Result<(), Error> {
+    fs::write(path, flags).map_err(|err| format!("Failed to write flags: {}", err).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let path = std::env::temp_dir().join("chip8-test-roundtrip.flags");
+        let _ = fs::remove_file(&path);
+        assert_eq!(load(&path).unwrap(), [0; FLAG_COUNT]);
+
+        save(&path, [1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+        assert_eq!(load(&path).unwrap(), [1, 2, 3, 4, 5, 6, 7, 8]);
+
+        fs::write(&path, [1, 2, 3]).unwrap();
+        assert!(load(&path).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,1387 @@
+use crate::{
+    alert::Alerts,
+    buzzer::Buzzer,
+    clock::Clock,
+    coverage::Coverage,
+    crash::CrashLog,
+    damage::DamageLog,
+    flags,
+    hangup::Hangup,
+    interrupt::Interrupt,
+    keypad::Keypad,
+    link::Link,
+    menu,
+    messages::{self, Message},
+    options::{self, Options, RunUntil, Target},
+    picker,
+    profile::Profiler,
+    recording::GifRecording,
+    renderer::Renderer,
+    replay::{self, KeyEvent, Recorder, Replay},
+    rewind::History,
+    screenshot,
+    stats::Stats,
+    suspend::{self, Suspension},
+    terminal::{Event, Key, Terminal},
+    trace::Tracer,
+    watch::{self, Reload, Watcher},
+};
+use chip8_core::{
+    debugger::{self, Command, SelfModificationDetector},
+    interpreter::FLAG_COUNT,
+    patches::Patches,
+    quirks::QuirkDetector,
+    savestate::State,
+    util, Error, Interpreter, Step,
+};
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    mem,
+    path::{Path, PathBuf},
+    process, thread,
+    time::{Duration, Instant},
+};
+
+/// Runs an interpreter in the terminal, handling the input, drawing and everything around it.
+pub struct Frontend<'a> {
+    interpreter: Interpreter,
+    program: Program,
+    renderer: Renderer,
+    options: &'a Options,
+    /// The recent states, for rewinding.
+    history: History,
+    /// States the user has saved under a name, to jump back to later.
+    bookmarks: Vec<(String, State)>,
+    input: Input,
+    /// A hardware keypad used in addition to the keyboard.
+    keypad: Option<Keypad>,
+    /// The link to another interpreter, if any.
+    link: Option<Link>,
+    /// Plays the sound, if enabled.
+    buzzer: Option<Buzzer>,
+    /// Shows that the sound is playing in other ways than playing it.
+    alerts: Alerts,
+    /// Logs every executed instruction, if enabled.
+    tracer: Option<Tracer>,
+    /// Where watched writes and self-modifying code are logged to, if not shown at the top of the screen.
+    watch_log: Option<BufWriter<File>>,
+    /// Counts the executed instructions, if enabled.
+    profiler: Option<Profiler>,
+    /// Records which instructions were executed, if enabled.
+    coverage: Option<Coverage>,
+    /// Remembers the recently executed instructions for a dump of the state if the interpreter fails, if enabled.
+    crash_log: Option<CrashLog>,
+    /// Warns about instructions whose behavior differs between implementations, if enabled.
+    quirk_detector: Option<QuirkDetector>,
+    /// For how many more frames each key of the keypad is held down.
+    ///
+    /// Terminals do not report key releases, so every key is considered held for a while after it was pressed.
+    held_keys: [u32; 16],
+    /// How many more instructions, or machine cycles at the speed of the VIP, may be executed in the current frame,
+    /// unless the speed is unlimited.
+    budget: Option<f64>,
+    /// The point of interest being run to, if any.
+    run_until: Option<RunUntil>,
+    /// For how many more frames the turbo key is considered held.
+    turbo_frames: u32,
+    /// The number of frames that have passed.
+    frame_count: u64,
+    /// When the current frame started.
+    frame_start: Instant,
+    /// Ticks 60 times per second in the background, ending a frame and decrementing the timers every time, unless
+    /// fast-forwarding.
+    clock: Clock,
+    /// When the speed was last measured and the number of instructions executed by then, for the status bar.
+    speed_measurement: (Instant, u64),
+    /// The speed as last measured, for the status bar.
+    instructions_per_second: u64,
+    /// Called with the contents of the display at the end of every frame.
+    frame_hooks: Vec<FrameHook>,
+    /// Records the screen to a GIF, if enabled.
+    gif_recording: Option<GifRecording>,
+    /// Notices when the terminal is lost, if set.
+    hangup: Option<Hangup>,
+    /// Notices when a signal asks to stop, if set.
+    interrupt: Option<Interrupt>,
+    /// Notices when a signal asks to suspend, if set.
+    suspension: Option<Suspension>,
+    /// Notices when the program changes to reload it, if enabled.
+    watcher: Option<Watcher>,
+    /// How much the programs are played, if kept.
+    stats: Option<Stats>,
+    /// The flags of the program as they were last loaded or saved, to save them once the program changes them.
+    saved_flags: [u8; FLAG_COUNT],
+    /// Since when the play time of the current program has not been recorded yet.
+    play_start: Instant,
+}
+
+/// The program being run, to start it over or replace it with another one.
+pub struct Program {
+    pub path: OsString,
+    pub binary: Vec<u8>,
+    /// The seed of the random number generator.
+    pub seed: u64,
+    /// The changes applied in memory after loading the program.
+    pub patches: Patches,
+}
+
+/// Why running stopped.
+#[derive(Debug, PartialEq)]
+pub enum Exit {
+    /// The program ended by itself.
+    Ended,
+    /// The terminal hung up or can no longer be written to.
+    TerminalLost,
+    /// A signal asked to stop.
+    Interrupted,
+}
+
+/// A function receiving the contents of the display at the end of every frame, in the format of
+/// [`chip8_core::display::Display::get_frame`].
+pub type FrameHook = Box<dyn FnMut(&[u8]) -> io::Result<()>>;
+
+/// Where the keypad input comes from.
+pub enum Input {
+    Terminal,
+    /// The terminal, with every key press being recorded.
+    Recording(Recorder),
+    /// A replay, until it has finished. After that, the input comes from the terminal.
+    Replay(Replay),
+}
+
+/// Keys outside of the keypad that control the interpreter itself.
+enum Hotkey {
+    SaveState,
+    LoadState,
+    /// Rewinds for as long as the key is held.
+    Rewind,
+    Bookmark,
+    JumpToBookmark,
+    /// Starts the program over as if it was just loaded.
+    Reset,
+    /// Picks another program to run instead.
+    LoadProgram,
+    /// Opens the menu to resume, reset, load another program or quit.
+    Menu,
+    /// Runs faster for as long as the key is held.
+    Turbo,
+    /// Saves the screen as an image.
+    Screenshot,
+    /// Pauses and lets the user enter debugger commands.
+    Debugger,
+    /// Restores the terminal and stops until continued.
+    Suspend,
+}
+
+pub const CLOCK_HERTZ: f64 = 60.0;
+pub const FRAME_DURATION: Duration = Duration::from_nanos((1_000_000_000.0 / CLOCK_HERTZ) as u64);
+/// How many frames back in time the program can be rewound. This is 10 seconds.
+const REWIND_FRAME_COUNT: usize = 10 * CLOCK_HERTZ as usize;
+/// How many snapshots are rewound for each key event while the rewind key is held.
+const REWIND_STEP: usize = 2;
+/// How long to wait for the next key event before deciding that the rewind key has been released.
+/// This needs to cover the delay before the terminal starts repeating a held key.
+const REWIND_RELEASE_TIMEOUT: Duration = Duration::from_millis(600);
+/// How many times as many instructions are executed per frame while the turbo key is held.
+const TURBO_FACTOR: f64 = 8.0;
+/// For how many frames after a key event the turbo key is considered held.
+/// Like [`REWIND_RELEASE_TIMEOUT`], this needs to cover the delay before the terminal starts repeating a held key.
+const TURBO_RELEASE_FRAMES: u32 = 36;
+const INPUT_TIMEOUT: Duration = Duration::from_millis(((1.0 / CLOCK_HERTZ) * 1000.0 + 0.5) as u64);
+
+impl<'a> Frontend<'a> {
+    pub fn new(program: Program, options: &'a Options, input: Input) -> Result<Self, Error> {
+        let interpreter = Self::create_interpreter(&program, options)?;
+        Ok(Self {
+            saved_flags: interpreter.flags(),
+            interpreter,
+            program,
+            renderer: Renderer::new(options),
+            options,
+            history: History::new(REWIND_FRAME_COUNT),
+            bookmarks: Vec::new(),
+            input,
+            keypad: None,
+            link: None,
+            buzzer: None,
+            alerts: Alerts::new(options.beep_alerts.clone()),
+            tracer: None,
+            watch_log: None,
+            profiler: None,
+            coverage: None,
+            crash_log: None,
+            quirk_detector: if options.quirk_warnings {
+                Some(QuirkDetector::default())
+            } else {
+                None
+            },
+            held_keys: [0; 16],
+            budget: options.speed.get_budget_per_frame(CLOCK_HERTZ),
+            run_until: options.run_until,
+            turbo_frames: 0,
+            frame_count: 0,
+            frame_start: Instant::now(),
+            clock: Clock::start(FRAME_DURATION),
+            speed_measurement: (Instant::now(), 0),
+            instructions_per_second: 0,
+            frame_hooks: Vec::new(),
+            gif_recording: None,
+            hangup: None,
+            interrupt: None,
+            suspension: None,
+            watcher: None,
+            stats: None,
+            play_start: Instant::now(),
+        })
+    }
+
+    /// Creates an interpreter running the program, set up as configured in the options.
+    pub fn create_interpreter(program: &Program, options: &Options) -> Result<Interpreter, Error> {
+        let mut interpreter = Interpreter::with_quirks(
+            program.binary.clone(),
+            program.seed,
+            options.load_address,
+            options.quirks.clone(),
+        )?;
+        interpreter.debugger_mut().draw_breakpoints = options.draw_breakpoints.clone();
+        interpreter.debugger_mut().write_watchpoints = options.write_watchpoints.clone();
+        interpreter.debugger_mut().symbols = options.symbols.clone();
+        interpreter.debugger_mut().self_modification =
+            options.self_modification.map(SelfModificationDetector::new);
+        program.patches.apply(interpreter.memory_mut())?;
+        interpreter.set_flags(flags::load(&options::get_flags_path(&program.path))?);
+        if options.show_collisions {
+            interpreter.display_mut().track_collisions();
+        }
+
+        Ok(interpreter)
+    }
+
+    /// Restores the state from a snapshot, e.g. before running.
+    pub fn load_state(&mut self, state: &State) -> Result<(), Error> {
+        self.interpreter.load_state(state)
+    }
+
+    /// Adds a function to be called with the contents of the display at the end of every frame.
+    pub fn add_frame_hook(&mut self, hook: FrameHook) {
+        self.frame_hooks.push(hook);
+    }
+
+    /// Records the screen to the GIF until exiting.
+    pub fn set_gif_recording(&mut self, gif_recording: GifRecording) {
+        self.gif_recording = Some(gif_recording);
+    }
+
+    /// Reads keys from the hardware keypad in addition to the keyboard.
+    pub fn set_keypad(&mut self, keypad: Keypad) {
+        self.keypad = Some(keypad);
+    }
+
+    /// Exchanges the bytes of the mailbox with another interpreter over the link.
+    pub fn set_link(&mut self, link: Link) {
+        self.link = Some(link);
+    }
+
+    /// Plays the sound with the buzzer.
+    pub fn set_buzzer(&mut self, buzzer: Buzzer) {
+        self.buzzer = Some(buzzer);
+    }
+
+    /// Stops running once the terminal is lost.
+    pub fn set_hangup(&mut self, hangup: Hangup) {
+        self.hangup = Some(hangup);
+    }
+
+    /// Stops running once a signal asks to.
+    pub fn set_interrupt(&mut self, interrupt: Interrupt) {
+        self.interrupt = Some(interrupt);
+    }
+
+    /// Suspends once a signal asks to.
+    pub fn set_suspension(&mut self, suspension: Suspension) {
+        self.suspension = Some(suspension);
+    }
+
+    /// Reloads the program whenever its file changes.
+    pub fn set_watcher(&mut self, watcher: Watcher) {
+        self.watcher = Some(watcher);
+    }
+
+    /// Keeps statistics of how much the programs are played, counting the current program as launched.
+    pub fn set_stats(&mut self, mut stats: Stats) {
+        stats.get_mut(util::hash(&self.program.binary)).launches += 1;
+        self.stats = Some(stats);
+        self.play_start = Instant::now();
+    }
+
+    /// Adds the time since it was last recorded to the play time of the current program.
+    fn record_play_time(&mut self) {
+        let elapsed = mem::replace(&mut self.play_start, Instant::now()).elapsed();
+        if let Some(stats) = &mut self.stats {
+            stats.get_mut(util::hash(&self.program.binary)).play_time += elapsed;
+        }
+    }
+
+    /// Records the play time of the current program and whether it ended by itself, then saves the statistics.
+    pub fn save_stats(&mut self, ended: bool) -> Result<(), Error> {
+        self.record_play_time();
+        match &mut self.stats {
+            Some(stats) => {
+                if ended {
+                    stats.get_mut(util::hash(&self.program.binary)).completions += 1;
+                }
+                stats.save()
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Saves the screen to the path given by `--screenshot-on-exit`, if any.
+    pub fn save_exit_screenshot(&self) -> Result<(), Error> {
+        match &self.options.screenshot_on_exit {
+            Some(path) => screenshot::save(self.interpreter.display(), path),
+            None => Ok(()),
+        }
+    }
+
+    /// Writes the memory to the path given by `--dump-memory-on-exit`, if any.
+    pub fn save_exit_memory_dump(&self) -> Result<(), Error> {
+        match &self.options.dump_memory_on_exit {
+            Some(path) => dump_memory(&self.interpreter, path),
+            None => Ok(()),
+        }
+    }
+
+    /// Finishes writing the GIF recording, if any.
+    pub fn finish_gif_recording(&mut self) -> Result<(), Error> {
+        match self.gif_recording.take() {
+            Some(gif_recording) => gif_recording.finish(),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns whether the terminal hung up or can no longer be written to.
+    pub fn is_terminal_lost(&self) -> bool {
+        self.hangup.as_ref().is_some_and(Hangup::is_detected)
+    }
+
+    /// Returns whether a signal asked to stop.
+    fn is_interrupted(&self) -> bool {
+        self.interrupt.as_ref().is_some_and(Interrupt::is_requested)
+    }
+
+    /// Saves the current state to the path for automatic savestates and finishes writing all files,
+    /// so that nothing is lost when the terminal is.
+    ///
+    /// Returns the path the state was saved to.
+    pub fn save_auto_state(&mut self) -> Result<PathBuf, Error> {
+        if let Some(tracer) = &mut self.tracer {
+            let _ = tracer.flush();
+        }
+        if let Some(watch_log) = &mut self.watch_log {
+            let _ = watch_log.flush();
+        }
+        if let Input::Recording(recorder) = &mut self.input {
+            let _ = recorder.finish();
+        }
+        let path = options::get_auto_state_path(&self.program.path);
+        self.interpreter.save_state().save(&path)?;
+        Ok(path)
+    }
+
+    /// Logs every executed instruction with the tracer.
+    pub fn set_tracer(&mut self, tracer: Tracer) {
+        self.tracer = Some(tracer);
+    }
+
+    /// Logs watched writes and self-modifying code to the file instead of showing them at the top of the screen.
+    pub fn set_watch_log(&mut self, file: File) {
+        self.watch_log = Some(BufWriter::new(file));
+    }
+
+    /// Counts every executed instruction with the profiler.
+    pub fn set_profiler(&mut self, profiler: Profiler) {
+        self.profiler = Some(profiler);
+    }
+
+    /// Records the address of every executed instruction for the coverage report.
+    pub fn set_coverage(&mut self, coverage: Coverage) {
+        self.coverage = Some(coverage);
+    }
+
+    /// Saves the coverage report, if any.
+    pub fn save_coverage(&self) -> Result<(), Error> {
+        match &self.coverage {
+            Some(coverage) => coverage.save(&self.program.binary, self.options.load_address),
+            None => Ok(()),
+        }
+    }
+
+    /// Dumps the state if the interpreter fails.
+    pub fn set_crash_log(&mut self, crash_log: CrashLog) {
+        self.crash_log = Some(crash_log);
+    }
+
+    /// Writes a dump of the state next to the program because of the error, if enabled, and returns its path.
+    pub fn save_crash_dump(&self, error: &str) -> Option<Result<PathBuf, Error>> {
+        self.crash_log
+            .as_ref()
+            .map(|crash_log| crash_log.save(&self.program.path, &self.interpreter, error))
+    }
+
+    /// Returns the profiler, to print what it counted once the program exits.
+    pub fn take_profiler(&mut self) -> Option<Profiler> {
+        self.profiler.take()
+    }
+
+    pub fn set_damage_log(&mut self, damage_log: DamageLog) {
+        self.renderer.set_damage_log(damage_log);
+    }
+
+    /// Draws the whole display again.
+    fn redraw(&mut self, terminal: &mut dyn Terminal) {
+        self.renderer.redraw(terminal, self.interpreter.display());
+    }
+
+    /// Draws the pixels that changed during the frame, all at once.
+    fn draw_changes(&mut self, terminal: &mut dyn Terminal) {
+        if matches!(self.run_until, Some(run_until) if !run_until.visible) {
+            self.interpreter.display_mut().take_changes();
+        } else {
+            self.renderer
+                .draw_changes(terminal, self.interpreter.display_mut());
+        }
+    }
+
+    /// Measures the number of instructions executed per second, about once per second.
+    fn update_speed(&mut self) {
+        let (start, start_count) = self.speed_measurement;
+        let elapsed = start.elapsed();
+        if elapsed >= Duration::from_secs(1) {
+            let count = self.interpreter.metrics().instructions;
+            let executed = count - start_count;
+            self.instructions_per_second = (executed as f64 / elapsed.as_secs_f64()).round() as u64;
+            self.speed_measurement = (Instant::now(), count);
+        }
+    }
+
+    /// Returns the text of the status bar with the given state, like `running`.
+    fn get_status(&self, state: &str) -> String {
+        let registers = self.interpreter.get_registers();
+        let mut status = format!(
+            "{} IPS  DT {:3}  ST {:3}  {}",
+            self.instructions_per_second, registers.delay_timer, registers.sound_timer, state
+        );
+        if let Some(symbol) = self.alerts.get_status_symbol(self.frame_count) {
+            status.push_str("  ");
+            status.push_str(symbol);
+        }
+        status
+    }
+
+    /// Pauses execution and shows the reason along with the current state until a key is pressed.
+    ///
+    /// Hotkeys can be used while paused, e.g. to bookmark the state.
+    fn pause(&mut self, terminal: &mut dyn Terminal, reason: &str) {
+        terminal.clear();
+        terminal.reset_cursor();
+        terminal.write(reason);
+        terminal.next_line();
+        for line in self.interpreter.get_info() {
+            terminal.write(&line);
+            terminal.next_line();
+        }
+        terminal.write(messages::get(Message::PressAnyKeyOrDebug));
+        self.renderer
+            .set_status(terminal, self.get_status("paused"));
+        terminal.flush();
+
+        match terminal.read_event() {
+            Some(Event::Key(Key::Char(':'))) => {
+                let mut output = vec![reason.to_string()];
+                output.extend(self.interpreter.get_info());
+                self.run_debugger(terminal, output);
+            }
+            Some(Event::Key(key)) => {
+                if let Some(hotkey) = Self::convert_hotkey(key) {
+                    self.handle_hotkey(terminal, hotkey);
+                }
+            }
+            _ => {}
+        }
+
+        self.resume(terminal);
+    }
+
+    /// Lets the user enter debugger commands at the bottom of the terminal, showing the output of each above, until
+    /// execution is continued.
+    fn run_debugger(&mut self, terminal: &mut dyn Terminal, mut output: Vec<String>) {
+        let prompt_line = terminal.size().height.saturating_sub(1);
+        loop {
+            terminal.clear();
+            terminal.reset_cursor();
+            for line in output.iter().take(prompt_line as usize) {
+                terminal.write(line);
+                terminal.next_line();
+            }
+
+            let line = menu::prompt_at(
+                terminal,
+                messages::get(Message::DebuggerPrompt),
+                prompt_line,
+            );
+            let symbols = &self.interpreter.debugger().symbols;
+            output = match line.map(|line| Command::parse(&line, symbols)) {
+                None | Some(Ok(Command::Continue)) => break,
+                Some(Ok(command)) => self.execute_command(command),
+                Some(Err(err)) => vec![err.into_owned()],
+            };
+        }
+    }
+
+    /// Executes the debugger command, returning the lines of its output.
+    fn execute_command(&mut self, command: Command) -> Vec<String> {
+        match command {
+            Command::Memory { address, length } => {
+                debugger::format_memory(self.interpreter.memory(), address, length)
+            }
+            Command::Registers => self.interpreter.get_info(),
+            Command::Step(count) => {
+                let mut output = Vec::new();
+                let mut executed = 0;
+                while executed < count {
+                    match self.interpreter.step() {
+                        Ok(Step::Executed) => executed += 1,
+                        // The breakpoint execution is paused at does not stop stepping.
+                        Ok(Step::Breakpoint(_)) if executed == 0 => {}
+                        Ok(Step::Breakpoint(reason)) => {
+                            output.push(reason);
+                            break;
+                        }
+                        Ok(Step::AwaitingKey) => {
+                            output.push("The program is waiting for a key.".into());
+                            break;
+                        }
+                        Ok(Step::Ended) => {
+                            output.push("The program ended.".into());
+                            break;
+                        }
+                        Err(err) => {
+                            output.extend(err.lines().map(String::from));
+                            break;
+                        }
+                    }
+                }
+                output.insert(0, format!("Executed {} instructions.", executed));
+                output.extend(self.interpreter.get_info());
+                output
+            }
+            Command::Break(address) => {
+                let breakpoints = &mut self.interpreter.debugger_mut().breakpoints;
+                if !breakpoints.contains(&address) {
+                    breakpoints.push(address);
+                }
+                vec![format!(
+                    "Breakpoint set at {}.",
+                    self.interpreter.debugger().describe(address)
+                )]
+            }
+            Command::ListBreakpoints => {
+                let debugger = self.interpreter.debugger();
+                if debugger.breakpoints.is_empty() {
+                    vec!["No breakpoints.".into()]
+                } else {
+                    debugger
+                        .breakpoints
+                        .iter()
+                        .map(|address| format!("Breakpoint at {}", debugger.describe(*address)))
+                        .collect()
+                }
+            }
+            Command::Delete(address) => {
+                let debugger = self.interpreter.debugger_mut();
+                let description = debugger.describe(address);
+                if debugger.breakpoints.contains(&address) {
+                    debugger
+                        .breakpoints
+                        .retain(|breakpoint| *breakpoint != address);
+                    vec![format!("Breakpoint at {} deleted.", description)]
+                } else {
+                    vec![format!("No breakpoint at {}.", description)]
+                }
+            }
+            Command::Dump { path, sprite_rows } => {
+                let mut text = screenshot::to_dump(self.interpreter.display());
+                if let Some(rows) = sprite_rows {
+                    let address = self.interpreter.get_registers().i;
+                    text.push_str(&format!(
+                        "\nSprite at I = {}:\n",
+                        self.interpreter.debugger().describe(address)
+                    ));
+                    for row in debugger::format_sprite(self.interpreter.memory(), address, rows) {
+                        text.push_str(&row);
+                        text.push('\n');
+                    }
+                }
+                match fs::write(&path, text) {
+                    Ok(()) => vec![format!("Display dumped to {}.", path)],
+                    Err(err) => vec![format!("Failed to dump the display: {}", err)],
+                }
+            }
+            Command::MemoryDump(path) => match dump_memory(&self.interpreter, Path::new(&path)) {
+                Ok(()) => vec![format!("Memory dumped to {}.", path)],
+                Err(err) => vec![err.into_owned()],
+            },
+            Command::Help => debugger::HELP.iter().map(|line| line.to_string()).collect(),
+            Command::Continue => Vec::new(),
+        }
+    }
+
+    /// Restores the terminal and stops until continued, e.g. with `fg`, then takes the terminal over again.
+    fn suspend(&mut self, terminal: &mut dyn Terminal) {
+        // The time spent suspended is not played.
+        self.record_play_time();
+        terminal.deinitialize();
+        terminal.flush();
+        if let Err(err) = suspend::stop() {
+            eprintln!("{}", err);
+        }
+        terminal.initialize("CHIP-8");
+        self.play_start = Instant::now();
+        self.resume(terminal);
+    }
+
+    /// Suspends if a signal asked to.
+    fn handle_suspension(&mut self, terminal: &mut dyn Terminal) {
+        if self
+            .suspension
+            .as_ref()
+            .is_some_and(Suspension::take_request)
+        {
+            self.suspend(terminal);
+        }
+    }
+
+    /// Continues running after being paused.
+    fn resume(&mut self, terminal: &mut dyn Terminal) {
+        terminal.clear();
+        self.redraw(terminal);
+        // The time spent paused does not count for the speed.
+        self.speed_measurement = (Instant::now(), self.interpreter.metrics().instructions);
+        self.frame_start = Instant::now();
+        self.clock.skip();
+    }
+
+    /// Finishes the current frame, for which the clock ticked the given number of times, and starts the next one.
+    fn end_frame(&mut self, terminal: &mut dyn Terminal, ticks: u64) -> Result<(), Error> {
+        let instruction = self.interpreter.instruction_count();
+        for _ in 0..ticks {
+            match &mut self.input {
+                Input::Terminal => self.tick(),
+                Input::Recording(recorder) => {
+                    recorder.record(KeyEvent {
+                        instruction,
+                        key: replay::TICK,
+                    })?;
+                    self.tick();
+                }
+                // The timers tick as recorded instead.
+                Input::Replay(_) => {}
+            }
+        }
+
+        // Fast-forwarding would produce the sound faster than it is played.
+        if let (Some(buzzer), None) = (&mut self.buzzer, self.run_until) {
+            let sounding = self.interpreter.get_registers().sound_timer > 0;
+            buzzer.play(sounding, FRAME_DURATION.as_secs_f64() * ticks as f64)?;
+        }
+        if self.run_until.is_none() {
+            let sound_timer = self.interpreter.get_registers().sound_timer;
+            self.alerts.update(terminal, sound_timer);
+            self.renderer
+                .set_border(terminal, self.alerts.is_border_lit());
+        }
+
+        self.draw_changes(terminal);
+        if self.options.show_collisions {
+            let collisions = self.interpreter.display_mut().take_collisions();
+            self.renderer
+                .update_collisions(terminal, self.interpreter.display(), collisions);
+        }
+        self.reload_if_changed(terminal);
+        if self.options.status_bar {
+            self.update_speed();
+            let state = if self.run_until.is_some() {
+                "fast-forwarding"
+            } else if self.turbo_frames > 0 {
+                "turbo"
+            } else {
+                "running"
+            };
+            let status = self.get_status(state);
+            self.renderer.set_status(terminal, status);
+        }
+        self.renderer.end_frame()?;
+
+        let state = self.interpreter.save_state();
+        self.history.push(state);
+
+        let flags = self.interpreter.flags();
+        if flags != self.saved_flags {
+            flags::save(&options::get_flags_path(&self.program.path), flags)?;
+            self.saved_flags = flags;
+        }
+
+        if !self.frame_hooks.is_empty() {
+            let frame = self.interpreter.display().get_frame();
+            for hook in &mut self.frame_hooks {
+                hook(&frame).map_err(|err| format!("Failed to export frame: {}", err))?;
+            }
+        }
+        if let Some(gif_recording) = &mut self.gif_recording {
+            gif_recording.add_frame(self.interpreter.display())?;
+        }
+
+        if let (Some(budget), Some(budget_per_frame)) = (
+            &mut self.budget,
+            self.options.speed.get_budget_per_frame(CLOCK_HERTZ),
+        ) {
+            let factor = if self.turbo_frames > 0 {
+                TURBO_FACTOR
+            } else {
+                1.0
+            };
+            // Keep the fraction of an instruction left over so that speeds not divisible by the frame rate are exact.
+            // Cycles that the last instruction took beyond the frame are taken from the next one.
+            *budget = budget_per_frame * factor + budget.min(1.0);
+        }
+        self.turbo_frames = self.turbo_frames.saturating_sub(1);
+
+        self.frame_count += ticks;
+        self.frame_start = Instant::now();
+
+        Ok(())
+    }
+
+    /// Advances everything that happens at 60 hertz: the timers and the release of held keys.
+    fn tick(&mut self) {
+        self.interpreter.update_timers();
+
+        for (key, frames) in self.held_keys.iter_mut().enumerate() {
+            if *frames > 0 {
+                *frames -= 1;
+                if *frames == 0 {
+                    self.interpreter.set_key_held(key as u8, false);
+                }
+            }
+        }
+    }
+
+    /// Holds the key down for the configured duration.
+    fn press_key(&mut self, key: u8) {
+        let frames = (self.options.key_hold.as_secs_f64() * CLOCK_HERTZ).ceil() as u32;
+        self.held_keys[key as usize] = frames.max(1);
+        self.interpreter.set_key_held(key, true);
+    }
+
+    /// Returns whether the point of interest being run to has been reached, given the current program counter.
+    fn reached_target(&self, pc: u16) -> bool {
+        match self.run_until.map(|run_until| run_until.target) {
+            Some(Target::Pc(address)) => pc == address,
+            Some(Target::Frame(frame)) => self.frame_count >= frame,
+            Some(Target::Activity) => self
+                .interpreter
+                .get_current_instruction()
+                .is_some_and(is_activity),
+            None => false,
+        }
+    }
+
+    /// Exits the program, making sure everything is written first.
+    fn exit(&mut self, terminal: &mut dyn Terminal) -> ! {
+        // We are exiting anyway.
+        if let Some(tracer) = &mut self.tracer {
+            let _ = tracer.flush();
+        }
+        if let Some(watch_log) = &mut self.watch_log {
+            let _ = watch_log.flush();
+        }
+        if let Input::Recording(recorder) = &mut self.input {
+            let _ = recorder.finish();
+        }
+        let _ = self.save_stats(false);
+        let _ = self.save_exit_screenshot();
+        let _ = self.save_exit_memory_dump();
+        let _ = self.finish_gif_recording();
+        let _ = self.save_coverage();
+        let profiler = self.take_profiler();
+        terminal.deinitialize();
+        terminal.flush();
+        // The terminal is back to normal, so the counts stay visible.
+        if let Some(profiler) = profiler {
+            print!("{}", profiler.to_text());
+        }
+        process::exit(0)
+    }
+
+    /// Shows a short message in the top left corner.
+    fn notify(terminal: &mut dyn Terminal, message: &str) {
+        terminal.reset_cursor();
+        for _ in 0..terminal.size().width {
+            terminal.write(" ");
+        }
+        terminal.reset_cursor();
+        terminal.write(message);
+        terminal.flush();
+    }
+
+    fn handle_hotkey(&mut self, terminal: &mut dyn Terminal, hotkey: Hotkey) {
+        match hotkey {
+            Hotkey::SaveState => match self
+                .interpreter
+                .save_state()
+                .save(&options::get_state_path(&self.program.path))
+            {
+                Ok(()) => Self::notify(terminal, messages::get(Message::StateSaved)),
+                Err(err) => Self::notify(terminal, &err),
+            },
+            Hotkey::LoadState => {
+                let result = State::load(&options::get_state_path(&self.program.path))
+                    .and_then(|state| self.interpreter.load_state(&state));
+                match result {
+                    Ok(()) => {
+                        self.redraw(terminal);
+                        Self::notify(terminal, messages::get(Message::StateLoaded));
+                    }
+                    Err(err) => Self::notify(terminal, &err),
+                }
+            }
+            Hotkey::Rewind => self.rewind(terminal),
+            Hotkey::Turbo => self.turbo_frames = TURBO_RELEASE_FRAMES,
+            Hotkey::Screenshot => {
+                let path = options::get_screenshot_path(&self.program.path);
+                match screenshot::save(self.interpreter.display(), &path) {
+                    Ok(()) => Self::notify(
+                        terminal,
+                        &messages::format(Message::ScreenshotSaved, path.display()),
+                    ),
+                    Err(err) => Self::notify(terminal, &err),
+                }
+            }
+            Hotkey::Bookmark => {
+                if let Some(name) = menu::prompt(terminal, messages::get(Message::BookmarkName)) {
+                    let name = if name.is_empty() {
+                        messages::format(Message::DefaultBookmarkName, self.bookmarks.len() + 1)
+                    } else {
+                        name
+                    };
+                    let state = self.interpreter.save_state();
+                    self.bookmarks.push((name, state));
+                    Self::notify(terminal, messages::get(Message::BookmarkAdded));
+                } else {
+                    Self::notify(terminal, "");
+                }
+            }
+            Hotkey::JumpToBookmark => {
+                if self.bookmarks.is_empty() {
+                    Self::notify(terminal, messages::get(Message::NoBookmarks));
+                    return;
+                }
+
+                let names: Vec<&str> = self
+                    .bookmarks
+                    .iter()
+                    .map(|(name, _)| name.as_str())
+                    .collect();
+                let selection =
+                    menu::select(terminal, messages::get(Message::JumpToBookmark), &names);
+                let result = match selection {
+                    Some(index) => self.interpreter.load_state(&self.bookmarks[index].1),
+                    None => Ok(()),
+                };
+                terminal.clear();
+                self.redraw(terminal);
+                if let Err(err) = result {
+                    Self::notify(terminal, &err);
+                }
+            }
+            Hotkey::Reset | Hotkey::LoadProgram => {
+                let result = if let Hotkey::Reset = hotkey {
+                    self.reset()
+                } else {
+                    self.load_program(terminal)
+                };
+                terminal.clear();
+                self.redraw(terminal);
+                if let Err(err) = result {
+                    Self::notify(terminal, &err);
+                }
+                self.frame_start = Instant::now();
+                self.clock.skip();
+            }
+            Hotkey::Suspend => self.suspend(terminal),
+            Hotkey::Debugger => {
+                let mut output = vec![messages::get(Message::Paused).to_string()];
+                output.extend(self.interpreter.get_info());
+                self.run_debugger(terminal, output);
+                self.resume(terminal);
+            }
+            Hotkey::Menu => {
+                let items = [
+                    Message::MenuResume,
+                    Message::MenuReset,
+                    Message::MenuLoadRom,
+                    Message::MenuQuit,
+                ]
+                .map(messages::get);
+                let selection = menu::select(terminal, messages::get(Message::MenuTitle), &items);
+                let result = match selection {
+                    Some(1) => self.reset(),
+                    Some(2) => self.load_program(terminal),
+                    Some(3) => self.exit(terminal),
+                    _ => Ok(()),
+                };
+                terminal.clear();
+                self.redraw(terminal);
+                if let Err(err) = result {
+                    Self::notify(terminal, &err);
+                }
+                self.frame_start = Instant::now();
+                self.clock.skip();
+            }
+        }
+    }
+
+    /// Returns an error if the input is recorded or replayed, which starting over or loading another program would
+    /// get out of sync.
+    fn check_input_is_live(&self) -> Result<(), Error> {
+        match self.input {
+            Input::Terminal => Ok(()),
+            Input::Recording(_) | Input::Replay(_) => {
+                Err("This is not possible while recording or replaying.".into())
+            }
+        }
+    }
+
+    /// Starts the program over as if it was just loaded.
+    fn reset(&mut self) -> Result<(), Error> {
+        self.check_input_is_live()?;
+
+        let mut interpreter = Self::create_interpreter(&self.program, self.options)?;
+        // The metrics count for the whole session.
+        *interpreter.metrics_mut() = self.interpreter.metrics().clone();
+        self.interpreter = interpreter;
+        self.held_keys = [0; 16];
+
+        Ok(())
+    }
+
+    /// Lets the user pick another program in the file browser, starting next to the current one, and runs it instead.
+    fn load_program(&mut self, terminal: &mut dyn Terminal) -> Result<(), Error> {
+        self.check_input_is_live()?;
+
+        let directory = Path::new(&self.program.path)
+            .parent()
+            .filter(|directory| !directory.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."));
+        let path = match picker::pick(terminal, directory, self.stats.as_ref())? {
+            Some(path) => OsString::from(path),
+            None => return Ok(()),
+        };
+        let program = Program {
+            binary: crate::read_binary(&path)?,
+            path,
+            seed: self.options.seed.unwrap_or_else(rand::random),
+            // The patches were meant for the other program.
+            patches: Patches::default(),
+        };
+
+        let mut interpreter = Self::create_interpreter(&program, self.options)?;
+        *interpreter.metrics_mut() = self.interpreter.metrics().clone();
+        self.interpreter = interpreter;
+        self.record_play_time();
+        self.program = program;
+        if let Some(stats) = &mut self.stats {
+            stats.get_mut(util::hash(&self.program.binary)).launches += 1;
+        }
+        self.held_keys = [0; 16];
+        // States of the other program cannot be loaded anymore.
+        self.history = History::new(REWIND_FRAME_COUNT);
+        self.bookmarks.clear();
+        if let Some(watcher) = &mut self.watcher {
+            *watcher = Watcher::new(PathBuf::from(&self.program.path));
+        }
+
+        Ok(())
+    }
+
+    /// Reloads the program if its file changed.
+    fn reload_if_changed(&mut self, terminal: &mut dyn Terminal) {
+        let path = match &mut self.watcher {
+            Some(watcher) => {
+                if !watcher.has_changed() {
+                    return;
+                }
+                watcher.path.clone()
+            }
+            None => return,
+        };
+
+        match self.reload(terminal, &path) {
+            Ok(Some(message)) => Self::notify(terminal, &message),
+            Ok(None) => {}
+            Err(err) => Self::notify(terminal, &format!("Failed to reload: {}", err)),
+        }
+    }
+
+    /// Loads the program from the path again, returning what happened if it changed.
+    ///
+    /// If code only changed in place or was appended, the changes are written to memory while the program keeps
+    /// running. Otherwise the program starts over.
+    fn reload(
+        &mut self,
+        terminal: &mut dyn Terminal,
+        path: &Path,
+    ) -> Result<Option<String>, Error> {
+        self.check_input_is_live()?;
+
+        let binary = crate::read_binary(path.as_os_str())?;
+        let message = match watch::compare(&self.program.binary, &binary) {
+            Reload::Unchanged => return Ok(None),
+            Reload::Patch(changes) => {
+                let start = self.options.load_address as usize;
+                let memory = &mut self.interpreter.memory_mut()[start..];
+                if binary.len() > memory.len() {
+                    return Err("The program does not fit in memory.".into());
+                }
+                for (offset, byte) in &changes {
+                    memory[*offset] = *byte;
+                }
+                // Savestates are for the program as it is now, so that they can be loaded when it is run again.
+                self.interpreter.set_program(&binary);
+                self.history = History::new(REWIND_FRAME_COUNT);
+                self.bookmarks.clear();
+                self.program.binary = binary;
+                format!("Reloaded: {} bytes changed.", changes.len())
+            }
+            Reload::Restart => {
+                let old_binary = mem::replace(&mut self.program.binary, binary);
+                if let Err(err) = self.reset() {
+                    self.program.binary = old_binary;
+                    return Err(err);
+                }
+                // The states were taken of the old program, which may even have had another display size.
+                self.history = History::new(REWIND_FRAME_COUNT);
+                self.bookmarks.clear();
+                terminal.clear();
+                self.redraw(terminal);
+                "Reloaded: started over.".to_string()
+            }
+        };
+
+        Ok(Some(message))
+    }
+
+    /// Rewinds the program until the rewind key is released.
+    ///
+    /// Terminals do not report key releases, so the key is considered released once it stops repeating.
+    fn rewind(&mut self, terminal: &mut dyn Terminal) {
+        loop {
+            let mut state = None;
+            for _ in 0..REWIND_STEP {
+                state = self.history.pop().or(state);
+            }
+
+            if let Some(state) = state {
+                match self.interpreter.load_state(&state) {
+                    Ok(()) => self.redraw(terminal),
+                    Err(err) => Self::notify(terminal, &err),
+                }
+            } else {
+                Self::notify(terminal, messages::get(Message::CannotRewind));
+            }
+
+            match terminal.poll_event(REWIND_RELEASE_TIMEOUT) {
+                Some(Event::Key(key))
+                    if matches!(Self::convert_hotkey(key), Some(Hotkey::Rewind)) =>
+                {
+                    continue
+                }
+                _ => break,
+            }
+        }
+    }
+
+    fn convert_hotkey(key: Key) -> Option<Hotkey> {
+        match key {
+            Key::Char(char) => match char.to_ascii_lowercase() {
+                'k' => Some(Hotkey::SaveState),
+                'l' => Some(Hotkey::LoadState),
+                'b' => Some(Hotkey::Bookmark),
+                'j' => Some(Hotkey::JumpToBookmark),
+                'n' => Some(Hotkey::Reset),
+                'o' => Some(Hotkey::LoadProgram),
+                'p' => Some(Hotkey::Screenshot),
+                ':' => Some(Hotkey::Debugger),
+                _ => None,
+            },
+            Key::Backspace => Some(Hotkey::Rewind),
+            Key::Tab => Some(Hotkey::Turbo),
+            Key::Esc => Some(Hotkey::Menu),
+            Key::Suspend => Some(Hotkey::Suspend),
+            _ => None,
+        }
+    }
+
+    /// Records the pressed key or replaces it with the replayed one, depending on the input.
+    fn process_key(
+        &mut self,
+        terminal: &mut dyn Terminal,
+        key: Option<u8>,
+    ) -> Result<Option<u8>, Error> {
+        let instruction = self.interpreter.instruction_count();
+        match &mut self.input {
+            Input::Terminal => Ok(key),
+            Input::Recording(recorder) => {
+                if let Some(key) = key {
+                    recorder.record(KeyEvent { instruction, key })?;
+                }
+                Ok(key)
+            }
+            Input::Replay(replay) => {
+                let ticks = replay.take_ticks(instruction);
+                let key = replay.next_key(instruction);
+                if replay.is_finished() {
+                    self.input = Input::Terminal;
+                    Self::notify(terminal, messages::get(Message::ReplayFinished));
+                }
+                for _ in 0..ticks {
+                    self.tick();
+                }
+                Ok(key)
+            }
+        }
+    }
+
+    /// Returns the key of the keypad the key on the keyboard is mapped to, if any.
+    fn convert_key(&self, key: char) -> Option<u8> {
+        let key = key.to_ascii_lowercase();
+        self.options
+            .keymap
+            .iter()
+            .position(|mapped| *mapped == key)
+            .map(|index| index as u8)
+    }
+
+    /// Polls for a pressed key, handling hotkeys, and returns it if it is on the keypad.
+    fn poll_key(&mut self, terminal: &mut dyn Terminal) -> Result<Option<u8>, Error> {
+        match terminal.poll_event(
+            std::time::Duration::from_secs_f64(0.0001), /*INPUT_TIMEOUT*/
+        ) {
+            Some(Event::Key(key)) => {
+                if let Some(hotkey) = Self::convert_hotkey(key) {
+                    self.handle_hotkey(terminal, hotkey);
+                }
+
+                match key {
+                    Key::Char(char) => Ok(self.convert_key(char)),
+                    _ => Ok(None),
+                }
+            }
+            Some(Event::Resize) => {
+                self.handle_resize(terminal);
+                Ok(None)
+            }
+            None => self.poll_hardware_key(),
+        }
+    }
+
+    /// Draws everything again at the new position after the terminal was resized, which garbles what was drawn.
+    fn handle_resize(&mut self, terminal: &mut dyn Terminal) {
+        terminal.clear();
+        self.redraw(terminal);
+    }
+
+    fn poll_hardware_key(&mut self) -> Result<Option<u8>, Error> {
+        match &mut self.keypad {
+            Some(keypad) => keypad.poll(),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns whether `FX0A` may receive the key.
+    fn accepts_awaited_key(&self, key: u8) -> bool {
+        self.options.fx0a_keys & (1 << key) != 0
+    }
+
+    /// Blocks execution until a hexadecimal key is pressed on the keyboard or the hardware keypad and returns it.
+    ///
+    /// Returns `None` if the terminal was lost.
+    fn await_hex_key(&mut self, terminal: &mut dyn Terminal) -> Result<Option<u8>, Error> {
+        loop {
+            if self.is_terminal_lost() || self.is_interrupted() {
+                return Ok(None);
+            }
+            self.handle_suspension(terminal);
+            if let Some(key) = self.poll_hardware_key()? {
+                if self.accepts_awaited_key(key) {
+                    return Ok(Some(key));
+                }
+            }
+
+            match terminal.poll_event(INPUT_TIMEOUT) {
+                Some(Event::Key(Key::Esc)) => {
+                    // The program may have been replaced, so check again whether it awaits a key.
+                    self.handle_hotkey(terminal, Hotkey::Menu);
+                    return Ok(None);
+                }
+                Some(Event::Key(Key::Char(char))) => {
+                    if let Some(key) = self
+                        .convert_key(char)
+                        .filter(|key| self.accepts_awaited_key(*key))
+                    {
+                        return Ok(Some(key));
+                    }
+                }
+                Some(Event::Key(Key::Suspend)) => self.suspend(terminal),
+                Some(Event::Resize) => self.handle_resize(terminal),
+                _ => {}
+            }
+        }
+    }
+
+    pub fn run(&mut self, terminal: &mut dyn Terminal) -> Result<Exit, Error> {
+        self.redraw(terminal);
+        self.clock.skip();
+
+        let mut awaiting_key = false;
+        loop {
+            if self.is_terminal_lost() {
+                return Ok(Exit::TerminalLost);
+            }
+            if self.is_interrupted() {
+                return Ok(Exit::Interrupted);
+            }
+            self.handle_suspension(terminal);
+
+            let out_of_budget = matches!(self.budget, Some(budget) if budget < 1.0);
+            if self.run_until.is_some() && self.budget.is_some() {
+                // Frames end as soon as all their instructions are executed instead of waiting for the time to pass,
+                // while still counting the same as at normal speed.
+                if out_of_budget {
+                    self.end_frame(terminal, 1)?;
+                }
+            } else {
+                let ticks = self.clock.take_ticks();
+                if ticks > 0 {
+                    // Every tick beyond the first is a whole frame that was missed.
+                    self.interpreter.metrics_mut().timer_underruns += ticks - 1;
+                    self.end_frame(terminal, ticks)?;
+                }
+
+                if out_of_budget {
+                    thread::sleep(FRAME_DURATION.saturating_sub(self.frame_start.elapsed()));
+                    continue;
+                }
+            }
+
+            if self.reached_target(self.interpreter.get_registers().pc) {
+                let target = self.run_until.take().map(|run_until| run_until.target);
+                self.interpreter.display_mut().take_changes();
+                if let Some(Target::Activity) = target {
+                    // Show whatever was drawn while skipping.
+                    self.redraw(terminal);
+                    self.frame_start = Instant::now();
+                    self.clock.skip();
+                } else {
+                    self.pause(terminal, messages::get(Message::ReachedTarget));
+                }
+            }
+
+            // A replay provides the awaited key by itself.
+            // While a key is held, the program may be waiting for it to be released instead.
+            let key = if awaiting_key
+                && !matches!(self.input, Input::Replay(_))
+                && self.held_keys.iter().all(|frames| *frames == 0)
+            {
+                self.await_hex_key(terminal)?
+            } else {
+                self.poll_key(terminal)?
+            };
+            // The program may be about to await a key too.
+            let instruction = self.interpreter.get_current_instruction();
+            let awaits_key =
+                matches!(instruction, Some(instruction) if instruction & 0xF0FF == 0xF00A);
+            let key = key.filter(|key| !awaits_key || self.accepts_awaited_key(*key));
+            let key = self.process_key(terminal, key)?;
+            if let Some(key) = key {
+                self.press_key(key);
+            }
+            self.interpreter.set_key(key);
+
+            let registers = self.interpreter.get_registers();
+
+            match self.interpreter.step()? {
+                Step::Executed => {
+                    awaiting_key = false;
+                    if let (Some(budget), Some(instruction)) = (&mut self.budget, instruction) {
+                        *budget -= self.options.speed.get_cost(instruction);
+                    }
+
+                    if let (Some(tracer), Some(instruction)) = (&mut self.tracer, instruction) {
+                        tracer.trace(instruction, &registers, &self.interpreter.get_registers())?;
+                    }
+                    if let (Some(profiler), Some(instruction)) = (&mut self.profiler, instruction) {
+                        profiler.record(registers.pc, instruction);
+                    }
+                    if let Some(coverage) = &mut self.coverage {
+                        coverage.record(registers.pc);
+                    }
+                    if let Some(crash_log) = &mut self.crash_log {
+                        crash_log.record(registers.pc);
+                    }
+                    if let (Some(detector), Some(instruction)) =
+                        (&mut self.quirk_detector, instruction)
+                    {
+                        if let Some(quirk) = detector.check(instruction, &registers.gpr) {
+                            Self::notify(terminal, quirk.get_warning());
+                        }
+                    }
+                    if let Some(link) = &mut self.link {
+                        link.exchange(self.interpreter.memory_mut())?;
+                    }
+                }
+                Step::AwaitingKey => awaiting_key = true,
+                Step::Breakpoint(reason) => self.pause(terminal, &reason),
+                Step::Ended => {
+                    // Show what was drawn during the last frame.
+                    self.draw_changes(terminal);
+                    break;
+                }
+            }
+            // The standard error is the terminal that is drawn to, so the messages go elsewhere.
+            for message in self.interpreter.debugger_mut().take_log() {
+                match &mut self.watch_log {
+                    Some(watch_log) => writeln!(watch_log, "{}", message)
+                        .map_err(|err| format!("Failed to write the watch log: {}", err))?,
+                    None => Self::notify(terminal, &message),
+                }
+            }
+        }
+
+        Ok(Exit::Ended)
+    }
+}
+
+/// Writes the whole memory of the interpreter to the file as it is, e.g. to look at it in a hex editor.
+pub fn dump_memory(interpreter: &Interpreter, path: &Path) -> Result<(), Error> {
+    fs::write(path, interpreter.memory())
+        .map_err(|err| format!("Failed to dump the memory: {}", err).into())
+}
+
+/// Returns whether the instruction draws a sprite or checks the keys, as programs do once their startup is over.
+fn is_activity(instruction: u16) -> bool {
+    matches!(instruction & 0xF000, 0xD000 | 0xE000) || instruction & 0xF0FF == 0xF00A
+}
@@ -0,0 +1,266 @@
+//! Encoding of animations of a few colors as GIF.
+
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+/// The size of the codes of the pixels before compression, which is also the number of bits of a color index.
+/// 2 is the minimum GIF allows, even for 2 colors.
+const MIN_CODE_SIZE: u8 = 2;
+/// The number of colors of the color table.
+pub const COLOR_COUNT: usize = 1 << MIN_CODE_SIZE;
+/// The largest code size GIF allows.
+const MAX_CODE_SIZE: u8 = 12;
+
+/// Writes frames of up to 4 colors to an animated GIF that loops forever.
+///
+/// Frames that are the same as the one before are merged into it.
+pub struct GifEncoder<W: Write> {
+    writer: W,
+    width: u16,
+    height: u16,
+    /// The last frame added, which is written once it is known for how long it is shown.
+    pending: Option<(Vec<u8>, u16)>,
+}
+
+impl<W: Write> GifEncoder<W> {
+    /// Writes the header of a GIF with the given size and colors, which the pixels are indices into.
+    pub fn new(
+        mut writer: W,
+        width: u16,
+        height: u16,
+        colors: [[u8; 3]; COLOR_COUNT],
+    ) -> io::Result<Self> {
+        writer.write_all(b"GIF89a")?;
+        writer.write_all(&width.to_le_bytes())?;
+        writer.write_all(&height.to_le_bytes())?;
+        // A global color table of 2 to the power of 1 plus the size field colors.
+        writer.write_all(&[0b1000_0000 | (MIN_CODE_SIZE - 1), 0, 0])?;
+        writer.write_all(&colors.concat())?;
+        // The application extension making the animation loop.
+        writer.write_all(b"\x21\xFF\x0BNETSCAPE2.0\x03\x01\x00\x00\x00")?;
+
+        Ok(Self {
+            writer,
+            width,
+            height,
+            pending: None,
+        })
+    }
+
+    /// Adds a frame of the color indices of the pixels, row by row, shown for the given number of hundredths of a
+    /// second.
+    pub fn add_frame(&mut self, pixels: Vec<u8>, delay: u16) -> io::Result<()> {
+        match &mut self.pending {
+            Some((pending, pending_delay)) if *pending == pixels => {
+                *pending_delay = pending_delay.saturating_add(delay);
+            }
+            _ => {
+                if let Some((pixels, delay)) = self.pending.take() {
+                    self.write_frame(&pixels, delay)?;
+                }
+                self.pending = Some((pixels, delay));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the last frame and the end of the GIF.
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some((pixels, delay)) = self.pending.take() {
+            self.write_frame(&pixels, delay)?;
+        }
+        self.writer.write_all(&[0x3B])?;
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+
+    fn write_frame(&mut self, pixels: &[u8], delay: u16) -> io::Result<()> {
+        // The graphic control extension with the delay.
+        self.writer.write_all(&[0x21, 0xF9, 0x04, 0x00])?;
+        self.writer.write_all(&delay.to_le_bytes())?;
+        self.writer.write_all(&[0x00, 0x00])?;
+
+        // The image descriptor, covering the whole image.
+        self.writer.write_all(&[0x2C, 0, 0, 0, 0])?;
+        self.writer.write_all(&self.width.to_le_bytes())?;
+        self.writer.write_all(&self.height.to_le_bytes())?;
+        self.writer.write_all(&[0x00])?;
+
+        self.writer.write_all(&[MIN_CODE_SIZE])?;
+        let data = compress(pixels);
+        for block in data.chunks(255) {
+            self.writer.write_all(&[block.len() as u8])?;
+            self.writer.write_all(block)?;
+        }
+        self.writer.write_all(&[0x00])
+    }
+}
+
+/// Packs codes of varying sizes into bytes, starting with the least significant bit.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    length: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u8) {
+        self.buffer |= (code as u32) << self.length;
+        self.length += size;
+        while self.length >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.length -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.length > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+/// Compresses the pixels with the variant of LZW used by GIF.
+fn compress(pixels: &[u8]) -> Vec<u8> {
+    let clear_code: u16 = 1 << MIN_CODE_SIZE;
+    let end_code = clear_code + 1;
+
+    let mut writer = BitWriter::default();
+    let mut table = HashMap::new();
+    let mut next_code = end_code + 1;
+    let mut code_size = MIN_CODE_SIZE + 1;
+
+    writer.write(clear_code, code_size);
+
+    let mut pixels = pixels.iter().map(|pixel| *pixel as u16);
+    let mut current = match pixels.next() {
+        Some(pixel) => pixel,
+        None => {
+            writer.write(end_code, code_size);
+            return writer.finish();
+        }
+    };
+
+    for pixel in pixels {
+        if let Some(code) = table.get(&(current, pixel)) {
+            current = *code;
+            continue;
+        }
+
+        writer.write(current, code_size);
+        if next_code == 1 << MAX_CODE_SIZE {
+            // The table is full, so start over.
+            writer.write(clear_code, code_size);
+            table.clear();
+            next_code = end_code + 1;
+            code_size = MIN_CODE_SIZE + 1;
+        } else {
+            table.insert((current, pixel), next_code);
+            if next_code == 1 << code_size {
+                code_size += 1;
+            }
+            next_code += 1;
+        }
+        current = pixel;
+    }
+
+    writer.write(current, code_size);
+    writer.write(end_code, code_size);
+
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decompresses the codes again, following the GIF specification.
+    fn decompress(bytes: &[u8]) -> Vec<u16> {
+        let clear_code: u16 = 1 << MIN_CODE_SIZE;
+        let end_code = clear_code + 1;
+
+        let mut bits = bytes
+            .iter()
+            .flat_map(|byte| (0..8).map(move |index| (byte >> index) & 1));
+        let mut read = |size: u8| {
+            (0..size).fold(0, |code, index| {
+                code | (bits.next().unwrap() as u16) << index
+            })
+        };
+
+        let mut output = Vec::new();
+        let mut table: Vec<Vec<u16>> = Vec::new();
+        let mut code_size = MIN_CODE_SIZE + 1;
+        let mut previous: Option<Vec<u16>> = None;
+        loop {
+            let code = read(code_size);
+            if code == clear_code {
+                table = (0..clear_code).map(|code| vec![code]).collect();
+                table.push(Vec::new());
+                table.push(Vec::new());
+                code_size = MIN_CODE_SIZE + 1;
+                previous = None;
+                continue;
+            }
+            if code == end_code {
+                return output;
+            }
+
+            let entry = match table.get(code as usize) {
+                Some(entry) => entry.clone(),
+                None => {
+                    let previous = previous.clone().unwrap();
+                    let mut entry = previous.clone();
+                    entry.push(previous[0]);
+                    entry
+                }
+            };
+            if let Some(mut previous) = previous {
+                previous.push(entry[0]);
+                table.push(previous);
+            }
+            if table.len() == 1 << code_size && code_size < MAX_CODE_SIZE {
+                code_size += 1;
+            }
+            output.extend_from_slice(&entry);
+            previous = Some(entry);
+        }
+    }
+
+    #[test]
+    fn test_compress() {
+        let pixels: Vec<u8> = (0..20_000)
+            .map(|index: u32| ((index / 7 + index / 300) % 4) as u8)
+            .collect();
+        let codes: Vec<u16> = pixels.iter().map(|pixel| *pixel as u16).collect();
+
+        assert_eq!(decompress(&compress(&pixels)), codes);
+        assert_eq!(decompress(&compress(&[])), []);
+    }
+
+    #[test]
+    fn test_merge_frames() {
+        let mut encoder =
+            GifEncoder::new(Vec::new(), 2, 1, [[0; 3], [255; 3], [0; 3], [0; 3]]).unwrap();
+        encoder.add_frame(vec![1, 0], 5).unwrap();
+        encoder.add_frame(vec![1, 0], 5).unwrap();
+        encoder.add_frame(vec![0, 0], 5).unwrap();
+        let bytes = encoder.finish().unwrap();
+
+        let delays: Vec<u16> = bytes
+            .windows(6)
+            .filter(|window| window[..4] == [0x21, 0xF9, 0x04, 0x00])
+            .map(|window| u16::from_le_bytes([window[4], window[5]]))
+            .collect();
+        assert_eq!(delays, [10, 5]);
+        assert!(bytes.starts_with(b"GIF89a"));
+        assert_eq!(bytes.last(), Some(&0x3B));
+    }
+}
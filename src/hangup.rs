@@ -0,0 +1,37 @@
+//! Detection of the terminal going away while running, e.g. because an SSH connection dropped.
+
+use chip8_core::Error;
+use signal_hook::consts::{SIGHUP, SIGPIPE};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// The exit code when the terminal was lost, `EX_IOERR` from `sysexits.h`.
+pub const EXIT_CODE: i32 = 74;
+
+/// Notices when the terminal hangs up or output can no longer be written.
+///
+/// Once that happens, writing to the terminal fails or is lost and reading from it returns immediately,
+/// so continuing would only spin.
+#[derive(Clone)]
+pub struct Hangup {
+    detected: Arc<AtomicBool>,
+}
+
+impl Hangup {
+    pub fn register() -> Result<Self, Error> {
+        let detected = Arc::new(AtomicBool::new(false));
+        for signal in [SIGHUP, SIGPIPE] {
+            signal_hook::flag::register(signal, Arc::clone(&detected))
+                .map_err(|err| format!("Failed to register signal handler: {}", err))?;
+        }
+
+        Ok(Self { detected })
+    }
+
+    /// Returns whether the terminal was lost.
+    pub fn is_detected(&self) -> bool {
+        self.detected.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,75 @@
+//! Running a program for a fixed number of instructions without a terminal, e.g. to test programs in CI by comparing
+//! what they drew.
+
+use crate::{
+    frontend::{self, Frontend, Program, CLOCK_HERTZ},
+    options::{Options, Speed},
+    screenshot,
+};
+use chip8_core::{Error, Interpreter, Step};
+use std::fs;
+
+/// After how many instructions the timers tick at the maximum speed, like in `chip8 bench`.
+const MAX_SPEED_INSTRUCTIONS_PER_FRAME: f64 = 1000.0;
+
+/// Runs the program for `--cycles` instructions, or until it ends, and writes the display to `--dump-display` and the
+/// memory to `--dump-memory-on-exit`.
+pub fn run(program: &Program, options: &Options, cycles: u64) -> Result<(), Error> {
+    let mut interpreter = Frontend::create_interpreter(program, options)?;
+    execute(&mut interpreter, cycles, options.speed)?;
+
+    if let Some(path) = &options.dump_display {
+        fs::write(path, screenshot::to_dump(interpreter.display()))
+            .map_err(|err| format!("Failed to dump the display: {}", err))?;
+    }
+    if let Some(path) = &options.dump_memory_on_exit {
+        frontend::dump_memory(&interpreter, path)?;
+    }
+
+    Ok(())
+}
+
+/// Executes up to the given number of instructions, ticking the timers as often as at the speed.
+///
+/// No keys are pressed, so a program waiting for a key keeps waiting, which counts as executing the instruction again.
+pub fn execute(interpreter: &mut Interpreter, cycles: u64, speed: Speed) -> Result<(), Error> {
+    let budget_per_frame = speed
+        .get_budget_per_frame(CLOCK_HERTZ)
+        .unwrap_or(MAX_SPEED_INSTRUCTIONS_PER_FRAME);
+    let mut budget = budget_per_frame;
+    for _ in 0..cycles {
+        let instruction = interpreter.get_current_instruction();
+        match interpreter.step()? {
+            // The frontend pauses at breakpoints, which only continues here.
+            Step::Executed | Step::AwaitingKey | Step::Breakpoint(_) => {}
+            Step::Ended => break,
+        }
+
+        budget -= instruction.map_or(1.0, |instruction| speed.get_cost(instruction));
+        while budget < 1.0 {
+            interpreter.update_timers();
+            budget += budget_per_frame;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_execute() {
+        // LD V0, 0x3C; LD DT, V0; LD V1, DT; JP 0x206
+        let program = vec![0x60, 0x3C, 0xF0, 0x15, 0xF1, 0x07, 0x12, 0x06];
+        let mut interpreter = Interpreter::new(program.clone(), 0).unwrap();
+        execute(&mut interpreter, 3, Speed::PerFrame(10)).unwrap();
+        assert_eq!(interpreter.get_registers().gpr[1], 0x3C);
+
+        // A second passes in 600 instructions.
+        let mut interpreter = Interpreter::new(program, 0).unwrap();
+        execute(&mut interpreter, 2 + 600, Speed::PerFrame(10)).unwrap();
+        assert_eq!(interpreter.get_registers().delay_timer, 0);
+    }
+}
@@ -0,0 +1,40 @@
+//! Stopping cleanly when asked to by a signal, e.g. Ctrl-C in another window or `kill`, so that the terminal is not
+//! left in raw mode.
+//!
+//! On Windows, the C runtime raises `SIGINT` for the Ctrl-C and Ctrl-Break console events.
+
+use chip8_core::Error;
+use signal_hook::consts::{SIGINT, SIGTERM};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// The exit code when interrupted, which is how shells report being interrupted by `SIGINT`.
+pub const EXIT_CODE: i32 = 130;
+
+/// Notices when the interpreter is asked to stop.
+#[derive(Clone)]
+pub struct Interrupt {
+    requested: Arc<AtomicBool>,
+}
+
+impl Interrupt {
+    pub fn register() -> Result<Self, Error> {
+        let requested = Arc::new(AtomicBool::new(false));
+        for signal in [SIGINT, SIGTERM] {
+            // A second signal exits right away, e.g. while waiting for input in a menu, where the first one is only
+            // noticed once a key is pressed.
+            signal_hook::flag::register_conditional_shutdown(signal, 1, Arc::clone(&requested))
+                .and_then(|_| signal_hook::flag::register(signal, Arc::clone(&requested)))
+                .map_err(|err| format!("Failed to register signal handler: {}", err))?;
+        }
+
+        Ok(Self { requested })
+    }
+
+    /// Returns whether the interpreter was asked to stop.
+    pub fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,180 @@
+//! Input from hardware keypads like 4x4 matrix keypads.
+
+use chip8_core::Error;
+use std::{
+    fs::File,
+    io::{self, Read},
+    mem,
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+};
+
+/// How the keys are sent by the hardware.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Protocol {
+    /// Every byte is a pressed key from `0x0` to `0xF`, e.g. from a microcontroller scanning the keypad.
+    Raw,
+    /// Every byte is a pressed key as a hexadecimal ASCII digit. Other bytes like line breaks are ignored.
+    Ascii,
+    /// Linux input events, e.g. of a keypad wired to GPIO pins and handled by the `matrix-keypad` driver.
+    /// The keys `0` to `9` and `A` to `F` are used.
+    Evdev,
+}
+
+/// The event type of key events.
+const EV_KEY: u16 = 0x01;
+/// The size of a Linux input event: the time as two `long`s, the type, the code and the value.
+const INPUT_EVENT_SIZE: usize = 2 * mem::size_of::<usize>() + 2 + 2 + 4;
+
+impl Protocol {
+    /// Returns how many bytes one message of the protocol is.
+    fn get_message_size(self) -> usize {
+        match self {
+            Self::Raw | Self::Ascii => 1,
+            Self::Evdev => INPUT_EVENT_SIZE,
+        }
+    }
+
+    /// Returns the pressed key of the message, if any.
+    fn decode(self, message: &[u8]) -> Option<u8> {
+        match self {
+            Self::Raw => Some(message[0]).filter(|key| *key <= 0xF),
+            Self::Ascii => (message[0] as char).to_digit(16).map(|key| key as u8),
+            Self::Evdev => decode_input_event(message),
+        }
+    }
+}
+
+fn decode_input_event(event: &[u8]) -> Option<u8> {
+    let time_size = 2 * mem::size_of::<usize>();
+    let r#type = u16::from_ne_bytes([event[time_size], event[time_size + 1]]);
+    let code = u16::from_ne_bytes([event[time_size + 2], event[time_size + 3]]);
+    let value = i32::from_ne_bytes([
+        event[time_size + 4],
+        event[time_size + 5],
+        event[time_size + 6],
+        event[time_size + 7],
+    ]);
+
+    // Only presses count, not releases (0) or repeats (2).
+    if r#type != EV_KEY || value != 1 {
+        return None;
+    }
+
+    // The key codes as defined in `linux/input-event-codes.h`.
+    match code {
+        11 => Some(0x0),
+        2..=10 => Some(code as u8 - 1),
+        30 => Some(0xA),
+        48 => Some(0xB),
+        46 => Some(0xC),
+        32 => Some(0xD),
+        18 => Some(0xE),
+        33 => Some(0xF),
+        _ => None,
+    }
+}
+
+/// A keypad connected to the machine, read in the background.
+pub struct Keypad {
+    keys: Receiver<io::Result<u8>>,
+}
+
+impl Keypad {
+    /// Connects to the keypad given like `serial:/dev/ttyUSB0`, `ascii:/dev/ttyACM0` or
+    /// `evdev:/dev/input/event0`.
+    pub fn connect(target: &str) -> Result<Self, Error> {
+        let (protocol, path) = if let Some(path) = target.strip_prefix("serial:") {
+            (Protocol::Raw, path)
+        } else if let Some(path) = target.strip_prefix("ascii:") {
+            (Protocol::Ascii, path)
+        } else if let Some(path) = target.strip_prefix("evdev:") {
+            (Protocol::Evdev, path)
+        } else {
+            return Err(format!(
+                "Invalid keypad `{}`. Expected `serial:<path>`, `ascii:<path>` or `evdev:<path>`.",
+                target
+            )
+            .into());
+        };
+
+        let mut file = File::open(path).map_err(|err| format!("Failed to open keypad: {}", err))?;
+
+        // Reading blocks, so it is done on another thread.
+        let (sender, keys) = mpsc::channel();
+        thread::spawn(move || {
+            let mut message = vec![0; protocol.get_message_size()];
+            loop {
+                match file.read_exact(&mut message) {
+                    Ok(()) => {
+                        if let Some(key) = protocol.decode(&message) {
+                            if sender.send(Ok(key)).is_err() {
+                                break;
+                            }
+                        }
+                    }
+                    Err(err) => {
+                        let _ = sender.send(Err(err));
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(Self { keys })
+    }
+
+    /// Returns the next pressed key, if any, without blocking.
+    pub fn poll(&mut self) -> Result<Option<u8>, Error> {
+        match self.keys.try_recv() {
+            Ok(Ok(key)) => Ok(Some(key)),
+            Ok(Err(err)) => Err(format!("Failed to read from keypad: {}", err).into()),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err("The keypad was disconnected.".into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_input_event(r#type: u16, code: u16, value: i32) -> Vec<u8> {
+        let mut event = vec![0; 2 * mem::size_of::<usize>()];
+        event.extend_from_slice(&r#type.to_ne_bytes());
+        event.extend_from_slice(&code.to_ne_bytes());
+        event.extend_from_slice(&value.to_ne_bytes());
+        event
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(Protocol::Raw.decode(&[0xA]), Some(0xA));
+        assert_eq!(Protocol::Raw.decode(&[0x10]), None);
+        assert_eq!(Protocol::Ascii.decode(b"f"), Some(0xF));
+        assert_eq!(Protocol::Ascii.decode(b"\n"), None);
+    }
+
+    #[test]
+    fn test_decode_input_event() {
+        assert_eq!(
+            Protocol::Evdev.decode(&get_input_event(EV_KEY, 11, 1)),
+            Some(0x0)
+        );
+        assert_eq!(
+            Protocol::Evdev.decode(&get_input_event(EV_KEY, 10, 1)),
+            Some(0x9)
+        );
+        assert_eq!(
+            Protocol::Evdev.decode(&get_input_event(EV_KEY, 46, 1)),
+            Some(0xC)
+        );
+        // Releases are ignored.
+        assert_eq!(
+            Protocol::Evdev.decode(&get_input_event(EV_KEY, 46, 0)),
+            None
+        );
+        // So are other events like synchronization.
+        assert_eq!(Protocol::Evdev.decode(&get_input_event(0x00, 0, 0)), None);
+    }
+}
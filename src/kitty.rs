@@ -0,0 +1,94 @@
+//! Encoding of images for the graphics protocol of the kitty terminal.
+//!
+//! An image is sent as escape sequences containing its pixels in base64, split into chunks. See
+//! <https://sw.kovidgoyal.net/kitty/graphics-protocol/>.
+
+/// The maximum length of the base64 data in one escape sequence, as given by the protocol.
+const CHUNK_SIZE: usize = 4096;
+
+/// Returns the escape sequences transmitting the image with the given RGB pixels and displaying it at the cursor,
+/// stretched to the given number of columns and rows.
+///
+/// Sending an image with the same ID again replaces it, so that the image can be updated every frame.
+pub fn encode_image(
+    id: u32,
+    pixels: &[u8],
+    width: usize,
+    height: usize,
+    columns: u16,
+    rows: u16,
+) -> String {
+    let data = encode_base64(pixels);
+    let chunks: Vec<&[u8]> = data.as_bytes().chunks(CHUNK_SIZE).collect();
+
+    let mut sequences = String::new();
+    for (index, chunk) in chunks.iter().enumerate() {
+        let more = (index + 1 < chunks.len()) as u8;
+        // The keys are only given in the first chunk.
+        let keys = if index == 0 {
+            // `C=1` keeps the cursor where it is and `q=2` suppresses the responses of the terminal.
+            format!(
+                "a=T,f=24,s={},v={},i={},c={},r={},C=1,q=2,m={}",
+                width, height, id, columns, rows, more
+            )
+        } else {
+            format!("m={}", more)
+        };
+        sequences.push_str("\x1b_G");
+        sequences.push_str(&keys);
+        sequences.push(';');
+        // The base64 alphabet is ASCII.
+        sequences.push_str(std::str::from_utf8(chunk).unwrap());
+        sequences.push_str("\x1b\\");
+    }
+    sequences
+}
+
+/// Encodes the bytes in standard base64 with padding.
+fn encode_base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut string = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = (chunk[0] as u32) << 16
+            | (*chunk.get(1).unwrap_or(&0) as u32) << 8
+            | *chunk.get(2).unwrap_or(&0) as u32;
+        for index in 0..4 {
+            if index <= chunk.len() {
+                string.push(ALPHABET[(group >> (18 - index * 6)) as usize & 0x3F] as char);
+            } else {
+                string.push('=');
+            }
+        }
+    }
+    string
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_base64() {
+        assert_eq!(encode_base64(b""), "");
+        assert_eq!(encode_base64(b"f"), "Zg==");
+        assert_eq!(encode_base64(b"fo"), "Zm8=");
+        assert_eq!(encode_base64(b"foo"), "Zm9v");
+        assert_eq!(encode_base64(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn test_encode_image() {
+        let sequences = encode_image(1, &[0xFF; 3], 1, 1, 2, 1);
+        assert_eq!(
+            sequences,
+            "\x1b_Ga=T,f=24,s=1,v=1,i=1,c=2,r=1,C=1,q=2,m=0;////\x1b\\"
+        );
+
+        // Large images are split into chunks, with only the last one ending the image.
+        let sequences = encode_image(1, &[0; 3 * 2048], 64, 32, 128, 32);
+        assert_eq!(sequences.matches("\x1b_G").count(), 2);
+        assert_eq!(sequences.matches("m=1").count(), 1);
+        assert!(sequences.contains("\x1b_Gm=0;"));
+    }
+}
@@ -0,0 +1,135 @@
+//! Output of the display to hardware like LED matrices.
+
+use chip8_core::{display::SCREEN_SIZE, util::Bits, Error};
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    net::UdpSocket,
+};
+
+/// The bytes every frame sent over a serial connection starts with, so that the receiver can synchronize.
+const SERIAL_SYNC: [u8; 2] = [0xC8, 0x08];
+
+/// How frames are sent to the hardware.
+enum Output {
+    /// A generic framing for serial connections: the sync bytes, the 256-byte bitmap and an XOR checksum of the bitmap.
+    Serial(File),
+    /// The protocol of the Flaschen-Taschen server, which is commonly used with the rpi-rgb-led-matrix library:
+    /// every frame is sent as a PPM image in a UDP packet.
+    FlaschenTaschen(UdpSocket),
+}
+
+/// A hardware display the frames are sent to.
+pub struct LedMatrix {
+    output: Output,
+    /// The last frame sent, to avoid sending the same frame again.
+    last_frame: Vec<u8>,
+}
+
+impl LedMatrix {
+    /// Connects to the display given like `serial:/dev/ttyUSB0` or `ft:192.168.0.10:1337`.
+    ///
+    /// Settings like the baud rate of a serial device need to be configured beforehand, e.g. with `stty`.
+    pub fn connect(target: &str) -> Result<Self, Error> {
+        let output = if let Some(path) = target.strip_prefix("serial:") {
+            let file = OpenOptions::new()
+                .write(true)
+                .open(path)
+                .map_err(|err| format!("Failed to open serial device: {}", err))?;
+            Output::Serial(file)
+        } else if let Some(address) = target.strip_prefix("ft:") {
+            let socket = UdpSocket::bind("0.0.0.0:0")
+                .and_then(|socket| socket.connect(address).map(|()| socket))
+                .map_err(|err| format!("Failed to connect to LED matrix: {}", err))?;
+            Output::FlaschenTaschen(socket)
+        } else {
+            return Err(format!(
+                "Invalid LED matrix `{}`. Expected `serial:<path>` or `ft:<host>:<port>`.",
+                target
+            )
+            .into());
+        };
+
+        Ok(Self {
+            output,
+            last_frame: Vec::new(),
+        })
+    }
+
+    /// Sends the frame, given in the format of [`chip8_core::display::Display::get_frame`], unless it has not changed.
+    pub fn send(&mut self, frame: &[u8]) -> io::Result<()> {
+        if frame == self.last_frame.as_slice() {
+            return Ok(());
+        }
+
+        match &mut self.output {
+            Output::Serial(file) => {
+                file.write_all(&encode_serial(frame))?;
+                file.flush()?;
+            }
+            Output::FlaschenTaschen(socket) => {
+                socket.send(&encode_ppm(frame))?;
+            }
+        }
+
+        self.last_frame = frame.to_vec();
+
+        Ok(())
+    }
+}
+
+fn encode_serial(frame: &[u8]) -> Vec<u8> {
+    let checksum = frame.iter().fold(0, |checksum, byte| checksum ^ byte);
+
+    let mut bytes = SERIAL_SYNC.to_vec();
+    bytes.extend_from_slice(frame);
+    bytes.push(checksum);
+    bytes
+}
+
+/// Encodes the frame as a binary PPM image with set pixels being white.
+fn encode_ppm(frame: &[u8]) -> Vec<u8> {
+    // The screen is higher in the hi-res mode.
+    let height = frame.len() * 8 / SCREEN_SIZE.width as usize;
+    let mut bytes = format!("P6\n{} {}\n255\n", SCREEN_SIZE.width, height).into_bytes();
+    for bit in frame.iter().flat_map(|byte| Bits::new(*byte)) {
+        let value = if bit { 0xFF } else { 0x00 };
+        bytes.extend_from_slice(&[value; 3]);
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_serial() {
+        let mut frame = vec![0; 256];
+        frame[0] = 0b1000_0001;
+        frame[255] = 0b0000_0011;
+
+        let bytes = encode_serial(&frame);
+
+        assert_eq!(bytes.len(), 2 + 256 + 1);
+        assert_eq!(bytes[..2], SERIAL_SYNC);
+        assert_eq!(bytes[2], 0b1000_0001);
+        assert_eq!(bytes[258], 0b1000_0010);
+    }
+
+    #[test]
+    fn test_encode_ppm() {
+        let mut frame = vec![0; 256];
+        frame[0] = 0b1000_0000;
+
+        let bytes = encode_ppm(&frame);
+        let header = b"P6\n64 32\n255\n";
+
+        assert_eq!(bytes.len(), header.len() + 64 * 32 * 3);
+        assert_eq!(&bytes[..header.len()], header);
+        assert_eq!(
+            bytes[header.len()..header.len() + 6],
+            [0xFF, 0xFF, 0xFF, 0, 0, 0]
+        );
+    }
+}
@@ -0,0 +1,108 @@
+//! A link to another instance of the interpreter over a local socket, exchanging the bytes of the mailbox in memory.
+
+use chip8_core::{link::Mailbox, Error};
+use std::{
+    collections::VecDeque,
+    fs,
+    io::{self, Read, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+};
+
+/// A connection to the other interpreter.
+pub struct Link {
+    stream: UnixStream,
+    received: Receiver<io::Result<u8>>,
+    /// Bytes received that the program has not taken yet.
+    pending: VecDeque<u8>,
+    mailbox: Mailbox,
+}
+
+impl Link {
+    /// Connects to the other interpreter given like `listen:<path>` or `connect:<path>`, where one side listens on
+    /// the socket at the path and the other connects to it.
+    ///
+    /// Listening blocks until the other side has connected.
+    pub fn connect(target: &str, mailbox: Mailbox) -> Result<Self, Error> {
+        let stream = if let Some(path) = target.strip_prefix("listen:") {
+            // A socket left behind by a previous run would make binding fail.
+            let _ = fs::remove_file(path);
+            let listener =
+                UnixListener::bind(path).map_err(|err| format!("Failed to listen: {}", err))?;
+            let (stream, _) = listener
+                .accept()
+                .map_err(|err| format!("Failed to accept link: {}", err))?;
+            stream
+        } else if let Some(path) = target.strip_prefix("connect:") {
+            UnixStream::connect(path).map_err(|err| format!("Failed to connect link: {}", err))?
+        } else {
+            return Err(format!(
+                "Invalid link `{}`. Expected `listen:<path>` or `connect:<path>`.",
+                target
+            )
+            .into());
+        };
+
+        let mut reader = stream
+            .try_clone()
+            .map_err(|err| format!("Failed to connect link: {}", err))?;
+
+        // Reading blocks, so it is done on another thread.
+        let (sender, received) = mpsc::channel();
+        thread::spawn(move || {
+            let mut byte = [0];
+            loop {
+                match reader.read_exact(&mut byte) {
+                    Ok(()) => {
+                        if sender.send(Ok(byte[0])).is_err() {
+                            break;
+                        }
+                    }
+                    // The other side disconnected, which is noticed by the channel disconnecting.
+                    Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => break,
+                    Err(err) => {
+                        let _ = sender.send(Err(err));
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            stream,
+            received,
+            pending: VecDeque::new(),
+            mailbox,
+        })
+    }
+
+    /// Sends the byte the program put in the mailbox, if any, and delivers the next received byte if the program is
+    /// ready for it.
+    pub fn exchange(&mut self, memory: &mut [u8]) -> Result<(), Error> {
+        if let Some(byte) = self.mailbox.take_outgoing(memory) {
+            self.stream
+                .write_all(&[byte])
+                .map_err(|err| format!("Failed to send over link: {}", err))?;
+        }
+
+        loop {
+            match self.received.try_recv() {
+                Ok(Ok(byte)) => self.pending.push_back(byte),
+                Ok(Err(err)) => return Err(format!("Failed to receive over link: {}", err).into()),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    return Err("The other side of the link disconnected.".into())
+                }
+            }
+        }
+
+        if let Some(byte) = self.pending.front() {
+            if self.mailbox.put_incoming(memory, *byte) {
+                self.pending.pop_front();
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,12 +1,84 @@
-mod display;
-mod interpreter;
-mod util;
-
-use interpreter::Interpreter;
-use std::{borrow::Cow, env, fs, io, process};
-use terminal::Terminal;
-
-type Error = Cow<'static, str>;
+mod alert;
+mod bench;
+mod buzzer;
+mod clock;
+mod compare;
+mod coverage;
+mod crash;
+mod damage;
+mod demo;
+mod flags;
+mod frontend;
+mod gif;
+mod hangup;
+mod headless;
+mod interrupt;
+mod keypad;
+mod kitty;
+mod led;
+mod link;
+mod menu;
+mod messages;
+mod options;
+mod picker;
+#[cfg(any(feature = "sdl2", feature = "window"))]
+mod player;
+mod profile;
+mod recording;
+mod render;
+mod renderer;
+mod replay;
+mod rewind;
+mod sandbox;
+mod screenshot;
+#[cfg(feature = "sdl2")]
+mod sdl;
+mod selftest;
+mod sidecar;
+mod stats;
+mod suspend;
+mod terminal;
+mod testsuite;
+mod trace;
+mod watch;
+mod wav;
+#[cfg(feature = "window")]
+mod window;
+
+use buzzer::Buzzer;
+use chip8_core::{
+    analysis, assembler, disassembler, display, interpreter, layout, octo, rom,
+    savestate::{self, State},
+    symbols::Symbols,
+    util, Error,
+};
+use coverage::Coverage;
+use crash::CrashLog;
+use damage::DamageLog;
+use frontend::{Exit, Frontend, Input, Program};
+use hangup::Hangup;
+use interrupt::Interrupt;
+use keypad::Keypad;
+use led::LedMatrix;
+use link::Link;
+use messages::Message;
+use options::Options;
+use profile::Profiler;
+use recording::GifRecording;
+use replay::{Recorder, Replay};
+use stats::Stats;
+use std::{
+    env,
+    ffi::OsStr,
+    fs::{self, File},
+    io::{self, IsTerminal, Read, Write},
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    process,
+};
+use suspend::Suspension;
+use terminal::{Event, Key, Size, Terminal};
+use watch::Watcher;
 
 fn main() {
     let exit_code = match run() {
@@ -28,35 +100,208 @@ fn get_args() -> env::ArgsOs {
     args
 }
 
-fn get_binary() -> Result<Vec<u8>, Error> {
-    let mut args = get_args();
-
-    if let Some(arg) = args.next() {
-        let path = match arg.as_os_str().to_str() {
-            Some(path) => path,
-            None => return Err("Given argument is not valid UTF-8.".into()),
-        };
-        let binary = fs::read(path);
-
-        match binary {
-            Ok(binary) => Ok(binary),
-            Err(err) => {
-                use io::ErrorKind::*;
-
-                let err = match err.kind() {
-                    PermissionDenied => "No permission to read binary.",
-                    NotFound => "Binary was not found.",
-                    _ => "Failed to read binary.",
-                };
-
-                Err(err.into())
+/// Reads the program at the path, or from the standard input if the path is `-`. Sources ending with `.s` are assembled
+/// first and Octo sources ending with `.8o` are compiled first, like demos.
+pub fn read_binary(path: &OsStr) -> Result<Vec<u8>, Error> {
+    if let Some(source) = demo::get_source(path) {
+        return octo::compile(source);
+    }
+    if path == options::STDIN_PATH {
+        let mut binary = Vec::new();
+        io::stdin()
+            .read_to_end(&mut binary)
+            .map_err(|err| format!("Failed to read the binary from the standard input: {}", err))?;
+        rom::check(&binary)?;
+        return Ok(binary);
+    }
+    let path = match path.to_str() {
+        Some(path) => path,
+        None => return Err("Given argument is not valid UTF-8.".into()),
+    };
+    let binary = fs::read(path);
+
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str());
+    match binary {
+        Ok(binary) if matches!(extension, Some("s" | "8o")) => {
+            let source = String::from_utf8(binary).map_err(|_| "The source is not valid UTF-8.")?;
+            if extension == Some("8o") {
+                return octo::compile(&source);
             }
+            let instructions = assembler::assemble(&source)?;
+            Ok(instructions
+                .iter()
+                .flat_map(|instruction| instruction.to_be_bytes())
+                .collect())
+        }
+        Ok(binary) => {
+            rom::check(&binary)?;
+            Ok(binary)
         }
+        Err(err) => {
+            use io::ErrorKind::*;
+
+            let err = match err.kind() {
+                PermissionDenied => "No permission to read binary.",
+                NotFound => "Binary was not found.",
+                _ => "Failed to read binary.",
+            };
+
+            Err(err.into())
+        }
+    }
+}
+
+pub fn get_binary(args: &mut env::ArgsOs) -> Result<Vec<u8>, Error> {
+    if let Some(arg) = args.next() {
+        read_binary(&arg)
     } else {
         Err("No path to the binary given.".into())
     }
 }
 
+/// Prints the disassembly of the binary given in the arguments.
+///
+/// The output format can be chosen with `--format text` (the default) or `--format json`.
+fn disassemble(mut args: env::ArgsOs) -> Result<(), Error> {
+    let binary = get_binary(&mut args)?;
+
+    let mut json = false;
+    let mut symbols = Symbols::default();
+    let mut load_address = interpreter::START_POINT;
+    while let Some(arg) = args.next() {
+        if arg == "--load-address" {
+            load_address =
+                options::parse_load_address(&options::get_value(&mut args, "--load-address")?)?;
+        } else if arg == "--format" {
+            json = match args.next() {
+                Some(format) if format == "text" => false,
+                Some(format) if format == "json" => true,
+                _ => return Err("Expected `text` or `json` after `--format`.".into()),
+            };
+        } else if arg == "--symbols" {
+            let path = options::get_value(&mut args, "--symbols")?;
+            symbols.load(path.as_ref())?;
+        } else {
+            return Err(format!("Unknown argument: {}", arg.to_string_lossy()).into());
+        }
+    }
+
+    let mut instructions = disassembler::disassemble(&binary, load_address);
+    disassembler::apply_symbols(&mut instructions, &symbols);
+    let output = if json {
+        disassembler::to_json(&instructions)
+    } else {
+        disassembler::to_text(&instructions)
+    };
+    print!("{}", output);
+
+    Ok(())
+}
+
+/// Prints the labels of the Octo source given in the arguments as a symbol file.
+fn print_symbols(mut args: env::ArgsOs) -> Result<(), Error> {
+    let path = args.next().ok_or("No path to the source given.")?;
+    let load_address = get_load_address(args)?;
+
+    let source =
+        fs::read_to_string(&path).map_err(|err| format!("Failed to read source: {}", err))?;
+    let (_, symbols) = octo::compile_with_symbols(&source, load_address)?;
+    print!("{}", symbols.to_text());
+
+    Ok(())
+}
+
+/// Prints the hex dump of the binary given in the arguments.
+fn hexdump(mut args: env::ArgsOs) -> Result<(), Error> {
+    let binary = get_binary(&mut args)?;
+    let load_address = get_load_address(args)?;
+
+    print!("{}", disassembler::to_hexdump(&binary, load_address));
+
+    Ok(())
+}
+
+/// Parses the rest of the arguments, which can only be `--load-address <address>`.
+fn get_load_address(mut args: env::ArgsOs) -> Result<u16, Error> {
+    let mut load_address = interpreter::START_POINT;
+    while let Some(arg) = args.next() {
+        if arg == "--load-address" {
+            load_address =
+                options::parse_load_address(&options::get_value(&mut args, "--load-address")?)?;
+        } else {
+            return Err(format!("Unknown argument: {}", arg.to_string_lossy()).into());
+        }
+    }
+    Ok(load_address)
+}
+
+/// Prints how the program given in the arguments uses each register.
+fn analyze_registers(mut args: env::ArgsOs) -> Result<(), Error> {
+    let binary = get_binary(&mut args)?;
+    if let Some(arg) = args.next() {
+        return Err(format!("Unknown argument: {}", arg.to_string_lossy()).into());
+    }
+
+    print!(
+        "{}",
+        analysis::to_text(&analysis::analyze_registers(&binary))
+    );
+
+    Ok(())
+}
+
+/// Checks the program given in the arguments and prints which platform it looks like it was written for.
+fn check(mut args: env::ArgsOs) -> Result<(), Error> {
+    let binary = get_binary(&mut args)?;
+    if let Some(arg) = args.next() {
+        return Err(format!("Unknown argument: {}", arg.to_string_lossy()).into());
+    }
+
+    let detection = rom::detect_platform(&binary);
+    let name = detection.platform.get_name();
+    match detection.evidence {
+        Some((address, instruction)) => println!(
+            "This looks like a {} program, as it contains {:04X} at {:#05X}.",
+            name, instruction, address
+        ),
+        None if detection.platform == rom::Platform::Chip8 => {
+            println!("This looks like a {} program.", name)
+        }
+        None => println!(
+            "This looks like a {} program, as it is {} bytes.",
+            name,
+            binary.len()
+        ),
+    }
+
+    Ok(())
+}
+
+/// Prints the description of the savestate and replay formats: a JSON Schema with `--schema`, or the layout of the
+/// bytes as JSON with `--json`.
+fn print_format(mut args: env::ArgsOs) -> Result<(), Error> {
+    let schema = match args.next() {
+        Some(arg) if arg == "--schema" => true,
+        Some(arg) if arg == "--json" => false,
+        Some(arg) => return Err(format!("Unknown argument: {}", arg.to_string_lossy()).into()),
+        None => return Err("Expected `--schema` or `--json`.".into()),
+    };
+    if let Some(arg) = args.next() {
+        return Err(format!("Unknown argument: {}", arg.to_string_lossy()).into());
+    }
+
+    let layouts = [savestate::LAYOUT, replay::LAYOUT];
+    if schema {
+        print!("{}", layout::to_json_schema(&layouts));
+    } else {
+        print!("{}", layout::to_json(&layouts));
+    }
+
+    Ok(())
+}
+
 // fn get_binary() -> Result<Vec<u8>, &'static str> {
 //     let file = get_fvile()?;
 
@@ -69,53 +314,335 @@ fn get_binary() -> Result<Vec<u8>, Error> {
 // }
 
 fn run() -> Result<(), Error> {
-    let binary = get_binary()?;
+    let mut args = get_args();
 
-    let stdout = io::stdout();
+    messages::initialize()?;
+    // Only loaded once needed, so that the other commands work even if the statistics cannot be read.
+    let mut stats = None;
+
+    let options = match args.next() {
+        Some(arg) if arg == "disasm" => return disassemble(args),
+        Some(arg) if arg == "symbols" => return print_symbols(args),
+        Some(arg) if arg == "hexdump" => return hexdump(args),
+        Some(arg) if arg == "registers" => return analyze_registers(args),
+        Some(arg) if arg == "check" => return check(args),
+        Some(arg) if arg == "bench" => return bench::run(args),
+        Some(arg) if arg == "selftest" => return selftest::run_all(),
+        Some(arg) if arg == "test" => return testsuite::run(args),
+        Some(arg) if arg == "render" => return render::run(args),
+        Some(arg) if arg == "sandbox" => return sandbox::run(args),
+        Some(arg) if arg == "compare" => return compare::run(args),
+        Some(arg) if arg == "format" => return print_format(args),
+        Some(arg) if arg == "--demo" => {
+            let name = args.next().ok_or("`--demo` needs the name of a demo.")?;
+            Options::parse(demo::get_path(&name)?, args)?
+        }
+        Some(arg) => Options::parse(arg, args)?,
+        // Something like an assembler is piping the program in.
+        None if !io::stdin().is_terminal() => Options::parse(options::STDIN_PATH.into(), args)?,
+        None => {
+            stats = load_stats();
+            match pick_program(stats.as_ref())? {
+                Some(path) => Options::parse(path.into(), args)?,
+                None => return Err("No path to the binary given.".into()),
+            }
+        }
+    };
+    let binary = read_binary(&options.path)?;
+    if binary.len() > rom::MAX_SIZE && !options.quirks.xo_chip {
+        return Err(format!(
+            "The program is {} bytes, which is too big for CHIP-8. If it is for XO-CHIP, try `--quirk xo-chip`.",
+            binary.len()
+        )
+        .into());
+    }
+
+    if let Some(cycles) = options.headless_cycles {
+        return headless::run(&create_program(binary, &options), &options, cycles);
+    }
+    if options.sdl {
+        return run_in_sdl(binary, &options);
+    }
+    if options.window {
+        return run_in_window(binary, &options);
+    }
 
-    let mut terminal = match Terminal::new(stdout.lock()) {
-        Ok(mut terminal) => {
-            terminal.initialize(Some("CHIP-8"), false);
+    let mut terminal = match terminal::open() {
+        Some(mut terminal) => {
+            terminal.initialize("CHIP-8");
             terminal.flush();
             terminal
         }
-        Err(_) => {
+        None => {
             return Err("This is not a terminal.".into());
         }
     };
+    let hangup = Hangup::register()?;
+    let interrupt = Interrupt::register()?;
+    terminal::restore_on_panic({
+        let hangup = hangup.clone();
+        move || !hangup.is_detected()
+    });
+
+    let screen_size = if interpreter::is_hires(&binary) {
+        display::HIRES_SCREEN_SIZE
+    } else {
+        display::SCREEN_SIZE
+    };
+    let size = options.render_mode.get_required_size(screen_size);
+    await_fitting_window_width(&mut *terminal, size);
+    await_fitting_window_height(&mut *terminal, size);
+
+    let rom_hash = util::hash(&binary);
+    let replay = options.replay.as_deref().map(Replay::load).transpose()?;
+    if let Some(replay) = &replay {
+        if replay.rom_hash != rom_hash {
+            return Err("The replay was recorded with a different program.".into());
+        }
+    }
+    let seed = match (&replay, options.seed) {
+        (Some(_), Some(_)) => {
+            return Err("A replay has its own seed, so `--seed` cannot be given with it.".into())
+        }
+        (Some(replay), None) => replay.seed,
+        (None, seed) => seed.unwrap_or_else(rand::random),
+    };
+    let input = if let Some(replay) = replay {
+        Input::Replay(replay)
+    } else if let Some(path) = &options.record {
+        Input::Recording(Recorder::create(path, seed, rom_hash)?)
+    } else {
+        Input::Terminal
+    };
+
+    let program = Program {
+        path: options.path.clone(),
+        binary,
+        seed,
+        patches: options.patches.clone(),
+    };
+    let mut frontend = Frontend::new(program, &options, input)?;
 
-    await_fitting_window_width(&mut terminal);
-    await_fitting_window_height(&mut terminal);
+    if let Some(path) = &options.load_state {
+        frontend.load_state(&State::load(path)?)?;
+    }
 
-    let mut interpreter = Interpreter::new(binary)?;
+    if let Some(path) = &options.damage_log {
+        frontend.set_damage_log(DamageLog::create(path)?);
+    }
 
-    let result = interpreter.run(&mut terminal);
+    if let Some(path) = &options.frame_pipe {
+        // Opening a named pipe blocks until the other end is opened too.
+        let mut pipe =
+            File::create(path).map_err(|err| format!("Failed to open frame pipe: {}", err))?;
+        frontend.add_frame_hook(Box::new(move |frame| pipe.write_all(frame)));
+    }
 
-    terminal.reset_cursor();
-    terminal.write("Program ended. Press any key to continue.");
-    terminal.flush();
+    if let Some(path) = &options.record_gif {
+        frontend.set_gif_recording(GifRecording::create(path, screen_size)?);
+    }
+
+    if let Some(target) = &options.led_matrix {
+        let mut led_matrix = LedMatrix::connect(target)?;
+        frontend.add_frame_hook(Box::new(move |frame| led_matrix.send(frame)));
+    }
+
+    if let Some(path) = &options.audio {
+        let mut buzzer = Buzzer::create(path, options.duty_cycle)?;
+        if let Some(path) = &options.beep_sound {
+            buzzer.set_sample(wav::load(path)?);
+        }
+        frontend.set_buzzer(buzzer);
+    }
+
+    if let Some(target) = &options.keypad {
+        frontend.set_keypad(Keypad::connect(target)?);
+    }
+
+    if let Some(target) = &options.link {
+        frontend.set_link(Link::connect(target, options.link_mailbox)?);
+    }
+
+    if let Some(path) = &options.trace {
+        frontend.set_tracer(trace::Tracer::create(path, options.annotations.clone())?);
+    }
+
+    if let Some(path) = &options.watch_log {
+        let file =
+            File::create(path).map_err(|err| format!("Failed to create the watch log: {}", err))?;
+        frontend.set_watch_log(file);
+    }
+
+    if let Some(path) = &options.coverage {
+        frontend.set_coverage(Coverage::create(path)?);
+    }
+
+    if options.crash_dump {
+        frontend.set_crash_log(CrashLog::default());
+    }
+
+    if options.profile {
+        frontend.set_profiler(Profiler::new());
+    }
+
+    if options.watch {
+        frontend.set_watcher(Watcher::new(PathBuf::from(&options.path)));
+    }
+
+    if let Some(stats) = stats.or_else(load_stats) {
+        frontend.set_stats(stats);
+    }
+
+    frontend.set_hangup(hangup);
+    frontend.set_interrupt(interrupt);
+    frontend.set_suspension(Suspension::register()?);
+
+    // Writing to a lost terminal may panic, in which case the state is still saved.
+    let result = panic::catch_unwind(AssertUnwindSafe(|| frontend.run(&mut *terminal)))
+        .unwrap_or_else(|payload| {
+            if frontend.is_terminal_lost() {
+                Ok(Exit::TerminalLost)
+            } else {
+                panic::resume_unwind(payload)
+            }
+        });
+    if let Ok(Exit::TerminalLost) = result {
+        let _ = frontend.save_stats(false);
+        let _ = frontend.finish_gif_recording();
+        let _ = frontend.save_coverage();
+        // Only the standard error may be left to report to, e.g. if it is redirected to a file.
+        match frontend.save_auto_state() {
+            Ok(path) => eprintln!(
+                "The terminal was lost. The state was saved to {}.",
+                path.display()
+            ),
+            Err(err) => eprintln!("The terminal was lost. {}", err),
+        }
+        process::exit(hangup::EXIT_CODE);
+    }
+
+    // The error is reported once the terminal is restored, along with where the state was dumped to.
+    let result = match result {
+        Err(err) => Err(match frontend.save_crash_dump(&err) {
+            Some(Ok(path)) => {
+                format!("{}\nThe state was dumped to {}.", err, path.display()).into()
+            }
+            Some(Err(dump_err)) => format!("{}\n{}", err, dump_err).into(),
+            None => err,
+        }),
+        result => result,
+    };
 
-    crate::read_event(&mut terminal);
+    let screenshot = frontend.save_exit_screenshot();
+    let memory_dump = frontend.save_exit_memory_dump();
+    let gif_recording = frontend.finish_gif_recording();
+    let coverage = frontend.save_coverage();
+    let saved = frontend.save_stats(result == Ok(Exit::Ended));
+
+    // When interrupted, nobody might be there to press a key.
+    let interrupted = result == Ok(Exit::Interrupted);
+    if !interrupted {
+        terminal.reset_cursor();
+        terminal.write(messages::get(Message::ProgramEnded));
+        terminal.flush();
+
+        // Not `read_event`, whose exit on Esc would skip the profile and the errors below.
+        terminal.read_event();
+    }
 
     terminal.deinitialize();
     terminal.flush();
+    terminal::panic_normally();
 
-    result
+    if let Some(profiler) = frontend.take_profiler() {
+        print!("{}", profiler.to_text());
+    }
+
+    result?;
+    screenshot?;
+    memory_dump?;
+    gif_recording?;
+    coverage?;
+    saved?;
+    if interrupted {
+        process::exit(interrupt::EXIT_CODE);
+    }
+    Ok(())
 }
 
-fn get_size_message(size: &str) -> String {
-    format!("Please increase your window {}", size)
+/// Returns the program to run outside of the terminal, where there are no replays that could give the seed.
+fn create_program(binary: Vec<u8>, options: &Options) -> Program {
+    Program {
+        path: options.path.clone(),
+        binary,
+        seed: options.seed.unwrap_or_else(rand::random),
+        patches: options.patches.clone(),
+    }
+}
+
+/// Runs the program in an SDL2 window instead of the terminal.
+#[cfg(feature = "sdl2")]
+fn run_in_sdl(binary: Vec<u8>, options: &Options) -> Result<(), Error> {
+    sdl::run(&create_program(binary, options), options)
 }
 
-use terminal::event::{Event, Key};
+#[cfg(not(feature = "sdl2"))]
+fn run_in_sdl(_binary: Vec<u8>, _options: &Options) -> Result<(), Error> {
+    Err("This build has no SDL2 frontend. Build it with `--features sdl2`.".into())
+}
+
+/// Runs the program in a plain native window instead of the terminal.
+#[cfg(feature = "window")]
+fn run_in_window(binary: Vec<u8>, options: &Options) -> Result<(), Error> {
+    window::run(&create_program(binary, options), options)
+}
+
+#[cfg(not(feature = "window"))]
+fn run_in_window(_binary: Vec<u8>, _options: &Options) -> Result<(), Error> {
+    Err("This build has no window frontend. Build it with `--features window`.".into())
+}
+
+/// Loads the statistics of the programs played, starting over if they cannot be read.
+///
+/// Returns `None` if there is nowhere to store them.
+fn load_stats() -> Option<Stats> {
+    let path = Stats::get_default_path()?;
+    match Stats::load(&path) {
+        Ok(stats) => Some(stats),
+        Err(err) => {
+            eprintln!("{}\nStarting with empty statistics.", err);
+            Some(Stats::new(path))
+        }
+    }
+}
+
+/// Lets the user pick the program to run in the terminal.
+///
+/// Returns `None` if there is no terminal or nothing was picked.
+fn pick_program(stats: Option<&Stats>) -> Result<Option<PathBuf>, Error> {
+    let mut terminal = match terminal::open() {
+        Some(terminal) => terminal,
+        None => return Ok(None),
+    };
+    terminal.initialize("CHIP-8");
+    terminal::restore_on_panic(|| true);
+
+    let path = picker::pick(&mut *terminal, Path::new("."), stats);
+
+    terminal.deinitialize();
+    terminal.flush();
+    terminal::panic_normally();
+
+    path
+}
 
-pub fn exit(terminal: &mut Terminal) -> ! {
+pub fn exit(terminal: &mut dyn Terminal) -> ! {
     terminal.deinitialize();
     terminal.flush();
     process::exit(0);
 }
 
-pub fn read_event(terminal: &mut Terminal) -> Option<Event> {
+pub fn read_event(terminal: &mut dyn Terminal) -> Option<Event> {
     let event = terminal.read_event();
     if let Some(Event::Key(Key::Esc)) = event {
         exit(terminal)
@@ -124,7 +651,7 @@ pub fn read_event(terminal: &mut Terminal) -> Option<Event> {
     }
 }
 
-fn await_window_resize(terminal: &mut Terminal) {
+fn await_window_resize(terminal: &mut dyn Terminal) {
     loop {
         let event = read_event(terminal);
         if let Some(Event::Resize) = event {
@@ -133,23 +660,23 @@ fn await_window_resize(terminal: &mut Terminal) {
     }
 }
 
-fn window_size_alert(terminal: &mut Terminal, size: &str) {
+fn window_size_alert(terminal: &mut dyn Terminal, message: Message) {
     terminal.reset_cursor();
-    terminal.write(&get_size_message(size));
+    terminal.write(messages::get(message));
     terminal.flush();
     await_window_resize(terminal);
 }
 
-pub fn await_fitting_window_width(terminal: &mut Terminal) {
-    while terminal.size.width < display::SIZE.width * 2 {
-        window_size_alert(terminal, "width");
+pub fn await_fitting_window_width(terminal: &mut dyn Terminal, size: Size) {
+    while terminal.size().width < size.width {
+        window_size_alert(terminal, Message::WindowTooNarrow);
     }
     //  terminal.clear();
 }
 
-pub fn await_fitting_window_height(terminal: &mut Terminal) {
-    while terminal.size.height < display::SIZE.height {
-        window_size_alert(terminal, "height");
+pub fn await_fitting_window_height(terminal: &mut dyn Terminal, size: Size) {
+    while terminal.size().height < size.height {
+        window_size_alert(terminal, Message::WindowTooLow);
     }
     // terminal.clear();
 }
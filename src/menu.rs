@@ -0,0 +1,72 @@
+use crate::terminal::{Event, Key, Point, Terminal};
+
+/// Shows a list of items and lets the user pick one with the arrow keys and Enter.
+///
+/// Returns the index of the picked item or `None` if Esc was pressed.
+pub fn select<T: AsRef<str>>(
+    terminal: &mut dyn Terminal,
+    title: &str,
+    items: &[T],
+) -> Option<usize> {
+    let mut selected: usize = 0;
+
+    loop {
+        terminal.clear();
+        terminal.reset_cursor();
+        terminal.write(title);
+        // Only as many items as fit below the title are shown, scrolling along with the selected one.
+        let visible = (terminal.size().height as usize).saturating_sub(2).max(1);
+        let first = (selected + 1).saturating_sub(visible);
+        for (index, item) in items.iter().enumerate().skip(first).take(visible) {
+            terminal.set_cursor(Point {
+                x: 0,
+                y: (index - first) as u16 + 2,
+            });
+            terminal.write(if index == selected { "> " } else { "  " });
+            terminal.write(item.as_ref());
+        }
+        terminal.flush();
+
+        match terminal.read_event() {
+            Some(Event::Key(Key::Up)) => selected = selected.saturating_sub(1),
+            Some(Event::Key(Key::Down)) if selected + 1 < items.len() => selected += 1,
+            Some(Event::Key(Key::Enter)) if !items.is_empty() => return Some(selected),
+            Some(Event::Key(Key::Esc)) => return None,
+            _ => {}
+        }
+    }
+}
+
+/// Asks the user to type a line of text and returns it once Enter is pressed.
+///
+/// Returns `None` if Esc was pressed.
+pub fn prompt(terminal: &mut dyn Terminal, question: &str) -> Option<String> {
+    prompt_at(terminal, question, 0)
+}
+
+/// Like [`prompt`], but on the given line of the terminal.
+pub fn prompt_at(terminal: &mut dyn Terminal, question: &str, y: u16) -> Option<String> {
+    let mut input = String::new();
+
+    loop {
+        terminal.set_cursor(Point { x: 0, y });
+        for _ in 0..terminal.size().width {
+            terminal.write(" ");
+        }
+        terminal.set_cursor(Point { x: 0, y });
+        terminal.write(question);
+        terminal.write(" ");
+        terminal.write(&input);
+        terminal.flush();
+
+        match terminal.read_event() {
+            Some(Event::Key(Key::Char(char))) => input.push(char),
+            Some(Event::Key(Key::Backspace)) => {
+                input.pop();
+            }
+            Some(Event::Key(Key::Enter)) => return Some(input),
+            Some(Event::Key(Key::Esc)) => return None,
+            _ => {}
+        }
+    }
+}
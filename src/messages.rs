@@ -0,0 +1,178 @@
+//! The texts shown to the user while running, so that they can be translated without changing the code.
+//!
+//! English is built in. A translation is a file at `$XDG_DATA_HOME/chip8/messages/<locale>` or
+//! `~/.local/share/chip8/messages/<locale>` with a line like `menu-resume = Weiter` for every message to translate.
+//! The locale is taken from `LC_ALL`, `LC_MESSAGES` or `LANG`, like `de_DE.UTF-8`, which looks for `de_DE` and then
+//! `de`. Messages missing from the translation are shown in English.
+
+use chip8_core::Error;
+use std::{collections::HashMap, env, fmt::Display, fs, io, path::PathBuf, sync::OnceLock};
+
+static CATALOG: OnceLock<Catalog> = OnceLock::new();
+
+macro_rules! messages {
+    ($($message:ident: $key:literal => $text:literal,)*) => {
+        /// A text shown to the user.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Message {
+            $($message,)*
+        }
+
+        impl Message {
+            const ALL: &'static [Self] = &[$(Self::$message,)*];
+
+            /// Returns the key of the message used in translation files.
+            fn get_key(self) -> &'static str {
+                match self {
+                    $(Self::$message => $key,)*
+                }
+            }
+
+            /// Returns the built-in English text.
+            fn get_english(self) -> &'static str {
+                match self {
+                    $(Self::$message => $text,)*
+                }
+            }
+        }
+    };
+}
+
+// `{}` is replaced with a value given along with the message.
+messages! {
+    WindowTooNarrow: "window-too-narrow" => "Please increase your window width",
+    WindowTooLow: "window-too-low" => "Please increase your window height",
+    ProgramEnded: "program-ended" => "Program ended. Press any key to continue.",
+    PressAnyKey: "press-any-key" => "Press any key to continue.",
+    PressAnyKeyOrDebug: "press-any-key-or-debug" => "Press : to enter debugger commands or any other key to continue.",
+    Paused: "paused" => "Paused.",
+    DebuggerPrompt: "debugger-prompt" => "(debug)",
+    MenuTitle: "menu-title" => "Paused",
+    MenuResume: "menu-resume" => "Resume",
+    MenuReset: "menu-reset" => "Reset",
+    MenuLoadRom: "menu-load-rom" => "Load ROM",
+    MenuQuit: "menu-quit" => "Quit",
+    PickProgram: "pick-program" => "Pick a program to run from {}:",
+    StateSaved: "state-saved" => "State saved.",
+    ScreenshotSaved: "screenshot-saved" => "Screenshot saved to {}.",
+    StateLoaded: "state-loaded" => "State loaded.",
+    BookmarkName: "bookmark-name" => "Bookmark name:",
+    DefaultBookmarkName: "default-bookmark-name" => "Bookmark {}",
+    BookmarkAdded: "bookmark-added" => "Bookmark added.",
+    NoBookmarks: "no-bookmarks" => "There are no bookmarks yet.",
+    JumpToBookmark: "jump-to-bookmark" => "Jump to bookmark:",
+    CannotRewind: "cannot-rewind" => "Cannot rewind any further.",
+    ReplayFinished: "replay-finished" => "Replay finished.",
+    ReachedTarget: "reached-target" => "Reached the target.",
+}
+
+/// The texts of all messages in one language.
+#[derive(Debug, Default)]
+struct Catalog {
+    texts: HashMap<Message, String>,
+}
+
+/// Selects the translation of the locale of the environment, if there is one.
+///
+/// Until this is called, all messages are shown in English.
+pub fn initialize() -> Result<(), Error> {
+    let catalog = match get_locale().and_then(|locale| find_translation(&locale)) {
+        Some(path) => Catalog {
+            texts: parse(
+                &fs::read_to_string(&path)
+                    .map_err(|err| format!("Failed to read {}: {}", path.display(), err))?,
+            )
+            .map_err(|err| format!("{}: {}", path.display(), err))?,
+        },
+        None => Catalog::default(),
+    };
+    let _ = CATALOG.set(catalog);
+    Ok(())
+}
+
+/// Returns the text of the message in the selected language.
+pub fn get(message: Message) -> &'static str {
+    CATALOG
+        .get()
+        .and_then(|catalog| catalog.texts.get(&message))
+        .map_or_else(|| message.get_english(), String::as_str)
+}
+
+/// Returns the text of the message with `{}` replaced by the value.
+pub fn format(message: Message, value: impl Display) -> String {
+    get(message).replacen("{}", &value.to_string(), 1)
+}
+
+/// Returns the locale of the environment without the encoding, like `de_DE`, unless it is the default one.
+fn get_locale() -> Option<String> {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(env::var_os)
+        .find(|locale| !locale.is_empty())?;
+    let locale = locale.to_string_lossy();
+    let locale = locale.split(['.', '@']).next().unwrap_or_default();
+    (!matches!(locale, "" | "C" | "POSIX")).then(|| locale.to_string())
+}
+
+/// Returns the path of the translation for the locale, like `de_DE`, or else for its language, like `de`.
+fn find_translation(locale: &str) -> Option<PathBuf> {
+    let directory = env::var_os("XDG_DATA_HOME")
+        .filter(|directory| !directory.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share"))
+        })?
+        .join("chip8")
+        .join("messages");
+    let language = locale.split('_').next().unwrap_or_default();
+    [locale, language]
+        .iter()
+        .map(|name| directory.join(name))
+        .find(|path| match fs::metadata(path) {
+            Ok(metadata) => metadata.is_file(),
+            Err(err) => err.kind() != io::ErrorKind::NotFound,
+        })
+}
+
+/// Parses lines like `menu-resume = Weiter`. Empty lines and lines starting with `#` are skipped, and so are unknown
+/// keys, so that translations made for other versions still work.
+fn parse(text: &str) -> Result<HashMap<Message, String>, Error> {
+    let mut texts = HashMap::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (key, text) = line
+            .split_once('=')
+            .ok_or_else(|| format!("Expected `<key> = <text>` at line {}.", index + 1))?;
+        let key = key.trim();
+        if let Some(message) = Message::ALL.iter().find(|message| message.get_key() == key) {
+            texts.insert(*message, text.trim().to_string());
+        }
+    }
+
+    Ok(texts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let texts = parse("# German\nmenu-resume = Weiter\n\nmenu-quit=Beenden\n").unwrap();
+        assert_eq!(texts[&Message::MenuResume], "Weiter");
+        assert_eq!(texts[&Message::MenuQuit], "Beenden");
+        assert!(!texts.contains_key(&Message::MenuReset));
+
+        assert!(parse("menu-resume Weiter").is_err());
+        assert!(parse("menu-foo = Foo").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(format(Message::DefaultBookmarkName, 3), "Bookmark 3");
+    }
+}
@@ -0,0 +1,887 @@
+use crate::{
+    alert::Alert,
+    demo,
+    renderer::{self, Mode, Origin, Scale, Theme},
+    sidecar,
+    terminal::Color,
+};
+use chip8_core::{
+    annotations::Annotations,
+    debugger::{Region, Watchpoint},
+    interpreter::START_POINT,
+    link::Mailbox,
+    patches::Patches,
+    quirks::{MemoryEnd, Quirks},
+    symbols::Symbols,
+    timing,
+    util::{parse_number, parse_range},
+    Error,
+};
+use std::{
+    convert::TryInto,
+    env,
+    ffi::{OsStr, OsString},
+    path::PathBuf,
+    time::Duration,
+};
+
+/// The options for running a program, given on the command line.
+#[derive(Debug)]
+pub struct Options {
+    /// The path to the binary to run.
+    pub path: OsString,
+    /// Regions of the display that pause execution when something is drawn within them.
+    pub draw_breakpoints: Vec<Region>,
+    /// Regions of memory whose writes pause execution or are logged.
+    pub write_watchpoints: Vec<Watchpoint>,
+    /// Whether self-modifying code pauses execution (`true`) or is only logged (`false`), if it is looked for.
+    pub self_modification: Option<bool>,
+    /// The savestate to load before running.
+    pub load_state: Option<PathBuf>,
+    /// Whether to imitate a CRT screen.
+    pub crt: bool,
+    /// How the pixels are drawn to the terminal.
+    pub render_mode: Mode,
+    /// Whether to run in an SDL2 window instead of the terminal.
+    pub sdl: bool,
+    /// Whether to run in a plain native window instead of the terminal.
+    pub window: bool,
+    /// How many instructions to run without a terminal, if the program is run headless.
+    pub headless_cycles: Option<u64>,
+    /// Where to write the display as text once the program ran headless.
+    pub dump_display: Option<PathBuf>,
+    /// The colors of the pixels.
+    pub theme: Theme,
+    /// Whether to flash the pixels that sprites turn off, which set VF.
+    pub show_collisions: bool,
+    /// The colors of the pixels of XO-CHIP programs by the planes they are set in, overriding the theme.
+    pub plane_colors: Option<[Color; 4]>,
+    /// Where the display is placed in the terminal.
+    pub origin: Origin,
+    /// How many times larger every pixel is drawn.
+    pub scale: Scale,
+    /// Whether to show the speed, the timers and whether it is paused below the display.
+    pub status_bar: bool,
+    /// How to show that the sound is playing besides playing it. Without audio, the terminal bell rings by default.
+    pub beep_alerts: Vec<Alert>,
+    /// Where the program is loaded in memory and starts.
+    pub load_address: u16,
+    /// The seed of the random number generator, if not random.
+    pub seed: Option<u64>,
+    /// Whether to reload the program whenever its file changes.
+    pub watch: bool,
+    /// The color to fill the area around the display with, if any.
+    pub letterbox: Option<Color>,
+    /// Whether to avoid visual effects like flickering.
+    pub reduced_motion: bool,
+    /// Where to record the keypad input to.
+    pub record: Option<PathBuf>,
+    /// The recorded keypad input to play back.
+    pub replay: Option<PathBuf>,
+    /// Where to write every frame to, e.g. a named pipe.
+    pub frame_pipe: Option<PathBuf>,
+    /// The hardware display to send every frame to, like `serial:/dev/ttyUSB0`.
+    pub led_matrix: Option<String>,
+    /// Where to write the sound to, e.g. a named pipe read by an audio player.
+    pub audio: Option<PathBuf>,
+    /// The WAV file played by the buzzer instead of its square wave.
+    pub beep_sound: Option<PathBuf>,
+    /// The fraction of every period of the buzzer's square wave that is high, which changes its timbre.
+    pub duty_cycle: f64,
+    /// The hardware keypad to read keys from in addition to the keyboard, like `serial:/dev/ttyUSB0`.
+    pub keypad: Option<String>,
+    /// The other interpreter to link to, like `listen:/tmp/chip8.sock`.
+    pub link: Option<String>,
+    /// Where in memory the bytes exchanged over the link are.
+    pub link_mailbox: Mailbox,
+    /// Where to log every executed instruction to.
+    pub trace: Option<PathBuf>,
+    /// Where to log watched writes and self-modifying code to, instead of showing them at the top of the screen.
+    pub watch_log: Option<PathBuf>,
+    /// Whether to count how often every opcode and address is executed and print the counts on exit.
+    pub profile: bool,
+    /// Where to save the disassembly with the executed instructions marked to once the program exits.
+    pub coverage: Option<PathBuf>,
+    /// Where to record the screen to as an animated GIF.
+    pub record_gif: Option<PathBuf>,
+    /// Where to log everything drawn to the terminal to.
+    pub damage_log: Option<PathBuf>,
+    /// Where to save the screen to once the program exits.
+    pub screenshot_on_exit: Option<PathBuf>,
+    /// Whether to write down the state if the interpreter fails.
+    pub crash_dump: bool,
+    /// Where to write the memory to once the program exits.
+    pub dump_memory_on_exit: Option<PathBuf>,
+    /// Whether to warn about instructions whose behavior differs between implementations.
+    pub quirk_warnings: bool,
+    /// How fast instructions are executed.
+    pub speed: Speed,
+    /// Where to run to as fast as possible before pausing.
+    pub run_until: Option<RunUntil>,
+    /// Names for regions of memory, shown when debugging.
+    pub annotations: Annotations,
+    /// Names for addresses in the code, shown by the debugger and in errors.
+    pub symbols: Symbols,
+    /// Changes to the program applied in memory after loading it.
+    pub patches: Patches,
+    /// For how long a key is considered held down after it was pressed.
+    pub key_hold: Duration,
+    /// The key on the keyboard for each key of the keypad, from 0 to F.
+    pub keymap: [char; 16],
+    /// The keys `FX0A` accepts, one bit per key.
+    pub fx0a_keys: u16,
+    /// How to behave where implementations differ.
+    pub quirks: Quirks,
+}
+
+/// A point of interest to run to as fast as possible and pause at.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RunUntil {
+    pub target: Target,
+    /// Whether to draw the display while running there.
+    pub visible: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Target {
+    /// The program counter reaching the address.
+    Pc(u16),
+    /// The number of frames passed.
+    Frame(u64),
+    /// The program first drawing a sprite or checking the keys, which skips the busy loops some programs start with.
+    /// Instead of pausing there, the program continues at the normal speed.
+    Activity,
+}
+
+/// How many instructions are executed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Speed {
+    PerSecond(u32),
+    PerFrame(u32),
+    /// As fast as possible.
+    Max,
+    /// As fast as on the COSMAC VIP, with every instruction taking about as many machine cycles as it did there.
+    Vip,
+}
+
+impl Speed {
+    /// Returns how much can be executed per frame at the given frame rate, or `None` if unlimited.
+    ///
+    /// This is a number of instructions, except for [`Speed::Vip`] where it is a number of machine cycles.
+    pub fn get_budget_per_frame(self, frames_per_second: f64) -> Option<f64> {
+        match self {
+            Self::PerSecond(count) => Some(count as f64 / frames_per_second),
+            Self::PerFrame(count) => Some(count as f64),
+            Self::Max => None,
+            Self::Vip => Some(timing::VIP_CYCLES_PER_FRAME as f64 * 60.0 / frames_per_second),
+        }
+    }
+
+    /// Returns how much of the budget of a frame executing the instruction uses up.
+    pub fn get_cost(self, instruction: u16) -> f64 {
+        match self {
+            Self::Vip => timing::get_vip_cycles(instruction) as f64,
+            _ => 1.0,
+        }
+    }
+}
+
+impl Options {
+    /// Returns the options for running the program at the path without any options given.
+    pub fn new(path: OsString) -> Self {
+        Self {
+            path,
+            draw_breakpoints: Vec::new(),
+            write_watchpoints: Vec::new(),
+            self_modification: None,
+            load_state: None,
+            crt: false,
+            render_mode: Mode::Blocks,
+            sdl: false,
+            window: false,
+            headless_cycles: None,
+            dump_display: None,
+            theme: Theme::default(),
+            plane_colors: None,
+            show_collisions: false,
+            origin: Origin::Center,
+            scale: Scale::Fixed(1),
+            status_bar: false,
+            watch: false,
+            load_address: START_POINT,
+            seed: None,
+            beep_alerts: vec![Alert::Bell],
+            letterbox: None,
+            reduced_motion: false,
+            record: None,
+            replay: None,
+            frame_pipe: None,
+            led_matrix: None,
+            audio: None,
+            beep_sound: None,
+            duty_cycle: 0.5,
+            keypad: None,
+            link: None,
+            // The last bytes of memory are unlikely to be used by programs not made for linking.
+            link_mailbox: Mailbox::new(0xFFC).unwrap(),
+            trace: None,
+            watch_log: None,
+            profile: false,
+            coverage: None,
+            damage_log: None,
+            screenshot_on_exit: None,
+            crash_dump: false,
+            dump_memory_on_exit: None,
+            record_gif: None,
+            quirk_warnings: false,
+            speed: DEFAULT_SPEED,
+            run_until: None,
+            annotations: Annotations::default(),
+            symbols: Symbols::default(),
+            patches: Patches::default(),
+            key_hold: DEFAULT_KEY_HOLD,
+            keymap: DEFAULT_KEYMAP,
+            fx0a_keys: u16::MAX,
+            quirks: Quirks::default(),
+        }
+    }
+
+    pub fn parse(path: OsString, mut args: env::ArgsOs) -> Result<Self, Error> {
+        let mut options = Self::new(path);
+        // The options given explicitly take precedence.
+        sidecar::apply(&options.path.clone(), &mut options)?;
+
+        let mut beep_alerts_given = false;
+        let mut headless = false;
+        let mut cycles = None;
+        while let Some(arg) = args.next() {
+            match arg.to_str() {
+                Some(option @ "--break-draw") => {
+                    let value = get_value(&mut args, option)?;
+                    options.draw_breakpoints.push(parse_region(&value)?);
+                }
+                Some(option @ "--watch-write") => {
+                    let value = get_value(&mut args, option)?;
+                    options.write_watchpoints.push(parse_watchpoint(&value)?);
+                }
+                Some(option @ "--self-modifying") => {
+                    options.self_modification = match get_value(&mut args, option)?.as_str() {
+                        "pause" => Some(true),
+                        "log" => Some(false),
+                        value => {
+                            return Err(format!(
+                            "Invalid value `{}` for `--self-modifying`. Expected `pause` or `log`.",
+                            value
+                        )
+                            .into())
+                        }
+                    };
+                }
+                Some(option @ "--load-state") => {
+                    options.load_state = Some(get_value(&mut args, option)?.into());
+                }
+                Some("--crt") => options.crt = true,
+                Some("--braille") => options.render_mode = Mode::Braille,
+                Some("--kitty") => options.render_mode = Mode::Kitty,
+                Some("--sdl") => options.sdl = true,
+                Some("--window") => options.window = true,
+                Some("--headless") => headless = true,
+                Some(option @ "--cycles") => {
+                    let value = get_value(&mut args, option)?;
+                    cycles = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("Invalid count `{}`.", value))?,
+                    );
+                }
+                Some(option @ "--dump-display") => {
+                    options.dump_display = Some(get_value(&mut args, option)?.into());
+                }
+                Some("--status-bar") => options.status_bar = true,
+                Some("--watch") => options.watch = true,
+                Some(option @ "--load-address") => {
+                    options.load_address = parse_load_address(&get_value(&mut args, option)?)?;
+                }
+                Some(option @ "--seed") => {
+                    options.seed = Some(parse_seed(&get_value(&mut args, option)?)?);
+                }
+                Some(option @ "--beep-alert") => {
+                    options.beep_alerts = Alert::parse_list(&get_value(&mut args, option)?)?;
+                    beep_alerts_given = true;
+                    // The status alert is shown in the status bar.
+                    options.status_bar |= options.beep_alerts.contains(&Alert::Status);
+                }
+                Some(option @ "--scale") => {
+                    options.scale = Scale::parse(&get_value(&mut args, option)?)?;
+                }
+                Some(option @ "--origin") => {
+                    options.origin = Origin::parse(&get_value(&mut args, option)?)?;
+                }
+                Some(option @ "--letterbox") => {
+                    let value = get_value(&mut args, option)?;
+                    options.letterbox =
+                        Some(renderer::parse_hex_color(&value).ok_or_else(|| {
+                            format!(
+                                "Invalid color `{}`. Expected something like `#202020`.",
+                                value
+                            )
+                        })?);
+                }
+                Some(option @ "--theme") => {
+                    options.theme = Theme::parse(&get_value(&mut args, option)?)?;
+                }
+                Some("--show-collisions") => options.show_collisions = true,
+                Some(option @ "--plane-colors") => {
+                    options.plane_colors = Some(renderer::parse_plane_colors(&get_value(
+                        &mut args, option,
+                    )?)?);
+                }
+                Some("--reduced-motion") => options.reduced_motion = true,
+                Some("--quirk-warnings") => options.quirk_warnings = true,
+                Some("--crash-dump") => options.crash_dump = true,
+                Some(option @ "--record") => {
+                    options.record = Some(get_value(&mut args, option)?.into());
+                }
+                Some(option @ "--replay") => {
+                    options.replay = Some(get_value(&mut args, option)?.into());
+                }
+                Some(option @ "--frame-pipe") => {
+                    options.frame_pipe = Some(get_value(&mut args, option)?.into());
+                }
+                Some(option @ "--led-matrix") => {
+                    options.led_matrix = Some(get_value(&mut args, option)?);
+                }
+                Some(option @ "--beep-sound") => {
+                    options.beep_sound = Some(get_value(&mut args, option)?.into());
+                }
+                Some(option @ "--audio") => {
+                    options.audio = Some(get_value(&mut args, option)?.into());
+                }
+                Some(option @ "--duty-cycle") => {
+                    let value = get_value(&mut args, option)?;
+                    options.duty_cycle = value
+                        .parse::<u8>()
+                        .ok()
+                        .filter(|percent| (1..100).contains(percent))
+                        .map(|percent| percent as f64 / 100.0)
+                        .ok_or_else(|| {
+                            format!(
+                                "Invalid duty cycle `{}`. Expected a percentage from 1 to 99.",
+                                value
+                            )
+                        })?;
+                }
+                Some(option @ "--keypad") => {
+                    options.keypad = Some(get_value(&mut args, option)?);
+                }
+                Some(option @ "--link") => {
+                    options.link = Some(get_value(&mut args, option)?);
+                }
+                Some(option @ "--link-mailbox") => {
+                    let value = get_value(&mut args, option)?;
+                    options.link_mailbox =
+                        parse_number(&value).and_then(Mailbox::new).ok_or_else(|| {
+                            format!(
+                                "Invalid mailbox address `{}`. It needs to be at most 0xFFC.",
+                                value
+                            )
+                        })?;
+                }
+                Some(option @ "--speed") => {
+                    options.speed = parse_speed(&get_value(&mut args, option)?)?;
+                }
+                Some(option @ "--key-hold") => {
+                    options.key_hold = parse_key_hold(&get_value(&mut args, option)?)?;
+                }
+                Some(option @ "--keymap") => {
+                    options.keymap = parse_keymap(&get_value(&mut args, option)?)?;
+                }
+                Some(option @ "--fx0a-keys") => {
+                    options.fx0a_keys = parse_key_set(&get_value(&mut args, option)?)?;
+                }
+                Some(option @ "--quirk") => {
+                    parse_quirk(&get_value(&mut args, option)?, &mut options.quirks)?;
+                }
+                Some(option @ "--run-until") => {
+                    options.run_until = Some(parse_run_until(&get_value(&mut args, option)?)?);
+                }
+                Some("--skip-startup") => {
+                    options.run_until = Some(RunUntil {
+                        target: Target::Activity,
+                        visible: false,
+                    });
+                }
+                Some(option @ "--patch") => {
+                    options
+                        .patches
+                        .add_from_str(&get_value(&mut args, option)?)?;
+                }
+                Some(option @ "--patches") => {
+                    let value = get_value(&mut args, option)?;
+                    options.patches.load(value.as_ref())?;
+                }
+                Some(option @ "--annotate") => {
+                    let value = get_value(&mut args, option)?;
+                    options.annotations.add_from_str(&value)?;
+                }
+                Some(option @ "--annotations") => {
+                    let value = get_value(&mut args, option)?;
+                    options.annotations.load(value.as_ref())?;
+                }
+                Some(option @ "--symbols") => {
+                    let value = get_value(&mut args, option)?;
+                    options.symbols.load(value.as_ref())?;
+                }
+                Some(option @ "--trace") => {
+                    options.trace = Some(get_value(&mut args, option)?.into());
+                }
+                Some(option @ "--watch-log") => {
+                    options.watch_log = Some(get_value(&mut args, option)?.into());
+                }
+                Some("--profile") => options.profile = true,
+                Some(option @ "--coverage") => {
+                    options.coverage = Some(get_value(&mut args, option)?.into());
+                }
+                Some(option @ "--screenshot-on-exit") => {
+                    options.screenshot_on_exit = Some(get_value(&mut args, option)?.into());
+                }
+                Some(option @ "--dump-memory-on-exit") => {
+                    options.dump_memory_on_exit = Some(get_value(&mut args, option)?.into());
+                }
+                Some(option @ "--record-gif") => {
+                    options.record_gif = Some(get_value(&mut args, option)?.into());
+                }
+                Some(option @ "--damage-log") => {
+                    options.damage_log = Some(get_value(&mut args, option)?.into());
+                }
+                _ => return Err(format!("Unknown argument: {}", arg.to_string_lossy()).into()),
+            }
+        }
+
+        // The bell is only a fallback for when the sound is not played.
+        if options.audio.is_some() && !beep_alerts_given {
+            options.beep_alerts.clear();
+        }
+
+        if options.beep_sound.is_some() && options.audio.is_none() && !options.sdl {
+            return Err("`--beep-sound` needs `--audio` or `--sdl` to play the sound.".into());
+        }
+
+        match (headless, cycles) {
+            (true, Some(_)) => options.headless_cycles = cycles,
+            (true, None) => {
+                return Err("`--headless` needs `--cycles` to know when to stop.".into());
+            }
+            (false, Some(_)) => return Err("`--cycles` only applies with `--headless`.".into()),
+            (false, None) if options.dump_display.is_some() => {
+                return Err("`--dump-display` only applies with `--headless`.".into());
+            }
+            (false, None) => {}
+        }
+
+        if options.record.is_some() && options.replay.is_some() {
+            return Err("`--record` and `--replay` cannot be used together.".into());
+        }
+
+        Ok(options)
+    }
+}
+
+/// How fast instructions are executed unless configured otherwise. Most programs are designed for roughly this speed.
+pub const DEFAULT_SPEED: Speed = Speed::PerSecond(700);
+
+/// The path that stands for reading the program from the standard input.
+pub const STDIN_PATH: &str = "-";
+
+/// For how long a key is considered held down after it was pressed, unless configured otherwise.
+pub const DEFAULT_KEY_HOLD: Duration = Duration::from_millis(200);
+
+/// Returns the value following the given option.
+pub fn get_value(args: &mut env::ArgsOs, option: &str) -> Result<String, Error> {
+    match args.next().map(OsString::into_string) {
+        Some(Ok(value)) => Ok(value),
+        Some(Err(_)) => Err(format!("The value of `{}` is not valid UTF-8.", option).into()),
+        None => Err(format!("Expected a value after `{}`.", option).into()),
+    }
+}
+
+/// Parses a duration in milliseconds for how long a key is held down.
+pub fn parse_key_hold(string: &str) -> Result<Duration, Error> {
+    let milliseconds = string
+        .parse()
+        .map_err(|_| format!("Invalid duration `{}`. Expected milliseconds.", string))?;
+
+    Ok(Duration::from_millis(milliseconds))
+}
+
+/// Returns what the files kept next to the program at the path are named after: its path, or `stdin` or `demo-<name>`
+/// in the current directory if it is read from the standard input or is a demo.
+pub fn get_base_path(program_path: &OsStr) -> OsString {
+    if program_path == STDIN_PATH {
+        OsString::from("stdin")
+    } else if let Some(name) = demo::get_name(program_path) {
+        OsString::from(format!("demo-{}", name))
+    } else {
+        program_path.to_os_string()
+    }
+}
+
+/// Returns where the savestate hotkeys save to and load from for the program at the path.
+pub fn get_state_path(program_path: &OsStr) -> PathBuf {
+    let mut path = get_base_path(program_path);
+    path.push(".state");
+    path.into()
+}
+
+/// Returns where the flags the program saves with `FX75` are kept for the program at the path: `<path>.flags`.
+pub fn get_flags_path(program_path: &OsStr) -> PathBuf {
+    let mut path = get_base_path(program_path);
+    path.push(".flags");
+    path.into()
+}
+
+/// Returns where the screenshot hotkey saves to for the program at the path: the first of `<path>.screenshot-1.png`,
+/// `<path>.screenshot-2.png` and so on that does not exist yet.
+pub fn get_screenshot_path(program_path: &OsStr) -> PathBuf {
+    (1..)
+        .map(|number| {
+            let mut path = get_base_path(program_path);
+            path.push(format!(".screenshot-{}.png", number));
+            PathBuf::from(path)
+        })
+        .find(|path| !path.exists())
+        .unwrap()
+}
+
+/// Returns where the state is saved to when the terminal is lost for the program at the path.
+pub fn get_auto_state_path(program_path: &OsStr) -> PathBuf {
+    let mut path = get_base_path(program_path);
+    path.push(".autosave.state");
+    path.into()
+}
+
+/// The keys on the keyboard for the keys of the keypad from 0 to F, in the same layout as the keypad of the COSMAC VIP:
+///
+/// ```text
+/// 1 2 3 4    1 2 3 C
+/// Q W E R    4 5 6 D
+/// A S D F    7 8 9 E
+/// Z X C V    A 0 B F
+/// ```
+const DEFAULT_KEYMAP: [char; 16] = [
+    'x', '1', '2', '3', 'q', 'w', 'e', 'a', 's', 'd', 'z', 'c', '4', 'r', 'f', 'v',
+];
+/// The keys used as hotkeys, which cannot be on the keypad too.
+const HOTKEYS: &str = "klbjnop:";
+
+/// Parses the keys on the keyboard for the keys of the keypad from 0 to F, like `x123qweasdzc4rfv`.
+pub fn parse_keymap(string: &str) -> Result<[char; 16], Error> {
+    let keys: Vec<char> = string.chars().map(|key| key.to_ascii_lowercase()).collect();
+    let keymap: [char; 16] = keys.as_slice().try_into().map_err(|_| {
+        format!(
+            "Invalid keymap `{}`. Expected 16 keys for the keypad from 0 to F, like `x123qweasdzc4rfv`.",
+            string
+        )
+    })?;
+    for (index, key) in keymap.iter().enumerate() {
+        if keymap[..index].contains(key) {
+            return Err(format!("The key `{}` is in the keymap twice.", key).into());
+        }
+        if HOTKEYS.contains(*key) {
+            return Err(
+                format!("The key `{}` is a hotkey and cannot be in the keymap.", key).into(),
+            );
+        }
+    }
+
+    Ok(keymap)
+}
+
+/// Parses a set of keys like `5,A-F` into one bit per key.
+pub fn parse_key_set(string: &str) -> Result<u16, Error> {
+    let parse_key = |key: &str| {
+        u8::from_str_radix(key.trim(), 16)
+            .ok()
+            .filter(|key| *key <= 0xF)
+    };
+
+    string
+        .split(',')
+        .try_fold(0, |keys, part| {
+            let (start, end) = match part.split_once('-') {
+                Some((start, end)) => (parse_key(start)?, parse_key(end)?),
+                None => (parse_key(part)?, parse_key(part)?),
+            };
+            let range = (start..=end).fold(0, |range, key| range | 1 << key);
+            (range != 0).then_some(keys | range)
+        })
+        .ok_or_else(|| {
+            format!(
+                "Invalid keys `{}`. Expected hexadecimal keys or ranges like `5,A-F`.",
+                string
+            )
+            .into()
+        })
+}
+
+/// Parses an address in memory to load the program at, like `0x600`.
+///
+/// Whether it is within the memory is checked by the interpreter, as the size of the memory depends on the quirks.
+pub fn parse_load_address(string: &str) -> Result<u16, Error> {
+    parse_number(string).ok_or_else(|| {
+        format!(
+            "Invalid load address `{}`. Expected an address like `0x600`.",
+            string
+        )
+        .into()
+    })
+}
+
+/// Parses the seed of the random number generator, which is any 64-bit number.
+fn parse_seed(string: &str) -> Result<u64, Error> {
+    string.parse().map_err(|_| {
+        format!(
+            "Invalid seed `{}`. Expected a number from 0 to {}.",
+            string,
+            u64::MAX
+        )
+        .into()
+    })
+}
+
+/// Enables the quirk of the given name.
+pub fn parse_quirk(name: &str, quirks: &mut Quirks) -> Result<(), Error> {
+    match name {
+        "fx0a-release" => quirks.await_key_release = true,
+        "wrap" => quirks.wrap_sprites = true,
+        "memory-wrap" => quirks.memory_end = MemoryEnd::Wrap,
+        "memory-clamp" => quirks.memory_end = MemoryEnd::Clamp,
+        "small-stack" => quirks.small_stack = true,
+        "xo-chip" => {
+            quirks.xo_chip = true;
+            quirks.wrap_sprites = true;
+        }
+        _ => {
+            return Err(format!(
+            "Unknown quirk `{}`. Expected `fx0a-release`, `wrap`, `memory-wrap`, `memory-clamp`, `small-stack` or \
+             `xo-chip`.",
+            name
+        )
+            .into())
+        }
+    }
+
+    Ok(())
+}
+
+/// Parses a region of the display like `0..8,0..8`, with the horizontal range first.
+fn parse_region(string: &str) -> Result<Region, Error> {
+    string
+        .split_once(',')
+        .and_then(|(x, y)| {
+            Some(Region {
+                x: parse_range(x)?,
+                y: parse_range(y)?,
+            })
+        })
+        .ok_or_else(|| {
+            format!(
+                "Invalid region `{}`. Expected something like `0..8,0..8`.",
+                string
+            )
+            .into()
+        })
+}
+
+/// Parses a watchpoint like `0x000-0x1FF`, where the end is inclusive, or a single address like `0x300`, optionally
+/// followed by `,log` to only log writes instead of pausing.
+fn parse_watchpoint(string: &str) -> Result<Watchpoint, Error> {
+    let (range, pause) = match string.strip_suffix(",log") {
+        Some(range) => (range, false),
+        None => (string, true),
+    };
+
+    let range = match range.split_once('-') {
+        Some((start, end)) => parse_number(start).zip(parse_number(end)),
+        None => parse_number(range).map(|address| (address, address)),
+    };
+
+    range
+        .filter(|(start, end)| start <= end)
+        .and_then(|(start, end)| {
+            Some(Watchpoint {
+                range: start..end.checked_add(1)?,
+                pause,
+            })
+        })
+        .ok_or_else(|| {
+            format!(
+                "Invalid watchpoint `{}`. Expected something like `0x000-0x1FF` or `0x300,log`.",
+                string
+            )
+            .into()
+        })
+}
+
+/// Parses a speed like `700` (per second), `12/frame`, `max` or `vip`.
+pub fn parse_speed(string: &str) -> Result<Speed, Error> {
+    let speed = if string == "max" {
+        Some(Speed::Max)
+    } else if string == "vip" {
+        Some(Speed::Vip)
+    } else if let Some(count) = string.strip_suffix("/frame") {
+        count.parse().ok().map(Speed::PerFrame)
+    } else {
+        string.parse().ok().map(Speed::PerSecond)
+    };
+
+    match speed {
+        Some(Speed::PerSecond(0) | Speed::PerFrame(0)) | None => Err(format!(
+            "Invalid speed `{}`. Expected something like `700`, `12/frame`, `max` or `vip`.",
+            string
+        )
+        .into()),
+        Some(speed) => Ok(speed),
+    }
+}
+
+/// Parses a point of interest like `pc=0x2F0` or `frame=600`, optionally followed by `,visible`.
+fn parse_run_until(string: &str) -> Result<RunUntil, Error> {
+    let (target, visible) = match string.strip_suffix(",visible") {
+        Some(target) => (target, true),
+        None => (string, false),
+    };
+
+    let target = match target.split_once('=') {
+        Some(("pc", address)) => parse_number(address).map(Target::Pc),
+        Some(("frame", frame)) => frame.parse().ok().map(Target::Frame),
+        _ => None,
+    };
+
+    target
+        .map(|target| RunUntil { target, visible })
+        .ok_or_else(|| {
+            format!(
+                "Invalid target `{}`. Expected something like `pc=0x2F0` or `frame=600`.",
+                string
+            )
+            .into()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_base_path() {
+        assert_eq!(
+            get_state_path(OsStr::new("pong.ch8")),
+            PathBuf::from("pong.ch8.state")
+        );
+        assert_eq!(
+            get_flags_path(OsStr::new(STDIN_PATH)),
+            PathBuf::from("stdin.flags")
+        );
+        assert_eq!(
+            get_state_path(OsStr::new("demo:pong")),
+            PathBuf::from("demo-pong.state")
+        );
+    }
+
+    #[test]
+    fn test_parse_region() {
+        assert_eq!(
+            parse_region("0..8,0x10..0x18").unwrap(),
+            Region {
+                x: 0..8,
+                y: 0x10..0x18
+            }
+        );
+        assert!(parse_region("0..8").is_err());
+        assert!(parse_region("8..0,0..8").is_err());
+    }
+
+    #[test]
+    fn test_parse_key_set() {
+        assert_eq!(parse_key_set("0-9").unwrap(), 0x03FF);
+        assert_eq!(parse_key_set("5, a-F").unwrap(), 0xFC20);
+        assert!(parse_key_set("F-A").is_err());
+        assert!(parse_key_set("10").is_err());
+        assert!(parse_key_set("").is_err());
+    }
+
+    #[test]
+    fn test_parse_watchpoint() {
+        assert_eq!(
+            parse_watchpoint("0x000-0x1FF"),
+            Ok(Watchpoint {
+                range: 0x000..0x200,
+                pause: true
+            })
+        );
+        assert_eq!(
+            parse_watchpoint("0x300,log"),
+            Ok(Watchpoint {
+                range: 0x300..0x301,
+                pause: false
+            })
+        );
+        assert!(parse_watchpoint("0x1FF-0x000").is_err());
+        assert!(parse_watchpoint("0x000-").is_err());
+    }
+
+    #[test]
+    fn test_parse_load_address() {
+        assert_eq!(parse_load_address("0x600").unwrap(), 0x600);
+        // For XO-CHIP, whose memory is bigger.
+        assert_eq!(parse_load_address("0x8000").unwrap(), 0x8000);
+        assert!(parse_load_address("0x10000").is_err());
+    }
+
+    #[test]
+    fn test_parse_keymap() {
+        let keymap = parse_keymap("X123QWEASDZC4RFV").unwrap();
+        assert_eq!(keymap, DEFAULT_KEYMAP);
+        assert!(parse_keymap("x123").is_err());
+        assert!(parse_keymap("x123qweasdzc4rfx").is_err());
+        assert!(parse_keymap("k123qweasdzc4rfv").is_err());
+        assert!(parse_keymap(":123qweasdzc4rfv").is_err());
+    }
+
+    #[test]
+    fn test_parse_seed() {
+        assert_eq!(parse_seed("42").unwrap(), 42);
+        assert!(parse_seed("-1").is_err());
+    }
+
+    #[test]
+    fn test_parse_speed() {
+        assert_eq!(parse_speed("700").unwrap(), Speed::PerSecond(700));
+        assert_eq!(parse_speed("12/frame").unwrap(), Speed::PerFrame(12));
+        assert_eq!(parse_speed("max").unwrap(), Speed::Max);
+        assert_eq!(parse_speed("vip").unwrap(), Speed::Vip);
+        assert!(parse_speed("0").is_err());
+        assert!(parse_speed("fast").is_err());
+    }
+
+    #[test]
+    fn test_parse_run_until() {
+        assert_eq!(
+            parse_run_until("pc=0x2F0").unwrap(),
+            RunUntil {
+                target: Target::Pc(0x2F0),
+                visible: false
+            }
+        );
+        assert_eq!(
+            parse_run_until("frame=600,visible").unwrap(),
+            RunUntil {
+                target: Target::Frame(600),
+                visible: true
+            }
+        );
+        assert!(parse_run_until("frame=").is_err());
+        assert!(parse_run_until("sp=0").is_err());
+    }
+}
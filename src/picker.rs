@@ -0,0 +1,122 @@
+//! Picking a program to run in the terminal, e.g. when no path is given.
+
+use crate::{
+    menu,
+    messages::{self, Message},
+    stats::Stats,
+    terminal::Terminal,
+};
+use chip8_core::{util, Error};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// The file extensions programs commonly have.
+const EXTENSIONS: [&str; 5] = ["ch8", "c8", "rom", "s", "8o"];
+
+/// An item of the file browser.
+enum Entry {
+    /// The directory containing the current one.
+    Parent,
+    Directory(PathBuf),
+    Program(PathBuf),
+}
+
+/// Returns whether the file looks like a program by its extension.
+fn is_program(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str()))
+}
+
+/// Returns the directories and programs in the directory, each sorted by name.
+fn get_entries(directory: &Path) -> io::Result<Vec<Entry>> {
+    let mut directories = Vec::new();
+    let mut programs = Vec::new();
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            directories.push(path);
+        } else if is_program(&path) {
+            programs.push(path);
+        }
+    }
+    directories.sort();
+    programs.sort();
+
+    let mut entries = Vec::new();
+    if directory.parent().is_some() {
+        entries.push(Entry::Parent);
+    }
+    entries.extend(directories.into_iter().map(Entry::Directory));
+    entries.extend(programs.into_iter().map(Entry::Program));
+    Ok(entries)
+}
+
+/// Lets the user browse the directories, starting at the given one, and pick a program.
+///
+/// The programs are shown with how much they were played, if the statistics are given.
+///
+/// Returns `None` if Esc was pressed.
+pub fn pick(
+    terminal: &mut dyn Terminal,
+    directory: &Path,
+    stats: Option<&Stats>,
+) -> Result<Option<PathBuf>, Error> {
+    let mut directory = directory
+        .canonicalize()
+        .map_err(|err| format!("Failed to open {}: {}", directory.display(), err))?;
+
+    loop {
+        let entries = get_entries(&directory)
+            .map_err(|err| format!("Failed to read {}: {}", directory.display(), err))?;
+        let names: Vec<String> = entries
+            .iter()
+            .map(|entry| match entry {
+                Entry::Parent => "../".to_string(),
+                Entry::Directory(path) => format!("{}/", get_file_name(path)),
+                Entry::Program(path) => {
+                    let entry = stats.and_then(|stats| {
+                        let binary = crate::read_binary(path.as_os_str()).ok()?;
+                        stats.get(util::hash(&binary))
+                    });
+                    match entry {
+                        Some(entry) => format!("{}  ({})", get_file_name(path), entry.describe()),
+                        None => get_file_name(path),
+                    }
+                }
+            })
+            .collect();
+
+        let title = messages::format(Message::PickProgram, directory.display());
+        match menu::select(terminal, &title, &names).map(|index| &entries[index]) {
+            Some(Entry::Parent) => {
+                directory.pop();
+            }
+            Some(Entry::Directory(path)) => directory = path.clone(),
+            Some(Entry::Program(path)) => return Ok(Some(path.clone())),
+            None => return Ok(None),
+        }
+    }
+}
+
+fn get_file_name(path: &Path) -> String {
+    path.file_name()
+        .map_or_else(String::new, |name| name.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_program() {
+        assert!(is_program(Path::new("games/PONG.CH8")));
+        assert!(is_program(Path::new("test.c8")));
+        assert!(is_program(Path::new("tetris.rom")));
+        assert!(is_program(Path::new("game.s")));
+        assert!(!is_program(Path::new("README.md")));
+        assert!(!is_program(Path::new("ch8")));
+    }
+}
@@ -0,0 +1,108 @@
+//! What the window frontends have in common: running the program frame by frame with keys that are released exactly
+//! when the keys of the keyboard are, instead of after a while like in the terminal.
+
+use crate::{
+    flags,
+    frontend::{Frontend, Program, CLOCK_HERTZ, FRAME_DURATION},
+    options::{self, Options},
+};
+use chip8_core::{display::Display, interpreter::FLAG_COUNT, Error, Interpreter, Step};
+use std::{path::PathBuf, time::Instant};
+
+pub struct Player<'a> {
+    interpreter: Interpreter,
+    options: &'a Options,
+    flags_path: PathBuf,
+    saved_flags: [u8; FLAG_COUNT],
+    /// How much of the speed is left to be executed, which carries fractions over to the next frame.
+    budget: f64,
+    /// The key pressed since the last instruction, which a program waiting for a key gets.
+    pressed_key: Option<u8>,
+}
+
+impl<'a> Player<'a> {
+    pub fn new(program: &Program, options: &'a Options) -> Result<Self, Error> {
+        let interpreter = Frontend::create_interpreter(program, options)?;
+
+        Ok(Self {
+            saved_flags: interpreter.flags(),
+            interpreter,
+            options,
+            flags_path: options::get_flags_path(&program.path),
+            budget: 0.0,
+            pressed_key: None,
+        })
+    }
+
+    pub fn display(&self) -> &Display {
+        self.interpreter.display()
+    }
+
+    pub fn is_sounding(&self) -> bool {
+        self.interpreter.get_registers().sound_timer > 0
+    }
+
+    /// Presses or releases the key of the keypad the character of the keyboard is mapped to, if any.
+    pub fn set_key(&mut self, char: char, pressed: bool) {
+        let key = match self.options.keymap.iter().position(|key| *key == char) {
+            Some(key) => key as u8,
+            None => return,
+        };
+        if pressed {
+            self.pressed_key = Some(key);
+        }
+        self.interpreter.set_key_held(key, pressed);
+    }
+
+    /// Executes the instructions of a frame at the configured speed and decrements the timers.
+    ///
+    /// At the maximum speed, instructions are executed until the frame that started at the given time is over.
+    /// Returns `false` once the program ended.
+    pub fn run_frame(&mut self, frame_start: Instant) -> Result<bool, Error> {
+        let budget_per_frame = self.options.speed.get_budget_per_frame(CLOCK_HERTZ);
+        if let Some(budget_per_frame) = budget_per_frame {
+            self.budget += budget_per_frame;
+        }
+        loop {
+            let out_of_budget = match budget_per_frame {
+                Some(_) => self.budget < 1.0,
+                None => frame_start.elapsed() >= FRAME_DURATION,
+            };
+            if out_of_budget {
+                break;
+            }
+
+            let instruction = self.interpreter.get_current_instruction();
+            let awaits_key =
+                matches!(instruction, Some(instruction) if instruction & 0xF0FF == 0xF00A);
+            let fx0a_keys = self.options.fx0a_keys;
+            self.interpreter.set_key(
+                self.pressed_key
+                    .take()
+                    .filter(|key| !awaits_key || fx0a_keys & (1 << key) != 0),
+            );
+            match self.interpreter.step()? {
+                Step::Executed => {
+                    if let Some(instruction) = instruction {
+                        self.budget -= self.options.speed.get_cost(instruction);
+                    }
+                }
+                Step::AwaitingKey => {
+                    self.budget = 0.0;
+                    break;
+                }
+                Step::Breakpoint(_) => {}
+                Step::Ended => return Ok(false),
+            }
+        }
+        self.interpreter.update_timers();
+
+        let flags = self.interpreter.flags();
+        if flags != self.saved_flags {
+            flags::save(&self.flags_path, flags)?;
+            self.saved_flags = flags;
+        }
+
+        Ok(true)
+    }
+}
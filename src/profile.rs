@@ -0,0 +1,181 @@
+//! Counting how often every kind of instruction and every address is executed, to find what to optimize in programs.
+
+use std::collections::HashMap;
+
+/// How many of the most executed addresses are listed.
+const HOT_ADDRESS_COUNT: usize = 32;
+/// How many characters the bar of the most executed entry of a histogram is wide.
+const BAR_WIDTH: u64 = 30;
+
+/// Counts the executed instructions by their opcode and by their address.
+#[derive(Default)]
+pub struct Profiler {
+    opcodes: HashMap<&'static str, u64>,
+    /// The instruction last executed at each address and how often instructions were executed there.
+    addresses: HashMap<u16, (u16, u64)>,
+    total: u64,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts the instruction executed at the address.
+    pub fn record(&mut self, address: u16, instruction: u16) {
+        *self.opcodes.entry(get_opcode(instruction)).or_insert(0) += 1;
+        let entry = self.addresses.entry(address).or_insert((instruction, 0));
+        *entry = (instruction, entry.1 + 1);
+        self.total += 1;
+    }
+
+    /// Formats histograms of the opcodes and of the most executed addresses, most executed first.
+    pub fn to_text(&self) -> String {
+        let mut opcodes: Vec<(&str, u64)> = self
+            .opcodes
+            .iter()
+            .map(|(opcode, count)| (*opcode, *count))
+            .collect();
+        opcodes.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        let mut addresses: Vec<(u16, u16, u64)> = self
+            .addresses
+            .iter()
+            .map(|(address, (instruction, count))| (*address, *instruction, *count))
+            .collect();
+        addresses.sort_by(|a, b| b.2.cmp(&a.2).then(a.0.cmp(&b.0)));
+
+        let mut text = format!("{} instructions executed\n\n", self.total);
+        text += "opcode     executions   share\n";
+        for (opcode, count) in &opcodes {
+            text += &format!(
+                "{:<9}  {}\n",
+                opcode,
+                self.format_count(*count, opcodes[0].1)
+            );
+        }
+        text += "\naddress  instruction  executions   share\n";
+        for (address, instruction, count) in addresses.iter().take(HOT_ADDRESS_COUNT) {
+            text += &format!(
+                "{:#05X}    {:04X}         {}\n",
+                address,
+                instruction,
+                self.format_count(*count, addresses[0].2)
+            );
+        }
+        if addresses.len() > HOT_ADDRESS_COUNT {
+            text += &format!(
+                "and {} more addresses\n",
+                addresses.len() - HOT_ADDRESS_COUNT
+            );
+        }
+        text
+    }
+
+    /// Formats the count with its share of all instructions and a bar as long relative to the largest count.
+    fn format_count(&self, count: u64, max: u64) -> String {
+        let bar = "#".repeat((count * BAR_WIDTH / max) as usize);
+        let share = count as f64 / self.total as f64 * 100.0;
+        format!("{:>10}  {:>5.1}%  {}", count, share, bar)
+            .trim_end()
+            .to_string()
+    }
+}
+
+/// Returns the opcode of the instruction with the operands replaced by letters, like `8XY4` or `FX1E`.
+fn get_opcode(instruction: u16) -> &'static str {
+    match instruction >> 12 {
+        0x0 => match instruction {
+            0x00E0 => "00E0",
+            0x00EE => "00EE",
+            0x00C0..=0x00CF => "00CN",
+            0x00D0..=0x00DF => "00DN",
+            0x00FB => "00FB",
+            0x00FC => "00FC",
+            0x00FD => "00FD",
+            0x00FE => "00FE",
+            0x00FF => "00FF",
+            _ => "0NNN",
+        },
+        0x1 => "1NNN",
+        0x2 => "2NNN",
+        0x3 => "3XNN",
+        0x4 => "4XNN",
+        0x5 => match instruction & 0xF {
+            0x2 => "5XY2",
+            0x3 => "5XY3",
+            _ => "5XY0",
+        },
+        0x6 => "6XNN",
+        0x7 => "7XNN",
+        0x8 => match instruction & 0xF {
+            0x0 => "8XY0",
+            0x1 => "8XY1",
+            0x2 => "8XY2",
+            0x3 => "8XY3",
+            0x4 => "8XY4",
+            0x5 => "8XY5",
+            0x6 => "8XY6",
+            0x7 => "8XY7",
+            0xE => "8XYE",
+            _ => "8XYN",
+        },
+        0x9 => "9XY0",
+        0xA => "ANNN",
+        0xB => "BNNN",
+        0xC => "CXNN",
+        0xD => "DXYN",
+        0xE => match instruction & 0xFF {
+            0x9E => "EX9E",
+            0xA1 => "EXA1",
+            _ => "EXNN",
+        },
+        _ => match instruction & 0xFF {
+            0x00 if instruction == 0xF000 => "F000 NNNN",
+            0x07 => "FX07",
+            0x0A => "FX0A",
+            0x15 => "FX15",
+            0x18 => "FX18",
+            0x1E => "FX1E",
+            0x29 => "FX29",
+            0x30 => "FX30",
+            0x33 => "FX33",
+            0x55 => "FX55",
+            0x65 => "FX65",
+            0x75 => "FX75",
+            0x85 => "FX85",
+            _ => "FXNN",
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profiler() {
+        let mut profiler = Profiler::new();
+        for _ in 0..3 {
+            profiler.record(0x200, 0x7001);
+            profiler.record(0x202, 0x8014);
+        }
+        profiler.record(0x204, 0x7102);
+        profiler.record(0x206, 0xF000);
+
+        assert_eq!(
+            profiler.to_text(),
+            "8 instructions executed\n\
+             \n\
+             opcode     executions   share\n\
+             7XNN                4   50.0%  ##############################\n\
+             8XY4                3   37.5%  ######################\n\
+             F000 NNNN           1   12.5%  #######\n\
+             \n\
+             address  instruction  executions   share\n\
+             0x200    7001                  3   37.5%  ##############################\n\
+             0x202    8014                  3   37.5%  ##############################\n\
+             0x204    7102                  1   12.5%  ##########\n\
+             0x206    F000                  1   12.5%  ##########\n"
+        );
+    }
+}
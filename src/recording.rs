@@ -0,0 +1,100 @@
+//! Recording of the screen to an animated GIF while running, e.g. to make demo clips of games.
+
+use crate::{
+    gif::GifEncoder,
+    render::{self, COLORS},
+};
+use chip8_core::{
+    display::{Display, Size},
+    Error,
+};
+use std::{fs::File, io::BufWriter, path::Path};
+
+/// How many pixels of the GIF every pixel of the screen is wide and high.
+const SCALE: u16 = 4;
+/// Every how many frames of the 60 per second one is recorded. Browsers show frames with delays below 2 hundredths
+/// of a second much slower, so recording every frame is not possible.
+const FRAME_INTERVAL: u64 = 3;
+/// For how many hundredths of a second every recorded frame is shown.
+const FRAME_DELAY: u16 = 5;
+
+/// Adds frames of the screen to a GIF as it is running, finishing the GIF on exit.
+pub struct GifRecording {
+    encoder: GifEncoder<BufWriter<File>>,
+    /// The size of the screen the GIF was created for. Frames of screens of other sizes, like after loading a
+    /// program for the hi-res mode, are left out.
+    screen_size: Size,
+    frame_count: u64,
+}
+
+impl GifRecording {
+    pub fn create(path: &Path, screen_size: Size) -> Result<Self, Error> {
+        let file = File::create(path).map_err(|err| format!("Failed to create GIF: {}", err))?;
+        let encoder = GifEncoder::new(
+            BufWriter::new(file),
+            screen_size.width * SCALE,
+            screen_size.height * SCALE,
+            COLORS,
+        )
+        .map_err(|err| format!("Failed to write GIF: {}", err))?;
+
+        Ok(Self {
+            encoder,
+            screen_size,
+            frame_count: 0,
+        })
+    }
+
+    /// Records the screen at the end of a frame, if the frame is one to be recorded.
+    pub fn add_frame(&mut self, display: &Display) -> Result<(), Error> {
+        let recorded = self.frame_count.is_multiple_of(FRAME_INTERVAL)
+            && display.screen_size() == self.screen_size;
+        self.frame_count += 1;
+        if !recorded {
+            return Ok(());
+        }
+
+        let pixels = render::get_scaled_pixels(display, (SCALE, SCALE), false);
+        self.encoder
+            .add_frame(pixels, FRAME_DELAY)
+            .map_err(|err| format!("Failed to write GIF: {}", err).into())
+    }
+
+    /// Writes the last frame and the end of the GIF.
+    pub fn finish(self) -> Result<(), Error> {
+        self.encoder
+            .finish()
+            .map(drop)
+            .map_err(|err| format!("Failed to write GIF: {}", err).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chip8_core::display::{Point, HIRES_SCREEN_SIZE, SCREEN_SIZE};
+
+    #[test]
+    fn test_gif_recording() {
+        let path = std::env::temp_dir().join("chip8-test-recording.gif");
+        let mut recording = GifRecording::create(&path, SCREEN_SIZE).unwrap();
+        let mut display = Display::new();
+        for _ in 0..6 {
+            recording.add_frame(&display).unwrap();
+        }
+        display.draw_sprite(Point { x: 0, y: 0 }, &[0xFF], false);
+        recording.add_frame(&display).unwrap();
+        recording
+            .add_frame(&Display::with_screen_size(HIRES_SCREEN_SIZE))
+            .unwrap();
+        recording.finish().unwrap();
+
+        let gif = std::fs::read(&path).unwrap();
+        assert!(gif.starts_with(b"GIF89a\x00\x01\x80\x00"));
+        assert_eq!(gif.last(), Some(&0x3B));
+        // The two blank frames recorded are merged into one shown for as long as both.
+        assert!(gif
+            .windows(6)
+            .any(|window| window == [0x21, 0xF9, 0x04, 0x00, 10, 0]));
+    }
+}
@@ -0,0 +1,316 @@
+//! Rendering of replays to animations without a terminal.
+
+use crate::{
+    gif::{GifEncoder, COLOR_COUNT},
+    options,
+    replay::Replay,
+};
+use chip8_core::{
+    display::{Display, Point, HIRES_SCREEN_SIZE, SCREEN_SIZE},
+    interpreter::{self, START_POINT},
+    patches::Patches,
+    quirks::Quirks,
+    util, Error, Interpreter, Step,
+};
+use std::{env, fs::File, io::BufWriter, path::PathBuf, time::Duration};
+
+/// The colors of unset pixels, set pixels and the grid.
+pub const COLORS: [[u8; 3]; COLOR_COUNT] = [[0x00; 3], [0xFF; 3], [0x40; 3], [0x00; 3]];
+const UNSET: u8 = 0;
+const SET: u8 = 1;
+const GRID: u8 = 2;
+
+/// The rate at which the timers tick, which a replay has one tick event for.
+const FRAMES_PER_SECOND: u32 = 60;
+
+/// How a replay is rendered.
+struct Settings {
+    gif: PathBuf,
+    /// How many pixels of the GIF every pixel of the screen is wide and high, before applying the aspect ratio.
+    scale: u16,
+    /// The ratio of the width to the height of every pixel of the screen.
+    aspect: (u16, u16),
+    /// Whether to draw lines between the pixels.
+    grid: bool,
+    /// How many frames per second the GIF has. This is at most the 60 frames per second of the replay.
+    frame_rate: u32,
+    /// For how many frames a key counts as held down after it was pressed, like in the terminal.
+    key_hold_frames: u32,
+    quirks: Quirks,
+    patches: Patches,
+    /// Where the program is loaded in memory and starts.
+    load_address: u16,
+}
+
+/// Plays back the replay of the binary given in the arguments and writes every frame to a GIF.
+///
+/// The arguments are the binary, the replay and `--gif <path>`, optionally followed by `--scale <factor>`,
+/// `--aspect <ratio>`, `--grid`, `--fps <frame rate>`, `--key-hold <milliseconds>`, `--quirk <quirk>`, `--patch <patch>`, `--patches <path>`
+/// and `--load-address <address>`, which need to match the recording.
+pub fn run(mut args: env::ArgsOs) -> Result<(), Error> {
+    let binary = crate::get_binary(&mut args)?;
+    let replay = match args.next() {
+        Some(path) => Replay::load(path.as_ref())?,
+        None => return Err("No path to the replay given.".into()),
+    };
+    let settings = parse_settings(args)?;
+
+    if replay.rom_hash != util::hash(&binary) {
+        return Err("The replay was recorded with a different program.".into());
+    }
+
+    let file =
+        File::create(&settings.gif).map_err(|err| format!("Failed to create GIF: {}", err))?;
+    let (pixel_width, pixel_height) = settings.get_pixel_size();
+    let screen_size = if interpreter::is_hires(&binary) {
+        HIRES_SCREEN_SIZE
+    } else {
+        SCREEN_SIZE
+    };
+    let mut encoder = GifEncoder::new(
+        BufWriter::new(file),
+        screen_size.width * pixel_width,
+        screen_size.height * pixel_height,
+        COLORS,
+    )
+    .map_err(|err| format!("Failed to write GIF: {}", err))?;
+
+    let frame_count = play(binary, replay, &settings, |interpreter, frame| {
+        // Only every few frames are kept if the frame rate is lower.
+        if frame % (FRAMES_PER_SECOND / settings.frame_rate) as u64 == 0 {
+            let pixels = get_scaled_pixels(
+                interpreter.display(),
+                settings.get_pixel_size(),
+                settings.grid,
+            );
+            encoder
+                .add_frame(pixels, (100 / settings.frame_rate) as u16)
+                .map_err(|err| format!("Failed to write GIF: {}", err).into())
+        } else {
+            Ok(())
+        }
+    })?;
+    encoder
+        .finish()
+        .map_err(|err| format!("Failed to write GIF: {}", err))?;
+
+    println!(
+        "Rendered {} frames to {}.",
+        frame_count,
+        settings.gif.display()
+    );
+
+    Ok(())
+}
+
+impl Settings {
+    /// Returns how many pixels of the GIF every pixel of the screen is wide and high.
+    fn get_pixel_size(&self) -> (u16, u16) {
+        (self.scale * self.aspect.0, self.scale * self.aspect.1)
+    }
+}
+
+/// Parses an aspect ratio like `2:1`, with the width first.
+fn parse_aspect(string: &str) -> Result<(u16, u16), Error> {
+    string
+        .split_once(':')
+        .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+        .filter(|(width, height)| (1..=4).contains(width) && (1..=4).contains(height))
+        .ok_or_else(|| {
+            format!(
+                "Invalid aspect ratio `{}`. Expected something like `2:1`, with numbers from 1 to 4.",
+                string
+            )
+            .into()
+        })
+}
+
+/// Returns for how many frames a key is held down, rounded up like in the terminal.
+fn get_key_hold_frames(key_hold: Duration) -> u32 {
+    ((key_hold.as_secs_f64() * FRAMES_PER_SECOND as f64).ceil() as u32).max(1)
+}
+
+fn parse_settings(mut args: env::ArgsOs) -> Result<Settings, Error> {
+    let mut gif = None;
+    let mut settings = Settings {
+        gif: PathBuf::new(),
+        scale: 4,
+        aspect: (1, 1),
+        grid: false,
+        frame_rate: 20,
+        key_hold_frames: get_key_hold_frames(options::DEFAULT_KEY_HOLD),
+        quirks: Quirks::default(),
+        patches: Patches::default(),
+        load_address: START_POINT,
+    };
+
+    while let Some(arg) = args.next() {
+        match arg.to_str() {
+            Some(option @ "--gif") => gif = Some(options::get_value(&mut args, option)?.into()),
+            Some(option @ "--scale") => {
+                let value = options::get_value(&mut args, option)?;
+                settings.scale = value
+                    .parse()
+                    .ok()
+                    .filter(|scale| (1..=16).contains(scale))
+                    .ok_or_else(|| format!("Invalid scale `{}`. Expected 1 to 16.", value))?;
+            }
+            Some(option @ "--aspect") => {
+                settings.aspect = parse_aspect(&options::get_value(&mut args, option)?)?;
+            }
+            Some("--grid") => settings.grid = true,
+            Some(option @ "--fps") => {
+                let value = options::get_value(&mut args, option)?;
+                // The frames of the replay are only shown for whole numbers of them.
+                settings.frame_rate = value
+                    .parse()
+                    .ok()
+                    .filter(|frame_rate| {
+                        [1, 2, 3, 4, 5, 6, 10, 12, 15, 20, 30].contains(frame_rate)
+                    })
+                    .ok_or_else(|| {
+                        format!(
+                            "Invalid frame rate `{}`. Expected a divisor of 60 up to 30, like 20.",
+                            value
+                        )
+                    })?;
+            }
+            Some(option @ "--key-hold") => {
+                let key_hold = options::parse_key_hold(&options::get_value(&mut args, option)?)?;
+                settings.key_hold_frames = get_key_hold_frames(key_hold);
+            }
+            Some(option @ "--quirk") => {
+                options::parse_quirk(
+                    &options::get_value(&mut args, option)?,
+                    &mut settings.quirks,
+                )?;
+            }
+            Some(option @ "--patch") => {
+                settings
+                    .patches
+                    .add_from_str(&options::get_value(&mut args, option)?)?;
+            }
+            Some(option @ "--patches") => {
+                let value = options::get_value(&mut args, option)?;
+                settings.patches.load(value.as_ref())?;
+            }
+            Some(option @ "--load-address") => {
+                settings.load_address =
+                    options::parse_load_address(&options::get_value(&mut args, option)?)?;
+            }
+            _ => return Err(format!("Unknown argument: {}", arg.to_string_lossy()).into()),
+        }
+    }
+
+    settings.gif = gif.ok_or("Expected `--gif <path>`.")?;
+    let (pixel_width, pixel_height) = settings.get_pixel_size();
+    if settings.grid && (pixel_width < 2 || pixel_height < 2) {
+        return Err("The grid needs pixels of at least 2x2, e.g. with `--scale 2`.".into());
+    }
+
+    Ok(settings)
+}
+
+/// Plays back the replay, calling the function with the interpreter and the number of the frame at every frame.
+///
+/// The input and the timers follow the replay just like when playing it back in the terminal. Returns the number of
+/// frames once the replay has finished.
+fn play<F>(
+    binary: Vec<u8>,
+    mut replay: Replay,
+    settings: &Settings,
+    mut on_frame: F,
+) -> Result<u64, Error>
+where
+    F: FnMut(&Interpreter, u64) -> Result<(), Error>,
+{
+    let mut interpreter = Interpreter::with_quirks(
+        binary,
+        replay.seed,
+        settings.load_address,
+        settings.quirks.clone(),
+    )?;
+    settings.patches.apply(interpreter.memory_mut())?;
+
+    let mut held_keys = [0; 16];
+    let mut frame = 0;
+
+    while !replay.is_finished() {
+        let instruction = interpreter.instruction_count();
+        let ticks = replay.take_ticks(instruction);
+        let key = replay.next_key(instruction);
+
+        for _ in 0..ticks {
+            on_frame(&interpreter, frame)?;
+            frame += 1;
+
+            interpreter.update_timers();
+            for (key, frames) in held_keys.iter_mut().enumerate() {
+                if *frames > 0 {
+                    *frames -= 1;
+                    if *frames == 0 {
+                        interpreter.set_key_held(key as u8, false);
+                    }
+                }
+            }
+        }
+
+        if let Some(key) = key {
+            held_keys[key as usize] = settings.key_hold_frames;
+            interpreter.set_key_held(key, true);
+        }
+        interpreter.set_key(key);
+
+        match interpreter.step()? {
+            Step::Executed | Step::Breakpoint(_) => {}
+            // The key comes from the replay, unless the replay does not match the program.
+            Step::AwaitingKey => {
+                let next_event = replay.events.front();
+                if next_event.is_none_or(|event| event.instruction != instruction) {
+                    break;
+                }
+            }
+            Step::Ended => break,
+        }
+    }
+
+    Ok(frame)
+}
+
+/// Returns the color indices of the pixels of the screen, row by row, with every pixel scaled up to a rectangle of
+/// the pixel size. With the grid, the right and bottom edge of every rectangle is a grid line.
+pub fn get_scaled_pixels(display: &Display, pixel_size: (u16, u16), grid: bool) -> Vec<u8> {
+    let (pixel_width, pixel_height) = pixel_size;
+
+    let screen_size = display.screen_size();
+    let mut pixels = Vec::new();
+    for y in 0..screen_size.height * pixel_height {
+        for x in 0..screen_size.width * pixel_width {
+            let on_grid =
+                x % pixel_width == pixel_width - 1 || y % pixel_height == pixel_height - 1;
+            pixels.push(if grid && on_grid {
+                GRID
+            } else if display.get(Point {
+                x: x / pixel_width,
+                y: y / pixel_height,
+            }) {
+                SET
+            } else {
+                UNSET
+            });
+        }
+    }
+    pixels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_aspect() {
+        assert_eq!(parse_aspect("2:1").unwrap(), (2, 1));
+        assert!(parse_aspect("0:1").is_err());
+        assert!(parse_aspect("2").is_err());
+    }
+}
@@ -0,0 +1,1064 @@
+use crate::{
+    damage::DamageLog,
+    kitty,
+    options::Options,
+    terminal::{Color, Point, Size, Terminal},
+};
+use chip8_core::{
+    display::{self, Display, SCREEN_SIZE},
+    Error,
+};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use std::convert::TryInto;
+
+/// Draws the display to the terminal.
+#[derive(Debug)]
+pub struct Renderer {
+    mode: Mode,
+    /// The CRT effect, if enabled.
+    crt: Option<Crt>,
+    theme: Theme,
+    /// The colors of pixels set only in the second plane and of pixels set in both planes.
+    plane_colors: [Color; 2],
+    origin: Origin,
+    scale: Scale,
+    /// The color of the area around the display, if it is filled.
+    letterbox: Option<Color>,
+    /// The line shown below the display, if the status bar is enabled.
+    status: Option<String>,
+    /// The size of the screen of the display drawn last, which is larger in the hi-res mode.
+    screen_size: display::Size,
+    /// Where everything written to the terminal is logged, if anywhere.
+    damage_log: Option<DamageLog>,
+    /// Whether the frame around the display is lit, which shows that the sound is playing.
+    border: bool,
+    /// The pixels that sprites turned off, setting VF, and for how many more frames they flash.
+    collisions: Vec<(display::Point, u32)>,
+}
+
+/// The color pixels that sprites turned off flash in.
+const COLLISION_COLOR: Color = Color::Rgb { r: 255, g: 0, b: 0 };
+/// For how many frames pixels that sprites turned off flash.
+const COLLISION_FRAMES: u32 = 10;
+
+/// How many times larger every pixel is drawn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scale {
+    Fixed(u16),
+    /// As large as fits in the terminal.
+    Auto,
+}
+
+/// The largest scale allowed.
+const MAX_SCALE: u16 = 8;
+
+impl Scale {
+    /// Parses a scale like `2` or `auto`.
+    pub fn parse(string: &str) -> Result<Self, Error> {
+        if string == "auto" {
+            return Ok(Self::Auto);
+        }
+
+        string
+            .parse()
+            .ok()
+            .filter(|scale| (1..=MAX_SCALE).contains(scale))
+            .map(Self::Fixed)
+            .ok_or_else(|| {
+                format!(
+                    "Invalid scale `{}`. Expected `auto` or a number from 1 to {}.",
+                    string, MAX_SCALE
+                )
+                .into()
+            })
+    }
+
+    /// Returns the scale for drawing an area of the given size in the terminal of the given size.
+    fn get_factor(self, size: Size, terminal_size: Size) -> u16 {
+        match self {
+            Self::Fixed(scale) => scale,
+            Self::Auto => (terminal_size.width / size.width)
+                .min(terminal_size.height / size.height)
+                .clamp(1, MAX_SCALE),
+        }
+    }
+}
+
+/// Where and how large the display is drawn.
+#[derive(Debug, Clone, Copy)]
+struct Layout {
+    /// The top left corner of the display in the terminal.
+    origin: Point,
+    scale: u16,
+    /// The size of the display in the terminal, without the status bar.
+    size: Size,
+}
+
+/// Where the display is placed in the terminal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Origin {
+    Center,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    /// A fixed offset from the top left corner in characters, moved in as far as needed for the display to fit.
+    Offset(Point),
+}
+
+impl Origin {
+    /// Parses an origin like `center`, `top-left` or an offset like `4,2`.
+    pub fn parse(string: &str) -> Result<Self, Error> {
+        let origin = match string {
+            "center" => Some(Self::Center),
+            "top-left" => Some(Self::TopLeft),
+            "top-right" => Some(Self::TopRight),
+            "bottom-left" => Some(Self::BottomLeft),
+            "bottom-right" => Some(Self::BottomRight),
+            _ => string.split_once(',').and_then(|(x, y)| {
+                Some(Self::Offset(Point {
+                    x: x.trim().parse().ok()?,
+                    y: y.trim().parse().ok()?,
+                }))
+            }),
+        };
+
+        origin.ok_or_else(|| {
+            format!(
+                "Invalid origin `{}`. Expected `center`, `top-left`, `top-right`, `bottom-left`, `bottom-right` or \
+                 an offset like `4,2`.",
+                string
+            )
+            .into()
+        })
+    }
+
+    /// Returns where the top left corner of an area of the given size is placed in the terminal of the given size.
+    fn get_position(self, size: Size, terminal_size: Size) -> Point {
+        let free = Size {
+            width: terminal_size.width.saturating_sub(size.width),
+            height: terminal_size.height.saturating_sub(size.height),
+        };
+
+        match self {
+            Self::Center => Point {
+                x: free.width / 2,
+                y: free.height / 2,
+            },
+            Self::TopLeft => Point { x: 0, y: 0 },
+            Self::TopRight => Point {
+                x: free.width,
+                y: 0,
+            },
+            Self::BottomLeft => Point {
+                x: 0,
+                y: free.height,
+            },
+            Self::BottomRight => Point {
+                x: free.width,
+                y: free.height,
+            },
+            Self::Offset(offset) => Point {
+                x: offset.x.min(free.width),
+                y: offset.y.min(free.height),
+            },
+        }
+    }
+}
+
+/// How the pixels are drawn to the terminal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    /// Every pixel is two full blocks, which makes it roughly square.
+    Blocks,
+    /// Every character is a Braille pattern of 2x4 pixels, which needs a much smaller terminal.
+    ///
+    /// Only the screen is drawn, without the margin around it.
+    Braille,
+    /// The screen is drawn as an image using the graphics protocol of the kitty terminal, which only some terminals
+    /// support.
+    ///
+    /// Only the screen is drawn, without the margin around it.
+    Kitty,
+}
+
+/// The ID of the image of the screen in [`Mode::Kitty`].
+const KITTY_IMAGE_ID: u32 = 1;
+/// How many times larger the image of the screen in [`Mode::Kitty`] is than the screen, so that the terminal does not
+/// blur the pixels much when stretching it.
+const KITTY_SCALE: usize = 4;
+
+/// The width of the pixels of a Braille character.
+const BRAILLE_WIDTH: u16 = 2;
+/// The height of the pixels of a Braille character.
+const BRAILLE_HEIGHT: u16 = 4;
+
+impl Mode {
+    /// Returns the size of the terminal needed to draw a display with the given screen at the normal scale.
+    pub fn get_required_size(self, screen_size: display::Size) -> Size {
+        match self {
+            Self::Blocks => {
+                let size = display::get_size(screen_size);
+                Size {
+                    width: size.width * 2,
+                    height: size.height,
+                }
+            }
+            Self::Braille => Size {
+                width: screen_size.width / BRAILLE_WIDTH,
+                height: screen_size.height / BRAILLE_HEIGHT,
+            },
+            Self::Kitty => Size {
+                width: screen_size.width * 2,
+                height: screen_size.height,
+            },
+        }
+    }
+}
+
+/// Returns the Braille character with the dots of the set pixels, given row by row.
+fn get_braille_character(
+    pixels: [[bool; BRAILLE_WIDTH as usize]; BRAILLE_HEIGHT as usize],
+) -> char {
+    // The bit of every dot, which are not numbered row by row for historical reasons.
+    const DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+    let mut bits = 0;
+    for (row, dots) in pixels.iter().zip(DOTS) {
+        for (pixel, dot) in row.iter().zip(dots) {
+            if *pixel {
+                bits |= dot;
+            }
+        }
+    }
+
+    char::from_u32(0x2800 + bits).unwrap()
+}
+
+/// Groups the points, given row by row, into runs of horizontally adjacent pixels of the same color, each given by its
+/// first point and its length.
+fn get_runs<P>(display: &Display, points: P) -> Vec<(display::Point, u16)>
+where
+    P: IntoIterator<Item = display::Point>,
+{
+    let mut runs: Vec<(display::Point, u16)> = Vec::new();
+    for point in points {
+        if let Some((start, count)) = runs.last_mut() {
+            if start.y == point.y
+                && start.x + *count == point.x
+                && display.get_color(*start) == display.get_color(point)
+            {
+                *count += 1;
+                continue;
+            }
+        }
+        runs.push((point, 1));
+    }
+    runs
+}
+
+/// The colors of the pixels. `None` means the terminal's default color.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Theme {
+    /// The color of set pixels.
+    pub foreground: Option<Color>,
+    /// The color of unset pixels.
+    pub background: Option<Color>,
+}
+
+impl Theme {
+    /// Parses a preset like `green` or custom colors like `#33FF66,#001100`, with the color of set pixels first.
+    pub fn parse(string: &str) -> Result<Self, Error> {
+        let theme = match string {
+            "default" => Some(Self::default()),
+            "white" => Self::from_rgb(0xFFFFFF, 0x000000),
+            "green" => Self::from_rgb(0x33FF66, 0x001100),
+            "amber" => Self::from_rgb(0xFFB000, 0x1A0F00),
+            "lcd" => Self::from_rgb(0x0F380F, 0x9BBC0F),
+            _ => string.split_once(',').and_then(|(foreground, background)| {
+                Some(Self {
+                    foreground: Some(parse_hex_color(foreground)?),
+                    background: Some(parse_hex_color(background)?),
+                })
+            }),
+        };
+
+        theme.ok_or_else(|| {
+            format!(
+                "Invalid theme `{}`. Expected `default`, `white`, `green`, `amber`, `lcd` or colors like \
+                 `#33FF66,#001100`.",
+                string
+            )
+            .into()
+        })
+    }
+
+    fn from_rgb(foreground: u32, background: u32) -> Option<Self> {
+        Some(Self {
+            foreground: Some(get_rgb_color(foreground)),
+            background: Some(get_rgb_color(background)),
+        })
+    }
+}
+
+fn get_rgb_color(rgb: u32) -> Color {
+    Color::Rgb {
+        r: (rgb >> 16) as u8,
+        g: (rgb >> 8) as u8,
+        b: rgb as u8,
+    }
+}
+
+/// Returns the theme and the colors of pixels set in the second plane, as configured in the options.
+fn get_colors(options: &Options) -> (Theme, [Color; 2]) {
+    match options.plane_colors {
+        Some([background, foreground, second, both]) => (
+            Theme {
+                foreground: Some(foreground),
+                background: Some(background),
+            },
+            [second, both],
+        ),
+        None => (options.theme, DEFAULT_PLANE_COLORS.map(get_rgb_color)),
+    }
+}
+
+/// Returns the colors of pixels by the planes they are set in, for drawing to a window instead of the terminal.
+///
+/// The terminal's default colors are white on black there.
+#[cfg(any(feature = "sdl2", feature = "window"))]
+pub fn get_palette(options: &Options) -> [Color; 4] {
+    let (theme, [second, both]) = get_colors(options);
+    [
+        theme.background.unwrap_or_else(|| get_rgb_color(0x000000)),
+        theme.foreground.unwrap_or_else(|| get_rgb_color(0xFFFFFF)),
+        second,
+        both,
+    ]
+}
+
+/// The colors of pixels set only in the second plane and of pixels set in both planes, which only XO-CHIP programs
+/// draw, if not configured.
+const DEFAULT_PLANE_COLORS: [u32; 2] = [0xFF6600, 0x662200];
+
+/// Parses the four colors of the pixels of XO-CHIP programs, like `#000000,#FFFFFF,#FF6600,#662200`: the colors of
+/// pixels set in no plane, only the first plane, only the second plane and both planes.
+pub fn parse_plane_colors(string: &str) -> Result<[Color; 4], Error> {
+    let colors: Option<Vec<Color>> = string.split(',').map(parse_hex_color).collect();
+
+    colors
+        .and_then(|colors| colors.try_into().ok())
+        .ok_or_else(|| {
+            format!(
+                "Invalid plane colors `{}`. Expected four colors like `#000000,#FFFFFF,#FF6600,#662200`.",
+                string
+            )
+            .into()
+        })
+}
+
+/// Parses a color like `#33FF66`.
+pub fn parse_hex_color(string: &str) -> Option<Color> {
+    let hex = string.trim().strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+
+    u32::from_str_radix(hex, 16).ok().map(get_rgb_color)
+}
+
+/// Returns the components of the color if it is an RGB color, otherwise the given default.
+fn get_rgb(color: Option<Color>, default: [u8; 3]) -> [u8; 3] {
+    match color {
+        Some(Color::Rgb { r, g, b }) => [r, g, b],
+        _ => default,
+    }
+}
+
+/// A cosmetic filter imitating a CRT screen by dimming every other row and adding some noise.
+#[derive(Debug)]
+struct Crt {
+    rng: SmallRng,
+}
+
+impl Crt {
+    /// Returns the color of a set pixel in the given row, based on the color of set pixels if it is known.
+    fn get_color(&mut self, y: u16, foreground: Option<Color>) -> Color {
+        let scanline_brightness: u8 = if y.is_multiple_of(2) { 255 } else { 180 };
+        let brightness = (scanline_brightness - self.rng.gen_range(0..40)) as u16;
+
+        let (r, g, b) = match foreground {
+            Some(Color::Rgb { r, g, b }) => (r, g, b),
+            _ => (255, 255, 255),
+        };
+        let dim = |component: u8| (component as u16 * brightness / 255) as u8;
+
+        Color::Rgb {
+            r: dim(r),
+            g: dim(g),
+            b: dim(b),
+        }
+    }
+}
+
+impl Renderer {
+    /// Creates a renderer as configured in the options.
+    pub fn new(options: &Options) -> Self {
+        let crt = if options.crt && !options.reduced_motion {
+            Some(Crt {
+                rng: SmallRng::from_entropy(),
+            })
+        } else {
+            None
+        };
+
+        let (theme, plane_colors) = get_colors(options);
+
+        Self {
+            mode: options.render_mode,
+            crt,
+            theme,
+            plane_colors,
+            origin: options.origin,
+            // Braille characters cannot be scaled.
+            scale: if options.render_mode == Mode::Braille {
+                Scale::Fixed(1)
+            } else {
+                options.scale
+            },
+            letterbox: options.letterbox,
+            status: if options.status_bar {
+                Some(String::new())
+            } else {
+                None
+            },
+            screen_size: SCREEN_SIZE,
+            damage_log: None,
+            border: false,
+            collisions: Vec::new(),
+        }
+    }
+
+    pub fn set_damage_log(&mut self, damage_log: DamageLog) {
+        self.damage_log = Some(damage_log);
+    }
+
+    /// Finishes the frame in the damage log, if any.
+    pub fn end_frame(&mut self) -> Result<(), Error> {
+        match &mut self.damage_log {
+            Some(damage_log) => damage_log.end_frame(),
+            None => Ok(()),
+        }
+    }
+
+    /// Moves the cursor of the terminal, logging it if enabled.
+    fn set_cursor(&mut self, terminal: &mut dyn Terminal, point: Point) {
+        if let Some(damage_log) = &mut self.damage_log {
+            damage_log.record_move(point);
+        }
+        terminal.set_cursor(point);
+    }
+
+    /// Writes the text to the terminal, logging it if enabled.
+    fn write(&mut self, terminal: &mut dyn Terminal, text: &str) {
+        if let Some(damage_log) = &mut self.damage_log {
+            damage_log.record_write(text);
+        }
+        terminal.write(text);
+    }
+
+    /// Returns where and how large the display is drawn in the terminal.
+    fn get_layout(&self, terminal: &mut dyn Terminal) -> Layout {
+        let mut size = self.mode.get_required_size(self.screen_size);
+        // The status bar takes up one more line.
+        let status_height = self.status.is_some() as u16;
+        let scale = self.scale.get_factor(
+            Size {
+                width: size.width,
+                height: size.height + status_height,
+            },
+            terminal.size(),
+        );
+        size.width *= scale;
+        size.height *= scale;
+        let required_size = Size {
+            width: size.width,
+            height: size.height + status_height,
+        };
+        crate::await_fitting_window_width(terminal, required_size);
+        crate::await_fitting_window_height(terminal, required_size);
+
+        Layout {
+            origin: self.origin.get_position(required_size, terminal.size()),
+            scale,
+            size,
+        }
+    }
+
+    /// Returns the color of set pixels in the given row, if it is not the default.
+    fn get_foreground_color(&mut self, y: u16) -> Option<Color> {
+        match &mut self.crt {
+            Some(crt) => Some(crt.get_color(y, self.theme.foreground)),
+            None => self.theme.foreground,
+        }
+    }
+
+    /// Returns the color of pixels of the given color of the display in the given row, if it is not the default.
+    fn get_pixel_color(&mut self, color: u8, y: u16) -> Option<Color> {
+        match color {
+            0 => self.theme.background,
+            1 => self.get_foreground_color(y),
+            _ => {
+                let plane_color = self.plane_colors[color as usize - 2];
+                match &mut self.crt {
+                    Some(crt) => Some(crt.get_color(y, Some(plane_color))),
+                    None => Some(plane_color),
+                }
+            }
+        }
+    }
+
+    /// Draws the given number of pixels of the same color in a row, starting at the given point, to the terminal.
+    fn draw_pixels(
+        &mut self,
+        terminal: &mut dyn Terminal,
+        display: &Display,
+        layout: Layout,
+        point: display::Point,
+        count: u16,
+    ) {
+        let color = self.get_pixel_color(display.get_color(point), point.y);
+        // Unset pixels are drawn as blocks too if they have a background color.
+        let cell = if display.get(point) || color.is_some() {
+            "██"
+        } else {
+            "  "
+        };
+        let row = cell.repeat(layout.scale as usize * count as usize);
+
+        if let Some(color) = color {
+            terminal.set_foreground_color(color);
+        }
+        self.draw_row(terminal, layout, point, &row);
+        if color.is_some() {
+            terminal.reset_colors();
+        }
+    }
+
+    /// Writes the characters of a row of pixels starting at the given point, as many times as the pixels are high.
+    fn draw_row(
+        &mut self,
+        terminal: &mut dyn Terminal,
+        layout: Layout,
+        point: display::Point,
+        row: &str,
+    ) {
+        for offset in 0..layout.scale {
+            self.set_cursor(
+                terminal,
+                Point {
+                    x: layout.origin.x + point.x * 2 * layout.scale,
+                    y: layout.origin.y + point.y * layout.scale + offset,
+                },
+            );
+            self.write(terminal, row);
+        }
+    }
+
+    fn is_flashing(&self, point: display::Point) -> bool {
+        self.collisions
+            .iter()
+            .any(|(flashing, _)| *flashing == point)
+    }
+
+    /// Lets the pixels that sprites turned off since the last frame flash for a few frames and draws the pixels that
+    /// stopped flashing as they are again.
+    pub fn update_collisions(
+        &mut self,
+        terminal: &mut dyn Terminal,
+        display: &Display,
+        collisions: Vec<display::Point>,
+    ) {
+        if collisions.is_empty() && self.collisions.is_empty() {
+            return;
+        }
+
+        let mut expired = Vec::new();
+        self.collisions.retain_mut(|(point, frames)| {
+            *frames -= 1;
+            if *frames == 0 {
+                expired.push(*point);
+            }
+            *frames > 0
+        });
+        for point in collisions {
+            match self
+                .collisions
+                .iter_mut()
+                .find(|(flashing, _)| *flashing == point)
+            {
+                Some((_, frames)) => *frames = COLLISION_FRAMES,
+                None => self.collisions.push((point, COLLISION_FRAMES)),
+            }
+        }
+
+        let layout = self.get_layout(terminal);
+        match self.mode {
+            Mode::Blocks => {
+                for point in expired {
+                    self.draw_pixels(terminal, display, layout, point, 1);
+                }
+                self.draw_collisions(terminal, layout);
+            }
+            Mode::Braille => {
+                let flashing: Vec<display::Point> =
+                    self.collisions.iter().map(|(point, _)| *point).collect();
+                for point in expired.into_iter().chain(flashing) {
+                    self.draw_braille_character(terminal, display, layout.origin, point);
+                }
+            }
+            Mode::Kitty => self.draw_image(terminal, display, layout),
+        }
+        terminal.flush();
+    }
+
+    /// Draws the flashing pixels that sprites turned off.
+    fn draw_collisions(&mut self, terminal: &mut dyn Terminal, layout: Layout) {
+        let row = "██".repeat(layout.scale as usize);
+        terminal.set_foreground_color(COLLISION_COLOR);
+        for (point, _) in self.collisions.clone() {
+            self.draw_row(terminal, layout, point, &row);
+        }
+        terminal.reset_colors();
+    }
+
+    /// Groups the points into runs of pixels to draw at once. With the CRT effect, every pixel has its own noise and
+    /// is a run of its own.
+    fn get_runs<P>(&self, display: &Display, points: P) -> Vec<(display::Point, u16)>
+    where
+        P: IntoIterator<Item = display::Point>,
+    {
+        if self.crt.is_some() {
+            points.into_iter().map(|point| (point, 1)).collect()
+        } else {
+            get_runs(display, points)
+        }
+    }
+
+    /// Draws the Braille character containing the pixel at the given point to the terminal.
+    fn draw_braille_character(
+        &mut self,
+        terminal: &mut dyn Terminal,
+        display: &Display,
+        origin: Point,
+        point: display::Point,
+    ) {
+        let cell = Point {
+            x: point.x / BRAILLE_WIDTH,
+            y: point.y / BRAILLE_HEIGHT,
+        };
+        let screen_size = display.screen_size();
+        if cell.x >= screen_size.width / BRAILLE_WIDTH
+            || cell.y >= screen_size.height / BRAILLE_HEIGHT
+        {
+            return;
+        }
+
+        let mut pixels = [[false; BRAILLE_WIDTH as usize]; BRAILLE_HEIGHT as usize];
+        let mut flashing = false;
+        for (y, row) in pixels.iter_mut().enumerate() {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                let point = display::Point {
+                    x: cell.x * BRAILLE_WIDTH + x as u16,
+                    y: cell.y * BRAILLE_HEIGHT + y as u16,
+                };
+                // Flashing pixels are drawn set, in the color of collisions.
+                let collision = self.is_flashing(point);
+                flashing |= collision;
+                *pixel = display.get(point) || collision;
+            }
+        }
+
+        self.set_cursor(
+            terminal,
+            Point {
+                x: origin.x + cell.x,
+                y: origin.y + cell.y,
+            },
+        );
+        let color = if flashing {
+            Some(COLLISION_COLOR)
+        } else {
+            self.get_foreground_color(cell.y * BRAILLE_HEIGHT)
+        };
+        if let Some(color) = color {
+            terminal.set_foreground_color(color);
+        }
+        if let Some(color) = self.theme.background {
+            terminal.set_background_color(color);
+        }
+        self.write(terminal, &get_braille_character(pixels).to_string());
+        terminal.reset_colors();
+    }
+
+    /// Draws the whole screen as one image using the graphics protocol of the kitty terminal.
+    fn draw_image(&mut self, terminal: &mut dyn Terminal, display: &Display, layout: Layout) {
+        let screen_size = display.screen_size();
+        let width = screen_size.width as usize * KITTY_SCALE;
+        let height = screen_size.height as usize * KITTY_SCALE;
+        let background = get_rgb(self.theme.background, [0, 0, 0]);
+
+        let mut pixels = Vec::with_capacity(width * height * 3);
+        for y in 0..screen_size.height {
+            let colors = [
+                background,
+                get_rgb(self.get_pixel_color(1, y), [255, 255, 255]),
+                get_rgb(self.get_pixel_color(2, y), [0, 0, 0]),
+                get_rgb(self.get_pixel_color(3, y), [0, 0, 0]),
+            ];
+            let mut row = Vec::with_capacity(width * 3);
+            for x in 0..screen_size.width {
+                let point = display::Point { x, y };
+                let color = if self.is_flashing(point) {
+                    get_rgb(Some(COLLISION_COLOR), [255, 0, 0])
+                } else {
+                    colors[display.get_color(point) as usize]
+                };
+                for _ in 0..KITTY_SCALE {
+                    row.extend_from_slice(&color);
+                }
+            }
+            for _ in 0..KITTY_SCALE {
+                pixels.extend_from_slice(&row);
+            }
+        }
+
+        self.set_cursor(terminal, layout.origin);
+        self.write(
+            terminal,
+            &kitty::encode_image(
+                KITTY_IMAGE_ID,
+                &pixels,
+                width,
+                height,
+                layout.size.width,
+                layout.size.height,
+            ),
+        );
+    }
+
+    /// Shows the text in the status bar below the display, if it is enabled and the text changed.
+    pub fn set_status(&mut self, terminal: &mut dyn Terminal, text: String) {
+        if self.status.as_ref().is_some_and(|status| *status != text) {
+            self.status = Some(text);
+            let layout = self.get_layout(terminal);
+            self.draw_status(terminal, layout);
+            terminal.flush();
+        }
+    }
+
+    /// Draws the status bar below the display, cut off at the width of the display.
+    fn draw_status(&mut self, terminal: &mut dyn Terminal, layout: Layout) {
+        if let Some(status) = &self.status {
+            let status: String = status.chars().take(layout.size.width as usize).collect();
+            self.set_cursor(
+                terminal,
+                Point {
+                    x: layout.origin.x,
+                    y: layout.origin.y + layout.size.height,
+                },
+            );
+            self.write(
+                terminal,
+                &format!("{:<width$}", status, width = layout.size.width as usize),
+            );
+        }
+    }
+
+    /// Lights up or clears the frame around the display, if it changed.
+    pub fn set_border(&mut self, terminal: &mut dyn Terminal, lit: bool) {
+        if self.border != lit {
+            self.border = lit;
+            let layout = self.get_layout(terminal);
+            self.draw_border(terminal, layout);
+            terminal.flush();
+        }
+    }
+
+    /// Draws the frame of one cell around the display and the status bar, lit or cleared. Only the parts with room
+    /// in the terminal are drawn.
+    fn draw_border(&mut self, terminal: &mut dyn Terminal, layout: Layout) {
+        let status_height = self.status.is_some() as u16;
+        let left = layout.origin.x.checked_sub(1);
+        let top = layout.origin.y.checked_sub(1);
+        let right =
+            Some(layout.origin.x + layout.size.width).filter(|x| *x < terminal.size().width);
+        let bottom = Some(layout.origin.y + layout.size.height + status_height)
+            .filter(|y| *y < terminal.size().height);
+        let x_range = left.unwrap_or(layout.origin.x)..=right.unwrap_or(terminal.size().width - 1);
+        let y_range = top.unwrap_or(layout.origin.y)..=bottom.unwrap_or(terminal.size().height - 1);
+
+        let cell = if self.border {
+            if let Some(color) = self.theme.foreground {
+                terminal.set_foreground_color(color);
+            }
+            "█"
+        } else {
+            if let Some(color) = self.letterbox {
+                terminal.set_background_color(color);
+            }
+            " "
+        };
+        for y in [top, bottom].iter().flatten() {
+            self.set_cursor(
+                terminal,
+                Point {
+                    x: *x_range.start(),
+                    y: *y,
+                },
+            );
+            self.write(terminal, &cell.repeat(x_range.clone().count()));
+        }
+        for x in [left, right].iter().flatten() {
+            for y in y_range.clone() {
+                self.set_cursor(terminal, Point { x: *x, y });
+                self.write(terminal, cell);
+            }
+        }
+        terminal.reset_colors();
+    }
+
+    /// Fills the whole terminal with the letterbox color, if any.
+    fn fill_letterbox(&mut self, terminal: &mut dyn Terminal) {
+        if let Some(color) = self.letterbox {
+            terminal.set_background_color(color);
+            let line = " ".repeat(terminal.size().width as usize);
+            for y in 0..terminal.size().height {
+                self.set_cursor(terminal, Point { x: 0, y });
+                self.write(terminal, &line);
+            }
+            terminal.reset_colors();
+        }
+    }
+
+    /// Draws the whole display again, e.g. after something else has been drawn over it.
+    pub fn redraw(&mut self, terminal: &mut dyn Terminal, display: &Display) {
+        self.screen_size = display.screen_size();
+        let layout = self.get_layout(terminal);
+        self.fill_letterbox(terminal);
+
+        match self.mode {
+            Mode::Blocks => {
+                let size = display.size();
+                let points = (0..size.height)
+                    .flat_map(|y| (0..size.width).map(move |x| display::Point { x, y }));
+                for (point, count) in self.get_runs(display, points) {
+                    self.draw_pixels(terminal, display, layout, point, count);
+                }
+                self.draw_collisions(terminal, layout);
+            }
+            Mode::Braille => {
+                let screen_size = display.screen_size();
+                for y in (0..screen_size.height).step_by(BRAILLE_HEIGHT as usize) {
+                    for x in (0..screen_size.width).step_by(BRAILLE_WIDTH as usize) {
+                        self.draw_braille_character(
+                            terminal,
+                            display,
+                            layout.origin,
+                            display::Point { x, y },
+                        );
+                    }
+                }
+            }
+            Mode::Kitty => self.draw_image(terminal, display, layout),
+        }
+        self.draw_status(terminal, layout);
+        if self.border {
+            self.draw_border(terminal, layout);
+        }
+
+        terminal.flush();
+    }
+
+    /// Draws the pixels that changed since the last time and flushes them all at once.
+    pub fn draw_changes(&mut self, terminal: &mut dyn Terminal, display: &mut Display) {
+        let changes = display.take_changes();
+        if changes.is_empty() {
+            return;
+        }
+
+        let layout = self.get_layout(terminal);
+        match self.mode {
+            // After scrolling, most pixels change, so whole runs of them are drawn at once.
+            Mode::Blocks => {
+                for (point, count) in self.get_runs(display, changes) {
+                    self.draw_pixels(terminal, display, layout, point, count);
+                }
+            }
+            Mode::Braille => {
+                // Every character is only drawn once, even if several of its pixels changed.
+                let mut cells: Vec<display::Point> = changes
+                    .iter()
+                    .map(|point| display::Point {
+                        x: point.x / BRAILLE_WIDTH * BRAILLE_WIDTH,
+                        y: point.y / BRAILLE_HEIGHT * BRAILLE_HEIGHT,
+                    })
+                    .collect();
+                cells.sort_by_key(|cell| (cell.y, cell.x));
+                cells.dedup();
+                for cell in cells {
+                    self.draw_braille_character(terminal, display, layout.origin, cell);
+                }
+            }
+            // The image is replaced as a whole.
+            Mode::Kitty => self.draw_image(terminal, display, layout),
+        }
+
+        terminal.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_theme() {
+        assert_eq!(Theme::parse("default").unwrap(), Theme::default());
+        assert_eq!(
+            Theme::parse("#33FF66, #000000").unwrap(),
+            Theme {
+                foreground: Some(Color::Rgb {
+                    r: 0x33,
+                    g: 0xFF,
+                    b: 0x66
+                }),
+                background: Some(Color::Rgb { r: 0, g: 0, b: 0 }),
+            }
+        );
+        assert!(Theme::parse("green").is_ok());
+        assert!(Theme::parse("#33FF66").is_err());
+        assert!(Theme::parse("#33FF6,#000000").is_err());
+        assert!(Theme::parse("purple").is_err());
+    }
+
+    #[test]
+    fn test_parse_plane_colors() {
+        let colors = parse_plane_colors("#000000,#FFFFFF,#FF6600,#662200").unwrap();
+        assert_eq!(colors[0], Color::Rgb { r: 0, g: 0, b: 0 });
+        assert_eq!(
+            colors[3],
+            Color::Rgb {
+                r: 0x66,
+                g: 0x22,
+                b: 0
+            }
+        );
+        assert!(parse_plane_colors("#000000,#FFFFFF,#FF6600").is_err());
+        assert!(parse_plane_colors("#000000,#FFFFFF,#FF6600,orange").is_err());
+    }
+
+    #[test]
+    fn test_origin() {
+        let size = Size {
+            width: 20,
+            height: 10,
+        };
+        let terminal_size = Size {
+            width: 100,
+            height: 30,
+        };
+
+        assert_eq!(
+            Origin::parse("center")
+                .unwrap()
+                .get_position(size, terminal_size),
+            Point { x: 40, y: 10 }
+        );
+        assert_eq!(
+            Origin::parse("bottom-right")
+                .unwrap()
+                .get_position(size, terminal_size),
+            Point { x: 80, y: 20 }
+        );
+        // The display is kept within the terminal.
+        assert_eq!(
+            Origin::parse("90, 2")
+                .unwrap()
+                .get_position(size, terminal_size),
+            Point { x: 80, y: 2 }
+        );
+        assert!(Origin::parse("left").is_err());
+    }
+
+    #[test]
+    fn test_scale() {
+        let size = Mode::Blocks.get_required_size(SCREEN_SIZE);
+        let terminal_size = Size {
+            width: 400,
+            height: 100,
+        };
+
+        assert_eq!(
+            Scale::parse("3").unwrap().get_factor(size, terminal_size),
+            3
+        );
+        assert_eq!(
+            Scale::parse("auto")
+                .unwrap()
+                .get_factor(size, terminal_size),
+            2
+        );
+        // The display is drawn at the normal scale if it does not fit at all.
+        assert_eq!(
+            Scale::Auto.get_factor(
+                size,
+                Size {
+                    width: 10,
+                    height: 10
+                }
+            ),
+            1
+        );
+        assert!(Scale::parse("0").is_err());
+        assert!(Scale::parse("100").is_err());
+    }
+
+    #[test]
+    fn test_get_braille_character() {
+        assert_eq!(get_braille_character([[false; 2]; 4]), '⠀');
+        assert_eq!(get_braille_character([[true; 2]; 4]), '⣿');
+        assert_eq!(
+            get_braille_character([[true, false], [false, false], [false, false], [false, true]]),
+            '⢁'
+        );
+    }
+
+    #[test]
+    fn test_get_runs() {
+        let mut display = Display::new();
+        display.draw_sprite(display::Point { x: 0, y: 0 }, &[0xE0], false);
+        let point = |x, y| display::Point { x, y };
+
+        assert_eq!(
+            get_runs(
+                &display,
+                vec![
+                    point(0, 0),
+                    point(1, 0),
+                    point(2, 0),
+                    point(3, 0),
+                    point(4, 0),
+                    point(6, 0),
+                    point(0, 1)
+                ]
+            ),
+            vec![
+                (point(0, 0), 3),
+                (point(3, 0), 2),
+                (point(6, 0), 1),
+                (point(0, 1), 1)
+            ]
+        );
+    }
+}
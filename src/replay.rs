@@ -0,0 +1,330 @@
+use chip8_core::{
+    layout::{Field, Kind, Layout},
+    util::{self, Reader},
+    Error,
+};
+use std::{
+    collections::VecDeque,
+    fs::{self, File},
+    io::{self, Write},
+    path::Path,
+};
+
+/// The bytes every replay file starts with.
+const MAGIC: &[u8; 8] = b"CH8INPUT";
+const VERSION: u8 = 3;
+
+/// The key of events that are not key presses but mark that the timers ticked.
+///
+/// The timers tick in real time, so they need to be recorded too for the run to be reproduced.
+pub const TICK: u8 = 0xFF;
+
+/// The layout of replay files, which needs to be updated along with [`Recorder`].
+pub const LAYOUT: Layout = Layout {
+    name: "replay",
+    extension: "c8rec",
+    magic: MAGIC,
+    version: VERSION,
+    header: &[
+        Field {
+            name: "seed",
+            kind: Kind::U64,
+            description: "The seed of the random number generator.",
+        },
+        Field {
+            name: "rom_hash",
+            kind: Kind::U64,
+            description: "The hash of the program the replay was recorded with.",
+        },
+    ],
+    payload: &[Field {
+        name: "events",
+        kind: Kind::List(
+            None,
+            &[
+                Field {
+                    name: "instruction",
+                    kind: Kind::U64,
+                    description: "The number of instructions executed before the event.",
+                },
+                Field {
+                    name: "key",
+                    kind: Kind::U8,
+                    description: "The key pressed, or 255 for a tick of the timers.",
+                },
+            ],
+        ),
+        description: "The key presses and ticks of the timers, in order.",
+    }],
+};
+
+/// A key pressed on the keypad, or a tick of the timers, along with when it happened.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyEvent {
+    /// The number of instructions executed before the key was pressed.
+    pub instruction: u64,
+    /// The key or [`TICK`].
+    pub key: u8,
+}
+
+impl KeyEvent {
+    fn to_bytes(self) -> [u8; 9] {
+        let mut bytes = [0; 9];
+        bytes[..8].copy_from_slice(&self.instruction.to_be_bytes());
+        bytes[8] = self.key;
+        bytes
+    }
+}
+
+fn get_header(seed: u64, rom_hash: u64) -> Vec<u8> {
+    let mut header = MAGIC.to_vec();
+    header.push(VERSION);
+    header.extend_from_slice(&seed.to_be_bytes());
+    header.extend_from_slice(&rom_hash.to_be_bytes());
+    header
+}
+
+/// Writes key events to a replay file as they happen.
+///
+/// After the header, the events are compressed with zstd along with a checksum, which is written once the recording
+/// is finished. This happens when the recorder is dropped at the latest.
+pub struct Recorder {
+    encoder: Option<zstd::Encoder<'static, File>>,
+}
+
+impl Recorder {
+    /// Creates the replay file for a run of the program with the given hash and random number generator seed.
+    pub fn create(path: &Path, seed: u64, rom_hash: u64) -> Result<Self, Error> {
+        let create = || -> io::Result<_> {
+            let mut file = File::create(path)?;
+            file.write_all(&get_header(seed, rom_hash))?;
+            let mut encoder = zstd::Encoder::new(file, 0)?;
+            encoder.include_checksum(true)?;
+            Ok(encoder)
+        };
+        let encoder = create().map_err(|err| format!("Failed to create replay: {}", err))?;
+
+        Ok(Self {
+            encoder: Some(encoder),
+        })
+    }
+
+    pub fn record(&mut self, event: KeyEvent) -> Result<(), Error> {
+        let encoder = self
+            .encoder
+            .as_mut()
+            .ok_or("The replay was already finished.")?;
+
+        encoder
+            .write_all(&event.to_bytes())
+            .map_err(|err| format!("Failed to write replay: {}", err).into())
+    }
+
+    /// Writes the rest of the replay. No more events can be recorded after this.
+    pub fn finish(&mut self) -> Result<(), Error> {
+        if let Some(encoder) = self.encoder.take() {
+            encoder
+                .finish()
+                .map_err(|err| format!("Failed to write replay: {}", err))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        // Errors can only be handled by finishing explicitly.
+        let _ = self.finish();
+    }
+}
+
+/// A recorded run that can be played back.
+#[derive(Debug, PartialEq)]
+pub struct Replay {
+    /// The seed of the random number generator, which needs to be the same for the run to be reproduced.
+    pub seed: u64,
+    /// The hash of the program the replay was recorded with.
+    pub rom_hash: u64,
+    pub events: VecDeque<KeyEvent>,
+}
+
+impl Replay {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader::new(bytes);
+
+        if reader.read(MAGIC.len())? != MAGIC {
+            return Err("This is not a replay.".into());
+        }
+        let version = reader.read_u8()?;
+        if version != VERSION {
+            return Err(format!("Unsupported replay version {}.", version).into());
+        }
+
+        let seed = reader.read_u64()?;
+        let rom_hash = reader.read_u64()?;
+
+        let events_bytes = util::decompress(reader.read_to_end())
+            .map_err(|err| format!("Failed to load replay: {}", err))?;
+        let mut reader = Reader::new(&events_bytes);
+        let mut events = VecDeque::new();
+        while !reader.is_empty() {
+            events.push_back(KeyEvent {
+                instruction: reader.read_u64()?,
+                key: reader.read_u8()?,
+            });
+        }
+
+        Ok(Self {
+            seed,
+            rom_hash,
+            events,
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let bytes = fs::read(path).map_err(|err| format!("Failed to read replay: {}", err))?;
+
+        Self::from_bytes(&bytes)
+    }
+
+    /// Returns the key pressed at the given instruction, if any.
+    pub fn next_key(&mut self, instruction: u64) -> Option<u8> {
+        let event = self.events.front()?;
+        if event.instruction == instruction && event.key != TICK {
+            self.events.pop_front().map(|event| event.key)
+        } else {
+            None
+        }
+    }
+
+    /// Returns how many times the timers ticked at the given instruction before the next key press.
+    pub fn take_ticks(&mut self, instruction: u64) -> usize {
+        let mut ticks = 0;
+        while let Some(event) = self.events.front() {
+            if event.instruction != instruction || event.key != TICK {
+                break;
+            }
+            self.events.pop_front();
+            ticks += 1;
+        }
+        ticks
+    }
+
+    /// Returns whether all events have been played back.
+    pub fn is_finished(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let path = std::env::temp_dir().join("chip8-test-roundtrip.c8rec");
+        let events = [
+            KeyEvent {
+                instruction: 5,
+                key: 0xA,
+            },
+            KeyEvent {
+                instruction: 1000,
+                key: 0x1,
+            },
+        ];
+
+        let mut recorder = Recorder::create(&path, 0xDEAD_BEEF, 0x1234).unwrap();
+        for event in events {
+            recorder.record(event).unwrap();
+        }
+        drop(recorder);
+
+        let replay = Replay::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(replay.seed, 0xDEAD_BEEF);
+        assert_eq!(replay.rom_hash, 0x1234);
+        assert_eq!(replay.events, events);
+    }
+
+    #[test]
+    fn test_corrupted() {
+        let path = std::env::temp_dir().join("chip8-test-corrupted.c8rec");
+        let mut recorder = Recorder::create(&path, 0, 0).unwrap();
+        recorder
+            .record(KeyEvent {
+                instruction: 5,
+                key: 0xA,
+            })
+            .unwrap();
+        recorder.finish().unwrap();
+
+        let mut bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(get_header(0, 0).len(), LAYOUT.get_header_size());
+        assert!(Replay::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        *bytes.last_mut().unwrap() ^= 0x01;
+        assert!(Replay::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_next_key() {
+        let mut replay = Replay {
+            seed: 0,
+            rom_hash: 0,
+            events: vec![
+                KeyEvent {
+                    instruction: 2,
+                    key: 0x4,
+                },
+                KeyEvent {
+                    instruction: 2,
+                    key: 0x5,
+                },
+            ]
+            .into(),
+        };
+
+        assert_eq!(replay.next_key(1), None);
+        assert_eq!(replay.next_key(2), Some(0x4));
+        assert_eq!(replay.next_key(2), Some(0x5));
+        assert!(replay.is_finished());
+    }
+
+    #[test]
+    fn test_take_ticks() {
+        let mut replay = Replay {
+            seed: 0,
+            rom_hash: 0,
+            events: vec![
+                KeyEvent {
+                    instruction: 3,
+                    key: TICK,
+                },
+                KeyEvent {
+                    instruction: 3,
+                    key: TICK,
+                },
+                KeyEvent {
+                    instruction: 3,
+                    key: 0x7,
+                },
+                KeyEvent {
+                    instruction: 3,
+                    key: TICK,
+                },
+            ]
+            .into(),
+        };
+
+        assert_eq!(replay.take_ticks(2), 0);
+        assert_eq!(replay.next_key(3), None);
+        assert_eq!(replay.take_ticks(3), 2);
+        assert_eq!(replay.next_key(3), Some(0x7));
+        assert_eq!(replay.take_ticks(3), 1);
+        assert!(replay.is_finished());
+    }
+}
@@ -0,0 +1,69 @@
+use chip8_core::savestate::State;
+use std::collections::VecDeque;
+
+/// Keeps a history of recent states so that the program can be rewound.
+#[derive(Debug)]
+pub struct History {
+    states: VecDeque<State>,
+    capacity: usize,
+}
+
+impl History {
+    /// Creates a history that keeps up to `capacity` snapshots.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            states: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Adds the snapshot to the history, forgetting the oldest one if the history is full.
+    pub fn push(&mut self, state: State) {
+        if self.states.len() == self.capacity {
+            self.states.pop_front();
+        }
+        self.states.push_back(state);
+    }
+
+    /// Removes the latest snapshot from the history and returns it.
+    pub fn pop(&mut self) -> Option<State> {
+        self.states.pop_back()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_state(pc: u16) -> State {
+        State {
+            rom_hash: 0,
+            pc,
+            gpr: [0; 16],
+            i: 0,
+            stack: Vec::new(),
+            memory: Vec::new(),
+            delay_timer: 0,
+            sound_timer: 0,
+            display_width: 0,
+            display_height: 0,
+            display: Vec::new(),
+            display_plane2: Vec::new(),
+            planes: 0,
+        }
+    }
+
+    #[test]
+    fn test_history() {
+        let mut history = History::new(3);
+
+        for pc in 0..5 {
+            history.push(get_state(pc));
+        }
+
+        assert_eq!(history.pop().map(|state| state.pc), Some(4));
+        assert_eq!(history.pop().map(|state| state.pc), Some(3));
+        assert_eq!(history.pop().map(|state| state.pc), Some(2));
+        assert_eq!(history.pop(), None);
+    }
+}
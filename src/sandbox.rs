@@ -0,0 +1,208 @@
+//! An empty interpreter to try out instructions interactively, e.g. to learn what they do.
+
+use crate::{screenshot, trace};
+use chip8_core::{annotations::Annotations, assembler, display::Display, Error, Interpreter, Step};
+use std::{
+    env,
+    io::{self, BufRead, Write},
+};
+
+const HELP: &str = "\
+Enter instructions like `LD V0, 0x05` to execute them at the program counter. Separate several with `;`.
+Other commands:
+  key <key>  press and hold a key, like `key A`
+  key        release all keys
+  frame      count down the timers once, like a frame passing
+  reset      start over with an empty interpreter
+  help       show this help
+  quit       leave the sandbox";
+
+/// Runs the sandbox until the input ends or the user quits.
+pub fn run(mut args: env::ArgsOs) -> Result<(), Error> {
+    if let Some(arg) = args.next() {
+        return Err(format!("Unknown argument: {}", arg.to_string_lossy()).into());
+    }
+
+    let mut interpreter = Interpreter::new(Vec::new(), rand::random())?;
+    let stdin = io::stdin();
+    let mut stdin = stdin.lock();
+
+    println!("{}", HELP);
+
+    loop {
+        print!("> ");
+        io::stdout()
+            .flush()
+            .map_err(|err| format!("Failed to write: {}", err))?;
+
+        let mut line = String::new();
+        let read = stdin
+            .read_line(&mut line)
+            .map_err(|err| format!("Failed to read input: {}", err))?;
+        if read == 0 {
+            println!();
+            return Ok(());
+        }
+
+        let line = line.trim();
+        let (command, argument) = line.split_once(' ').unwrap_or((line, ""));
+        match command {
+            "" => {}
+            "quit" | "exit" => return Ok(()),
+            "help" => println!("{}", HELP),
+            "reset" => interpreter = Interpreter::new(Vec::new(), rand::random())?,
+            "frame" => interpreter.update_timers(),
+            "key" => {
+                if let Err(err) = press_key(&mut interpreter, argument.trim()) {
+                    println!("{}", err);
+                }
+            }
+            _ => match assemble(line) {
+                Ok(instructions) => {
+                    if let Err(err) = execute(&mut interpreter, &instructions) {
+                        println!("{}", err);
+                    }
+                }
+                Err(err) => println!("{}", err),
+            },
+        }
+    }
+}
+
+/// Assembles the instructions on the line, separated by `;`.
+fn assemble(line: &str) -> Result<Vec<u16>, Error> {
+    line.split(';')
+        .filter(|instruction| !instruction.trim().is_empty())
+        .map(assembler::assemble_instruction)
+        .collect()
+}
+
+/// Presses and holds the key, or releases all keys if none is given.
+///
+/// If the program is waiting for a key, the waiting instruction is executed.
+fn press_key(interpreter: &mut Interpreter, key: &str) -> Result<(), Error> {
+    if key.is_empty() {
+        for key in 0..16 {
+            interpreter.set_key_held(key, false);
+        }
+        return Ok(());
+    }
+
+    let key = u8::from_str_radix(key, 16)
+        .ok()
+        .filter(|key| *key < 16)
+        .ok_or_else(|| format!("Invalid key `{}`. Expected 0 to F.", key))?;
+    interpreter.set_key_held(key, true);
+
+    let awaits_key = interpreter
+        .get_current_instruction()
+        .is_some_and(|instruction| instruction & 0xF0FF == 0xF00A);
+    if awaits_key {
+        interpreter.set_key(Some(key));
+        step(interpreter)?;
+        interpreter.set_key(None);
+        print_display(interpreter.display_mut());
+    }
+
+    Ok(())
+}
+
+/// Writes the instructions to memory at the program counter and executes as many instructions.
+///
+/// Because of jumps, these do not need to be the instructions that were just written.
+fn execute(interpreter: &mut Interpreter, instructions: &[u16]) -> Result<(), Error> {
+    let pc = interpreter.get_registers().pc as usize;
+    let bytes: Vec<u8> = instructions
+        .iter()
+        .flat_map(|instruction| instruction.to_be_bytes())
+        .collect();
+    interpreter
+        .memory_mut()
+        .get_mut(pc..pc + bytes.len())
+        .ok_or_else(|| format!("The instructions do not fit in memory at {:#05X}.", pc))?
+        .copy_from_slice(&bytes);
+
+    let mut result = Ok(());
+    for _ in instructions {
+        match step(interpreter) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(err) => {
+                result = Err(err);
+                break;
+            }
+        }
+    }
+    print_display(interpreter.display_mut());
+
+    result
+}
+
+/// Executes the next instruction and prints it with the registers it changed.
+///
+/// Returns whether the execution can continue.
+fn step(interpreter: &mut Interpreter) -> Result<bool, Error> {
+    let before = interpreter.get_registers();
+    let instruction = interpreter.get_current_instruction();
+
+    match interpreter.step()? {
+        Step::Executed | Step::Breakpoint(_) => {
+            let after = interpreter.get_registers();
+            if let Some(instruction) = instruction {
+                println!(
+                    "{}",
+                    trace::format_entry(instruction, &before, &after, &Annotations::default())
+                );
+            }
+            Ok(true)
+        }
+        Step::AwaitingKey => {
+            println!("Waiting for a key. Enter `key <key>` to press one.");
+            Ok(false)
+        }
+        Step::Ended => {
+            println!("The program has ended. Enter `reset` to start over.");
+            Ok(false)
+        }
+    }
+}
+
+/// Prints the screen if anything on it changed since it was last printed.
+fn print_display(display: &mut Display) {
+    if !display.take_changes().is_empty() {
+        print!("{}", format_display(display));
+    }
+}
+
+/// Formats the screen as text in a frame, two pixel rows per line of text.
+fn format_display(display: &Display) -> String {
+    let screen_size = display.screen_size();
+    let border = format!("+{}+\n", "-".repeat(screen_size.width as usize));
+    let mut text = border.clone();
+    for line in screenshot::to_text_art(display).lines() {
+        text.push('|');
+        text.push_str(line);
+        text.push_str("|\n");
+    }
+    text.push_str(&border);
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chip8_core::display::{Point, SCREEN_SIZE};
+
+    #[test]
+    fn test_format_display() {
+        let mut display = Display::new();
+        // The top 3 rows of the font character 0.
+        display.draw_sprite(Point { x: 0, y: 0 }, &[0xF0, 0x90, 0x90], false);
+
+        let text = format_display(&display);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), SCREEN_SIZE.height as usize / 2 + 2);
+        assert!(lines[1].starts_with("|█▀▀█ "));
+        assert!(lines[2].starts_with("|▀  ▀ "));
+    }
+}
@@ -0,0 +1,180 @@
+//! Saving the screen as an image or as text, e.g. to document bugs.
+
+use chip8_core::{
+    display::{Display, Point},
+    Error,
+};
+use std::{fs, path::Path};
+
+/// Saves the screen to the path in the format given by its extension: `.png`, `.pbm` or `.txt` for text art.
+pub fn save(display: &Display, path: &Path) -> Result<(), Error> {
+    let bytes = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("png") => encode_png(display),
+        Some("pbm") => encode_pbm(display),
+        Some("txt") => to_text_art(display).into_bytes(),
+        _ => {
+            return Err(format!(
+            "Cannot save a screenshot to {}. Expected a path ending with `.png`, `.pbm` or `.txt`.",
+            path.display()
+        )
+            .into())
+        }
+    };
+
+    fs::write(path, bytes).map_err(|err| format!("Failed to save screenshot: {}", err).into())
+}
+
+/// Draws the screen with two pixel rows per line of text using half blocks.
+pub fn to_text_art(display: &Display) -> String {
+    let screen_size = display.screen_size();
+    let mut text = String::new();
+    for y in (0..screen_size.height).step_by(2) {
+        for x in 0..screen_size.width {
+            let top = display.get(Point { x, y });
+            let bottom = display.get(Point { x, y: y + 1 });
+            text.push(match (top, bottom) {
+                (false, false) => ' ',
+                (true, false) => '▀',
+                (false, true) => '▄',
+                (true, true) => '█',
+            });
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// Writes every pixel of the screen as `#` if set or `.` if not, one line per row, which is easy to compare in tests.
+pub fn to_dump(display: &Display) -> String {
+    let screen_size = display.screen_size();
+    let mut text = String::new();
+    for y in 0..screen_size.height {
+        for x in 0..screen_size.width {
+            text.push(if display.get(Point { x, y }) {
+                '#'
+            } else {
+                '.'
+            });
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// Encodes the screen as a binary PBM image, where set pixels are black.
+fn encode_pbm(display: &Display) -> Vec<u8> {
+    let screen_size = display.screen_size();
+    let mut bytes = format!("P4\n{} {}\n", screen_size.width, screen_size.height).into_bytes();
+    // The rows of the frame are whole bytes like in PBM because the width is a multiple of 8.
+    bytes.extend(display.get_frame());
+    bytes
+}
+
+/// Encodes the screen as a black and white PNG image, where set pixels are white.
+///
+/// The image data is not compressed, which is fine for images this small.
+fn encode_png(display: &Display) -> Vec<u8> {
+    let screen_size = display.screen_size();
+    let frame = display.get_frame();
+
+    let mut header = Vec::new();
+    header.extend_from_slice(&(screen_size.width as u32).to_be_bytes());
+    header.extend_from_slice(&(screen_size.height as u32).to_be_bytes());
+    // A bit depth of 1, grayscale, the default compression and filtering and no interlacing.
+    header.extend_from_slice(&[1, 0, 0, 0, 0]);
+
+    // Every row starts with the filter type, which is none.
+    let mut data = Vec::new();
+    for row in frame.chunks(screen_size.width as usize / 8) {
+        data.push(0);
+        data.extend_from_slice(row);
+    }
+
+    let mut bytes = b"\x89PNG\r\n\x1a\n".to_vec();
+    write_chunk(&mut bytes, b"IHDR", &header);
+    write_chunk(&mut bytes, b"IDAT", &zlib_store(&data));
+    write_chunk(&mut bytes, b"IEND", &[]);
+    bytes
+}
+
+fn write_chunk(bytes: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    bytes.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = bytes.len();
+    bytes.extend_from_slice(kind);
+    bytes.extend_from_slice(data);
+    let crc = crc32(&bytes[start..]);
+    bytes.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Wraps the data in a zlib stream of uncompressed deflate blocks.
+fn zlib_store(data: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![0x78, 0x01];
+    let mut blocks = data.chunks(u16::MAX as usize).peekable();
+    if blocks.peek().is_none() {
+        bytes.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        bytes.push(last as u8);
+        bytes.extend_from_slice(&(block.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(&(!(block.len() as u16)).to_le_bytes());
+        bytes.extend_from_slice(block);
+    }
+    bytes.extend_from_slice(&adler32(data).to_be_bytes());
+    bytes
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0, |crc, byte| {
+        (0..8).fold(crc ^ *byte as u32, |crc, _| {
+            if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (a, b) = bytes.iter().fold((1, 0), |(a, b), byte| {
+        let a = (a + *byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    b << 16 | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chip8_core::display::SCREEN_SIZE;
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+
+    #[test]
+    fn test_encode() {
+        let mut display = Display::new();
+        display.draw_sprite(Point { x: 0, y: 0 }, &[0xF0, 0x90], false);
+
+        let pbm = encode_pbm(&display);
+        assert!(pbm.starts_with(b"P4\n64 32\n"));
+        assert_eq!(pbm.len(), 9 + 256);
+        assert_eq!(pbm[9..11], [0xF0, 0]);
+
+        let png = encode_png(&display);
+        assert!(png.starts_with(b"\x89PNG"));
+        assert!(png.ends_with(b"IEND\xAE\x42\x60\x82"));
+
+        let text = to_text_art(&display);
+        assert_eq!(text.lines().count(), SCREEN_SIZE.height as usize / 2);
+        assert!(text.starts_with("█▀▀█ "));
+
+        let dump = to_dump(&display);
+        assert_eq!(dump.lines().count(), SCREEN_SIZE.height as usize);
+        assert!(dump.starts_with(&format!("####{}\n#..#.", ".".repeat(60))));
+    }
+}
@@ -0,0 +1,191 @@
+//! Running a program in an SDL2 window instead of the terminal, with square pixels, keys that are held for exactly as
+//! long as they are pressed and the buzzer played on the sound card.
+//!
+//! Unlike the terminal frontend, there is no debugger, rewinding or recording. This is only for playing.
+
+use crate::{
+    buzzer::{Buzzer, SAMPLE_RATE},
+    frontend::{Program, FRAME_DURATION},
+    options::Options,
+    player::Player,
+    renderer,
+    terminal::Color,
+    wav,
+};
+use chip8_core::{
+    display::{Display, Point},
+    Error,
+};
+use sdl2::{
+    audio::{AudioCallback, AudioSpecDesired},
+    event::Event,
+    keyboard::Keycode,
+    pixels::PixelFormatEnum,
+    render::{Texture, TextureCreator},
+    video::WindowContext,
+};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Instant,
+};
+
+/// How many pixels of the window a pixel of the display initially is.
+const SCALE: u32 = 10;
+
+/// Plays the buzzer while the sound timer is active, on the audio thread of SDL.
+struct Beeper {
+    buzzer: Buzzer,
+    sounding: Arc<AtomicBool>,
+}
+
+impl AudioCallback for Beeper {
+    type Channel = i16;
+
+    fn callback(&mut self, samples: &mut [i16]) {
+        let sounding = self.sounding.load(Ordering::Relaxed);
+        for sample in samples {
+            *sample = self.buzzer.next_sample(sounding);
+        }
+    }
+}
+
+/// Runs the program in a window until it ends or the window is closed.
+pub fn run(program: &Program, options: &Options) -> Result<(), Error> {
+    let mut player = Player::new(program, options)?;
+    let palette = renderer::get_palette(options);
+
+    let sdl = sdl2::init()?;
+    let mut screen_size = player.display().screen_size();
+    let window = sdl
+        .video()?
+        .window(
+            "CHIP-8",
+            screen_size.width as u32 * SCALE,
+            screen_size.height as u32 * SCALE,
+        )
+        .position_centered()
+        .resizable()
+        .build()
+        .map_err(|err| format!("Failed to open the window: {}", err))?;
+    let mut canvas = window
+        .into_canvas()
+        .build()
+        .map_err(|err| format!("Failed to open the window: {}", err))?;
+    let texture_creator = canvas.texture_creator();
+    let mut texture = create_texture(&texture_creator, player.display())?;
+    canvas
+        .set_logical_size(screen_size.width as u32, screen_size.height as u32)
+        .map_err(|err| err.to_string())?;
+
+    let mut buzzer = Buzzer::new(options.duty_cycle);
+    if let Some(path) = &options.beep_sound {
+        buzzer.set_sample(wav::load(path)?);
+    }
+    let sounding = Arc::new(AtomicBool::new(false));
+    let audio = sdl.audio()?.open_playback(
+        None,
+        &AudioSpecDesired {
+            freq: Some(SAMPLE_RATE as i32),
+            channels: Some(1),
+            samples: None,
+        },
+        |_| Beeper {
+            buzzer,
+            sounding: Arc::clone(&sounding),
+        },
+    )?;
+    audio.resume();
+
+    let mut events = sdl.event_pump()?;
+    let mut frame_start = Instant::now();
+    loop {
+        for event in events.poll_iter() {
+            match event {
+                Event::Quit { .. }
+                | Event::KeyDown {
+                    keycode: Some(Keycode::Escape),
+                    ..
+                } => return Ok(()),
+                Event::KeyDown {
+                    keycode: Some(keycode),
+                    repeat: false,
+                    ..
+                } => {
+                    if let Some(char) = convert_keycode(keycode) {
+                        player.set_key(char, true);
+                    }
+                }
+                Event::KeyUp {
+                    keycode: Some(keycode),
+                    ..
+                } => {
+                    if let Some(char) = convert_keycode(keycode) {
+                        player.set_key(char, false);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if !player.run_frame(frame_start)? {
+            return Ok(());
+        }
+        sounding.store(player.is_sounding(), Ordering::Relaxed);
+
+        let display = player.display();
+        if display.screen_size() != screen_size {
+            screen_size = display.screen_size();
+            texture = create_texture(&texture_creator, display)?;
+            canvas
+                .set_logical_size(screen_size.width as u32, screen_size.height as u32)
+                .map_err(|err| err.to_string())?;
+        }
+        draw(&mut texture, display, &palette)?;
+        canvas.clear();
+        canvas.copy(&texture, None, None)?;
+        canvas.present();
+
+        thread::sleep(FRAME_DURATION.saturating_sub(frame_start.elapsed()));
+        frame_start = Instant::now();
+    }
+}
+
+/// Returns the character of the key of the keyboard, if it is a letter or a digit.
+fn convert_keycode(keycode: Keycode) -> Option<char> {
+    // The keycodes of letters and digits are their lowercase characters.
+    char::from_u32(keycode as u32).filter(char::is_ascii_alphanumeric)
+}
+
+fn create_texture<'a>(
+    texture_creator: &'a TextureCreator<WindowContext>,
+    display: &Display,
+) -> Result<Texture<'a>, Error> {
+    let screen_size = display.screen_size();
+    texture_creator
+        .create_texture_streaming(
+            PixelFormatEnum::RGB24,
+            screen_size.width as u32,
+            screen_size.height as u32,
+        )
+        .map_err(|err| err.to_string().into())
+}
+
+/// Copies the pixels of the display into the texture, which is as large as the screen.
+fn draw(texture: &mut Texture, display: &Display, palette: &[Color; 4]) -> Result<(), Error> {
+    let screen_size = display.screen_size();
+    texture.with_lock(None, |pixels, pitch| {
+        for y in 0..screen_size.height {
+            for x in 0..screen_size.width {
+                let Color::Rgb { r, g, b } = palette[display.get_color(Point { x, y }) as usize];
+                let offset = y as usize * pitch + x as usize * 3;
+                pixels[offset..offset + 3].copy_from_slice(&[r, g, b]);
+            }
+        }
+    })?;
+
+    Ok(())
+}
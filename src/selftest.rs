@@ -0,0 +1,326 @@
+//! A quick check of every implemented instruction against small built-in programs, to verify a build.
+
+use chip8_core::{display::Point, interpreter::START_POINT, Error, Interpreter, Step};
+use std::panic::{self, AssertUnwindSafe};
+
+/// A small program testing one instruction.
+struct Fixture {
+    /// The instruction tested, like `8XY4`.
+    opcode: &'static str,
+    program: &'static [u16],
+    /// Prepares the interpreter before the program runs, e.g. by pressing keys.
+    setup: fn(&mut Interpreter),
+    /// Returns whether the state after running the whole program is as expected.
+    check: fn(&Interpreter) -> bool,
+}
+
+fn no_setup(_: &mut Interpreter) {}
+
+fn gpr(interpreter: &Interpreter, register: usize) -> u8 {
+    interpreter.get_registers().gpr[register]
+}
+
+fn pc(interpreter: &Interpreter) -> u16 {
+    interpreter.get_registers().pc
+}
+
+#[rustfmt::skip]
+const FIXTURES: &[Fixture] = &[
+    Fixture {
+        opcode: "00E0",
+        program: &[0xA000, 0xD015, 0x00E0],
+        setup: no_setup,
+        check: |interpreter| !interpreter.display().get(Point { x: 0, y: 0 }),
+    },
+    Fixture {
+        opcode: "00EE",
+        program: &[0x2206, 0x6101, 0x1208, 0x00EE],
+        setup: no_setup,
+        check: |interpreter| gpr(interpreter, 1) == 1,
+    },
+    Fixture {
+        opcode: "1NNN",
+        program: &[0x1204, 0x0000, 0x6001],
+        setup: no_setup,
+        check: |interpreter| gpr(interpreter, 0) == 1,
+    },
+    Fixture {
+        opcode: "2NNN",
+        program: &[0x2204, 0x0000, 0x6001],
+        setup: no_setup,
+        check: |interpreter| gpr(interpreter, 0) == 1,
+    },
+    Fixture {
+        opcode: "3XNN",
+        program: &[0x6005, 0x3005, 0x6101, 0x3006, 0x6201],
+        setup: no_setup,
+        check: |interpreter| gpr(interpreter, 1) == 0 && gpr(interpreter, 2) == 1,
+    },
+    Fixture {
+        opcode: "4XNN",
+        program: &[0x6005, 0x4006, 0x6101, 0x4005, 0x6201],
+        setup: no_setup,
+        check: |interpreter| gpr(interpreter, 1) == 0 && gpr(interpreter, 2) == 1,
+    },
+    Fixture {
+        opcode: "5XY0",
+        program: &[0x6005, 0x6105, 0x5010, 0x6201, 0x5020, 0x6301],
+        setup: no_setup,
+        check: |interpreter| gpr(interpreter, 2) == 0 && gpr(interpreter, 3) == 1,
+    },
+    Fixture {
+        opcode: "6XNN",
+        program: &[0x6A42],
+        setup: no_setup,
+        check: |interpreter| gpr(interpreter, 0xA) == 0x42,
+    },
+    Fixture {
+        opcode: "7XNN",
+        program: &[0x60FF, 0x7002],
+        setup: no_setup,
+        // This wraps around without setting the carry flag.
+        check: |interpreter| gpr(interpreter, 0) == 0x01 && gpr(interpreter, 0xF) == 0,
+    },
+    Fixture {
+        opcode: "8XY0",
+        program: &[0x6107, 0x8010],
+        setup: no_setup,
+        check: |interpreter| gpr(interpreter, 0) == 7,
+    },
+    Fixture {
+        opcode: "8XY1",
+        program: &[0x600C, 0x610A, 0x8011],
+        setup: no_setup,
+        check: |interpreter| gpr(interpreter, 0) == 0x0E,
+    },
+    Fixture {
+        opcode: "8XY2",
+        program: &[0x600C, 0x610A, 0x8012],
+        setup: no_setup,
+        check: |interpreter| gpr(interpreter, 0) == 0x08,
+    },
+    Fixture {
+        opcode: "8XY3",
+        program: &[0x600C, 0x610A, 0x8013],
+        setup: no_setup,
+        check: |interpreter| gpr(interpreter, 0) == 0x06,
+    },
+    Fixture {
+        opcode: "8XY4",
+        program: &[0x60F0, 0x6120, 0x8014],
+        setup: no_setup,
+        check: |interpreter| gpr(interpreter, 0) == 0x10 && gpr(interpreter, 0xF) == 1,
+    },
+    Fixture {
+        opcode: "8XY5",
+        program: &[0x6010, 0x6120, 0x8015],
+        setup: no_setup,
+        check: |interpreter| gpr(interpreter, 0) == 0xF0 && gpr(interpreter, 0xF) == 0,
+    },
+    Fixture {
+        opcode: "8XY6",
+        program: &[0x6005, 0x8006],
+        setup: no_setup,
+        check: |interpreter| gpr(interpreter, 0) == 0x02 && gpr(interpreter, 0xF) == 1,
+    },
+    Fixture {
+        opcode: "8XY7",
+        program: &[0x6010, 0x6120, 0x8017],
+        setup: no_setup,
+        check: |interpreter| gpr(interpreter, 0) == 0x10 && gpr(interpreter, 0xF) == 1,
+    },
+    Fixture {
+        opcode: "8XYE",
+        program: &[0x6081, 0x800E],
+        setup: no_setup,
+        check: |interpreter| gpr(interpreter, 0) == 0x02 && gpr(interpreter, 0xF) == 1,
+    },
+    Fixture {
+        opcode: "9XY0",
+        program: &[0x6005, 0x6106, 0x9010, 0x6201, 0x9000, 0x6301],
+        setup: no_setup,
+        check: |interpreter| gpr(interpreter, 2) == 0 && gpr(interpreter, 3) == 1,
+    },
+    Fixture {
+        opcode: "ANNN",
+        program: &[0xA123],
+        setup: no_setup,
+        check: |interpreter| interpreter.get_registers().i == 0x123,
+    },
+    Fixture {
+        opcode: "BNNN",
+        program: &[0x6004, 0xB202, 0x0000, 0x6101],
+        setup: no_setup,
+        check: |interpreter| gpr(interpreter, 1) == 1,
+    },
+    Fixture {
+        opcode: "CXNN",
+        program: &[0x60FF, 0xC000, 0x61FF, 0xC10F],
+        setup: no_setup,
+        check: |interpreter| gpr(interpreter, 0) == 0 && gpr(interpreter, 1) <= 0x0F,
+    },
+    Fixture {
+        opcode: "DXYN",
+        // The font's 0 drawn a second time at the same place erases itself and collides.
+        // The collision flag is kept in V2 before drawing it a third time.
+        program: &[0xA000, 0x6002, 0x6103, 0xD015, 0xD015, 0x82F0, 0xD015],
+        setup: no_setup,
+        check: |interpreter| {
+            let display = interpreter.display();
+            display.get(Point { x: 2, y: 3 })
+                && display.get(Point { x: 5, y: 4 })
+                && !display.get(Point { x: 3, y: 4 })
+                && gpr(interpreter, 2) == 1
+                && gpr(interpreter, 0xF) == 0
+        },
+    },
+    Fixture {
+        opcode: "EX9E",
+        program: &[0x6005, 0xE09E, 0x6101, 0x6006, 0xE09E, 0x6201],
+        setup: |interpreter| interpreter.set_key_held(0x5, true),
+        check: |interpreter| gpr(interpreter, 1) == 0 && gpr(interpreter, 2) == 1,
+    },
+    Fixture {
+        opcode: "EXA1",
+        program: &[0x6006, 0xE0A1, 0x6101, 0x6005, 0xE0A1, 0x6201],
+        setup: |interpreter| interpreter.set_key_held(0x5, true),
+        check: |interpreter| gpr(interpreter, 1) == 0 && gpr(interpreter, 2) == 1,
+    },
+    Fixture {
+        opcode: "FX07",
+        program: &[0x6009, 0xF015, 0xF107],
+        setup: no_setup,
+        check: |interpreter| gpr(interpreter, 1) == 9,
+    },
+    Fixture {
+        opcode: "FX0A",
+        program: &[0xF30A],
+        setup: |interpreter| interpreter.set_key(Some(0xB)),
+        check: |interpreter| gpr(interpreter, 3) == 0xB,
+    },
+    Fixture {
+        opcode: "FX15",
+        program: &[0x6009, 0xF015],
+        setup: no_setup,
+        check: |interpreter| interpreter.get_registers().delay_timer == 9,
+    },
+    Fixture {
+        opcode: "FX18",
+        program: &[0x6009, 0xF018],
+        setup: no_setup,
+        check: |interpreter| interpreter.get_registers().sound_timer == 9,
+    },
+    Fixture {
+        opcode: "FX1E",
+        program: &[0xA100, 0x6010, 0xF01E],
+        setup: no_setup,
+        check: |interpreter| interpreter.get_registers().i == 0x110,
+    },
+    Fixture {
+        opcode: "FX29",
+        // The sprite of 1 starts with the byte 0b00110000.
+        program: &[0x6001, 0xF029],
+        setup: no_setup,
+        check: |interpreter| {
+            let i = interpreter.get_registers().i as usize;
+            interpreter.save_state().memory[i] == 0b0011_0000
+        },
+    },
+    Fixture {
+        opcode: "FX33",
+        program: &[0x60FE, 0xA300, 0xF033],
+        setup: no_setup,
+        check: |interpreter| interpreter.save_state().memory[0x300..0x303] == [2, 5, 4],
+    },
+    Fixture {
+        opcode: "FX55",
+        program: &[0x6001, 0x6102, 0x6203, 0xA300, 0xF155],
+        setup: no_setup,
+        check: |interpreter| interpreter.save_state().memory[0x300..0x303] == [1, 2, 0],
+    },
+    Fixture {
+        opcode: "FX65",
+        // Loads the program's own first two bytes back.
+        program: &[0xA200, 0xF165],
+        setup: no_setup,
+        check: |interpreter| gpr(interpreter, 0) == 0xA2 && gpr(interpreter, 1) == 0x00,
+    },
+];
+
+/// Runs the program of the fixture until the end of it and returns whether the check passed.
+fn run(fixture: &Fixture) -> bool {
+    let program: Vec<u8> = fixture
+        .program
+        .iter()
+        .flat_map(|instruction| instruction.to_be_bytes())
+        .collect();
+    let end = START_POINT + program.len() as u16;
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut interpreter = Interpreter::new(program, 0).ok()?;
+        (fixture.setup)(&mut interpreter);
+
+        // Every fixture is short, so anything taking longer than this is stuck.
+        for _ in 0..100 {
+            if pc(&interpreter) == end {
+                return Some((fixture.check)(&interpreter));
+            }
+            if interpreter.step().ok()? != Step::Executed {
+                return None;
+            }
+        }
+
+        None
+    }));
+
+    matches!(result, Ok(Some(true)))
+}
+
+/// Prints a table of which instructions pass.
+///
+/// Returns an error if any failed.
+pub fn run_all() -> Result<(), Error> {
+    // Instructions panicking are reported as failures, so the panic messages would only be noise.
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+
+    println!("Opcode  Result");
+    let mut failures = 0;
+    for fixture in FIXTURES {
+        let passed = run(fixture);
+        if !passed {
+            failures += 1;
+        }
+        println!(
+            "{:<8}{}",
+            fixture.opcode,
+            if passed { "pass" } else { "FAIL" }
+        );
+    }
+
+    panic::set_hook(hook);
+
+    println!(
+        "{} of {} passed.",
+        FIXTURES.len() - failures,
+        FIXTURES.len()
+    );
+
+    if failures == 0 {
+        Ok(())
+    } else {
+        Err(format!("{} instructions failed.", failures).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixtures() {
+        for fixture in FIXTURES {
+            assert!(run(fixture), "{} failed", fixture.opcode);
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! Settings stored next to a program, like `game.ch8.toml` for `game.ch8`, applied whenever it is run.
+//!
+//! Only a small part of TOML is understood: lines like `key = "value"`, `key = 700` or `key = ["a", "b"]`, and
+//! comments starting with `#`.
+
+use crate::{options, renderer::Theme};
+use chip8_core::Error;
+use std::{ffi::OsString, fs, io, path::PathBuf};
+
+/// The value of a setting.
+#[derive(Debug, PartialEq)]
+enum Value {
+    /// A string or a number, which are not told apart.
+    Single(String),
+    Array(Vec<String>),
+}
+
+/// Returns the path of the settings of the program at the given path.
+pub fn get_path(program: &OsString) -> PathBuf {
+    let mut path = options::get_base_path(program);
+    path.push(".toml");
+    path.into()
+}
+
+/// Applies the settings of the program at the given path to the options, if it has any.
+pub fn apply(program: &OsString, options: &mut options::Options) -> Result<(), Error> {
+    let path = get_path(program);
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(format!("Failed to read {}: {}", path.display(), err).into()),
+    };
+
+    for (index, line) in text.lines().enumerate() {
+        apply_line(line, options)
+            .map_err(|err| format!("{}, line {}: {}", path.display(), index + 1, err))?;
+    }
+
+    Ok(())
+}
+
+fn apply_line(line: &str, options: &mut options::Options) -> Result<(), Error> {
+    let (key, value) = match parse_line(line)? {
+        Some(setting) => setting,
+        None => return Ok(()),
+    };
+
+    match (key.as_str(), value) {
+        ("speed", Value::Single(speed)) => options.speed = options::parse_speed(&speed)?,
+        ("quirks", Value::Array(quirks)) => {
+            for quirk in quirks {
+                options::parse_quirk(&quirk, &mut options.quirks)?;
+            }
+        }
+        ("keymap", Value::Single(keymap)) => options.keymap = options::parse_keymap(&keymap)?,
+        ("fx0a-keys", Value::Single(keys)) => options.fx0a_keys = options::parse_key_set(&keys)?,
+        ("theme", Value::Single(theme)) => options.theme = Theme::parse(&theme)?,
+        ("patches", Value::Array(patches)) => {
+            for patch in patches {
+                options.patches.add_from_str(&patch)?;
+            }
+        }
+        ("speed" | "keymap" | "fx0a-keys" | "theme", Value::Array(_)) => {
+            return Err(format!("Expected a single value for `{}`.", key).into())
+        }
+        ("quirks", Value::Single(_)) => {
+            return Err("Expected a list like `[\"wrap\"]` for `quirks`.".into())
+        }
+        ("patches", Value::Single(_)) => {
+            return Err("Expected a list like `[\"0x3A2 = 0x00 0xEE\"]` for `patches`.".into())
+        }
+        _ => {
+            return Err(format!(
+            "Unknown setting `{}`. Expected `speed`, `quirks`, `keymap`, `fx0a-keys`, `theme` or \
+                 `patches`.",
+            key
+        )
+            .into())
+        }
+    }
+
+    Ok(())
+}
+
+/// Parses a line like `key = "value"` into the key and the value, or returns `None` if there is no setting on it.
+fn parse_line(line: &str) -> Result<Option<(String, Value)>, Error> {
+    let line = strip_comment(line).trim();
+    if line.is_empty() {
+        return Ok(None);
+    }
+
+    let (key, value) = line
+        .split_once('=')
+        .ok_or_else(|| format!("Expected `<key> = <value>`, got `{}`.", line))?;
+    let value = value.trim();
+    let invalid = || format!("Invalid value `{}`.", value);
+
+    let value = if let Some(items) = value.strip_prefix('[') {
+        let items = items.strip_suffix(']').ok_or_else(invalid)?;
+        Value::Array(
+            items
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| parse_string(item).ok_or_else(invalid))
+                .collect::<Result<_, _>>()?,
+        )
+    } else if value.starts_with('"') {
+        Value::Single(parse_string(value).ok_or_else(invalid)?)
+    } else if !value.is_empty() && value.chars().all(|char| char.is_ascii_alphanumeric()) {
+        Value::Single(value.to_string())
+    } else {
+        return Err(invalid().into());
+    };
+
+    Ok(Some((key.trim().to_string(), value)))
+}
+
+/// Parses a string in double quotes. Escape sequences are not supported.
+fn parse_string(string: &str) -> Option<String> {
+    let string = string.strip_prefix('"')?.strip_suffix('"')?;
+    (!string.contains(['"', '\\'])).then(|| string.to_string())
+}
+
+/// Removes the comment at the end of the line, if any, unless the `#` is in a string like in `"#33FF66"`.
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    for (index, char) in line.char_indices() {
+        match char {
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..index],
+            _ => {}
+        }
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(
+            parse_line("speed = 700 # Fast enough").unwrap(),
+            Some(("speed".to_string(), Value::Single("700".to_string())))
+        );
+        assert_eq!(
+            parse_line(r##"theme = "#33FF66,#001100""##).unwrap(),
+            Some((
+                "theme".to_string(),
+                Value::Single("#33FF66,#001100".to_string())
+            ))
+        );
+        assert_eq!(
+            parse_line(r#"quirks = ["wrap", "fx0a-release"]"#).unwrap(),
+            Some((
+                "quirks".to_string(),
+                Value::Array(vec!["wrap".to_string(), "fx0a-release".to_string()])
+            ))
+        );
+        assert_eq!(parse_line("  # Settings for Pong").unwrap(), None);
+        assert!(parse_line("speed").is_err());
+        assert!(parse_line("speed = 12/frame").is_err());
+        assert!(parse_line(r#"quirks = ["wrap""#).is_err());
+    }
+
+    #[test]
+    fn test_apply_line() {
+        let mut options = options::Options::new("game.ch8".into());
+        apply_line(
+            r#"patches = ["0x3A2 = 0x00 0xEE", "patch 0x300 = 0x12"]"#,
+            &mut options,
+        )
+        .unwrap();
+        let mut memory = [0; 0x400];
+        options.patches.apply(&mut memory).unwrap();
+        assert_eq!(memory[0x3A2..0x3A4], [0x00, 0xEE]);
+        assert_eq!(memory[0x300], 0x12);
+
+        assert!(apply_line(r#"patches = "0x3A2 = 0x00""#, &mut options).is_err());
+        assert!(apply_line(r#"patches = ["0x3A2"]"#, &mut options).is_err());
+
+        apply_line(r#"fx0a-keys = "5,A-F""#, &mut options).unwrap();
+        assert_eq!(options.fx0a_keys, 0xFC20);
+        assert!(apply_line(r#"fx0a-keys = ["5"]"#, &mut options).is_err());
+        assert!(apply_line(r#"fx0a-keys = "G""#, &mut options).is_err());
+    }
+
+    #[test]
+    fn test_get_path() {
+        assert_eq!(
+            get_path(&"games/pong.ch8".into()),
+            PathBuf::from("games/pong.ch8.toml")
+        );
+    }
+}
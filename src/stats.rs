@@ -0,0 +1,201 @@
+//! Statistics of how much each program was played, kept across runs.
+//!
+//! Programs are identified by the hash of their binary so that the statistics stay with a program when it is moved or
+//! renamed.
+
+use chip8_core::Error;
+use std::{
+    collections::BTreeMap,
+    env, fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// The statistics of a single program.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Entry {
+    /// How often the program was started.
+    pub launches: u64,
+    pub play_time: Duration,
+    /// How often the program ended by itself.
+    pub completions: u64,
+}
+
+impl Entry {
+    /// Describes the statistics in a few words, like `3 launches, 1h 02m played, ended once`.
+    pub fn describe(&self) -> String {
+        let mut description = format!(
+            "{} launch{}, {} played",
+            self.launches,
+            if self.launches == 1 { "" } else { "es" },
+            format_play_time(self.play_time)
+        );
+        match self.completions {
+            0 => {}
+            1 => description.push_str(", ended once"),
+            completions => description.push_str(&format!(", ended {} times", completions)),
+        }
+        description
+    }
+}
+
+fn format_play_time(play_time: Duration) -> String {
+    let minutes = play_time.as_secs() / 60;
+    if minutes < 60 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    }
+}
+
+/// The statistics of all programs, stored in a small text file with a line per program.
+#[derive(Debug)]
+pub struct Stats {
+    path: PathBuf,
+    entries: BTreeMap<u64, Entry>,
+}
+
+impl Stats {
+    /// Returns where the statistics are stored: `$XDG_DATA_HOME/chip8/stats` or `~/.local/share/chip8/stats`.
+    ///
+    /// Returns `None` if neither of the directories is known.
+    pub fn get_default_path() -> Option<PathBuf> {
+        let data_directory = env::var_os("XDG_DATA_HOME")
+            .filter(|directory| !directory.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| {
+                env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share"))
+            })?;
+        Some(data_directory.join("chip8").join("stats"))
+    }
+
+    /// Starts with no statistics, to be stored in the file.
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Loads the statistics from the file, or starts with none if it does not exist yet.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(format!("Failed to read statistics: {}", err).into()),
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            entries: parse(&text)?,
+        })
+    }
+
+    /// Saves the statistics to a temporary file first and then replaces the file with it, so that the file is not
+    /// left half-written if saving fails.
+    pub fn save(&self) -> Result<(), Error> {
+        let save = || -> io::Result<()> {
+            if let Some(directory) = self.path.parent() {
+                fs::create_dir_all(directory)?;
+            }
+            let mut temporary_path = self.path.clone().into_os_string();
+            temporary_path.push(".tmp");
+            fs::write(&temporary_path, to_text(&self.entries))?;
+            fs::rename(&temporary_path, &self.path)
+        };
+
+        save().map_err(|err| format!("Failed to save statistics: {}", err).into())
+    }
+
+    /// Returns the statistics of the program with the given hash, if it was ever played.
+    pub fn get(&self, hash: u64) -> Option<&Entry> {
+        self.entries.get(&hash)
+    }
+
+    pub fn get_mut(&mut self, hash: u64) -> &mut Entry {
+        self.entries.entry(hash).or_default()
+    }
+}
+
+/// Parses lines like `00ab12cd34ef5678 3 3720 1`: the hash of the program in hexadecimal, the launches, the play time in
+/// seconds and the completions.
+fn parse(text: &str) -> Result<BTreeMap<u64, Entry>, Error> {
+    let mut entries = BTreeMap::new();
+    for (index, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let entry = parse_entry(&line.split_whitespace().collect::<Vec<&str>>());
+        let (hash, entry) = entry
+            .ok_or_else(|| format!("The statistics file is corrupted at line {}.", index + 1))?;
+        entries.insert(hash, entry);
+    }
+
+    Ok(entries)
+}
+
+fn parse_entry(fields: &[&str]) -> Option<(u64, Entry)> {
+    match *fields {
+        [hash, launches, seconds, completions] => {
+            let entry = Entry {
+                launches: launches.parse().ok()?,
+                play_time: Duration::from_secs(seconds.parse().ok()?),
+                completions: completions.parse().ok()?,
+            };
+            Some((u64::from_str_radix(hash, 16).ok()?, entry))
+        }
+        _ => None,
+    }
+}
+
+fn to_text(entries: &BTreeMap<u64, Entry>) -> String {
+    entries
+        .iter()
+        .map(|(hash, entry)| {
+            format!(
+                "{:016x} {} {} {}\n",
+                hash,
+                entry.launches,
+                entry.play_time.as_secs(),
+                entry.completions
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let text = "00ab12cd34ef5678 3 3720 1\n";
+        let entries = parse(text).unwrap();
+        let entry = entries[&0x00ab12cd34ef5678];
+        assert_eq!(
+            entry,
+            Entry {
+                launches: 3,
+                play_time: Duration::from_secs(3720),
+                completions: 1
+            }
+        );
+        assert_eq!(entry.describe(), "3 launches, 1h 02m played, ended once");
+        assert_eq!(to_text(&entries), text);
+
+        assert!(parse("00ab12cd34ef5678 3 3720").is_err());
+    }
+
+    #[test]
+    fn test_save() {
+        let path = env::temp_dir().join("chip8-test-stats");
+        let mut stats = Stats::new(path.clone());
+        stats.get_mut(0x1234).launches = 2;
+        stats.save().unwrap();
+
+        assert_eq!(Stats::load(&path).unwrap().get(0x1234).unwrap().launches, 2);
+        assert!(!path.with_file_name("chip8-test-stats.tmp").exists());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,39 @@
+//! Suspending like other terminal programs, with Ctrl-Z or `SIGTSTP`, and continuing with `fg` or `SIGCONT`.
+//!
+//! In raw mode, Ctrl-Z is read as a key instead of suspending, and stopping on `SIGTSTP` without restoring the terminal
+//! first would leave the shell in raw mode, so both are handled here.
+
+use chip8_core::Error;
+use signal_hook::{
+    consts::{SIGSTOP, SIGTSTP},
+    low_level,
+};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Notices when the interpreter is asked to suspend by a signal.
+pub struct Suspension {
+    requested: Arc<AtomicBool>,
+}
+
+impl Suspension {
+    pub fn register() -> Result<Self, Error> {
+        let requested = Arc::new(AtomicBool::new(false));
+        signal_hook::flag::register(SIGTSTP, Arc::clone(&requested))
+            .map_err(|err| format!("Failed to register signal handler: {}", err))?;
+
+        Ok(Self { requested })
+    }
+
+    /// Returns whether a signal asked to suspend since the last call.
+    pub fn take_request(&self) -> bool {
+        self.requested.swap(false, Ordering::Relaxed)
+    }
+}
+
+/// Stops the process until it is continued. The terminal needs to be restored before.
+pub fn stop() -> Result<(), Error> {
+    low_level::raise(SIGSTOP).map_err(|err| format!("Failed to suspend: {}", err).into())
+}
@@ -0,0 +1,119 @@
+//! The operations on the terminal the frontend needs, so that it can be drawn to with either tanmatsu or crossterm.
+//!
+//! tanmatsu is used by default. crossterm, which also works well on Windows, is used when the `crossterm` feature is
+//! enabled.
+
+#[cfg(feature = "crossterm")]
+mod crossterm;
+#[cfg(all(feature = "tanmatsu", not(feature = "crossterm")))]
+mod tanmatsu;
+
+#[cfg(not(any(feature = "tanmatsu", feature = "crossterm")))]
+compile_error!(
+    "Either the `tanmatsu` or the `crossterm` feature is needed to draw to the terminal."
+);
+
+use std::{panic, thread, time::Duration};
+
+/// A cell of the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Point {
+    pub x: u16,
+    pub y: u16,
+}
+
+/// A size in cells of the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Size {
+    pub width: u16,
+    pub height: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Rgb { r: u8, g: u8, b: u8 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Esc,
+    Enter,
+    Backspace,
+    Tab,
+    Up,
+    Down,
+    Left,
+    Right,
+    /// Ctrl-Z, which raw mode reads as a key instead of suspending.
+    Suspend,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    Key(Key),
+    /// The terminal was resized. [`Terminal::size`] returns the new size.
+    Resize,
+}
+
+/// A terminal in raw mode. Everything written is buffered until [`Terminal::flush`].
+pub trait Terminal {
+    fn size(&self) -> Size;
+
+    /// Switches to the alternate screen with the title and hides the cursor.
+    fn initialize(&mut self, title: &str);
+    /// Undoes [`Terminal::initialize`].
+    fn deinitialize(&mut self);
+
+    fn flush(&mut self);
+    fn write(&mut self, text: &str);
+    fn set_cursor(&mut self, point: Point);
+    /// Moves the cursor to the top left.
+    fn reset_cursor(&mut self);
+    /// Moves the cursor to the start of the next line.
+    fn next_line(&mut self);
+    fn clear(&mut self);
+    fn set_foreground_color(&mut self, color: Color);
+    fn set_background_color(&mut self, color: Color);
+    fn reset_colors(&mut self);
+
+    /// Waits for the next event. Returns `None` for events that are not handled, like mouse events.
+    fn read_event(&mut self) -> Option<Event>;
+    /// Waits for the next event for at most the timeout.
+    fn poll_event(&mut self, timeout: Duration) -> Option<Event>;
+}
+
+/// Opens the terminal with the backend the crate was built with.
+///
+/// Returns `None` if the standard output is not a terminal.
+pub fn open() -> Option<Box<dyn Terminal>> {
+    #[cfg(feature = "crossterm")]
+    return crossterm::Crossterm::new().map(|terminal| Box::new(terminal) as Box<dyn Terminal>);
+
+    #[cfg(all(feature = "tanmatsu", not(feature = "crossterm")))]
+    return tanmatsu::open();
+}
+
+/// Makes panics on the main thread deinitialize the terminal before the panic is printed, so that the message is visible
+/// and the terminal is not left in raw mode with a hidden cursor. The terminal is only restored while the condition
+/// holds, e.g. not once it is lost, where writing to it might panic again.
+///
+/// [`panic_normally`] undoes this once the terminal is deinitialized.
+pub fn restore_on_panic(condition: impl Fn() -> bool + Send + Sync + 'static) {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        // The main thread holds the standard output while running, which other threads would wait for forever.
+        if thread::current().name() == Some("main") && condition() {
+            if let Some(mut terminal) = open() {
+                terminal.deinitialize();
+                terminal.flush();
+            }
+        }
+        hook(info);
+    }));
+}
+
+/// Stops restoring the terminal on panics.
+pub fn panic_normally() {
+    let _ = panic::take_hook();
+}
@@ -0,0 +1,137 @@
+use super::{Color, Event, Key, Point, Size, Terminal};
+use crossterm::{cursor, event, queue, style, terminal};
+use std::{
+    io::{self, IsTerminal, Stdout, Write},
+    time::Duration,
+};
+
+pub struct Crossterm {
+    stdout: Stdout,
+    size: Size,
+}
+
+impl Crossterm {
+    pub fn new() -> Option<Self> {
+        let stdout = io::stdout();
+        if !stdout.is_terminal() {
+            return None;
+        }
+        let (width, height) = terminal::size().ok()?;
+
+        Some(Self {
+            stdout,
+            size: Size { width, height },
+        })
+    }
+
+    fn convert_event(&mut self, event: event::Event) -> Option<Event> {
+        let key = match event {
+            event::Event::Key(key) => key,
+            event::Event::Resize(width, height) => {
+                self.size = Size { width, height };
+                return Some(Event::Resize);
+            }
+            event::Event::Mouse(_) => return None,
+        };
+        let key = match key.code {
+            event::KeyCode::Char('z') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                Key::Suspend
+            }
+            event::KeyCode::Char(char) => Key::Char(char),
+            event::KeyCode::Esc => Key::Esc,
+            event::KeyCode::Enter => Key::Enter,
+            event::KeyCode::Backspace => Key::Backspace,
+            event::KeyCode::Tab => Key::Tab,
+            event::KeyCode::Up => Key::Up,
+            event::KeyCode::Down => Key::Down,
+            event::KeyCode::Left => Key::Left,
+            event::KeyCode::Right => Key::Right,
+            _ => return None,
+        };
+        Some(Event::Key(key))
+    }
+}
+
+fn convert_color(color: Color) -> style::Color {
+    match color {
+        Color::Rgb { r, g, b } => style::Color::Rgb { r, g, b },
+    }
+}
+
+// Like with tanmatsu, failing to write to the terminal is not an error. There is nowhere to report it anyway.
+impl Terminal for Crossterm {
+    fn size(&self) -> Size {
+        self.size
+    }
+
+    fn initialize(&mut self, title: &str) {
+        let _ = terminal::enable_raw_mode();
+        let _ = queue!(
+            self.stdout,
+            terminal::EnterAlternateScreen,
+            terminal::SetTitle(title),
+            cursor::Hide,
+            terminal::Clear(terminal::ClearType::All),
+            cursor::MoveTo(0, 0)
+        );
+    }
+
+    fn deinitialize(&mut self) {
+        let _ = queue!(
+            self.stdout,
+            style::ResetColor,
+            cursor::Show,
+            terminal::LeaveAlternateScreen
+        );
+        let _ = terminal::disable_raw_mode();
+    }
+
+    fn flush(&mut self) {
+        let _ = self.stdout.flush();
+    }
+
+    fn write(&mut self, text: &str) {
+        let _ = queue!(self.stdout, style::Print(text));
+    }
+
+    fn set_cursor(&mut self, point: Point) {
+        let _ = queue!(self.stdout, cursor::MoveTo(point.x, point.y));
+    }
+
+    fn reset_cursor(&mut self) {
+        let _ = queue!(self.stdout, cursor::MoveTo(0, 0));
+    }
+
+    fn next_line(&mut self) {
+        let _ = queue!(self.stdout, cursor::MoveToNextLine(1));
+    }
+
+    fn clear(&mut self) {
+        let _ = queue!(self.stdout, terminal::Clear(terminal::ClearType::All));
+    }
+
+    fn set_foreground_color(&mut self, color: Color) {
+        let _ = queue!(self.stdout, style::SetForegroundColor(convert_color(color)));
+    }
+
+    fn set_background_color(&mut self, color: Color) {
+        let _ = queue!(self.stdout, style::SetBackgroundColor(convert_color(color)));
+    }
+
+    fn reset_colors(&mut self) {
+        let _ = queue!(self.stdout, style::ResetColor);
+    }
+
+    fn read_event(&mut self) -> Option<Event> {
+        let event = event::read().ok()?;
+        self.convert_event(event)
+    }
+
+    fn poll_event(&mut self, timeout: Duration) -> Option<Event> {
+        if event::poll(timeout).ok()? {
+            self.read_event()
+        } else {
+            None
+        }
+    }
+}
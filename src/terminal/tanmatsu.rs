@@ -0,0 +1,103 @@
+use super::{Color, Event, Key, Point, Size, Terminal};
+use std::{io, time::Duration};
+use tanmatsu::{event, util};
+
+pub fn open() -> Option<Box<dyn Terminal>> {
+    let terminal = tanmatsu::Terminal::new(io::stdout().lock()).ok()?;
+    Some(Box::new(terminal))
+}
+
+fn convert_color(color: Color) -> util::Color {
+    match color {
+        Color::Rgb { r, g, b } => util::Color::Rgb { r, g, b },
+    }
+}
+
+// tanmatsu has more keys and events than are handled.
+#[allow(unreachable_patterns)]
+fn convert_event(event: event::Event) -> Option<Event> {
+    let key = match event {
+        event::Event::Key(key) => key,
+        event::Event::Resize => return Some(Event::Resize),
+        _ => return None,
+    };
+    let key = match key {
+        // The control character Ctrl-Z is typed as.
+        event::Key::Char('\u{1A}') => Key::Suspend,
+        event::Key::Char(char) => Key::Char(char),
+        event::Key::Esc => Key::Esc,
+        event::Key::Enter => Key::Enter,
+        event::Key::Backspace => Key::Backspace,
+        event::Key::Tab => Key::Tab,
+        event::Key::Up => Key::Up,
+        event::Key::Down => Key::Down,
+        event::Key::Left => Key::Left,
+        event::Key::Right => Key::Right,
+        _ => return None,
+    };
+    Some(Event::Key(key))
+}
+
+impl Terminal for tanmatsu::Terminal<'static> {
+    fn size(&self) -> Size {
+        Size {
+            width: self.size.width,
+            height: self.size.height,
+        }
+    }
+
+    fn initialize(&mut self, title: &str) {
+        self.initialize(Some(title), false);
+    }
+
+    fn deinitialize(&mut self) {
+        self.deinitialize();
+    }
+
+    fn flush(&mut self) {
+        self.flush();
+    }
+
+    fn write(&mut self, text: &str) {
+        self.write(text);
+    }
+
+    fn set_cursor(&mut self, point: Point) {
+        self.set_cursor(util::Point {
+            x: point.x,
+            y: point.y,
+        });
+    }
+
+    fn reset_cursor(&mut self) {
+        self.reset_cursor();
+    }
+
+    fn next_line(&mut self) {
+        self.next_line();
+    }
+
+    fn clear(&mut self) {
+        self.clear();
+    }
+
+    fn set_foreground_color(&mut self, color: Color) {
+        self.set_foreground_color(convert_color(color));
+    }
+
+    fn set_background_color(&mut self, color: Color) {
+        self.set_background_color(convert_color(color));
+    }
+
+    fn reset_colors(&mut self) {
+        self.reset_colors();
+    }
+
+    fn read_event(&mut self) -> Option<Event> {
+        self.read_event().and_then(convert_event)
+    }
+
+    fn poll_event(&mut self, timeout: Duration) -> Option<Event> {
+        self.poll_event(timeout).and_then(convert_event)
+    }
+}
@@ -0,0 +1,202 @@
+//! Running test ROMs, like the CHIP-8 test suite by Timendus with its opcode, flags and quirks tests, without a
+//! terminal and comparing the screens they end on with screens saved from a run that was checked by eye.
+//!
+//! The tests draw a mark next to every check, so any change to the screen means that a check now passes or fails
+//! differently, which catches regressions of the interpreter automatically. Given the mark of a failed check, tests are
+//! judged by whether they show it, even without screens saved before.
+
+use crate::{headless, options, screenshot};
+use chip8_core::{interpreter::START_POINT, quirks::Quirks, Error, Interpreter};
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// How many instructions every test runs, which is plenty for the tests of the suite to draw all their results.
+const DEFAULT_CYCLES: u64 = 1_000_000;
+/// Where the quirks test of the suite looks for the platform to test, which skips its menu waiting for a key.
+///
+/// Other tests may use this address for something else, so it is only set for tests with `quirks` in their name.
+const PLATFORM_ADDRESS: usize = 0x1FF;
+/// The platform picked in the quirks test, which is CHIP-8.
+const PLATFORM_CHIP8: u8 = 1;
+
+/// Runs every `.ch8` file in the directory given in the arguments and compares its screen with the `.expected.txt`
+/// file next to it, printing whether each test passed.
+///
+/// The arguments are the directory, optionally followed by `--bless` to save the current screens as the expected ones,
+/// `--fail-mark <path>` with the mark of a failed check in the format of the screens, `--cycles <count>` and
+/// `--quirk <quirk>`.
+pub fn run(mut args: env::ArgsOs) -> Result<(), Error> {
+    let directory = match args.next() {
+        Some(directory) => PathBuf::from(directory),
+        None => return Err("No directory with test ROMs given.".into()),
+    };
+
+    let mut bless = false;
+    let mut fail_mark = None;
+    let mut cycles = DEFAULT_CYCLES;
+    let mut quirks = Quirks::default();
+    while let Some(arg) = args.next() {
+        match arg.to_str() {
+            Some("--bless") => bless = true,
+            Some(option @ "--fail-mark") => {
+                let path = options::get_value(&mut args, option)?;
+                let mark = fs::read_to_string(&path)
+                    .map_err(|err| format!("Failed to read the fail mark: {}", err))?;
+                fail_mark = Some(parse_mark(&mark)?);
+            }
+            Some(option @ "--cycles") => {
+                let value = options::get_value(&mut args, option)?;
+                cycles = value
+                    .parse()
+                    .map_err(|_| format!("Invalid count `{}`.", value))?;
+            }
+            Some(option @ "--quirk") => {
+                options::parse_quirk(&options::get_value(&mut args, option)?, &mut quirks)?
+            }
+            _ => return Err(format!("Unknown argument: {}", arg.to_string_lossy()).into()),
+        }
+    }
+
+    let mut paths: Vec<PathBuf> = fs::read_dir(&directory)
+        .map_err(|err| format!("Failed to read the test ROMs: {}", err))?
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "ch8"))
+        .collect();
+    paths.sort();
+    if paths.is_empty() {
+        return Err(format!("There are no .ch8 files in {}.", directory.display()).into());
+    }
+
+    let mut failures = 0;
+    for path in &paths {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let screen = run_test(path, cycles, &quirks)?;
+        if let Some(row) = fail_mark.as_ref().and_then(|mark| find_mark(&screen, mark)) {
+            // Such a screen is not saved when blessing either.
+            failures += 1;
+            println!("{}: FAILED, row {} shows the fail mark", name, row);
+            continue;
+        }
+        let expected_path = path.with_extension("expected.txt");
+        if bless {
+            fs::write(&expected_path, screen)
+                .map_err(|err| format!("Failed to save the expected screen: {}", err))?;
+            println!("{}: saved", name);
+            continue;
+        }
+
+        match fs::read_to_string(&expected_path) {
+            Ok(expected) => match get_differing_rows(&expected, &screen) {
+                rows if rows.is_empty() => println!("{}: ok", name),
+                rows => {
+                    failures += 1;
+                    let rows: Vec<String> = rows.iter().map(usize::to_string).collect();
+                    println!("{}: FAILED, rows {} differ", name, rows.join(", "));
+                }
+            },
+            Err(err) if err.kind() == io::ErrorKind::NotFound => match fail_mark {
+                Some(_) => println!("{}: ok, there is no fail mark", name),
+                None => println!("{}: skipped, there is no expected screen yet", name),
+            },
+            Err(err) => return Err(format!("Failed to read the expected screen: {}", err).into()),
+        }
+    }
+
+    if failures > 0 {
+        return Err(format!("{} of {} tests failed.", failures, paths.len()).into());
+    }
+
+    Ok(())
+}
+
+/// Runs the test ROM and returns the screen it ended on.
+fn run_test(path: &Path, cycles: u64, quirks: &Quirks) -> Result<String, Error> {
+    let binary = crate::read_binary(path.as_os_str())?;
+    let mut interpreter = Interpreter::with_quirks(binary, 0, START_POINT, quirks.clone())?;
+    if path
+        .file_name()
+        .is_some_and(|name| name.to_string_lossy().contains("quirks"))
+    {
+        interpreter.memory_mut()[PLATFORM_ADDRESS] = PLATFORM_CHIP8;
+    }
+    headless::execute(&mut interpreter, cycles, options::DEFAULT_SPEED)
+        .map_err(|err| format!("{}: {}", path.display(), err))?;
+
+    Ok(screenshot::to_dump(interpreter.display()))
+}
+
+/// Parses the mark of a failed check drawn like the screens, with `#` for set pixels and `.` for unset ones, into its
+/// rows.
+fn parse_mark(text: &str) -> Result<Vec<Vec<bool>>, Error> {
+    let mark: Vec<Vec<bool>> = text
+        .lines()
+        .map(|line| line.trim_end().chars().map(|char| char == '#').collect())
+        .collect();
+
+    if !mark.iter().flatten().any(|pixel| *pixel) {
+        return Err("The fail mark has no set pixels.".into());
+    }
+
+    Ok(mark)
+}
+
+/// Returns the number of the row of the screen the mark is shown at first, counting from 0.
+///
+/// Pixels the mark leaves unset need to be unset on the screen too.
+fn find_mark(screen: &str, mark: &[Vec<bool>]) -> Option<usize> {
+    let screen: Vec<Vec<bool>> = screen
+        .lines()
+        .map(|line| line.chars().map(|char| char == '#').collect())
+        .collect();
+    let mark_width = mark.iter().map(Vec::len).max().unwrap_or_default();
+    let screen_width = screen.iter().map(Vec::len).max().unwrap_or_default();
+    if mark.len() > screen.len() || mark_width > screen_width {
+        return None;
+    }
+
+    (0..=screen.len() - mark.len()).find(|y| {
+        (0..=screen_width - mark_width).any(|x| {
+            mark.iter().enumerate().all(|(row, pixels)| {
+                pixels.iter().enumerate().all(|(column, pixel)| {
+                    screen[y + row].get(x + column).copied().unwrap_or_default() == *pixel
+                })
+            })
+        })
+    })
+}
+
+/// Returns the numbers of the rows of the screens that differ, counting from 0.
+fn get_differing_rows(expected: &str, actual: &str) -> Vec<usize> {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+
+    (0..expected.len().max(actual.len()))
+        .filter(|row| expected.get(*row) != actual.get(*row))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_differing_rows() {
+        assert!(get_differing_rows("#.\n.#\n", "#.\n.#\n").is_empty());
+        assert_eq!(get_differing_rows("#.\n.#\n", "#.\n##\n"), [1]);
+        // A hi-res screen has more rows.
+        assert_eq!(get_differing_rows("#.\n", "..\n..\n"), [0, 1]);
+    }
+
+    #[test]
+    fn test_find_mark() {
+        let mark = parse_mark("#.#\n.#.\n#.#\n").unwrap();
+        let screen = "......\n...#.#\n....#.\n...#.#\n";
+        assert_eq!(find_mark(screen, &mark), Some(1));
+        assert_eq!(find_mark("......\n...###\n...###\n...###\n", &mark), None);
+        assert_eq!(find_mark("#.#\n.#.\n", &mark), None);
+
+        assert!(parse_mark("...\n").is_err());
+    }
+}
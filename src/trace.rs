@@ -0,0 +1,152 @@
+//! Logging of every executed instruction, to compare the execution with other emulators.
+
+use chip8_core::{annotations::Annotations, disassembler, Error, Registers};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+/// Writes a line for every executed instruction to a log file.
+pub struct Tracer {
+    file: BufWriter<File>,
+    /// Names for the memory addresses in the log.
+    annotations: Annotations,
+}
+
+impl Tracer {
+    pub fn create(path: &Path, annotations: Annotations) -> Result<Self, Error> {
+        let file = File::create(path).map_err(|err| format!("Failed to create trace: {}", err))?;
+
+        Ok(Self {
+            file: BufWriter::new(file),
+            annotations,
+        })
+    }
+
+    /// Writes everything logged so far to the file.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.file
+            .flush()
+            .map_err(|err| format!("Failed to write trace: {}", err).into())
+    }
+
+    /// Logs the instruction executed with the registers before and after it.
+    pub fn trace(
+        &mut self,
+        instruction: u16,
+        before: &Registers,
+        after: &Registers,
+    ) -> Result<(), Error> {
+        writeln!(
+            self.file,
+            "{}",
+            format_entry(instruction, before, after, &self.annotations)
+        )
+        .map_err(|err| format!("Failed to write trace: {}", err).into())
+    }
+}
+
+/// Formats the instruction like `0x200  6A02  LD VA, 0x02     VA=0x02`: the address, the instruction, its disassembly
+/// and the registers that changed, except for the program counter.
+///
+/// Addresses in annotated regions are followed by their name, and instructions accessing the memory at an annotated
+/// address end with `@` and its name.
+pub fn format_entry(
+    instruction: u16,
+    before: &Registers,
+    after: &Registers,
+    annotations: &Annotations,
+) -> String {
+    let (mnemonic, operands, _) = disassembler::decode(instruction);
+    let mut entry = format!(
+        "{:#05X}  {:04X}  {:<16}",
+        before.pc,
+        instruction,
+        format!("{} {}", mnemonic, operands.join(", "))
+    );
+
+    let mut changes = Vec::new();
+    for (index, (before, after)) in before.gpr.iter().zip(&after.gpr).enumerate() {
+        if before != after {
+            changes.push(format!("V{:X}={:#04X}", index, after));
+        }
+    }
+    if before.i != after.i {
+        match annotations.describe(after.i) {
+            Some(name) => changes.push(format!("I={:#05X} ({})", after.i, name)),
+            None => changes.push(format!("I={:#05X}", after.i)),
+        }
+    }
+    if before.delay_timer != after.delay_timer {
+        changes.push(format!("DT={:#04X}", after.delay_timer));
+    }
+    if before.sound_timer != after.sound_timer {
+        changes.push(format!("ST={:#04X}", after.sound_timer));
+    }
+    if accesses_memory(instruction) {
+        if let Some(name) = annotations.describe(before.i) {
+            changes.push(format!("@{}", name));
+        }
+    }
+    entry.push_str(&changes.join(" "));
+
+    entry.trim_end().to_string()
+}
+
+/// Returns whether the instruction reads or writes the memory the address register points to.
+fn accesses_memory(instruction: u16) -> bool {
+    instruction & 0xF000 == 0xD000 || matches!(instruction & 0xF0FF, 0xF033 | 0xF055 | 0xF065)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_entry() {
+        let before = Registers {
+            pc: 0x200,
+            gpr: [0; 16],
+            i: 0,
+            delay_timer: 0,
+            sound_timer: 0,
+        };
+        let mut after = before;
+        after.pc = 0x202;
+        after.gpr[0xA] = 0x02;
+
+        assert_eq!(
+            format_entry(0x6A02, &before, &after, &Annotations::default()),
+            "0x200  6A02  LD VA, 0x02     VA=0x02"
+        );
+        assert_eq!(
+            format_entry(0x00E0, &before, &before, &Annotations::default()),
+            "0x200  00E0  CLS"
+        );
+    }
+
+    #[test]
+    fn test_format_entry_annotations() {
+        let mut annotations = Annotations::default();
+        annotations.add(0x300..0x303, "score".into());
+        let before = Registers {
+            pc: 0x200,
+            gpr: [0; 16],
+            i: 0,
+            delay_timer: 0,
+            sound_timer: 0,
+        };
+        let mut after = before;
+        after.i = 0x300;
+
+        assert_eq!(
+            format_entry(0xA300, &before, &after, &annotations),
+            "0x200  A300  LD I, 0x300     I=0x300 (score)"
+        );
+        assert_eq!(
+            format_entry(0xF233, &after, &after, &annotations),
+            "0x200  F233  LD B, V2        @score"
+        );
+    }
+}
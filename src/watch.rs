@@ -0,0 +1,105 @@
+//! Reloading the program whenever its file changes, e.g. while writing it.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+/// How often the file is checked for changes.
+const CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Notices when the file of the program was modified.
+pub struct Watcher {
+    pub path: PathBuf,
+    /// When the file was last modified, as of the last check.
+    modified: Option<SystemTime>,
+    last_check: Instant,
+}
+
+impl Watcher {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            modified: get_modified(&path),
+            path,
+            last_check: Instant::now(),
+        }
+    }
+
+    /// Returns whether the file was modified since this last returned `true`.
+    ///
+    /// The file is only actually checked every [`CHECK_INTERVAL`], so this can be called often.
+    pub fn has_changed(&mut self) -> bool {
+        if self.last_check.elapsed() < CHECK_INTERVAL {
+            return false;
+        }
+        self.last_check = Instant::now();
+
+        let modified = get_modified(&self.path);
+        // While the file is being written, it may briefly be missing.
+        if modified.is_none() || modified == self.modified {
+            return false;
+        }
+        self.modified = modified;
+        true
+    }
+}
+
+fn get_modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// How to get from the old to the new program.
+#[derive(Debug, PartialEq)]
+pub enum Reload {
+    Unchanged,
+    /// Only bytes were changed in place or appended, so the code that stayed the same is still at the same addresses.
+    /// The changed bytes are written to memory while running, as offsets into the program with their new values.
+    Patch(Vec<(usize, u8)>),
+    /// Bytes were removed, so code may have moved and the program has to start over.
+    Restart,
+}
+
+/// Compares the old and the new program to decide how to reload it.
+pub fn compare(old: &[u8], new: &[u8]) -> Reload {
+    if old == new {
+        Reload::Unchanged
+    } else if new.len() >= old.len() && (new.len() == old.len() || new.starts_with(old)) {
+        let changes = new
+            .iter()
+            .enumerate()
+            .filter(|(offset, byte)| old.get(*offset) != Some(byte))
+            .map(|(offset, byte)| (offset, *byte))
+            .collect();
+        Reload::Patch(changes)
+    } else {
+        Reload::Restart
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare() {
+        let old = [0x60, 0x05, 0x12, 0x00];
+        assert_eq!(compare(&old, &old), Reload::Unchanged);
+        assert_eq!(
+            compare(&old, &[0x60, 0x06, 0x12, 0x00]),
+            Reload::Patch(vec![(1, 0x06)])
+        );
+        assert_eq!(
+            compare(&old, &[0x60, 0x05, 0x12, 0x00, 0x00, 0xE0]),
+            Reload::Patch(vec![(4, 0x00), (5, 0xE0)])
+        );
+        // An instruction was inserted, moving the ones after it.
+        assert_eq!(
+            compare(&old, &[0x60, 0x05, 0x00, 0xE0, 0x12, 0x00]),
+            Reload::Restart
+        );
+        assert_eq!(compare(&old, &[0x60, 0x05]), Reload::Restart);
+    }
+}
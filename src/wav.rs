@@ -0,0 +1,139 @@
+//! Reading of WAV files, for playing samples as the sound of the buzzer.
+
+use crate::buzzer::SAMPLE_RATE;
+use chip8_core::Error;
+use std::{convert::TryInto, fs, path::Path};
+
+/// Loads the samples of an uncompressed 8-bit or 16-bit WAV file, mixed down to mono and resampled to
+/// [`SAMPLE_RATE`].
+pub fn load(path: &Path) -> Result<Vec<i16>, Error> {
+    let bytes = fs::read(path).map_err(|err| format!("Failed to read beep sound: {}", err))?;
+    decode(&bytes).map_err(|err| format!("Invalid beep sound: {}", err).into())
+}
+
+/// The format of the samples, as given in the `fmt ` chunk.
+struct Format {
+    channels: u16,
+    sample_rate: u32,
+    bits_per_sample: u16,
+}
+
+fn decode(bytes: &[u8]) -> Result<Vec<i16>, Error> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err("This is not a WAV file.".into());
+    }
+
+    let mut format = None;
+    let mut data = None;
+    let mut rest = &bytes[12..];
+    while rest.len() >= 8 {
+        let id = &rest[0..4];
+        let size = u32::from_le_bytes(rest[4..8].try_into().unwrap()) as usize;
+        let body = rest
+            .get(8..8 + size)
+            .ok_or("A chunk is cut off. The file may be incomplete.")?;
+        match id {
+            b"fmt " if size >= 16 => {
+                let read_u16 = |offset: usize| u16::from_le_bytes([body[offset], body[offset + 1]]);
+                if read_u16(0) != 1 {
+                    return Err("Only uncompressed PCM is supported.".into());
+                }
+                format = Some(Format {
+                    channels: read_u16(2),
+                    sample_rate: u32::from_le_bytes(body[4..8].try_into().unwrap()),
+                    bits_per_sample: read_u16(14),
+                });
+            }
+            b"data" => data = Some(body),
+            _ => {}
+        }
+        // Chunks are padded to an even size.
+        rest = rest.get(8 + size + size % 2..).unwrap_or_default();
+    }
+
+    let format = format.ok_or("The format chunk is missing.")?;
+    let data = data.ok_or("The data chunk is missing.")?;
+    if format.channels == 0 || format.sample_rate == 0 {
+        return Err("The format is invalid.".into());
+    }
+
+    let samples: Vec<i16> = match format.bits_per_sample {
+        // 8-bit samples are unsigned.
+        8 => data.iter().map(|byte| (*byte as i16 - 128) << 8).collect(),
+        16 => data
+            .chunks_exact(2)
+            .map(|bytes| i16::from_le_bytes([bytes[0], bytes[1]]))
+            .collect(),
+        bits => return Err(format!("{}-bit samples are not supported.", bits).into()),
+    };
+    let mono: Vec<i16> = samples
+        .chunks_exact(format.channels as usize)
+        .map(|frame| {
+            (frame.iter().map(|sample| *sample as i32).sum::<i32>() / frame.len() as i32) as i16
+        })
+        .collect();
+    if mono.is_empty() {
+        return Err("There are no samples.".into());
+    }
+
+    Ok(resample(&mono, format.sample_rate))
+}
+
+/// Resamples the samples from the sample rate to [`SAMPLE_RATE`] by interpolating linearly.
+fn resample(samples: &[i16], sample_rate: u32) -> Vec<i16> {
+    if sample_rate == SAMPLE_RATE {
+        return samples.to_vec();
+    }
+
+    let step = sample_rate as f64 / SAMPLE_RATE as f64;
+    let count = (samples.len() as f64 / step).ceil() as usize;
+    (0..count)
+        .map(|index| {
+            let position = index as f64 * step;
+            let before = samples[position as usize] as f64;
+            let after = *samples
+                .get(position as usize + 1)
+                .unwrap_or(&samples[samples.len() - 1]) as f64;
+            (before + (after - before) * position.fract()).round() as i16
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a WAV file with the given format and data.
+    fn build(channels: u16, sample_rate: u32, bits_per_sample: u16, data: &[u8]) -> Vec<u8> {
+        let mut bytes = b"RIFF\0\0\0\0WAVE".to_vec();
+        bytes.extend(b"fmt \x10\0\0\0\x01\0");
+        bytes.extend(channels.to_le_bytes());
+        bytes.extend(sample_rate.to_le_bytes());
+        bytes.extend([0; 6]);
+        bytes.extend(bits_per_sample.to_le_bytes());
+        bytes.extend(b"LIST\x01\0\0\0\0\0");
+        bytes.extend(b"data");
+        bytes.extend((data.len() as u32).to_le_bytes());
+        bytes.extend(data);
+        bytes
+    }
+
+    #[test]
+    fn test_decode() {
+        // Two stereo frames of 16-bit samples.
+        let wav = build(
+            2,
+            SAMPLE_RATE,
+            16,
+            &[0x00, 0x10, 0x00, 0x30, 0xFF, 0xFF, 0x01, 0x00],
+        );
+        assert_eq!(decode(&wav).unwrap(), [0x2000, 0]);
+
+        // 8-bit samples at half the sample rate are interpolated.
+        let wav = build(1, SAMPLE_RATE / 2, 8, &[128, 192]);
+        assert_eq!(decode(&wav).unwrap(), [0, 0x2000, 0x4000, 0x4000]);
+
+        assert!(decode(b"RIFF\0\0\0\0AVI ").is_err());
+        assert!(decode(&build(1, SAMPLE_RATE, 24, &[0; 3])).is_err());
+    }
+}
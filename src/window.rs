@@ -0,0 +1,193 @@
+//! Running a program in a plain native window drawn with winit and pixels, for systems where neither a capable terminal
+//! nor SDL2 is available.
+//!
+//! There is no sound card output here. Like in the terminal, the buzzer is only played with `--audio`.
+
+use crate::{
+    buzzer::Buzzer,
+    frontend::{Program, FRAME_DURATION},
+    options::Options,
+    player::Player,
+    renderer,
+    terminal::Color,
+    wav,
+};
+use chip8_core::{
+    display::{Display, Point},
+    Error,
+};
+use pixels::{Pixels, SurfaceTexture};
+use std::{panic, time::Instant};
+use winit::{
+    dpi::LogicalSize,
+    event::{ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent},
+    event_loop::EventLoop,
+    platform::run_return::EventLoopExtRunReturn,
+    window::WindowBuilder,
+};
+
+/// How many pixels of the window a pixel of the display initially is.
+const SCALE: u32 = 10;
+
+/// Runs the program in a window until it ends or the window is closed.
+pub fn run(program: &Program, options: &Options) -> Result<(), Error> {
+    let mut player = Player::new(program, options)?;
+    let palette = renderer::get_palette(options);
+    let mut buzzer = match &options.audio {
+        Some(path) => {
+            let mut buzzer = Buzzer::create(path, options.duty_cycle)?;
+            if let Some(path) = &options.beep_sound {
+                buzzer.set_sample(wav::load(path)?);
+            }
+            Some(buzzer)
+        }
+        None => None,
+    };
+
+    // winit panics instead of returning an error if there is no display to open a window on.
+    let mut event_loop = panic::catch_unwind(EventLoop::new)
+        .map_err(|_| "Failed to open the window: No display was found.")?;
+    let mut screen_size = player.display().screen_size();
+    let window = WindowBuilder::new()
+        .with_title("CHIP-8")
+        .with_inner_size(LogicalSize::new(
+            screen_size.width as u32 * SCALE,
+            screen_size.height as u32 * SCALE,
+        ))
+        .build(&event_loop)
+        .map_err(|err| format!("Failed to open the window: {}", err))?;
+    let window_size = window.inner_size();
+    let mut pixels = Pixels::new(
+        screen_size.width as u32,
+        screen_size.height as u32,
+        SurfaceTexture::new(window_size.width, window_size.height, &window),
+    )
+    .map_err(|err| format!("Failed to open the window: {}", err))?;
+
+    let mut result = Ok(());
+    let mut frame_start = Instant::now();
+    event_loop.run_return(|event, _, control_flow| {
+        // Returns whether to keep running.
+        let handle_event = || -> Result<bool, Error> {
+            match event {
+                Event::WindowEvent { event, .. } => match event {
+                    WindowEvent::CloseRequested => return Ok(false),
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                state,
+                                virtual_keycode: Some(keycode),
+                                ..
+                            },
+                        ..
+                    } => {
+                        if keycode == VirtualKeyCode::Escape {
+                            return Ok(false);
+                        }
+                        if let Some(char) = convert_keycode(keycode) {
+                            player.set_key(char, state == ElementState::Pressed);
+                        }
+                    }
+                    WindowEvent::Resized(size) => pixels
+                        .resize_surface(size.width, size.height)
+                        .map_err(|err| err.to_string())?,
+                    _ => {}
+                },
+                Event::MainEventsCleared if frame_start.elapsed() >= FRAME_DURATION => {
+                    frame_start = Instant::now();
+                    if !player.run_frame(frame_start)? {
+                        return Ok(false);
+                    }
+                    if let Some(buzzer) = &mut buzzer {
+                        buzzer.play(player.is_sounding(), FRAME_DURATION.as_secs_f64())?;
+                    }
+                    window.request_redraw();
+                }
+                Event::RedrawRequested(_) => {
+                    let display = player.display();
+                    if display.screen_size() != screen_size {
+                        screen_size = display.screen_size();
+                        pixels
+                            .resize_buffer(screen_size.width as u32, screen_size.height as u32)
+                            .map_err(|err| err.to_string())?;
+                    }
+                    draw(pixels.frame_mut(), display, &palette);
+                    pixels.render().map_err(|err| err.to_string())?;
+                }
+                _ => {}
+            }
+
+            Ok(true)
+        };
+
+        match handle_event() {
+            Ok(true) => control_flow.set_wait_until(frame_start + FRAME_DURATION),
+            Ok(false) => control_flow.set_exit(),
+            Err(err) => {
+                result = Err(err);
+                control_flow.set_exit();
+            }
+        }
+    });
+
+    result
+}
+
+/// Returns the character of the key of the keyboard, if it is a letter or a digit.
+fn convert_keycode(keycode: VirtualKeyCode) -> Option<char> {
+    use VirtualKeyCode::*;
+
+    let char = match keycode {
+        Key0 | Numpad0 => '0',
+        Key1 | Numpad1 => '1',
+        Key2 | Numpad2 => '2',
+        Key3 | Numpad3 => '3',
+        Key4 | Numpad4 => '4',
+        Key5 | Numpad5 => '5',
+        Key6 | Numpad6 => '6',
+        Key7 | Numpad7 => '7',
+        Key8 | Numpad8 => '8',
+        Key9 | Numpad9 => '9',
+        A => 'a',
+        B => 'b',
+        C => 'c',
+        D => 'd',
+        E => 'e',
+        F => 'f',
+        G => 'g',
+        H => 'h',
+        I => 'i',
+        J => 'j',
+        K => 'k',
+        L => 'l',
+        M => 'm',
+        N => 'n',
+        O => 'o',
+        P => 'p',
+        Q => 'q',
+        R => 'r',
+        S => 's',
+        T => 't',
+        U => 'u',
+        V => 'v',
+        W => 'w',
+        X => 'x',
+        Y => 'y',
+        Z => 'z',
+        _ => return None,
+    };
+    Some(char)
+}
+
+/// Copies the pixels of the display into the frame of RGBA pixels, which is as large as the screen.
+fn draw(frame: &mut [u8], display: &Display, palette: &[Color; 4]) {
+    let screen_size = display.screen_size();
+    for (index, pixel) in frame.chunks_exact_mut(4).enumerate() {
+        let point = Point {
+            x: (index % screen_size.width as usize) as u16,
+            y: (index / screen_size.width as usize) as u16,
+        };
+        let Color::Rgb { r, g, b } = palette[display.get_color(point) as usize];
+        pixel.copy_from_slice(&[r, g, b, 0xFF]);
+    }
+}